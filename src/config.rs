@@ -0,0 +1,2120 @@
+use crate::cost_explorer::{CostMetric, Granularity};
+use crate::message_builder::filter::ServicePattern;
+use crate::message_builder::locale::Locale;
+use crate::message_builder::table::ServiceLayout;
+use crate::message_builder::DEFAULT_COST_DECIMALS;
+use crate::reporting_date::{DailyBaseline, WindowAlignment};
+use crate::slack_notifier::{FallbackSink, NotifierKind, SlackFormat};
+use chrono::format::{Item, StrftimeItems};
+use chrono_tz::Tz;
+use reqwest::Url;
+use rusoto_core::Region;
+use serde_json::Value;
+use slack_hook::{HexColor, TryFrom};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::io;
+use std::str::FromStr;
+
+/// Path of the file of the configuration profiles when `CONFIG_FILE` is not set.
+const DEFAULT_CONFIG_FILE: &str = "config.json";
+
+/// Settings of the notification.
+///
+/// Every field is read from an environment variable
+/// and falls back to its default value when the variable is not set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Metric of the costs requested to Cost Explorer. (`COST_METRIC`)
+    pub cost_metric: CostMetric,
+    /// Time granularity in which the total and service costs are requested.
+    /// (`COST_GRANULARITY`, `daily` or `monthly`)
+    pub cost_granularity: Granularity,
+    /// Window in which the costs are aggregated.
+    /// (`WINDOW_ALIGNMENT`, `calendar` or `rolling_{N}_days` such as `rolling_7_days`)
+    ///
+    /// The costs of the rolling window are always requested in DAILY granularity
+    /// and summed up, since the window does not align with the months.
+    pub window_alignment: WindowAlignment,
+    /// ID of the linked account to which all the costs are restricted.
+    /// (`FILTER_LINKED_ACCOUNT`)
+    pub filter_linked_account: Option<String>,
+    /// If true, credits and refunds are excluded from the costs. (`EXCLUDE_CREDITS`)
+    pub exclude_credits: bool,
+    /// Key of the cost allocation tag whose values are ranked by cost. (`RANKING_TAG_KEY`)
+    ///
+    /// The ranking is not displayed when it is not set.
+    pub ranking_tag_key: Option<String>,
+    /// The number of tag values displayed in the ranking. (`RANKING_TOP_N`)
+    pub ranking_top_n: usize,
+    /// Key of the cost allocation tag by whose values all the costs are grouped.
+    /// (`GROUP_BY_TAG`)
+    pub group_by_tag: Option<String>,
+    /// The maximum number of retries when Slack rate limits the request.
+    /// (`SLACK_RATE_LIMIT_MAX_RETRIES`)
+    pub slack_rate_limit_max_retries: u32,
+    /// Upper limit of seconds to wait before each retry. (`SLACK_RATE_LIMIT_MAX_WAIT_SECS`)
+    pub slack_rate_limit_max_wait_secs: u64,
+    /// Format of the payload posted to Slack Incoming Webhook.
+    /// (`SLACK_FORMAT`, `attachments` or `blocks`)
+    pub slack_format: SlackFormat,
+    /// The maximum number of retries shared by all the requests to Cost Explorer in a run.
+    /// (`AWS_RETRY_BUDGET`)
+    pub aws_retry_budget: u32,
+    /// Region to which the requests to Cost Explorer are sent. (`AWS_CE_REGION`)
+    pub ce_region: Region,
+    /// Endpoint to which the requests to Cost Explorer are sent instead of that of `ce_region`,
+    /// for testing against an emulator such as LocalStack. (`CE_ENDPOINT_URL`)
+    pub ce_endpoint_url: Option<String>,
+    /// Milliseconds to wait before the first retry of a request, which doubles on each retry.
+    /// (`AWS_RETRY_BACKOFF_MILLIS`)
+    pub aws_retry_backoff_millis: u64,
+    /// If true, a random time up to the backoff is waited before each retry.
+    /// (`AWS_RETRY_JITTER`)
+    pub aws_retry_jitter: bool,
+    /// Seed of the randomness of the jitter, which is taken from the OS if not set.
+    /// (`AWS_RETRY_JITTER_SEED`)
+    pub aws_retry_jitter_seed: Option<u64>,
+    /// Service to which the message is sent.
+    /// (`NOTIFIER`, `slack`, `slack_thread`, `teams`, `discord` or `sns`)
+    pub notifier: NotifierKind,
+    /// If true, the message is printed to the standard output
+    /// instead of being sent to `notifier`. (`DRY_RUN`)
+    pub dry_run: bool,
+    /// Bot token of Slack Web API, which is required
+    /// when `notifier` is `slack_thread`. (`SLACK_BOT_TOKEN`)
+    pub slack_bot_token: Option<String>,
+    /// Channel to which the thread is posted, which is required
+    /// when `notifier` is `slack_thread`. (`SLACK_CHANNEL`)
+    pub slack_channel: Option<String>,
+    /// Incoming Webhook URL of Microsoft Teams, which is required
+    /// when `notifier` is `teams`. (`TEAMS_WEBHOOK_URL`)
+    pub teams_webhook_url: Option<String>,
+    /// Webhook URL of Discord, which is required
+    /// when `notifier` is `discord`. (`DISCORD_WEBHOOK_URL`)
+    pub discord_webhook_url: Option<String>,
+    /// ARN of the SNS topic to which the message is published, which is required
+    /// when `notifier` is `sns`. (`SNS_TOPIC_ARN`)
+    pub sns_topic_arn: Option<String>,
+    /// If set, the message is routed to this sink when it cannot be delivered
+    /// to Slack after the retries. (`FALLBACK_SINK`, `stdout`)
+    pub fallback_sink: Option<FallbackSink>,
+    /// If true, the total cost and its breakdown are sent as separate messages.
+    /// (`SPLIT_MESSAGES`)
+    pub split_messages: bool,
+    /// Static text displayed at the bottom of every message
+    /// (e.g. a link to the cost dashboard). (`MESSAGE_FOOTER`)
+    pub message_footer: Option<String>,
+    /// Names of services displayed regardless of the amount.
+    /// (`ALWAYS_SHOW_SERVICES`, comma-separated)
+    pub always_show_services: Vec<String>,
+    /// Only services whose names start with this prefix are displayed.
+    /// (`SERVICE_NAME_PREFIX`, e.g. `Amazon `)
+    pub service_name_prefix: Option<String>,
+    /// Only services whose names match this regular expression are displayed.
+    /// (`SERVICE_NAME_PATTERN`)
+    pub service_name_pattern: Option<ServicePattern>,
+    /// Only services whose names contain any of these substrings are displayed.
+    /// (`SERVICE_ALLOWLIST`, comma-separated and case-insensitive)
+    pub service_allowlist: Vec<String>,
+    /// Services whose names contain any of these substrings are hidden.
+    /// (`SERVICE_BLOCKLIST`, comma-separated and case-insensitive)
+    pub service_blocklist: Vec<String>,
+    /// If false, the costs of services are neither requested nor displayed,
+    /// and the message consists of the total only. (`SHOW_SERVICE_BREAKDOWN`)
+    pub show_service_breakdown: bool,
+    /// If true, the total cost is summed up from the service costs
+    /// instead of being requested separately, which halves the charged requests.
+    /// (`SINGLE_COST_REQUEST`)
+    pub single_cost_request: bool,
+    /// If true, the total cost of data transfer is displayed. (`REPORT_DATA_TRANSFER`)
+    pub report_data_transfer: bool,
+    /// Usage types which contain any of these patterns are regarded as data transfer.
+    /// (`DATA_TRANSFER_PATTERN`, comma-separated)
+    pub data_transfer_patterns: Vec<String>,
+    /// Number of decimals displayed for each metric, which overrides the built-in ones.
+    /// (`METRIC_DECIMALS`, formatted as `{metric}={decimals},...`)
+    pub metric_decimals: HashMap<String, usize>,
+    /// If true, the usage type whose cost has increased the most since
+    /// the previous period is displayed. (`SHOW_TOP_INCREASING_USAGE_TYPE`)
+    pub show_top_increasing_usage_type: bool,
+    /// If set, this number of usage types are displayed in descending order by cost.
+    /// (`TOP_USAGE_TYPES`)
+    pub top_usage_types: Option<usize>,
+    /// If true, the usage amounts are displayed next to the costs of the top usage types.
+    /// (`SHOW_USAGE_QUANTITY`)
+    pub show_usage_quantity: bool,
+    /// If true, the costs for each region are displayed. (`SHOW_REGION_COSTS`)
+    pub show_region_costs: bool,
+    /// If true, the pseudo-regions of global services (`NoRegion`, `global`)
+    /// are displayed together as `グローバル`. (`GROUP_GLOBAL_REGIONS`)
+    pub group_global_regions: bool,
+    /// If true, the costs for each linked account are displayed with the account names.
+    /// (`SHOW_ACCOUNT_COSTS`)
+    pub show_account_costs: bool,
+    /// If true, the utilization of Savings Plans and Reserved Instances is displayed.
+    /// (`SHOW_UTILIZATION`)
+    pub show_utilization: bool,
+    /// Utilization below this percentage is regarded as waste of the commitments
+    /// and the message is escalated. (`LOW_UTILIZATION_PERCENT`)
+    pub low_utilization_percent: f64,
+    /// If true, the estimated monthly savings by the rightsizing recommendations
+    /// of EC2 instances are displayed. (`SHOW_RIGHTSIZING_SAVINGS`)
+    pub show_rightsizing_savings: bool,
+    /// If true, the time when the report was generated is displayed.
+    /// (`SHOW_GENERATED_AT`)
+    pub show_generated_at: bool,
+    /// If true, the total costs of each day are displayed. (`SHOW_DAILY_COSTS`)
+    pub show_daily_costs: bool,
+    /// If set, the cost of the latest day is compared with that of this day.
+    /// (`DAILY_COMPARISON_BASELINE`, `previous_day` or `same_weekday_last_week`)
+    pub daily_comparison_baseline: Option<DailyBaseline>,
+    /// If set, the table of the total costs of this number of recent months
+    /// is displayed. (`MONTHLY_TABLE_PERIODS`)
+    pub monthly_table_periods: Option<u32>,
+    /// If true, the days from the reporting date on, whose costs are not fixed yet,
+    /// are excluded from the daily costs. (`EXCLUDE_INCOMPLETE_DAY`)
+    pub exclude_incomplete_day: bool,
+    /// Budgets of services, against which the costs are displayed.
+    /// (`SERVICE_BUDGETS`, formatted as `{service name}={budget},...`)
+    pub service_budgets: HashMap<String, f64>,
+    /// If true, the report of the same reporting date is notified only once.
+    /// (`IDEMPOTENCY_ENABLED`)
+    pub idempotency_enabled: bool,
+    /// DynamoDB table in which the notified reporting dates are recorded,
+    /// which must be set if `idempotency_enabled` is true. (`IDEMPOTENCY_TABLE_NAME`)
+    pub idempotency_table_name: Option<String>,
+    /// If true, the change of the total cost since the last notification
+    /// of the same period recorded in `idempotency_table_name` is displayed.
+    /// (`SHOW_CHANGE_SINCE_LAST_NOTIFICATION`)
+    pub show_change_since_last_notification: bool,
+    /// Name of the selected configuration profile. (`CONFIG_PROFILE`)
+    pub config_profile: Option<String>,
+    /// Name of the Lambda function, set by the runtime. (`AWS_LAMBDA_FUNCTION_NAME`)
+    pub function_name: Option<String>,
+    /// If set, the costs are reported from the beginning of the fiscal year
+    /// starting in this month (1-12) instead of the beginning of the month.
+    /// (`FISCAL_YEAR_START_MONTH`)
+    pub fiscal_year_start_month: Option<u32>,
+    /// If true, a terse message is sent instead of the full breakdown
+    /// when the costs have hardly changed since the previous period.
+    /// (`UNCHANGED_SUMMARY`)
+    pub unchanged_summary: bool,
+    /// Changes within this percentage are regarded as unchanged.
+    /// (`UNCHANGED_TOLERANCE_PERCENT`)
+    pub unchanged_tolerance_percent: f64,
+    /// Changes within this amount are regarded as unchanged
+    /// even if they exceed `unchanged_tolerance_percent`.
+    /// (`UNCHANGED_TOLERANCE_AMOUNT`)
+    pub unchanged_tolerance_amount: f64,
+    /// Slack user IDs of the owners of services, who are mentioned
+    /// when the costs of the services are high.
+    /// (`SERVICE_OWNERS`, formatted as `{service name}={user ID},...`)
+    pub service_owners: HashMap<String, String>,
+    /// Owners are mentioned when the costs of their services exceed this amount.
+    /// (`OWNER_MENTION_THRESHOLD`)
+    pub owner_mention_threshold: f64,
+    /// Services whose costs are below this amount are not displayed. (`MIN_SERVICE_COST`)
+    ///
+    /// Services whose costs are displayed as zero (e.g. `0.00 USD`) are skipped
+    /// when it is not set.
+    pub min_service_cost: Option<f64>,
+    /// The number of services listed individually. (`MAX_SERVICES`)
+    ///
+    /// The rest are collapsed into a line of their total cost.
+    /// All the services are listed when it is not set.
+    pub max_services: Option<usize>,
+    /// If true, the line of the collapsed services shows their number.
+    /// (`OTHERS_SHOW_COUNT`)
+    pub others_show_count: bool,
+    /// If set, the total cost of the collapsed services is rounded to
+    /// the nearest multiple of this amount and marked with `~`. (`OTHERS_ROUNDING`)
+    pub others_rounding: Option<f64>,
+    /// Number of decimals of the total and service costs. (`COST_DECIMALS`)
+    pub cost_decimals: usize,
+    /// If true, each service line shows its share of the total cost. (`SHOW_PERCENTAGE`)
+    pub show_percentage: bool,
+    /// Layout of the lines of the service costs. (`SERVICE_LAYOUT`, `bullet` or `aligned`)
+    pub service_layout: ServiceLayout,
+    /// Language of the header and the service lines. (`LOCALE`, `ja` or `en`)
+    pub locale: Locale,
+    /// strftime-style format of the dates in the header (e.g. `%Y-%m-%d`). (`DATE_FORMAT`)
+    /// If None, the dates are displayed as `MM/DD~MM/DD`.
+    pub date_format: Option<String>,
+    /// If true, the comparison with the same period of the previous month
+    /// is sent as a separate attachment. (`COMPARISON_ATTACHMENT`)
+    pub comparison_attachment: bool,
+    /// If true, the services in the comparison are grouped by their trends
+    /// (new, growing, shrinking and stable). (`GROUP_BY_TREND`)
+    pub group_by_trend: bool,
+    /// If true, the services charged only in the previous period are kept
+    /// in the comparison as `→ 0.00 USD (-100.0%)`. (`INCLUDE_DROPPED_SERVICES`)
+    pub include_dropped_services: bool,
+    /// If set, the message is escalated when the total cost has increased
+    /// by more than this percentage since the previous period.
+    /// (`ALERT_INCREASE_PERCENT`)
+    pub alert_increase_percent: Option<f64>,
+    /// The message is colored as a warning when the total cost reaches this amount.
+    /// (`COST_WARN_THRESHOLD`)
+    pub cost_warn_threshold: Option<f64>,
+    /// The message is colored as critical when the total cost reaches this amount.
+    /// (`COST_CRIT_THRESHOLD`)
+    pub cost_crit_threshold: Option<f64>,
+    /// If true, the change rate of the total cost since the same period
+    /// of the previous month is appended to the header. (`SHOW_MONTH_OVER_MONTH`)
+    pub show_month_over_month: bool,
+    /// If true, the services which were charged in the same period of the previous month
+    /// but are not charged in the current period are flagged. (`ALERT_DROPPED_SERVICES`)
+    pub alert_dropped_services: bool,
+    /// If set, the totals of AmortizedCost and UnblendedCost are cross-checked
+    /// and a warning is appended when they differ by more than this percentage.
+    /// (`METRIC_DIVERGENCE_TOLERANCE_PERCENT`)
+    pub metric_divergence_tolerance_percent: Option<f64>,
+    /// If true, the service names and tag values are escaped for Slack formatting
+    /// so that `&`, `<` and `>` in them are displayed as they are. (`ESCAPE_SLACK_TEXT`)
+    pub escape_slack_text: bool,
+    /// Mention put in the escalated message (e.g. `<!channel>`, `<@U012AB3CD>`).
+    /// (`ALERT_MENTION`)
+    pub alert_mention: Option<String>,
+    /// Hex colors (e.g. `#36a64f`) of the message for each severity,
+    /// which override the built-in ones.
+    /// (`COLOR_NORMAL`, `COLOR_WARNING`, `COLOR_CRITICAL`)
+    pub color_normal: Option<HexColor>,
+    pub color_warning: Option<HexColor>,
+    pub color_critical: Option<HexColor>,
+    /// Old names of services mapped to their current names, with which
+    /// the services are matched across periods in the comparison.
+    /// (`SERVICE_ALIASES`, formatted as `{old name}={current name},...`)
+    pub service_aliases: HashMap<String, String>,
+    /// Labels displayed instead of the units of the costs.
+    /// (`UNIT_LABELS`, formatted as `{unit}={label},...` e.g. `USD=ドル,JPY=円`)
+    pub unit_labels: HashMap<String, String>,
+    /// If true, the service costs are grouped by categories
+    /// (e.g. Compute, Storage) with their subtotals. (`GROUP_BY_CATEGORY`)
+    pub group_by_category: bool,
+    /// Categories of services which override the built-in mapping.
+    /// (`SERVICE_CATEGORIES`, formatted as `{service name}={category},...`)
+    pub service_categories: HashMap<String, String>,
+    /// If true, the projected total at the end of the month is displayed.
+    /// (`SHOW_FORECAST`)
+    pub show_forecast: bool,
+    /// If true, the header shows the total so far, the projected total
+    /// at the end of the month (with `show_forecast`) and `monthly_budget` side by side.
+    /// (`PROGRESS_HEADER`)
+    pub progress_header: bool,
+    /// Budget of the total cost for a month in the original currency.
+    /// (`MONTHLY_BUDGET`)
+    pub monthly_budget: Option<f64>,
+    /// If true, the notification is sent only when the projected total
+    /// at the end of the month exceeds `monthly_budget`, and it is escalated.
+    /// (`NOTIFY_ONLY_OVER_BUDGET`)
+    ///
+    /// It must be set together with `monthly_budget`.
+    pub notify_only_over_budget: bool,
+    /// If set, the notification is skipped while the total cost
+    /// is below this amount. (`NOTIFY_THRESHOLD`)
+    pub notify_threshold: Option<f64>,
+    /// If true, the date on which the total cost is projected to exceed
+    /// `monthly_budget` is displayed. (`SHOW_OVERAGE_DATE`)
+    ///
+    /// It must be set together with `monthly_budget`.
+    pub show_overage_date: bool,
+    /// Currency unit into which the costs are converted (e.g. `JPY`).
+    /// (`DISPLAY_CURRENCY`)
+    ///
+    /// It must be set together with `exchange_rate`.
+    pub display_currency: Option<String>,
+    /// Amount of the display currency per unit of the original currency.
+    /// (`EXCHANGE_RATE`)
+    pub exchange_rate: Option<f64>,
+    /// Name of the business metric by which the total cost is divided (e.g. `ユーザー`).
+    /// (`UNIT_METRIC_NAME`)
+    pub unit_metric_name: String,
+    /// Value of the business metric (e.g. the number of active users).
+    /// (`UNIT_METRIC_VALUE`)
+    ///
+    /// The cost per unit is not displayed when it is not set.
+    pub unit_metric_value: Option<f64>,
+    /// Endpoint of the OpenTelemetry collector (e.g. `http://localhost:4318`),
+    /// to which the span of each run is exported via OTLP/HTTP.
+    /// (`OTEL_EXPORTER_OTLP_ENDPOINT`)
+    ///
+    /// No span is exported when it is not set.
+    pub otlp_endpoint: Option<String>,
+    /// If true, the total cost and service costs are published to EventBridge
+    /// as a custom event. (`PUBLISH_REPORT_EVENT`)
+    pub publish_report_event: bool,
+    /// If true, the total cost and service costs are printed to the standard output
+    /// as JSON for downstream consumers. (`OUTPUT_JSON`)
+    pub output_json: bool,
+    /// If true, the service costs are printed to the standard output as CSV.
+    /// (`OUTPUT_CSV`)
+    pub output_csv: bool,
+    /// Source of the published event. (`EVENT_SOURCE`)
+    pub event_source: String,
+    /// Detail type of the published event. (`EVENT_DETAIL_TYPE`)
+    pub event_detail_type: String,
+    /// Event bus to which the event is published. (`EVENT_BUS_NAME`)
+    ///
+    /// The default event bus is used when it is not set.
+    pub event_bus_name: Option<String>,
+    /// Monthly credit allotment not reflected in the costs, which is subtracted
+    /// from the total cost to display the net cost. (`MONTHLY_CREDIT`)
+    pub monthly_credit: Option<f64>,
+    /// Incoming Webhook URL of the Slack channel to notify. (`SLACK_WEBHOOK_URL`)
+    pub slack_webhook_url: Option<String>,
+    /// Incoming Webhook URLs of the Slack channels to which the same message is sent.
+    /// (`SLACK_WEBHOOK_URLS`, comma-separated)
+    ///
+    /// `SLACK_WEBHOOK_URL` is ignored when they are set.
+    pub slack_webhook_urls: Vec<String>,
+    /// ARN of the secret in Secrets Manager which stores the Incoming Webhook URL.
+    /// (`SLACK_WEBHOOK_SECRET_ARN`)
+    ///
+    /// The URL in the secret is used instead of `SLACK_WEBHOOK_URL` when it is set.
+    pub slack_webhook_secret_arn: Option<String>,
+    /// Timezone of the reporting date (e.g. `Asia/Tokyo`). (`REPORTING_TIMEZONE`)
+    pub reporting_timezone: Option<String>,
+}
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            cost_metric: CostMetric::default(),
+            cost_granularity: Granularity::Monthly,
+            window_alignment: WindowAlignment::default(),
+            filter_linked_account: None,
+            exclude_credits: false,
+            ranking_tag_key: None,
+            ranking_top_n: 5,
+            group_by_tag: None,
+            slack_rate_limit_max_retries: 3,
+            slack_rate_limit_max_wait_secs: 30,
+            slack_format: SlackFormat::default(),
+            aws_retry_budget: 3,
+            ce_region: Region::UsEast1,
+            ce_endpoint_url: None,
+            aws_retry_backoff_millis: 500,
+            aws_retry_jitter: false,
+            aws_retry_jitter_seed: None,
+            notifier: NotifierKind::default(),
+            dry_run: false,
+            slack_bot_token: None,
+            slack_channel: None,
+            teams_webhook_url: None,
+            discord_webhook_url: None,
+            sns_topic_arn: None,
+            fallback_sink: None,
+            split_messages: false,
+            message_footer: None,
+            always_show_services: vec![],
+            service_name_prefix: None,
+            service_name_pattern: None,
+            service_allowlist: vec![],
+            service_blocklist: vec![],
+            show_service_breakdown: true,
+            single_cost_request: false,
+            report_data_transfer: false,
+            data_transfer_patterns: vec![
+                "DataTransfer".to_string(),
+                "-AWS-Out-Bytes".to_string(),
+                "-AWS-In-Bytes".to_string(),
+            ],
+            metric_decimals: HashMap::new(),
+            show_top_increasing_usage_type: false,
+            top_usage_types: None,
+            show_usage_quantity: false,
+            show_region_costs: false,
+            group_global_regions: true,
+            show_account_costs: false,
+            show_utilization: false,
+            low_utilization_percent: 80.0,
+            show_rightsizing_savings: false,
+            show_generated_at: false,
+            show_daily_costs: false,
+            daily_comparison_baseline: None,
+            monthly_table_periods: None,
+            exclude_incomplete_day: false,
+            service_budgets: HashMap::new(),
+            idempotency_enabled: false,
+            idempotency_table_name: None,
+            show_change_since_last_notification: false,
+            config_profile: None,
+            function_name: None,
+            fiscal_year_start_month: None,
+            unchanged_summary: false,
+            unchanged_tolerance_percent: 5.0,
+            unchanged_tolerance_amount: 0.0,
+            service_owners: HashMap::new(),
+            owner_mention_threshold: 0.0,
+            min_service_cost: None,
+            max_services: None,
+            others_show_count: true,
+            others_rounding: None,
+            cost_decimals: DEFAULT_COST_DECIMALS,
+            show_percentage: false,
+            service_layout: ServiceLayout::default(),
+            locale: Locale::default(),
+            date_format: None,
+            comparison_attachment: false,
+            group_by_trend: false,
+            include_dropped_services: true,
+            alert_increase_percent: None,
+            cost_warn_threshold: None,
+            cost_crit_threshold: None,
+            show_month_over_month: false,
+            alert_dropped_services: false,
+            metric_divergence_tolerance_percent: None,
+            escape_slack_text: true,
+            alert_mention: None,
+            color_normal: None,
+            color_warning: None,
+            color_critical: None,
+            service_aliases: HashMap::new(),
+            unit_labels: HashMap::new(),
+            group_by_category: false,
+            service_categories: HashMap::new(),
+            show_forecast: false,
+            progress_header: false,
+            monthly_budget: None,
+            notify_only_over_budget: false,
+            notify_threshold: None,
+            show_overage_date: false,
+            display_currency: None,
+            exchange_rate: None,
+            unit_metric_name: "ユーザー".to_string(),
+            unit_metric_value: None,
+            otlp_endpoint: None,
+            publish_report_event: false,
+            output_json: false,
+            output_csv: false,
+            event_source: "aws-cost-notification".to_string(),
+            event_detail_type: "AWS Cost Report".to_string(),
+            event_bus_name: None,
+            monthly_credit: None,
+            slack_webhook_url: None,
+            slack_webhook_urls: vec![],
+            slack_webhook_secret_arn: None,
+            reporting_timezone: None,
+        }
+    }
+}
+impl Config {
+    /// Read the settings from environment variables
+    /// and the profile selected by `CONFIG_PROFILE`,
+    /// checking that the variables required to run the function are set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        dotenv::dotenv().ok();
+        Config::from_required_vars(|key| dotenv::var(key).ok(), |path| fs::read_to_string(path))
+    }
+
+    /// Read the settings by `get_var`, falling back to the variables of the profile
+    /// (e.g. `prod`) selected by `CONFIG_PROFILE`.
+    ///
+    /// The profiles are read by `read_file` from the JSON file `CONFIG_FILE`
+    /// (`config.json` by default), which maps each profile to its variables
+    /// (e.g. `{"dev":{"MONTHLY_BUDGET":"100"},"prod":{"MONTHLY_BUDGET":"1000"}}`).
+    /// No file is read if `CONFIG_PROFILE` is not set.
+    ///
+    /// The required variables which are not set (e.g. `REPORTING_TIMEZONE`)
+    /// are reported together with all the invalid ones at once.
+    pub fn from_required_vars<F, R>(get_var: F, read_file: R) -> Result<Self, ConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+        R: Fn(&str) -> io::Result<String>,
+    {
+        let (config, mut problems) = Config::parse_vars(with_profile(get_var, read_file)?);
+        problems.extend(config.missing_required_vars());
+        ConfigError::check(config, problems)
+    }
+
+    /// Read the settings by `get_var` and return them with the problems of the variables.
+    ///
+    /// Variables which are not set or empty are regarded as unset.
+    /// The fields of the invalid variables are left as their defaults.
+    fn parse_vars<F>(get_var: F) -> (Self, Vec<String>)
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let vars = VarReader::new(get_var);
+        let default = Config::default();
+
+        let config = Config {
+            cost_metric: vars.parse("COST_METRIC", default.cost_metric),
+            cost_granularity: vars.parse("COST_GRANULARITY", default.cost_granularity),
+            window_alignment: vars.parse("WINDOW_ALIGNMENT", default.window_alignment),
+            filter_linked_account: vars.get("FILTER_LINKED_ACCOUNT"),
+            exclude_credits: vars.parse("EXCLUDE_CREDITS", default.exclude_credits),
+            ranking_tag_key: vars.get("RANKING_TAG_KEY"),
+            ranking_top_n: vars.parse("RANKING_TOP_N", default.ranking_top_n),
+            group_by_tag: vars.get("GROUP_BY_TAG"),
+            slack_rate_limit_max_retries: vars.parse(
+                "SLACK_RATE_LIMIT_MAX_RETRIES",
+                default.slack_rate_limit_max_retries,
+            ),
+            slack_rate_limit_max_wait_secs: vars.parse(
+                "SLACK_RATE_LIMIT_MAX_WAIT_SECS",
+                default.slack_rate_limit_max_wait_secs,
+            ),
+            slack_format: vars.parse("SLACK_FORMAT", default.slack_format),
+            aws_retry_budget: vars.parse("AWS_RETRY_BUDGET", default.aws_retry_budget),
+            ce_region: vars.parse("AWS_CE_REGION", default.ce_region),
+            ce_endpoint_url: vars.get("CE_ENDPOINT_URL"),
+            aws_retry_backoff_millis: vars
+                .parse("AWS_RETRY_BACKOFF_MILLIS", default.aws_retry_backoff_millis),
+            aws_retry_jitter: vars.parse("AWS_RETRY_JITTER", default.aws_retry_jitter),
+            aws_retry_jitter_seed: vars.parse_optional("AWS_RETRY_JITTER_SEED"),
+            notifier: vars.parse("NOTIFIER", default.notifier),
+            dry_run: vars.parse("DRY_RUN", default.dry_run),
+            slack_bot_token: vars.get("SLACK_BOT_TOKEN"),
+            slack_channel: vars.get("SLACK_CHANNEL"),
+            teams_webhook_url: vars.get("TEAMS_WEBHOOK_URL"),
+            discord_webhook_url: vars.get("DISCORD_WEBHOOK_URL"),
+            sns_topic_arn: vars.get("SNS_TOPIC_ARN"),
+            fallback_sink: vars.parse_optional("FALLBACK_SINK"),
+            split_messages: vars.parse("SPLIT_MESSAGES", default.split_messages),
+            message_footer: vars.get("MESSAGE_FOOTER"),
+            always_show_services: parse_list(vars.get("ALWAYS_SHOW_SERVICES")),
+            service_name_prefix: vars.get("SERVICE_NAME_PREFIX"),
+            service_name_pattern: vars.parse_optional("SERVICE_NAME_PATTERN"),
+            service_allowlist: parse_list(vars.get("SERVICE_ALLOWLIST")),
+            service_blocklist: parse_list(vars.get("SERVICE_BLOCKLIST")),
+            show_service_breakdown: vars
+                .parse("SHOW_SERVICE_BREAKDOWN", default.show_service_breakdown),
+            single_cost_request: vars.parse("SINGLE_COST_REQUEST", default.single_cost_request),
+            report_data_transfer: vars.parse("REPORT_DATA_TRANSFER", default.report_data_transfer),
+            data_transfer_patterns: match vars.get("DATA_TRANSFER_PATTERN") {
+                Some(value) => parse_list(Some(value)),
+                None => default.data_transfer_patterns,
+            },
+            metric_decimals: vars.parse_map("METRIC_DECIMALS"),
+            show_top_increasing_usage_type: vars.parse(
+                "SHOW_TOP_INCREASING_USAGE_TYPE",
+                default.show_top_increasing_usage_type,
+            ),
+            top_usage_types: vars.parse_optional("TOP_USAGE_TYPES"),
+            show_usage_quantity: vars.parse("SHOW_USAGE_QUANTITY", default.show_usage_quantity),
+            show_region_costs: vars.parse("SHOW_REGION_COSTS", default.show_region_costs),
+            group_global_regions: vars.parse("GROUP_GLOBAL_REGIONS", default.group_global_regions),
+            show_account_costs: vars.parse("SHOW_ACCOUNT_COSTS", default.show_account_costs),
+            show_utilization: vars.parse("SHOW_UTILIZATION", default.show_utilization),
+            low_utilization_percent: vars
+                .parse("LOW_UTILIZATION_PERCENT", default.low_utilization_percent),
+            show_rightsizing_savings: vars
+                .parse("SHOW_RIGHTSIZING_SAVINGS", default.show_rightsizing_savings),
+            show_generated_at: vars.parse("SHOW_GENERATED_AT", default.show_generated_at),
+            show_daily_costs: vars.parse("SHOW_DAILY_COSTS", default.show_daily_costs),
+            daily_comparison_baseline: vars.parse_optional("DAILY_COMPARISON_BASELINE"),
+            monthly_table_periods: vars.parse_optional("MONTHLY_TABLE_PERIODS"),
+            exclude_incomplete_day: vars
+                .parse("EXCLUDE_INCOMPLETE_DAY", default.exclude_incomplete_day),
+            service_budgets: vars.parse_map("SERVICE_BUDGETS"),
+            idempotency_enabled: vars.parse("IDEMPOTENCY_ENABLED", default.idempotency_enabled),
+            idempotency_table_name: vars.get("IDEMPOTENCY_TABLE_NAME"),
+            show_change_since_last_notification: vars.parse(
+                "SHOW_CHANGE_SINCE_LAST_NOTIFICATION",
+                default.show_change_since_last_notification,
+            ),
+            config_profile: vars.get("CONFIG_PROFILE"),
+            function_name: vars.get("AWS_LAMBDA_FUNCTION_NAME"),
+            fiscal_year_start_month: vars.parse_optional("FISCAL_YEAR_START_MONTH"),
+            unchanged_summary: vars.parse("UNCHANGED_SUMMARY", default.unchanged_summary),
+            unchanged_tolerance_percent: vars.parse(
+                "UNCHANGED_TOLERANCE_PERCENT",
+                default.unchanged_tolerance_percent,
+            ),
+            unchanged_tolerance_amount: vars.parse(
+                "UNCHANGED_TOLERANCE_AMOUNT",
+                default.unchanged_tolerance_amount,
+            ),
+            service_owners: vars.parse_map("SERVICE_OWNERS"),
+            owner_mention_threshold: vars
+                .parse("OWNER_MENTION_THRESHOLD", default.owner_mention_threshold),
+            comparison_attachment: vars
+                .parse("COMPARISON_ATTACHMENT", default.comparison_attachment),
+            group_by_trend: vars.parse("GROUP_BY_TREND", default.group_by_trend),
+            include_dropped_services: vars
+                .parse("INCLUDE_DROPPED_SERVICES", default.include_dropped_services),
+            alert_increase_percent: vars.parse_optional("ALERT_INCREASE_PERCENT"),
+            cost_warn_threshold: vars.parse_optional("COST_WARN_THRESHOLD"),
+            cost_crit_threshold: vars.parse_optional("COST_CRIT_THRESHOLD"),
+            show_month_over_month: vars
+                .parse("SHOW_MONTH_OVER_MONTH", default.show_month_over_month),
+            alert_dropped_services: vars
+                .parse("ALERT_DROPPED_SERVICES", default.alert_dropped_services),
+            metric_divergence_tolerance_percent: vars
+                .parse_optional("METRIC_DIVERGENCE_TOLERANCE_PERCENT"),
+            escape_slack_text: vars.parse("ESCAPE_SLACK_TEXT", default.escape_slack_text),
+            alert_mention: vars.get("ALERT_MENTION"),
+            color_normal: vars.parse_color("COLOR_NORMAL"),
+            color_warning: vars.parse_color("COLOR_WARNING"),
+            color_critical: vars.parse_color("COLOR_CRITICAL"),
+            service_aliases: vars.parse_map("SERVICE_ALIASES"),
+            unit_labels: vars.parse_map("UNIT_LABELS"),
+            group_by_category: vars.parse("GROUP_BY_CATEGORY", default.group_by_category),
+            service_categories: vars.parse_map("SERVICE_CATEGORIES"),
+            show_forecast: vars.parse("SHOW_FORECAST", default.show_forecast),
+            progress_header: vars.parse("PROGRESS_HEADER", default.progress_header),
+            monthly_budget: vars.parse_optional("MONTHLY_BUDGET"),
+            notify_only_over_budget: vars
+                .parse("NOTIFY_ONLY_OVER_BUDGET", default.notify_only_over_budget),
+            notify_threshold: vars.parse_optional("NOTIFY_THRESHOLD"),
+            show_overage_date: vars.parse("SHOW_OVERAGE_DATE", default.show_overage_date),
+            display_currency: vars.get("DISPLAY_CURRENCY"),
+            exchange_rate: vars.parse_optional("EXCHANGE_RATE"),
+            unit_metric_name: vars
+                .get("UNIT_METRIC_NAME")
+                .unwrap_or(default.unit_metric_name),
+            unit_metric_value: vars.parse_optional("UNIT_METRIC_VALUE"),
+            otlp_endpoint: vars.get("OTEL_EXPORTER_OTLP_ENDPOINT"),
+            publish_report_event: vars.parse("PUBLISH_REPORT_EVENT", default.publish_report_event),
+            output_json: vars.parse("OUTPUT_JSON", default.output_json),
+            output_csv: vars.parse("OUTPUT_CSV", default.output_csv),
+            event_source: vars.get("EVENT_SOURCE").unwrap_or(default.event_source),
+            event_detail_type: vars
+                .get("EVENT_DETAIL_TYPE")
+                .unwrap_or(default.event_detail_type),
+            event_bus_name: vars.get("EVENT_BUS_NAME"),
+            slack_webhook_url: vars.get("SLACK_WEBHOOK_URL"),
+            slack_webhook_urls: parse_list(vars.get("SLACK_WEBHOOK_URLS")),
+            slack_webhook_secret_arn: vars.get("SLACK_WEBHOOK_SECRET_ARN"),
+            reporting_timezone: vars.get("REPORTING_TIMEZONE"),
+            monthly_credit: vars.parse_optional("MONTHLY_CREDIT"),
+            min_service_cost: vars.parse_optional("MIN_SERVICE_COST"),
+            max_services: vars.parse_optional("MAX_SERVICES"),
+            others_show_count: vars.parse("OTHERS_SHOW_COUNT", default.others_show_count),
+            others_rounding: vars.parse_optional("OTHERS_ROUNDING"),
+            cost_decimals: vars.parse("COST_DECIMALS", default.cost_decimals),
+            show_percentage: vars.parse("SHOW_PERCENTAGE", default.show_percentage),
+            service_layout: vars.parse("SERVICE_LAYOUT", default.service_layout),
+            locale: vars.parse("LOCALE", default.locale),
+            date_format: vars.get("DATE_FORMAT"),
+        };
+
+        if let Some(month) = config.fiscal_year_start_month {
+            if !(1..=12).contains(&month) {
+                vars.add_problem(format!("Invalid FISCAL_YEAR_START_MONTH {:?}", month));
+            }
+            if config.window_alignment != WindowAlignment::Calendar {
+                vars.add_problem(
+                    "FISCAL_YEAR_START_MONTH must be set with WINDOW_ALIGNMENT=calendar",
+                );
+            }
+        }
+        if let WindowAlignment::Rolling(_) = config.window_alignment {
+            // The forecast and the budget are of the calendar month,
+            // which cannot be added to or compared with the costs of the rolling window.
+            for (key, enabled) in [
+                ("SHOW_FORECAST", config.show_forecast),
+                ("PROGRESS_HEADER", config.progress_header),
+                ("NOTIFY_ONLY_OVER_BUDGET", config.notify_only_over_budget),
+                ("SHOW_OVERAGE_DATE", config.show_overage_date),
+            ] {
+                if enabled {
+                    vars.add_problem(format!(
+                        "{} must be set with WINDOW_ALIGNMENT=calendar",
+                        key
+                    ));
+                }
+            }
+        }
+        match (&config.display_currency, config.exchange_rate) {
+            (Some(_), Some(rate)) if rate <= 0.0 => {
+                vars.add_problem(format!("Invalid EXCHANGE_RATE {:?}", rate));
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                vars.add_problem("DISPLAY_CURRENCY and EXCHANGE_RATE must be set together");
+            }
+            _ => {}
+        }
+        if config.idempotency_enabled && config.idempotency_table_name.is_none() {
+            vars.add_problem("IDEMPOTENCY_TABLE_NAME must be set with IDEMPOTENCY_ENABLED=true");
+        }
+        if config.show_change_since_last_notification && config.idempotency_table_name.is_none() {
+            vars.add_problem(
+                "IDEMPOTENCY_TABLE_NAME must be set with SHOW_CHANGE_SINCE_LAST_NOTIFICATION=true",
+            );
+        }
+        if let Some(rounding) = config.others_rounding {
+            if rounding <= 0.0 {
+                vars.add_problem(format!("Invalid OTHERS_ROUNDING {:?}", rounding));
+            }
+        }
+        if let Some(timezone) = &config.reporting_timezone {
+            if let Err(e) = timezone.parse::<Tz>() {
+                vars.add_problem(format!("Invalid REPORTING_TIMEZONE {:?}: {}", timezone, e));
+            }
+        }
+        if let Some(endpoint_url) = &config.ce_endpoint_url {
+            if let Err(e) = Url::parse(endpoint_url) {
+                vars.add_problem(format!("Invalid CE_ENDPOINT_URL {:?}: {}", endpoint_url, e));
+            }
+        }
+        if let Some(date_format) = &config.date_format {
+            if StrftimeItems::new(date_format).any(|item| item == Item::Error) {
+                vars.add_problem(format!("Invalid DATE_FORMAT {:?}", date_format));
+            }
+        }
+        if config.monthly_table_periods == Some(0) {
+            vars.add_problem("Invalid MONTHLY_TABLE_PERIODS 0");
+        }
+        if config.notify_only_over_budget && config.monthly_budget.is_none() {
+            vars.add_problem("NOTIFY_ONLY_OVER_BUDGET must be set with MONTHLY_BUDGET");
+        }
+        if config.show_overage_date && config.monthly_budget.is_none() {
+            vars.add_problem("SHOW_OVERAGE_DATE must be set with MONTHLY_BUDGET");
+        }
+        if let (Some(warn), Some(crit)) = (config.cost_warn_threshold, config.cost_crit_threshold) {
+            if warn > crit {
+                vars.add_problem("COST_WARN_THRESHOLD must not exceed COST_CRIT_THRESHOLD");
+            }
+        }
+        if config.notifier == NotifierKind::SlackThread
+            && (config.slack_bot_token.is_none() || config.slack_channel.is_none())
+        {
+            vars.add_problem(
+                "NOTIFIER=slack_thread must be set with SLACK_BOT_TOKEN and SLACK_CHANNEL",
+            );
+        }
+        if config.notifier == NotifierKind::Teams && config.teams_webhook_url.is_none() {
+            vars.add_problem("NOTIFIER=teams must be set with TEAMS_WEBHOOK_URL");
+        }
+        if config.notifier == NotifierKind::Discord && config.discord_webhook_url.is_none() {
+            vars.add_problem("NOTIFIER=discord must be set with DISCORD_WEBHOOK_URL");
+        }
+        if config.notifier == NotifierKind::Sns && config.sns_topic_arn.is_none() {
+            vars.add_problem("NOTIFIER=sns must be set with SNS_TOPIC_ARN");
+        }
+        (config, vars.problems.into_inner())
+    }
+
+    /// Problems of the variables required to run the function which are not set.
+    fn missing_required_vars(&self) -> Vec<String> {
+        let mut problems = vec![];
+        if self.reporting_timezone.is_none() {
+            problems.push("REPORTING_TIMEZONE is not set".to_string());
+        }
+        if self.notifier == NotifierKind::Slack
+            && !self.dry_run
+            && self.slack_webhook_url.is_none()
+            && self.slack_webhook_urls.is_empty()
+            && self.slack_webhook_secret_arn.is_none()
+        {
+            problems.push("SLACK_WEBHOOK_URL is not set".to_string());
+        }
+        problems
+    }
+
+    /// Scope of the records in the DynamoDB table (e.g. `CostNotifier/prod`),
+    /// which distinguishes the functions and profiles sharing the table.
+    pub fn store_scope(&self) -> Option<String> {
+        let names: Vec<&str> = [&self.function_name, &self.config_profile]
+            .iter()
+            .filter_map(|x| x.as_deref())
+            .collect();
+        match names.is_empty() {
+            true => None,
+            false => Some(names.join("/")),
+        }
+    }
+}
+
+/// Error of the settings, which lists the problems of all the invalid or missing variables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub problems: Vec<String>,
+}
+impl ConfigError {
+    /// Return `config` if there are no `problems`, or the error listing them.
+    fn check(config: Config, problems: Vec<String>) -> Result<Config, ConfigError> {
+        match problems.is_empty() {
+            true => Ok(config),
+            false => Err(ConfigError { problems }),
+        }
+    }
+}
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid configuration: {}", self.problems.join("; "))
+    }
+}
+impl error::Error for ConfigError {}
+
+/// Reader of the variables which records the problems of the invalid values
+/// instead of failing at the first one.
+struct VarReader<F: Fn(&str) -> Option<String>> {
+    get_var: F,
+    problems: RefCell<Vec<String>>,
+}
+impl<F: Fn(&str) -> Option<String>> VarReader<F> {
+    fn new(get_var: F) -> Self {
+        VarReader {
+            get_var,
+            problems: RefCell::new(vec![]),
+        }
+    }
+
+    /// The value of the variable `key`, which is None if it is not set or empty.
+    fn get(&self, key: &str) -> Option<String> {
+        (self.get_var)(key).filter(|value| !value.trim().is_empty())
+    }
+
+    /// Record the `problem` of a variable, which is reported when all are read.
+    fn add_problem(&self, problem: impl Into<String>) {
+        self.problems.borrow_mut().push(problem.into());
+    }
+
+    /// The parsed value, or None after recording the error.
+    fn record<T>(&self, result: Result<T, Box<dyn error::Error>>) -> Option<T> {
+        result.map_err(|e| self.add_problem(e.to_string())).ok()
+    }
+
+    /// Parse the value of the variable `key`, or return `default` when it is not set.
+    fn parse<T>(&self, key: &str, default: T) -> T
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.parse_optional(key).unwrap_or(default)
+    }
+
+    /// Parse the value of the variable `key`, or return None when it is not set.
+    fn parse_optional<T>(&self, key: &str) -> Option<T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.record(parse_optional_var(&|key: &str| self.get(key), key))
+            .flatten()
+    }
+
+    /// Parse the value of the variable `key` formatted as `{name}={value},...` into a map.
+    fn parse_map<T>(&self, key: &str) -> HashMap<String, T>
+    where
+        T: FromStr,
+        <T as FromStr>::Err: Display,
+    {
+        self.record(parse_map(self.get(key), key))
+            .unwrap_or_default()
+    }
+
+    /// Parse the value of the variable `key` into a hex color.
+    fn parse_color(&self, key: &str) -> Option<HexColor> {
+        self.record(parse_color(self.get(key), key)).flatten()
+    }
+}
+
+/// Combine `get_var` with the variables of the profile selected by `CONFIG_PROFILE`,
+/// which is read by `read_file`. The variables set by `get_var` take precedence.
+fn with_profile<F, R>(
+    get_var: F,
+    read_file: R,
+) -> Result<impl Fn(&str) -> Option<String>, ConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+    R: Fn(&str) -> io::Result<String>,
+{
+    let get_var = move |key: &str| get_var(key).filter(|value| !value.trim().is_empty());
+    let profile = match get_var("CONFIG_PROFILE") {
+        Some(name) => {
+            let path = get_var("CONFIG_FILE").unwrap_or_else(|| DEFAULT_CONFIG_FILE.to_string());
+            let content = read_file(&path).map_err(|e| ConfigError {
+                problems: vec![format!("Failed to read CONFIG_FILE {:?}: {}", path, e)],
+            })?;
+            parse_profile(&content, name.trim()).map_err(|e| ConfigError {
+                problems: vec![e.to_string()],
+            })?
+        }
+        None => HashMap::new(),
+    };
+    Ok(move |key: &str| get_var(key).or_else(|| profile.get(key).cloned()))
+}
+
+/// Parse the value of the variable `key`, or return None when it is not set.
+fn parse_optional_var<F, T>(get_var: &F, key: &str) -> Result<Option<T>, Box<dyn error::Error>>
+where
+    F: Fn(&str) -> Option<String>,
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    match get_var(key) {
+        Some(value) => value
+            .trim()
+            .parse::<T>()
+            .map(Some)
+            .map_err(|e| format!("Invalid {} {:?}: {}", key, value, e).into()),
+        None => Ok(None),
+    }
+}
+
+/// Parse the `value` of the variable `key` into a hex color (e.g. `#36a64f`).
+fn parse_color(
+    value: Option<String>,
+    key: &str,
+) -> Result<Option<HexColor>, Box<dyn error::Error>> {
+    match value {
+        Some(value) => HexColor::try_from(value.trim())
+            .map(Some)
+            .map_err(|e| format!("Invalid {} {:?}: {}", key, value, e).into()),
+        None => Ok(None),
+    }
+}
+
+/// Split the comma-separated `value` into a list, skipping empty items.
+fn parse_list(value: Option<String>) -> Vec<String> {
+    match value {
+        Some(value) => value
+            .split(',')
+            .map(|x| x.trim().to_string())
+            .filter(|x| !x.is_empty())
+            .collect(),
+        None => vec![],
+    }
+}
+
+/// Parse the variables of the profile `name` in the JSON `content` of the config file.
+///
+/// The values may be strings, numbers or booleans.
+fn parse_profile(
+    content: &str,
+    name: &str,
+) -> Result<HashMap<String, String>, Box<dyn error::Error>> {
+    let profiles: HashMap<String, HashMap<String, Value>> =
+        serde_json::from_str(content).map_err(|e| format!("Invalid CONFIG_FILE: {}", e))?;
+    let profile = profiles
+        .get(name)
+        .ok_or_else(|| format!("Profile {:?} not found in CONFIG_FILE", name))?;
+    profile
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(x) => Ok((key.to_string(), x.to_string())),
+            Value::Number(_) | Value::Bool(_) => Ok((key.to_string(), value.to_string())),
+            _ => Err(format!("Invalid {} {} in profile {:?}", key, value, name).into()),
+        })
+        .collect()
+}
+
+/// Parse the comma-separated `value` of the variable `key` formatted as
+/// `{name}={value},...` into a map.
+fn parse_map<T>(
+    value: Option<String>,
+    key: &str,
+) -> Result<HashMap<String, T>, Box<dyn error::Error>>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Display,
+{
+    parse_list(value)
+        .iter()
+        .map(|item| match item.rsplit_once('=') {
+            Some((name, value)) => value
+                .trim()
+                .parse::<T>()
+                .map(|value| (name.trim().to_string(), value))
+                .map_err(|e| format!("Invalid {} {:?}: {}", key, item, e).into()),
+            None => Err(format!("Invalid {} {:?}: `=` not found", key, item).into()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_config {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn read_config(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let (config, problems) = Config::parse_vars(|key| vars.get(key).cloned());
+        ConfigError::check(config, problems)
+    }
+
+    const SAMPLE_PROFILES: &str = r#"{
+        "dev": {"MONTHLY_BUDGET": 100, "ALERT_MENTION": "<@U012AB3CD>", "SHOW_FORECAST": false},
+        "prod": {
+            "MONTHLY_BUDGET": "1000",
+            "ALERT_MENTION": "<!channel>",
+            "SERVICE_NAME_PATTERN": "^Amazon",
+            "SLACK_WEBHOOK_URL": "https://hooks.slack.com/services/prod"
+        }
+    }"#;
+
+    fn read_required_config(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Config::from_required_vars(
+            |key| vars.get(key).cloned(),
+            |_| Err(io::Error::new(io::ErrorKind::NotFound, "not found")),
+        )
+    }
+
+    #[test]
+    fn read_required_vars_correctly() {
+        let actual_config = read_required_config(&[
+            ("REPORTING_TIMEZONE", "Asia/Tokyo"),
+            ("SLACK_WEBHOOK_URL", "https://hooks.slack.com/services/a"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Some("Asia/Tokyo".to_string()),
+            actual_config.reporting_timezone
+        );
+        assert!(read_required_config(
+            &[("REPORTING_TIMEZONE", "Asia/Tokyo"), ("DRY_RUN", "true"),]
+        )
+        .is_ok());
+        assert!(read_required_config(&[
+            ("REPORTING_TIMEZONE", "Asia/Tokyo"),
+            ("SLACK_WEBHOOK_URLS", "https://hooks.slack.com/services/a"),
+        ])
+        .is_ok());
+        assert!(read_required_config(&[
+            ("REPORTING_TIMEZONE", "Asia/Tokyo"),
+            (
+                "SLACK_WEBHOOK_SECRET_ARN",
+                "arn:aws:secretsmanager:ap-northeast-1:123456789012:secret:slack-webhook"
+            ),
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn report_all_missing_required_vars() {
+        assert_eq!(
+            vec![
+                "REPORTING_TIMEZONE is not set".to_string(),
+                "SLACK_WEBHOOK_URL is not set".to_string(),
+            ],
+            read_required_config(&[]).unwrap_err().problems
+        );
+        assert!(read_config(&[]).is_ok());
+    }
+
+    #[test]
+    fn report_invalid_and_missing_vars_at_once() {
+        let actual_error = read_required_config(&[
+            ("COST_METRIC", "Cost"),
+            ("RANKING_TOP_N", "five"),
+            ("REPORTING_TIMEZONE", "Mars/Olympus"),
+            ("NOTIFY_ONLY_OVER_BUDGET", "true"),
+        ])
+        .unwrap_err();
+
+        let problems = &actual_error.problems;
+        assert_eq!(5, problems.len());
+        assert!(problems[0].starts_with("Invalid COST_METRIC \"Cost\""));
+        assert!(problems[1].starts_with("Invalid RANKING_TOP_N \"five\""));
+        assert!(problems[2].starts_with("Invalid REPORTING_TIMEZONE \"Mars/Olympus\""));
+        assert_eq!(
+            "NOTIFY_ONLY_OVER_BUDGET must be set with MONTHLY_BUDGET",
+            problems[3]
+        );
+        assert_eq!("SLACK_WEBHOOK_URL is not set", problems[4]);
+        assert!(actual_error
+            .to_string()
+            .starts_with("Invalid configuration: Invalid COST_METRIC"));
+    }
+
+    fn read_config_with_profile(vars: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let get_var = with_profile(
+            |key| vars.get(key).cloned(),
+            |path| match path {
+                "profiles.json" => Ok(SAMPLE_PROFILES.to_string()),
+                _ => Err(io::Error::new(io::ErrorKind::NotFound, "not found")),
+            },
+        )?;
+        let (config, problems) = Config::parse_vars(get_var);
+        ConfigError::check(config, problems)
+    }
+
+    #[test]
+    fn read_selected_profile_correctly() {
+        let dev_config = read_config_with_profile(&[
+            ("CONFIG_FILE", "profiles.json"),
+            ("CONFIG_PROFILE", "dev"),
+        ])
+        .unwrap();
+        let prod_config = read_config_with_profile(&[
+            ("CONFIG_FILE", "profiles.json"),
+            ("CONFIG_PROFILE", "prod"),
+        ])
+        .unwrap();
+
+        assert_eq!(Some(100.0), dev_config.monthly_budget);
+        assert_eq!(Some("<@U012AB3CD>".to_string()), dev_config.alert_mention);
+        assert_eq!(None, dev_config.service_name_pattern);
+        assert_eq!(Some(1000.0), prod_config.monthly_budget);
+        assert_eq!(Some("<!channel>".to_string()), prod_config.alert_mention);
+        assert_eq!(
+            Some("^Amazon".parse().unwrap()),
+            prod_config.service_name_pattern
+        );
+        assert_eq!(
+            Some("https://hooks.slack.com/services/prod".to_string()),
+            prod_config.slack_webhook_url
+        );
+    }
+
+    #[test]
+    fn read_slack_webhook_urls_correctly() {
+        let actual_config = read_config(&[(
+            "SLACK_WEBHOOK_URLS",
+            "https://hooks.slack.com/services/a, https://hooks.slack.com/services/b",
+        )])
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                "https://hooks.slack.com/services/a".to_string(),
+                "https://hooks.slack.com/services/b".to_string(),
+            ],
+            actual_config.slack_webhook_urls
+        );
+    }
+
+    #[test]
+    fn prefer_variables_to_profile() {
+        let actual_config = read_config_with_profile(&[
+            ("CONFIG_FILE", "profiles.json"),
+            ("CONFIG_PROFILE", "prod"),
+            ("MONTHLY_BUDGET", "2000"),
+        ])
+        .unwrap();
+
+        assert_eq!(Some(2000.0), actual_config.monthly_budget);
+        assert_eq!(Some("<!channel>".to_string()), actual_config.alert_mention);
+    }
+
+    #[test]
+    fn return_error_for_unknown_profile_or_file() {
+        assert!(read_config_with_profile(&[
+            ("CONFIG_FILE", "profiles.json"),
+            ("CONFIG_PROFILE", "staging"),
+        ])
+        .is_err());
+        assert!(read_config_with_profile(&[("CONFIG_PROFILE", "prod")]).is_err());
+        assert_eq!(
+            Config::default(),
+            read_config_with_profile(&[("CONFIG_FILE", "profiles.json")]).unwrap()
+        );
+    }
+
+    #[test]
+    fn use_default_values_when_no_variable_is_set() {
+        let actual_config = read_config(&[]).unwrap();
+
+        assert_eq!(Config::default(), actual_config);
+    }
+
+    #[test]
+    fn read_filter_linked_account_correctly() {
+        assert_eq!(None, read_config(&[]).unwrap().filter_linked_account);
+        assert_eq!(
+            Some("123456789012".to_string()),
+            read_config(&[("FILTER_LINKED_ACCOUNT", "123456789012")])
+                .unwrap()
+                .filter_linked_account
+        );
+    }
+
+    #[test]
+    fn read_exclude_credits_correctly() {
+        assert!(!read_config(&[]).unwrap().exclude_credits);
+        assert!(
+            read_config(&[("EXCLUDE_CREDITS", "true")])
+                .unwrap()
+                .exclude_credits
+        );
+    }
+
+    #[test]
+    fn read_ranking_settings_correctly() {
+        let actual_config =
+            read_config(&[("RANKING_TAG_KEY", "Project"), ("RANKING_TOP_N", "3")]).unwrap();
+
+        assert_eq!(Some("Project".to_string()), actual_config.ranking_tag_key);
+        assert_eq!(3, actual_config.ranking_top_n);
+        assert_eq!(None, actual_config.group_by_tag);
+        assert_eq!(
+            Some("Environment".to_string()),
+            read_config(&[("GROUP_BY_TAG", "Environment")])
+                .unwrap()
+                .group_by_tag
+        );
+    }
+
+    #[test]
+    fn read_slack_format_correctly() {
+        assert_eq!(
+            SlackFormat::Attachments,
+            read_config(&[]).unwrap().slack_format
+        );
+        assert_eq!(
+            SlackFormat::Blocks,
+            read_config(&[("SLACK_FORMAT", "blocks")])
+                .unwrap()
+                .slack_format
+        );
+        assert!(read_config(&[("SLACK_FORMAT", "markdown")]).is_err());
+    }
+
+    #[test]
+    fn read_slack_rate_limit_settings_correctly() {
+        let actual_config = read_config(&[
+            ("SLACK_RATE_LIMIT_MAX_RETRIES", "5"),
+            ("SLACK_RATE_LIMIT_MAX_WAIT_SECS", "60"),
+        ])
+        .unwrap();
+
+        assert_eq!(5, actual_config.slack_rate_limit_max_retries);
+        assert_eq!(60, actual_config.slack_rate_limit_max_wait_secs);
+    }
+
+    #[test]
+    fn read_split_messages_correctly() {
+        let actual_config = read_config(&[("SPLIT_MESSAGES", "true")]).unwrap();
+
+        assert!(actual_config.split_messages);
+    }
+
+    #[test]
+    fn read_always_show_services_correctly() {
+        let actual_config = read_config(&[(
+            "ALWAYS_SHOW_SERVICES",
+            "Amazon Elastic Compute Cloud - Compute, AWS Lambda,",
+        )])
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                "Amazon Elastic Compute Cloud - Compute".to_string(),
+                "AWS Lambda".to_string()
+            ],
+            actual_config.always_show_services
+        );
+    }
+
+    #[test]
+    fn read_service_name_filters_correctly() {
+        let actual_config = read_config(&[
+            ("SERVICE_NAME_PREFIX", "Amazon "),
+            ("SERVICE_NAME_PATTERN", "^AWS (Lambda|Glue)$"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Some("Amazon ".to_string()),
+            actual_config.service_name_prefix
+        );
+        assert_eq!(
+            Some("^AWS (Lambda|Glue)$".parse().unwrap()),
+            actual_config.service_name_pattern
+        );
+        assert!(read_config(&[("SERVICE_NAME_PATTERN", "AWS (Lambda")]).is_err());
+    }
+
+    #[test]
+    fn read_service_lists_correctly() {
+        let actual_config = read_config(&[
+            ("SERVICE_ALLOWLIST", "EC2, Simple Storage"),
+            ("SERVICE_BLOCKLIST", "CloudTrail"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            vec!["EC2".to_string(), "Simple Storage".to_string()],
+            actual_config.service_allowlist
+        );
+        assert_eq!(
+            vec!["CloudTrail".to_string()],
+            actual_config.service_blocklist
+        );
+    }
+
+    #[test]
+    fn read_data_transfer_patterns_correctly() {
+        assert_eq!(
+            vec!["DataTransfer", "-AWS-Out-Bytes", "-AWS-In-Bytes"],
+            read_config(&[]).unwrap().data_transfer_patterns
+        );
+        assert_eq!(
+            vec!["DataTransfer-Out", "CloudFront-Out"],
+            read_config(&[("DATA_TRANSFER_PATTERN", "DataTransfer-Out, CloudFront-Out")])
+                .unwrap()
+                .data_transfer_patterns
+        );
+    }
+
+    #[test]
+    fn read_service_budgets_correctly() {
+        let actual_config = read_config(&[(
+            "SERVICE_BUDGETS",
+            "Amazon Elastic Compute Cloud - Compute=100, AWS Lambda=20.5",
+        )])
+        .unwrap();
+
+        assert_eq!(
+            Some(&100.0),
+            actual_config
+                .service_budgets
+                .get("Amazon Elastic Compute Cloud - Compute")
+        );
+        assert_eq!(Some(&20.5), actual_config.service_budgets.get("AWS Lambda"));
+    }
+
+    #[test]
+    fn return_error_for_invalid_service_budgets() {
+        assert!(read_config(&[("SERVICE_BUDGETS", "AWS Lambda")]).is_err());
+        assert!(read_config(&[("SERVICE_BUDGETS", "AWS Lambda=twenty")]).is_err());
+    }
+
+    #[test]
+    fn read_metric_decimals_correctly() {
+        let actual_config =
+            read_config(&[("METRIC_DECIMALS", "AmortizedCost=0, UsageQuantity=3")]).unwrap();
+
+        assert_eq!(Some(&0), actual_config.metric_decimals.get("AmortizedCost"));
+        assert_eq!(Some(&3), actual_config.metric_decimals.get("UsageQuantity"));
+        assert!(read_config(&[("METRIC_DECIMALS", "UsageQuantity=-1")]).is_err());
+    }
+
+    #[test]
+    fn read_fiscal_year_start_month_correctly() {
+        let actual_config = read_config(&[("FISCAL_YEAR_START_MONTH", "4")]).unwrap();
+
+        assert_eq!(Some(4), actual_config.fiscal_year_start_month);
+    }
+
+    #[test]
+    fn return_error_for_invalid_month() {
+        assert!(read_config(&[("FISCAL_YEAR_START_MONTH", "13")]).is_err());
+        assert!(read_config(&[("FISCAL_YEAR_START_MONTH", "0")]).is_err());
+    }
+
+    #[test]
+    fn read_unchanged_summary_settings_correctly() {
+        let actual_config = read_config(&[
+            ("UNCHANGED_SUMMARY", "true"),
+            ("UNCHANGED_TOLERANCE_PERCENT", "2.5"),
+            ("UNCHANGED_TOLERANCE_AMOUNT", "1.5"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.unchanged_summary);
+        assert_eq!(2.5, actual_config.unchanged_tolerance_percent);
+        assert_eq!(1.5, actual_config.unchanged_tolerance_amount);
+    }
+
+    #[test]
+    fn read_show_top_increasing_usage_type_correctly() {
+        let actual_config = read_config(&[("SHOW_TOP_INCREASING_USAGE_TYPE", "true")]).unwrap();
+
+        assert!(actual_config.show_top_increasing_usage_type);
+    }
+
+    #[test]
+    fn read_region_settings_correctly() {
+        let actual_config = read_config(&[
+            ("SHOW_REGION_COSTS", "true"),
+            ("GROUP_GLOBAL_REGIONS", "false"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.show_region_costs);
+        assert!(!actual_config.group_global_regions);
+    }
+
+    #[test]
+    fn read_monthly_table_periods_correctly() {
+        let actual_config = read_config(&[("MONTHLY_TABLE_PERIODS", "3")]).unwrap();
+
+        assert_eq!(Some(3), actual_config.monthly_table_periods);
+        assert!(read_config(&[("MONTHLY_TABLE_PERIODS", "0")]).is_err());
+    }
+
+    #[test]
+    fn read_show_account_costs_correctly() {
+        let actual_config = read_config(&[("SHOW_ACCOUNT_COSTS", "true")]).unwrap();
+
+        assert!(actual_config.show_account_costs);
+    }
+
+    #[test]
+    fn read_utilization_settings_correctly() {
+        let actual_config = read_config(&[
+            ("SHOW_UTILIZATION", "true"),
+            ("LOW_UTILIZATION_PERCENT", "90"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.show_utilization);
+        assert_eq!(90.0, actual_config.low_utilization_percent);
+    }
+
+    #[test]
+    fn read_show_rightsizing_savings_correctly() {
+        let actual_config = read_config(&[("SHOW_RIGHTSIZING_SAVINGS", "true")]).unwrap();
+
+        assert!(actual_config.show_rightsizing_savings);
+        assert!(!Config::default().show_rightsizing_savings);
+    }
+
+    #[test]
+    fn read_service_owners_correctly() {
+        let actual_config = read_config(&[
+            ("SERVICE_OWNERS", "AWS Lambda=U012AB3CD"),
+            ("OWNER_MENTION_THRESHOLD", "100"),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            Some(&"U012AB3CD".to_string()),
+            actual_config.service_owners.get("AWS Lambda")
+        );
+        assert_eq!(100.0, actual_config.owner_mention_threshold);
+    }
+
+    #[test]
+    fn read_comparison_attachment_correctly() {
+        let actual_config = read_config(&[
+            ("COMPARISON_ATTACHMENT", "true"),
+            ("GROUP_BY_TREND", "true"),
+            ("INCLUDE_DROPPED_SERVICES", "false"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.comparison_attachment);
+        assert!(actual_config.group_by_trend);
+        assert!(!actual_config.include_dropped_services);
+    }
+
+    #[test]
+    fn read_alert_dropped_services_correctly() {
+        let actual_config = read_config(&[("ALERT_DROPPED_SERVICES", "true")]).unwrap();
+
+        assert!(actual_config.alert_dropped_services);
+    }
+
+    #[test]
+    fn read_metric_divergence_tolerance_percent_correctly() {
+        let actual_config = read_config(&[("METRIC_DIVERGENCE_TOLERANCE_PERCENT", "5")]).unwrap();
+
+        assert_eq!(Some(5.0), actual_config.metric_divergence_tolerance_percent);
+    }
+
+    #[test]
+    fn read_show_change_since_last_notification_correctly() {
+        let actual_config = read_config(&[
+            ("SHOW_CHANGE_SINCE_LAST_NOTIFICATION", "true"),
+            ("IDEMPOTENCY_TABLE_NAME", "cost-notification-reports"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.show_change_since_last_notification);
+        assert!(read_config(&[("SHOW_CHANGE_SINCE_LAST_NOTIFICATION", "true")]).is_err());
+    }
+
+    #[test]
+    fn read_daily_comparison_baseline_correctly() {
+        let actual_config =
+            read_config(&[("DAILY_COMPARISON_BASELINE", "same_weekday_last_week")]).unwrap();
+
+        assert_eq!(
+            Some(DailyBaseline::SameWeekdayLastWeek),
+            actual_config.daily_comparison_baseline
+        );
+        assert!(read_config(&[("DAILY_COMPARISON_BASELINE", "last_week")]).is_err());
+    }
+
+    #[test]
+    fn read_top_usage_types_settings_correctly() {
+        let actual_config =
+            read_config(&[("TOP_USAGE_TYPES", "3"), ("SHOW_USAGE_QUANTITY", "true")]).unwrap();
+
+        assert_eq!(Some(3), actual_config.top_usage_types);
+        assert!(actual_config.show_usage_quantity);
+    }
+
+    #[test]
+    fn read_cost_thresholds_correctly() {
+        let actual_config = read_config(&[
+            ("COST_WARN_THRESHOLD", "100"),
+            ("COST_CRIT_THRESHOLD", "500"),
+        ])
+        .unwrap();
+
+        assert_eq!(Some(100.0), actual_config.cost_warn_threshold);
+        assert_eq!(Some(500.0), actual_config.cost_crit_threshold);
+        assert!(read_config(&[
+            ("COST_WARN_THRESHOLD", "500"),
+            ("COST_CRIT_THRESHOLD", "100"),
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn read_show_service_breakdown_correctly() {
+        assert!(read_config(&[]).unwrap().show_service_breakdown);
+        assert!(
+            !read_config(&[("SHOW_SERVICE_BREAKDOWN", "false")])
+                .unwrap()
+                .show_service_breakdown
+        );
+    }
+
+    #[test]
+    fn read_single_cost_request_correctly() {
+        assert!(!read_config(&[]).unwrap().single_cost_request);
+        assert!(
+            read_config(&[("SINGLE_COST_REQUEST", "true")])
+                .unwrap()
+                .single_cost_request
+        );
+    }
+
+    #[test]
+    fn read_cost_metric_correctly() {
+        let actual_config = read_config(&[("COST_METRIC", "UnblendedCost")]).unwrap();
+
+        assert_eq!(CostMetric::Unblended, actual_config.cost_metric);
+        assert_eq!(CostMetric::Amortized, read_config(&[]).unwrap().cost_metric);
+        assert!(read_config(&[("COST_METRIC", "unblended")]).is_err());
+    }
+
+    #[test]
+    fn read_window_alignment_correctly() {
+        assert_eq!(
+            WindowAlignment::Calendar,
+            read_config(&[]).unwrap().window_alignment
+        );
+        assert_eq!(
+            WindowAlignment::Rolling(30),
+            read_config(&[("WINDOW_ALIGNMENT", "rolling_30_days")])
+                .unwrap()
+                .window_alignment
+        );
+        assert_eq!(
+            WindowAlignment::Rolling(7),
+            read_config(&[("WINDOW_ALIGNMENT", "rolling_7_days")])
+                .unwrap()
+                .window_alignment
+        );
+        assert!(read_config(&[("WINDOW_ALIGNMENT", "weekly")]).is_err());
+        assert!(read_config(&[("WINDOW_ALIGNMENT", "rolling_99999999999_days")]).is_err());
+        assert!(read_config(&[
+            ("WINDOW_ALIGNMENT", "rolling_30_days"),
+            ("FISCAL_YEAR_START_MONTH", "4")
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn return_error_for_month_end_projection_with_rolling_window() {
+        for key in [
+            "SHOW_FORECAST",
+            "PROGRESS_HEADER",
+            "NOTIFY_ONLY_OVER_BUDGET",
+            "SHOW_OVERAGE_DATE",
+        ] {
+            let problems = read_config(&[
+                ("WINDOW_ALIGNMENT", "rolling_30_days"),
+                ("MONTHLY_BUDGET", "100"),
+                (key, "true"),
+            ])
+            .unwrap_err()
+            .problems;
+
+            assert_eq!(
+                vec![format!(
+                    "{} must be set with WINDOW_ALIGNMENT=calendar",
+                    key
+                )],
+                problems
+            );
+        }
+        assert!(read_config(&[
+            ("WINDOW_ALIGNMENT", "rolling_30_days"),
+            ("MONTHLY_BUDGET", "100"),
+        ])
+        .is_ok());
+    }
+
+    #[test]
+    fn read_cost_granularity_correctly() {
+        let actual_config = read_config(&[("COST_GRANULARITY", "daily")]).unwrap();
+
+        assert_eq!(Granularity::Daily, actual_config.cost_granularity);
+        assert!(read_config(&[("COST_GRANULARITY", "hourly")]).is_err());
+    }
+
+    #[test]
+    fn read_notifier_correctly() {
+        let actual_config = read_config(&[
+            ("NOTIFIER", "teams"),
+            (
+                "TEAMS_WEBHOOK_URL",
+                "https://example.webhook.office.com/webhookb2/xxx",
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(NotifierKind::Teams, actual_config.notifier);
+        assert_eq!(
+            Some("https://example.webhook.office.com/webhookb2/xxx".to_string()),
+            actual_config.teams_webhook_url
+        );
+        assert!(read_config(&[("NOTIFIER", "line")]).is_err());
+        assert!(read_config(&[("NOTIFIER", "teams")]).is_err());
+    }
+
+    #[test]
+    fn read_slack_thread_notifier_correctly() {
+        let actual_config = read_config(&[
+            ("NOTIFIER", "slack_thread"),
+            ("SLACK_BOT_TOKEN", "xoxb-123"),
+            ("SLACK_CHANNEL", "C0123456789"),
+        ])
+        .unwrap();
+
+        assert_eq!(NotifierKind::SlackThread, actual_config.notifier);
+        assert_eq!(Some("xoxb-123".to_string()), actual_config.slack_bot_token);
+        assert_eq!(Some("C0123456789".to_string()), actual_config.slack_channel);
+        assert!(read_config(&[
+            ("NOTIFIER", "slack_thread"),
+            ("SLACK_BOT_TOKEN", "xoxb-123")
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn read_discord_notifier_correctly() {
+        let actual_config = read_config(&[
+            ("NOTIFIER", "discord"),
+            (
+                "DISCORD_WEBHOOK_URL",
+                "https://discord.com/api/webhooks/123/abc",
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(NotifierKind::Discord, actual_config.notifier);
+        assert_eq!(
+            Some("https://discord.com/api/webhooks/123/abc".to_string()),
+            actual_config.discord_webhook_url
+        );
+        assert!(read_config(&[("NOTIFIER", "discord")]).is_err());
+    }
+
+    #[test]
+    fn read_sns_notifier_correctly() {
+        let actual_config = read_config(&[
+            ("NOTIFIER", "sns"),
+            (
+                "SNS_TOPIC_ARN",
+                "arn:aws:sns:ap-northeast-1:123456789012:cost-notification",
+            ),
+        ])
+        .unwrap();
+
+        assert_eq!(NotifierKind::Sns, actual_config.notifier);
+        assert_eq!(
+            Some("arn:aws:sns:ap-northeast-1:123456789012:cost-notification".to_string()),
+            actual_config.sns_topic_arn
+        );
+        assert!(read_config(&[("NOTIFIER", "sns")]).is_err());
+    }
+
+    #[test]
+    fn read_dry_run_correctly() {
+        assert!(!read_config(&[]).unwrap().dry_run);
+        assert!(read_config(&[("DRY_RUN", "true")]).unwrap().dry_run);
+    }
+
+    #[test]
+    fn read_fallback_sink_correctly() {
+        let actual_config = read_config(&[("FALLBACK_SINK", "stdout")]).unwrap();
+
+        assert_eq!(Some(FallbackSink::Stdout), actual_config.fallback_sink);
+        assert!(read_config(&[("FALLBACK_SINK", "s3")]).is_err());
+    }
+
+    #[test]
+    fn read_escape_slack_text_correctly() {
+        assert!(read_config(&[]).unwrap().escape_slack_text);
+        assert!(
+            !read_config(&[("ESCAPE_SLACK_TEXT", "false")])
+                .unwrap()
+                .escape_slack_text
+        );
+    }
+
+    #[test]
+    fn read_custom_colors_correctly() {
+        let actual_config =
+            read_config(&[("COLOR_NORMAL", "#2eb886"), ("COLOR_WARNING", "#ffcc00")]).unwrap();
+
+        assert_eq!(
+            Some(HexColor::try_from("#2eb886").unwrap()),
+            actual_config.color_normal
+        );
+        assert_eq!(
+            Some(HexColor::try_from("#ffcc00").unwrap()),
+            actual_config.color_warning
+        );
+        assert_eq!(None, actual_config.color_critical);
+    }
+
+    #[test]
+    fn return_error_for_invalid_color() {
+        assert!(read_config(&[("COLOR_CRITICAL", "red")]).is_err());
+        assert!(read_config(&[("COLOR_CRITICAL", "#12345")]).is_err());
+    }
+
+    #[test]
+    fn read_alert_settings_correctly() {
+        let actual_config = read_config(&[
+            ("ALERT_INCREASE_PERCENT", "50"),
+            ("ALERT_MENTION", "<!channel>"),
+        ])
+        .unwrap();
+
+        assert_eq!(Some(50.0), actual_config.alert_increase_percent);
+        assert_eq!(Some("<!channel>".to_string()), actual_config.alert_mention);
+    }
+
+    #[test]
+    fn read_service_aliases_correctly() {
+        let actual_config =
+            read_config(&[("SERVICE_ALIASES", "Amazon EC2=Amazon Elastic Compute Cloud")]).unwrap();
+
+        assert_eq!(
+            Some(&"Amazon Elastic Compute Cloud".to_string()),
+            actual_config.service_aliases.get("Amazon EC2")
+        );
+    }
+
+    #[test]
+    fn read_unit_labels_correctly() {
+        let actual_config = read_config(&[("UNIT_LABELS", "USD=ドル,JPY=円")]).unwrap();
+
+        assert_eq!(
+            Some(&"ドル".to_string()),
+            actual_config.unit_labels.get("USD")
+        );
+        assert_eq!(
+            Some(&"円".to_string()),
+            actual_config.unit_labels.get("JPY")
+        );
+    }
+
+    #[test]
+    fn read_category_settings_correctly() {
+        let actual_config = read_config(&[
+            ("GROUP_BY_CATEGORY", "true"),
+            (
+                "SERVICE_CATEGORIES",
+                "AWS Lambda=Serverless, AWS Glue=Analytics",
+            ),
+        ])
+        .unwrap();
+
+        let mut expected_categories = HashMap::new();
+        expected_categories.insert("AWS Lambda".to_string(), "Serverless".to_string());
+        expected_categories.insert("AWS Glue".to_string(), "Analytics".to_string());
+
+        assert!(actual_config.group_by_category);
+        assert_eq!(expected_categories, actual_config.service_categories);
+    }
+
+    #[test]
+    fn read_currency_settings_correctly() {
+        let actual_config =
+            read_config(&[("DISPLAY_CURRENCY", "JPY"), ("EXCHANGE_RATE", "110.5")]).unwrap();
+
+        assert_eq!(Some("JPY".to_string()), actual_config.display_currency);
+        assert_eq!(Some(110.5), actual_config.exchange_rate);
+    }
+
+    #[test]
+    fn return_error_for_incomplete_currency_settings() {
+        assert!(read_config(&[("DISPLAY_CURRENCY", "JPY")]).is_err());
+        assert!(read_config(&[("EXCHANGE_RATE", "110.5")]).is_err());
+        assert!(read_config(&[("DISPLAY_CURRENCY", "JPY"), ("EXCHANGE_RATE", "0")]).is_err());
+    }
+
+    #[test]
+    fn read_idempotency_settings_correctly() {
+        let actual_config = read_config(&[
+            ("IDEMPOTENCY_ENABLED", "true"),
+            ("IDEMPOTENCY_TABLE_NAME", "cost-notification-reports"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.idempotency_enabled);
+        assert_eq!(
+            Some("cost-notification-reports".to_string()),
+            actual_config.idempotency_table_name
+        );
+        assert!(read_config(&[("IDEMPOTENCY_ENABLED", "true")]).is_err());
+    }
+
+    #[test]
+    fn build_store_scope_from_function_name_and_profile() {
+        let scope = |vars: &[(&str, &str)]| read_config(vars).unwrap().store_scope();
+
+        assert_eq!(None, scope(&[]));
+        assert_eq!(
+            Some("CostNotifier".to_string()),
+            scope(&[("AWS_LAMBDA_FUNCTION_NAME", "CostNotifier")])
+        );
+        assert_eq!(
+            Some("CostNotifier/prod".to_string()),
+            scope(&[
+                ("AWS_LAMBDA_FUNCTION_NAME", "CostNotifier"),
+                ("CONFIG_PROFILE", "prod")
+            ])
+        );
+    }
+
+    #[test]
+    fn read_budget_settings_correctly() {
+        let actual_config = read_config(&[
+            ("MONTHLY_BUDGET", "100"),
+            ("NOTIFY_ONLY_OVER_BUDGET", "true"),
+        ])
+        .unwrap();
+
+        assert_eq!(Some(100.0), actual_config.monthly_budget);
+        assert!(actual_config.notify_only_over_budget);
+    }
+
+    #[test]
+    fn read_notify_threshold_correctly() {
+        assert_eq!(None, read_config(&[]).unwrap().notify_threshold);
+        assert_eq!(
+            Some(50.0),
+            read_config(&[("NOTIFY_THRESHOLD", "50")])
+                .unwrap()
+                .notify_threshold
+        );
+        assert!(read_config(&[("NOTIFY_THRESHOLD", "fifty")]).is_err());
+    }
+
+    #[test]
+    fn return_error_for_notify_only_over_budget_without_budget() {
+        assert!(read_config(&[("NOTIFY_ONLY_OVER_BUDGET", "true")]).is_err());
+    }
+
+    #[test]
+    fn read_show_overage_date_correctly() {
+        let actual_config =
+            read_config(&[("MONTHLY_BUDGET", "100"), ("SHOW_OVERAGE_DATE", "true")]).unwrap();
+
+        assert!(actual_config.show_overage_date);
+        assert!(read_config(&[("SHOW_OVERAGE_DATE", "true")]).is_err());
+    }
+
+    #[test]
+    fn read_unit_metric_correctly() {
+        let actual_config =
+            read_config(&[("UNIT_METRIC_NAME", "注文"), ("UNIT_METRIC_VALUE", "1200")]).unwrap();
+
+        assert_eq!("注文", actual_config.unit_metric_name);
+        assert_eq!(Some(1200.0), actual_config.unit_metric_value);
+    }
+
+    #[test]
+    fn read_aws_retry_settings_correctly() {
+        let actual_config = read_config(&[
+            ("AWS_RETRY_BUDGET", "5"),
+            ("AWS_RETRY_BACKOFF_MILLIS", "200"),
+            ("AWS_RETRY_JITTER", "true"),
+            ("AWS_RETRY_JITTER_SEED", "42"),
+        ])
+        .unwrap();
+
+        assert_eq!(5, actual_config.aws_retry_budget);
+        assert_eq!(200, actual_config.aws_retry_backoff_millis);
+        assert!(actual_config.aws_retry_jitter);
+        assert_eq!(Some(42), actual_config.aws_retry_jitter_seed);
+    }
+
+    #[test]
+    fn read_cost_explorer_endpoint_correctly() {
+        assert_eq!(Region::UsEast1, read_config(&[]).unwrap().ce_region);
+
+        let actual_config = read_config(&[
+            ("AWS_CE_REGION", "us-west-2"),
+            ("CE_ENDPOINT_URL", "http://localhost:4566"),
+        ])
+        .unwrap();
+
+        assert_eq!(Region::UsWest2, actual_config.ce_region);
+        assert_eq!(
+            Some("http://localhost:4566".to_string()),
+            actual_config.ce_endpoint_url
+        );
+        assert!(read_config(&[("AWS_CE_REGION", "mars-1")]).is_err());
+        assert!(read_config(&[("CE_ENDPOINT_URL", "localhost")]).is_err());
+    }
+
+    #[test]
+    fn read_message_footer_correctly() {
+        let actual_config =
+            read_config(&[("MESSAGE_FOOTER", "Dashboard: https://example.com/cost")]).unwrap();
+
+        assert_eq!(
+            Some("Dashboard: https://example.com/cost".to_string()),
+            actual_config.message_footer
+        );
+        assert_eq!(
+            None,
+            read_config(&[("MESSAGE_FOOTER", "")])
+                .unwrap()
+                .message_footer
+        );
+    }
+
+    #[test]
+    fn read_monthly_credit_correctly() {
+        let actual_config = read_config(&[("MONTHLY_CREDIT", "100.5")]).unwrap();
+
+        assert_eq!(Some(100.5), actual_config.monthly_credit);
+    }
+
+    #[test]
+    fn read_min_service_cost_correctly() {
+        let actual_config = read_config(&[("MIN_SERVICE_COST", "1.0")]).unwrap();
+
+        assert_eq!(Some(1.0), actual_config.min_service_cost);
+        assert!(read_config(&[("MIN_SERVICE_COST", "one")]).is_err());
+    }
+
+    #[test]
+    fn read_max_services_correctly() {
+        let actual_config = read_config(&[("MAX_SERVICES", "10")]).unwrap();
+
+        assert_eq!(Some(10), actual_config.max_services);
+        assert!(read_config(&[("MAX_SERVICES", "-1")]).is_err());
+    }
+
+    #[test]
+    fn read_cost_decimals_correctly() {
+        assert_eq!(2, read_config(&[]).unwrap().cost_decimals);
+        assert_eq!(
+            4,
+            read_config(&[("COST_DECIMALS", "4")])
+                .unwrap()
+                .cost_decimals
+        );
+        assert!(read_config(&[("COST_DECIMALS", "-1")]).is_err());
+    }
+
+    #[test]
+    fn read_others_label_options_correctly() {
+        let actual_config =
+            read_config(&[("OTHERS_SHOW_COUNT", "false"), ("OTHERS_ROUNDING", "10")]).unwrap();
+
+        assert!(!actual_config.others_show_count);
+        assert_eq!(Some(10.0), actual_config.others_rounding);
+        assert!(read_config(&[]).unwrap().others_show_count);
+        assert!(read_config(&[("OTHERS_ROUNDING", "0")]).is_err());
+    }
+
+    #[test]
+    fn read_service_layout_correctly() {
+        let actual_config = read_config(&[("SERVICE_LAYOUT", "aligned")]).unwrap();
+
+        assert_eq!(ServiceLayout::Aligned, actual_config.service_layout);
+        assert!(read_config(&[("SERVICE_LAYOUT", "table")]).is_err());
+    }
+
+    #[test]
+    fn read_show_percentage_correctly() {
+        assert!(!read_config(&[]).unwrap().show_percentage);
+        assert!(
+            read_config(&[("SHOW_PERCENTAGE", "true")])
+                .unwrap()
+                .show_percentage
+        );
+    }
+
+    #[test]
+    fn read_locale_correctly() {
+        assert_eq!(Locale::Ja, read_config(&[]).unwrap().locale);
+        assert_eq!(Locale::En, read_config(&[("LOCALE", "en")]).unwrap().locale);
+        assert!(read_config(&[("LOCALE", "fr")]).is_err());
+    }
+
+    #[test]
+    fn read_date_format_correctly() {
+        assert_eq!(None, read_config(&[]).unwrap().date_format);
+        assert_eq!(
+            Some("%Y-%m-%d".to_string()),
+            read_config(&[("DATE_FORMAT", "%Y-%m-%d")])
+                .unwrap()
+                .date_format
+        );
+        assert!(read_config(&[("DATE_FORMAT", "%Q")]).is_err());
+    }
+
+    #[test]
+    fn read_otlp_endpoint_correctly() {
+        let actual_config =
+            read_config(&[("OTEL_EXPORTER_OTLP_ENDPOINT", "http://localhost:4318")]).unwrap();
+
+        assert_eq!(
+            Some("http://localhost:4318".to_string()),
+            actual_config.otlp_endpoint
+        );
+    }
+
+    #[test]
+    fn read_event_settings_correctly() {
+        let actual_config = read_config(&[
+            ("PUBLISH_REPORT_EVENT", "true"),
+            ("EVENT_SOURCE", "com.example.cost"),
+            ("EVENT_BUS_NAME", "cost-events"),
+        ])
+        .unwrap();
+
+        assert!(actual_config.publish_report_event);
+        assert_eq!("com.example.cost", actual_config.event_source);
+        assert_eq!("AWS Cost Report", actual_config.event_detail_type);
+        assert_eq!(
+            Some("cost-events".to_string()),
+            actual_config.event_bus_name
+        );
+    }
+
+    #[test]
+    fn read_output_json_correctly() {
+        assert!(!read_config(&[]).unwrap().output_json);
+        assert!(read_config(&[("OUTPUT_JSON", "true")]).unwrap().output_json);
+        assert!(read_config(&[("OUTPUT_JSON", "yes")]).is_err());
+    }
+
+    #[test]
+    fn read_output_csv_correctly() {
+        assert!(!read_config(&[]).unwrap().output_csv);
+        assert!(read_config(&[("OUTPUT_CSV", "true")]).unwrap().output_csv);
+    }
+
+    #[test]
+    fn return_error_for_invalid_number() {
+        let actual_config = read_config(&[("RANKING_TOP_N", "three")]);
+
+        assert!(actual_config.is_err());
+    }
+}
@@ -1,18 +1,174 @@
+/// Fixed-point decimal amounts of the costs.
+pub mod amount;
 /// Parse the CostExplorer API Response
 pub mod cost_response_parser;
 /// Client to retrieve the AWS costs.
 /// It wraps [CostExplorerClient](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html).
 pub mod cost_usage_client;
+/// Errors in retrieving the AWS costs.
+pub mod error;
+/// Retry the failed requests within the budget shared by all of them.
+pub mod retry;
 /// Functions and structs used for tests.
+#[cfg(test)]
 pub mod test_utils;
 
 use chrono::TimeZone;
-use rusoto_ce::{GetCostAndUsageRequest, GroupDefinition};
+use rusoto_ce::{
+    DimensionValues, Expression, GetCostAndUsageRequest, GetCostAndUsageResponse,
+    GetCostForecastRequest, GetReservationUtilizationError, GetReservationUtilizationRequest,
+    GetRightsizingRecommendationRequest, GetSavingsPlansUtilizationError,
+    GetSavingsPlansUtilizationRequest, GroupDefinition,
+};
+use rusoto_core::RusotoError;
 use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::reporting_date::{DailyBaseline, ReportDateRange};
+use cost_response_parser::{
+    AccountCost, DailyCost, ForecastCost, MonthlyCost, RegionCost, SavingsOpportunity, ServiceCost,
+    TagCost, TotalCost, UsageTypeCost, Utilization,
+};
+use cost_usage_client::{
+    GetCostAndUsage, GetCostForecast, GetRightsizingRecommendation, GetUtilization,
+};
+use error::{CostNotificationError, ParseError};
+
+/// Key to group the costs by in the request.
+#[derive(Debug, PartialEq, Clone)]
+pub enum GroupBy {
+    /// Group by AWS services.
+    Service,
+    /// Group by values of the cost allocation tag with the designated key.
+    Tag(String),
+    /// Group by usage types (e.g. `APN1-DataTransfer-Out-Bytes`).
+    UsageType,
+    /// Group by regions (e.g. `ap-northeast-1`).
+    Region,
+    /// Group by linked account IDs (e.g. `123456789012`).
+    LinkedAccount,
+}
+impl From<&GroupBy> for GroupDefinition {
+    fn from(from: &GroupBy) -> GroupDefinition {
+        match from {
+            GroupBy::Service => GroupDefinition {
+                type_: Some("DIMENSION".to_string()),
+                key: Some("SERVICE".to_string()),
+            },
+            GroupBy::Tag(tag_key) => GroupDefinition {
+                type_: Some("TAG".to_string()),
+                key: Some(tag_key.to_string()),
+            },
+            GroupBy::UsageType => GroupDefinition {
+                type_: Some("DIMENSION".to_string()),
+                key: Some("USAGE_TYPE".to_string()),
+            },
+            GroupBy::Region => GroupDefinition {
+                type_: Some("DIMENSION".to_string()),
+                key: Some("REGION".to_string()),
+            },
+            GroupBy::LinkedAccount => GroupDefinition {
+                type_: Some("DIMENSION".to_string()),
+                key: Some("LINKED_ACCOUNT".to_string()),
+            },
+        }
+    }
+}
+
+/// Time granularity of the costs in the request.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Granularity {
+    Daily,
+    Monthly,
+}
+impl FromStr for Granularity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily" => Ok(Granularity::Daily),
+            "monthly" => Ok(Granularity::Monthly),
+            _ => Err("must be daily or monthly".to_string()),
+        }
+    }
+}
+impl Granularity {
+    /// Value of `granularity` field in the request.
+    fn as_str(&self) -> &'static str {
+        match self {
+            Granularity::Daily => "DAILY",
+            Granularity::Monthly => "MONTHLY",
+        }
+    }
+}
+
+/// Cost metric requested to CostExplorer API and read from its response.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum CostMetric {
+    /// `AmortizedCost`
+    #[default]
+    Amortized,
+    /// `UnblendedCost`
+    Unblended,
+    /// `BlendedCost`
+    Blended,
+    /// `NetAmortizedCost`
+    NetAmortized,
+    /// `NetUnblendedCost`
+    NetUnblended,
+}
+impl CostMetric {
+    const ALL: [CostMetric; 5] = [
+        CostMetric::Amortized,
+        CostMetric::Unblended,
+        CostMetric::Blended,
+        CostMetric::NetAmortized,
+        CostMetric::NetUnblended,
+    ];
+
+    /// Name of the metric in GetCostAndUsage API (e.g. `AmortizedCost`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostMetric::Amortized => "AmortizedCost",
+            CostMetric::Unblended => "UnblendedCost",
+            CostMetric::Blended => "BlendedCost",
+            CostMetric::NetAmortized => "NetAmortizedCost",
+            CostMetric::NetUnblended => "NetUnblendedCost",
+        }
+    }
+
+    /// Name of the metric in GetCostForecast API (e.g. `AMORTIZED_COST`).
+    fn forecast_metric(&self) -> &'static str {
+        match self {
+            CostMetric::Amortized => "AMORTIZED_COST",
+            CostMetric::Unblended => "UNBLENDED_COST",
+            CostMetric::Blended => "BLENDED_COST",
+            CostMetric::NetAmortized => "NET_AMORTIZED_COST",
+            CostMetric::NetUnblended => "NET_UNBLENDED_COST",
+        }
+    }
+}
+impl FromStr for CostMetric {
+    type Err = String;
 
-use crate::reporting_date::ReportDateRange;
-use cost_response_parser::{ServiceCost, TotalCost};
-use cost_usage_client::GetCostAndUsage;
+    /// Parse the name of the metric in GetCostAndUsage API (e.g. `UnblendedCost`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CostMetric::ALL
+            .iter()
+            .find(|x| x.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                format!(
+                    "must be one of {}",
+                    CostMetric::ALL
+                        .iter()
+                        .map(|x| x.as_str())
+                        .collect::<Vec<&str>>()
+                        .join(", ")
+                )
+            })
+    }
+}
 
 /// Object to send request to CostExplorer API and retrieve AWS costs.
 pub struct CostExplorerService<C: GetCostAndUsage, T>
@@ -24,6 +180,13 @@ where
     client: C,
     /// The date period to retrieve the costs.
     report_date_range: ReportDateRange<T>,
+    /// The metric of the costs.
+    metric: CostMetric,
+    /// Time granularity in which the total and service costs are requested.
+    granularity: Granularity,
+    /// Filters of the costs applied to the requests together.
+    /// (e.g. a linked account)
+    filters: Vec<Expression>,
 }
 impl<C: GetCostAndUsage, T> CostExplorerService<C, T>
 where
@@ -31,57 +194,506 @@ where
     <T as chrono::TimeZone>::Offset: Display,
 {
     /// Constructor method
-    pub fn new(client: C, report_date_range: ReportDateRange<T>) -> Self {
+    pub fn new(client: C, report_date_range: ReportDateRange<T>, metric: CostMetric) -> Self {
         CostExplorerService {
-            client: client,
-            report_date_range: report_date_range,
+            client,
+            report_date_range,
+            metric,
+            granularity: Granularity::Monthly,
+            filters: vec![],
+        }
+    }
+
+    /// Set the time granularity in which the total and service costs are requested.
+    ///
+    /// The costs of DAILY granularity are summed up over the period after parsing.
+    pub fn granularity(mut self, granularity: Granularity) -> Self {
+        self.granularity = granularity;
+        self
+    }
+
+    /// Restrict the costs to those of the linked account `account_id` if given.
+    pub fn linked_account(mut self, account_id: Option<&str>) -> Self {
+        self.filters.extend(account_id.map(build_account_filter));
+        self
+    }
+
+    /// Exclude credits and refunds from the costs if `exclude` is true.
+    pub fn exclude_credits(mut self, exclude: bool) -> Self {
+        if exclude {
+            self.filters.push(build_credits_exclusion_filter());
         }
+        self
+    }
+
+    /// The filter applied to the requests, which requires all the `filters`.
+    fn filter(&self) -> Option<Expression> {
+        combine_filters(&self.filters)
     }
 
     /// Sends request to GetCostAndUsage endpoint of CostExplorer API
     /// and returns parsed total cost.
-    pub async fn request_total_cost(&self) -> TotalCost {
-        let request: GetCostAndUsageRequest =
-            build_cost_and_usage_request(&self.report_date_range, true);
-
-        let res = self.client.get_cost_and_usage(request).await.unwrap();
-        res.into()
+    ///
+    /// If the period spans multiple months, the costs of all the months are summed up.
+    pub async fn request_total_cost(&self) -> Result<TotalCost<T>, CostNotificationError> {
+        self.request_total_cost_in(&self.report_date_range).await
     }
 
     /// Sends request to GetCostAndUsage endpoint of CostExplorer API
     /// and returns a vector of parsed service costs.
-    pub async fn request_service_costs(&self) -> Vec<ServiceCost> {
-        let request: GetCostAndUsageRequest =
-            build_cost_and_usage_request(&self.report_date_range, false);
-        let res = self.client.get_cost_and_usage(request).await.unwrap();
-        ServiceCost::from_response(&res)
+    pub async fn request_service_costs(&self) -> Result<Vec<ServiceCost>, CostNotificationError> {
+        self.request_service_costs_in(&self.report_date_range).await
+    }
+
+    /// Sends a request grouped by service to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns the total cost, summed up from the service costs, with the service costs.
+    ///
+    /// It halves the requests of `request_total_cost` and `request_service_costs`,
+    /// each of which is charged.
+    pub async fn request_all(
+        &self,
+    ) -> Result<(TotalCost<T>, Vec<ServiceCost>), CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range,
+            Some(&GroupBy::Service),
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.request_all_pages(request).await?;
+        let service_costs = ServiceCost::try_from_response(&res, self.metric)?;
+        let total_cost =
+            TotalCost::try_from_service_costs((&self.report_date_range).into(), &service_costs)?;
+        Ok((total_cost, service_costs))
+    }
+
+    /// Returns parsed total cost of the same period in the previous month.
+    pub async fn request_previous_period_total(
+        &self,
+    ) -> Result<TotalCost<T>, CostNotificationError> {
+        self.request_total_cost_in(&self.report_date_range.previous_period())
+            .await
+    }
+
+    /// Returns a vector of parsed service costs of the same period in the previous month.
+    pub async fn request_previous_period_service_costs(
+        &self,
+    ) -> Result<Vec<ServiceCost>, CostNotificationError> {
+        self.request_service_costs_in(&self.report_date_range.previous_period())
+            .await
+    }
+
+    /// Returns parsed total costs of the `baseline` day
+    /// and of the latest day of the period which is compared with it.
+    pub async fn request_daily_comparison(
+        &self,
+        baseline: DailyBaseline,
+    ) -> Result<(TotalCost<T>, TotalCost<T>), CostNotificationError> {
+        let latest_day = self.report_date_range.latest_day();
+        let baseline_total = self
+            .request_total_cost_in(&latest_day.days_before(baseline.days()))
+            .await?;
+        let latest_total = self.request_total_cost_in(&latest_day).await?;
+        Ok((baseline_total, latest_total))
+    }
+
+    async fn request_total_cost_in(
+        &self,
+        date_range: &ReportDateRange<T>,
+    ) -> Result<TotalCost<T>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            date_range,
+            None,
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+
+        let res = self.client.get_cost_and_usage(request).await?;
+        Ok(parse_total_cost(&res, date_range, self.metric)?)
+    }
+
+    async fn request_service_costs_in(
+        &self,
+        date_range: &ReportDateRange<T>,
+    ) -> Result<Vec<ServiceCost>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            date_range,
+            Some(&GroupBy::Service),
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.request_all_pages(request).await?;
+        Ok(ServiceCost::try_from_response(&res, self.metric)?)
+    }
+
+    /// Sends `request` repeatedly with `next_page_token` of the previous response
+    /// until the last page, and returns the response whose results have
+    /// the groups of all the pages.
+    async fn request_all_pages(
+        &self,
+        mut request: GetCostAndUsageRequest,
+    ) -> Result<GetCostAndUsageResponse, CostNotificationError> {
+        let mut res = self.client.get_cost_and_usage(request.clone()).await?;
+        while let Some(token) = res.next_page_token.take() {
+            request.next_page_token = Some(token);
+            let mut next_page = self.client.get_cost_and_usage(request.clone()).await?;
+            res.next_page_token = next_page.next_page_token.take();
+            merge_results_by_time(&mut res, next_page);
+        }
+        Ok(res)
+    }
+
+    /// Sends request to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns a vector of parsed costs for each value of the tag `tag_key`.
+    pub async fn request_tag_costs(
+        &self,
+        tag_key: &str,
+    ) -> Result<Vec<TagCost>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range,
+            Some(&GroupBy::Tag(tag_key.to_string())),
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.request_all_pages(request).await?;
+        Ok(TagCost::try_from_response(&res, self.metric)?)
+    }
+
+    /// Sends request to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns a vector of parsed costs for each usage type.
+    pub async fn request_usage_type_costs(
+        &self,
+    ) -> Result<Vec<UsageTypeCost>, CostNotificationError> {
+        self.request_usage_type_costs_in(&self.report_date_range)
+            .await
+    }
+
+    /// Returns a vector of parsed costs for each usage type
+    /// of the same period in the previous month.
+    pub async fn request_previous_period_usage_type_costs(
+        &self,
+    ) -> Result<Vec<UsageTypeCost>, CostNotificationError> {
+        self.request_usage_type_costs_in(&self.report_date_range.previous_period())
+            .await
+    }
+
+    async fn request_usage_type_costs_in(
+        &self,
+        date_range: &ReportDateRange<T>,
+    ) -> Result<Vec<UsageTypeCost>, CostNotificationError> {
+        let mut request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            date_range,
+            Some(&GroupBy::UsageType),
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        request.metrics.push("UsageQuantity".to_string());
+        let res = self.request_all_pages(request).await?;
+        Ok(UsageTypeCost::try_from_response(&res, self.metric)?)
+    }
+
+    /// Sends request to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns a vector of parsed costs for each region.
+    pub async fn request_region_costs(&self) -> Result<Vec<RegionCost>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range,
+            Some(&GroupBy::Region),
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.request_all_pages(request).await?;
+        Ok(RegionCost::try_from_response(&res, self.metric)?)
+    }
+
+    /// Sends request to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns a vector of parsed total costs of the last `months` months
+    /// up to the reporting date.
+    pub async fn request_monthly_costs(
+        &self,
+        months: u32,
+    ) -> Result<Vec<MonthlyCost<T>>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range.recent_months(months),
+            None,
+            Granularity::Monthly,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.client.get_cost_and_usage(request).await?;
+        Ok(MonthlyCost::try_from_response(
+            &res,
+            &self.report_date_range.timezone(),
+            self.metric,
+        )?)
+    }
+
+    /// Sends request to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns a vector of parsed costs for each linked account with its name.
+    pub async fn request_account_costs(&self) -> Result<Vec<AccountCost>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range,
+            Some(&GroupBy::LinkedAccount),
+            self.granularity,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.request_all_pages(request).await?;
+        Ok(AccountCost::try_from_response(&res, self.metric)?)
+    }
+
+    /// Sends request to GetCostAndUsage endpoint of CostExplorer API
+    /// and returns a vector of parsed total costs for each day.
+    pub async fn request_daily_costs(&self) -> Result<Vec<DailyCost<T>>, CostNotificationError> {
+        let request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range,
+            None,
+            Granularity::Daily,
+            self.metric,
+            self.filter().as_ref(),
+        );
+        let res = self.client.get_cost_and_usage(request).await?;
+        Ok(DailyCost::try_from_response(
+            &res,
+            &self.report_date_range.timezone(),
+            self.metric,
+        )?)
+    }
+}
+
+impl<C: GetCostAndUsage + GetCostForecast, T> CostExplorerService<C, T>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    /// Returns parsed total costs of AmortizedCost and UnblendedCost,
+    /// which are requested together to cross-check each other.
+    pub async fn request_amortized_and_unblended_totals(
+        &self,
+    ) -> Result<(TotalCost<T>, TotalCost<T>), CostNotificationError> {
+        let mut request: GetCostAndUsageRequest = build_cost_and_usage_request(
+            &self.report_date_range,
+            None,
+            self.granularity,
+            CostMetric::Amortized,
+            self.filter().as_ref(),
+        );
+        request
+            .metrics
+            .push(CostMetric::Unblended.as_str().to_string());
+
+        let res = self.client.get_cost_and_usage(request).await?;
+        Ok((
+            parse_total_cost(&res, &self.report_date_range, CostMetric::Amortized)?,
+            parse_total_cost(&res, &self.report_date_range, CostMetric::Unblended)?,
+        ))
+    }
+
+    /// Sends request to GetCostForecast endpoint of CostExplorer API
+    /// and returns parsed forecast from the reporting date to the end of the month.
+    ///
+    /// Returns None if the reported month has already ended.
+    pub async fn request_forecast(&self) -> Result<Option<ForecastCost>, CostNotificationError> {
+        let forecast_period = match self.report_date_range.forecast_period() {
+            Some(period) => period,
+            None => return Ok(None),
+        };
+        let request = GetCostForecastRequest {
+            filter: self.filter(),
+            granularity: Granularity::Monthly.as_str().to_string(),
+            metric: self.metric.forecast_metric().to_string(),
+            prediction_interval_level: None,
+            time_period: (&forecast_period).into(),
+        };
+        let res = self.client.get_cost_forecast(request).await?;
+        Ok(Some(ForecastCost::try_from_response(&res)?))
+    }
+}
+
+impl<C: GetCostAndUsage + GetUtilization, T> CostExplorerService<C, T>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    /// Sends request to GetSavingsPlansUtilization endpoint of CostExplorer API
+    /// and returns parsed utilization in the reported period.
+    ///
+    /// Returns None if no Savings Plans are active (`DataUnavailable`).
+    pub async fn request_savings_plans_utilization(
+        &self,
+    ) -> Result<Option<Utilization>, CostNotificationError> {
+        let request = GetSavingsPlansUtilizationRequest {
+            time_period: (&self.report_date_range).into(),
+            ..Default::default()
+        };
+        match self.client.get_savings_plans_utilization(request).await {
+            Ok(res) => Ok(Utilization::from_savings_plans_response(&res)),
+            Err(RusotoError::Service(GetSavingsPlansUtilizationError::DataUnavailable(_))) => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Sends request to GetReservationUtilization endpoint of CostExplorer API
+    /// and returns parsed utilization in the reported period.
+    ///
+    /// Returns None if no reservations are active (`DataUnavailable`).
+    pub async fn request_reservation_utilization(
+        &self,
+    ) -> Result<Option<Utilization>, CostNotificationError> {
+        let request = GetReservationUtilizationRequest {
+            time_period: (&self.report_date_range).into(),
+            ..Default::default()
+        };
+        match self.client.get_reservation_utilization(request).await {
+            Ok(res) => Ok(Utilization::from_reservation_response(&res)),
+            Err(RusotoError::Service(GetReservationUtilizationError::DataUnavailable(_))) => {
+                Ok(None)
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Service whose rightsizing recommendations are requested.
+/// Only EC2 is supported by GetRightsizingRecommendation endpoint.
+const RIGHTSIZING_SERVICE: &str = "AmazonEC2";
+
+impl<C: GetCostAndUsage + GetRightsizingRecommendation, T> CostExplorerService<C, T>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    /// Sends request to GetRightsizingRecommendation endpoint of CostExplorer API
+    /// and returns the estimated monthly savings summarized over the account.
+    ///
+    /// Returns None if the response has no summary.
+    pub async fn request_rightsizing_savings(
+        &self,
+    ) -> Result<Option<SavingsOpportunity>, CostNotificationError> {
+        let request = GetRightsizingRecommendationRequest {
+            service: RIGHTSIZING_SERVICE.to_string(),
+            ..Default::default()
+        };
+        let res = self.client.get_rightsizing_recommendation(request).await?;
+        Ok(SavingsOpportunity::from_response(&res))
+    }
+}
+
+/// Parse the total cost of `metric` in the response to the request of `date_range`.
+///
+/// It is zero during `date_range` if the response has no results,
+/// which happens for an account with no spend yet.
+fn parse_total_cost<T>(
+    res: &GetCostAndUsageResponse,
+    date_range: &ReportDateRange<T>,
+    metric: CostMetric,
+) -> Result<TotalCost<T>, ParseError>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    if res.results_by_time.as_deref().is_none_or(|x| x.is_empty()) {
+        return Ok(TotalCost::zero(date_range.into()));
+    }
+    TotalCost::try_from_response(res, &date_range.timezone(), metric)
+}
+
+/// Build the filter which restricts the costs to the linked account `account_id`.
+fn build_account_filter(account_id: &str) -> Expression {
+    Expression {
+        dimensions: Some(DimensionValues {
+            key: Some("LINKED_ACCOUNT".to_string()),
+            values: Some(vec![account_id.to_string()]),
+            match_options: None,
+        }),
+        ..Default::default()
+    }
+}
+
+/// Record types excluded by `build_credits_exclusion_filter`.
+const CREDIT_RECORD_TYPES: [&str; 2] = ["Credit", "Refund"];
+
+/// Build the filter which excludes credits and refunds from the costs.
+fn build_credits_exclusion_filter() -> Expression {
+    Expression {
+        not: Box::new(Some(Expression {
+            dimensions: Some(DimensionValues {
+                key: Some("RECORD_TYPE".to_string()),
+                values: Some(CREDIT_RECORD_TYPES.iter().map(|x| x.to_string()).collect()),
+                match_options: None,
+            }),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+}
+
+/// Combine `filters` so that the costs must match all of them.
+/// Returns None if there are no filters.
+fn combine_filters(filters: &[Expression]) -> Option<Expression> {
+    match filters {
+        [] => None,
+        [filter] => Some(filter.clone()),
+        _ => Some(Expression {
+            and: Some(filters.to_vec()),
+            ..Default::default()
+        }),
+    }
+}
+
+/// Merge the results of `next_page` into those of `res`.
+/// The groups of the same period are appended to the result of that period,
+/// and the attributes of the dimension values (e.g. account names) are appended.
+fn merge_results_by_time(res: &mut GetCostAndUsageResponse, next_page: GetCostAndUsageResponse) {
+    if let Some(attributes) = next_page.dimension_value_attributes {
+        res.dimension_value_attributes
+            .get_or_insert_with(Vec::new)
+            .extend(attributes);
+    }
+    let results_by_time = res.results_by_time.get_or_insert_with(Vec::new);
+    for result_by_time in next_page.results_by_time.into_iter().flatten() {
+        match results_by_time
+            .iter_mut()
+            .find(|x| x.time_period == result_by_time.time_period)
+        {
+            Some(x) => x
+                .groups
+                .get_or_insert_with(Vec::new)
+                .extend(result_by_time.groups.into_iter().flatten()),
+            None => results_by_time.push(result_by_time),
+        }
     }
 }
 
 /// Build the request object of the CostExplorer API.
 /// The data aquisition period is designated by `report_date_range`.
-/// If `is_total` is true, it builds request for total cost.
-/// Otherwise, it requests the costs grouped by AWS services.
+/// If `group_by` is None, it builds request for total cost.
+/// Otherwise, it requests the costs grouped by the designated key.
+/// The costs are aggregated by `granularity` and requested as `metric`.
+/// If `filter` is given, only the costs matching it are requested.
 fn build_cost_and_usage_request<T>(
     report_date_range: &ReportDateRange<T>,
-    is_total: bool,
+    group_by: Option<&GroupBy>,
+    granularity: Granularity,
+    metric: CostMetric,
+    filter: Option<&Expression>,
 ) -> GetCostAndUsageRequest
 where
     T: TimeZone,
     <T as chrono::TimeZone>::Offset: Display,
 {
-    let group_by: Option<Vec<GroupDefinition>> = match is_total {
-        true => None,
-        false => Some(vec![GroupDefinition {
-            type_: Some("DIMENSION".to_string()),
-            key: Some("SERVICE".to_string()),
-        }]),
-    };
+    let group_by: Option<Vec<GroupDefinition>> = group_by.map(|x| vec![x.into()]);
     GetCostAndUsageRequest {
-        filter: None,
-        granularity: "MONTHLY".to_string(),
-        group_by: group_by,
-        metrics: vec!["AmortizedCost".to_string()],
+        filter: filter.cloned(),
+        granularity: granularity.as_str().to_string(),
+        group_by,
+        metrics: vec![metric.as_str().to_string()],
         next_page_token: None,
         time_period: report_date_range.into(),
     }
@@ -92,19 +704,23 @@ mod test_cost_explorer_service {
 
     use super::*;
     use crate::reporting_date::ReportDateRange;
-    use chrono::{Local, TimeZone};
-    use cost_response_parser::{Cost, ReportedDateRange};
-    use test_utils::{CostAndUsageClientStub, InputServiceCost};
-    use tokio;
+    use amount::Amount;
+    use chrono::{Date, Local, TimeZone};
+    use chrono_tz::Asia::Tokyo;
+    use cost_response_parser::{Cost, ReportedDateRange, SavingsOpportunity, Utilization};
+    use std::sync::atomic::AtomicU32;
+    use test_utils::{CostAndUsageClientStub, InputServiceCost, PeriodCosts};
 
     #[tokio::test]
     async fn request_total_cost_correctly() {
         let client_stub = CostAndUsageClientStub {
             service_costs: None,
             total_cost: Some(String::from("1234.56")),
+            ..Default::default()
         };
         let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
-        let explorer = CostExplorerService::new(client_stub, report_date_range);
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
 
         let expected_total_cost = TotalCost {
             date_range: ReportedDateRange {
@@ -112,16 +728,91 @@ mod test_cost_explorer_service {
                 end_date: Local.ymd(2021, 7, 23),
             },
             cost: Cost {
-                amount: 1234.56,
+                amount: 1234.56.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        let actual_total_cost = explorer.request_total_cost().await.unwrap();
+
+        assert_eq!(expected_total_cost, actual_total_cost);
+    }
+
+    #[tokio::test]
+    async fn request_total_cost_over_multiple_months() {
+        let client_stub = CostAndUsageClientStub {
+            monthly_costs: Some(vec![String::from("1200.5"), String::from("34.25")]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::fiscal_year_to_date(Local.ymd(2021, 5, 10), 4);
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 4, 1),
+                end_date: Local.ymd(2021, 5, 10),
+            },
+            cost: Cost {
+                amount: 1234.75.into(),
                 unit: String::from("USD"),
             },
         };
 
-        let actual_total_cost = explorer.request_total_cost().await;
+        let actual_total_cost = explorer.request_total_cost().await.unwrap();
 
         assert_eq!(expected_total_cost, actual_total_cost);
     }
 
+    #[tokio::test]
+    async fn request_amortized_and_unblended_totals_at_once() {
+        let client_stub = CostAndUsageClientStub {
+            total_cost: Some(String::from("120.00")),
+            cross_check_total: Some(String::from("100.00")),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Unblended);
+
+        let (actual_amortized, actual_unblended) = explorer
+            .request_amortized_and_unblended_totals()
+            .await
+            .unwrap();
+
+        assert_eq!(Amount::from(120.0), actual_amortized.cost.amount);
+        assert_eq!(Amount::from(100.0), actual_unblended.cost.amount);
+        assert_eq!(
+            1,
+            explorer
+                .client
+                .attempts
+                .load(std::sync::atomic::Ordering::SeqCst)
+        );
+    }
+
+    #[tokio::test]
+    async fn request_total_cost_in_reporting_timezone() {
+        let client_stub = CostAndUsageClientStub {
+            total_cost: Some(String::from("1234.56")),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Tokyo.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let actual_total_cost = explorer.request_total_cost().await.unwrap();
+
+        assert_eq!(
+            ReportedDateRange {
+                start_date: Tokyo.ymd(2021, 7, 1),
+                end_date: Tokyo.ymd(2021, 7, 23),
+            },
+            actual_total_cost.date_range
+        );
+        assert_eq!("07/01~07/23", format!("{}", actual_total_cost.date_range));
+    }
+
     #[tokio::test]
     async fn request_service_costs_correctly() {
         let client_stub = CostAndUsageClientStub {
@@ -130,77 +821,980 @@ mod test_cost_explorer_service {
                 InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
             ]),
             total_cost: None,
+            ..Default::default()
         };
         let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
-        let explorer = CostExplorerService::new(client_stub, report_date_range);
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
 
         let expected_service_costs = vec![
             ServiceCost {
                 service_name: String::from("Amazon Simple Storage Service"),
                 cost: Cost {
-                    amount: 1234.56,
+                    amount: 1234.56.into(),
                     unit: String::from("USD"),
                 },
             },
             ServiceCost {
                 service_name: String::from("Amazon Elastic Compute Cloud"),
                 cost: Cost {
-                    amount: 31415.92,
+                    amount: 31415.92.into(),
                     unit: String::from("USD"),
                 },
             },
         ];
 
-        let actual_service_costs = explorer.request_service_costs().await;
+        let actual_service_costs = explorer.request_service_costs().await.unwrap();
 
         assert_eq!(expected_service_costs, actual_service_costs);
     }
-}
 
-#[cfg(test)]
-mod test_build_request {
-    use super::*;
-    use crate::reporting_date::ReportDateRange;
-    use chrono::{Local, TimeZone};
-    use rusoto_ce::DateInterval;
+    #[tokio::test]
+    async fn request_total_and_service_costs_at_once() {
+        let client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("Amazon Simple Storage Service", "1234.56"),
+                InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
+            ]),
+            total_cost: Some(String::from("1.00")),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
 
-    #[test]
-    fn build_total_cost_request_correctly() {
-        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
-        let expected_request = GetCostAndUsageRequest {
-            filter: None,
-            granularity: String::from("MONTHLY"),
-            group_by: None,
-            metrics: vec![String::from("AmortizedCost")],
-            next_page_token: None,
-            time_period: DateInterval {
-                start: "2021-07-01".to_string(),
-                end: "2021-07-23".to_string(),
+        let (actual_total_cost, actual_service_costs) = explorer.request_all().await.unwrap();
+
+        assert_eq!(
+            TotalCost {
+                date_range: ReportedDateRange {
+                    start_date: Local.ymd(2021, 7, 1),
+                    end_date: Local.ymd(2021, 7, 23),
+                },
+                cost: Cost {
+                    amount: Amount::new(3265048, 2),
+                    unit: String::from("USD"),
+                },
             },
-        };
-        let actual_request = build_cost_and_usage_request(&input_date_range, true);
-        assert_eq!(expected_request, actual_request);
+            actual_total_cost
+        );
+        assert_eq!(
+            actual_service_costs
+                .iter()
+                .map(|x| x.cost.amount)
+                .sum::<Amount>(),
+            actual_total_cost.cost.amount
+        );
+        assert_eq!(2, actual_service_costs.len());
+        assert_eq!(
+            1,
+            explorer
+                .client
+                .attempts
+                .load(std::sync::atomic::Ordering::SeqCst)
+        );
     }
 
-    #[test]
-    fn build_service_costs_request_correctly() {
-        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
-        let expected_request = GetCostAndUsageRequest {
-            filter: None,
-            granularity: String::from("MONTHLY"),
-            group_by: Some(vec![GroupDefinition {
-                type_: Some("DIMENSION".to_string()),
-                key: Some("SERVICE".to_string()),
-            }]),
-            metrics: vec![String::from("AmortizedCost")],
-            next_page_token: None,
-            time_period: DateInterval {
-                start: "2021-07-01".to_string(),
-                end: "2021-07-23".to_string(),
+    #[tokio::test]
+    async fn return_zero_costs_for_empty_response() {
+        // The stub responds to the DAILY requests without `daily_costs` with no results.
+        let client_stub = CostAndUsageClientStub::default();
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized)
+                .granularity(Granularity::Daily);
+
+        let actual_total_cost = explorer.request_total_cost().await.unwrap();
+        let actual_service_costs = explorer.request_service_costs().await.unwrap();
+
+        assert_eq!(
+            TotalCost {
+                date_range: ReportedDateRange {
+                    start_date: Local.ymd(2021, 7, 1),
+                    end_date: Local.ymd(2021, 7, 23),
+                },
+                cost: Cost::zero(),
             },
+            actual_total_cost
+        );
+        assert!(actual_service_costs.is_empty());
+        assert_eq!(
+            TotalCost::zero(actual_total_cost.date_range.clone()),
+            explorer.request_all().await.unwrap().0
+        );
+    }
+
+    #[tokio::test]
+    async fn request_service_costs_across_pages() {
+        let client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("AWS Lambda", "1.0"),
+                InputServiceCost::new("AWS CloudTrail", "2.0"),
+            ]),
+            next_page_service_costs: Some(vec![InputServiceCost::new(
+                "Amazon Simple Storage Service",
+                "3.0",
+            )]),
+            ..Default::default()
         };
-        let actual_request = build_cost_and_usage_request(&input_date_range, false);
+        let attempts = client_stub.attempts.clone();
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
 
-        assert_eq!(expected_request, actual_request);
+        let actual_service_names: Vec<String> = explorer
+            .request_service_costs()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|x| x.service_name)
+            .collect();
+
+        assert_eq!(
+            vec![
+                "AWS Lambda".to_string(),
+                "AWS CloudTrail".to_string(),
+                "Amazon Simple Storage Service".to_string(),
+            ],
+            actual_service_names
+        );
+        assert_eq!(2, attempts.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn request_costs_of_each_metric_correctly() {
+        for metric in CostMetric::ALL.iter() {
+            let client_stub = CostAndUsageClientStub {
+                service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "2.34")]),
+                total_cost: Some(String::from("2.34")),
+                ..Default::default()
+            };
+            let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+            let explorer = CostExplorerService::new(client_stub, report_date_range, *metric);
+
+            let actual_total_cost = explorer.request_total_cost().await.unwrap();
+            let actual_service_costs = explorer.request_service_costs().await.unwrap();
+
+            assert_eq!(Amount::from(2.34), actual_total_cost.cost.amount);
+            assert_eq!(Amount::from(2.34), actual_service_costs[0].cost.amount);
+        }
+    }
+
+    #[tokio::test]
+    async fn sum_up_costs_of_daily_granularity() {
+        let client_stub = CostAndUsageClientStub {
+            daily_costs: Some(vec![
+                String::from("1.25"),
+                String::from("2.5"),
+                String::from("3.0"),
+            ]),
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "0.5")]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized)
+                .granularity(Granularity::Daily);
+
+        let actual_total_cost = explorer.request_total_cost().await.unwrap();
+        let actual_service_costs = explorer.request_service_costs().await.unwrap();
+
+        assert_eq!(
+            Cost {
+                amount: 6.75.into(),
+                unit: String::from("USD"),
+            },
+            actual_total_cost.cost
+        );
+        assert_eq!(
+            Local.ymd(2021, 7, 1),
+            actual_total_cost.date_range.start_date
+        );
+        assert_eq!(
+            vec![ServiceCost {
+                service_name: String::from("AWS CloudTrail"),
+                cost: Cost {
+                    amount: 1.5.into(),
+                    unit: String::from("USD"),
+                },
+            }],
+            actual_service_costs
+        );
+    }
+
+    #[tokio::test]
+    async fn request_previous_period_costs_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "2.34")]),
+            total_cost: Some(String::from("2.34")),
+            other_periods: vec![PeriodCosts {
+                start_date: String::from("2021-06-01"),
+                total_cost: Some(String::from("1.23")),
+                service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "1.23")]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 6, 1),
+                end_date: Local.ymd(2021, 6, 23),
+            },
+            cost: Cost {
+                amount: 1.23.into(),
+                unit: String::from("USD"),
+            },
+        };
+        let expected_service_costs = vec![ServiceCost {
+            service_name: String::from("AWS CloudTrail"),
+            cost: Cost {
+                amount: 1.23.into(),
+                unit: String::from("USD"),
+            },
+        }];
+
+        let actual_total_cost = explorer.request_previous_period_total().await.unwrap();
+        let actual_service_costs = explorer
+            .request_previous_period_service_costs()
+            .await
+            .unwrap();
+
+        assert_eq!(expected_total_cost, actual_total_cost);
+        assert_eq!(expected_service_costs, actual_service_costs);
+    }
+
+    #[tokio::test]
+    async fn request_daily_comparison_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            total_cost: Some(String::from("3.00")),
+            other_periods: vec![PeriodCosts {
+                start_date: String::from("2021-07-26"),
+                total_cost: Some(String::from("2.00")),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 8, 3));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let (actual_baseline_total, actual_latest_total) = explorer
+            .request_daily_comparison(DailyBaseline::SameWeekdayLastWeek)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 26),
+                end_date: Local.ymd(2021, 7, 27),
+            },
+            actual_baseline_total.date_range
+        );
+        assert_eq!(Amount::from(2.0), actual_baseline_total.cost.amount);
+        assert_eq!(
+            ReportedDateRange {
+                start_date: Local.ymd(2021, 8, 2),
+                end_date: Local.ymd(2021, 8, 3),
+            },
+            actual_latest_total.date_range
+        );
+        assert_eq!(Amount::from(3.0), actual_latest_total.cost.amount);
+    }
+
+    #[tokio::test]
+    async fn request_forecast_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            forecast: Some(String::from("123.45")),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_forecast = ForecastCost {
+            cost: Cost {
+                amount: 123.45.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        assert_eq!(
+            Some(expected_forecast),
+            explorer.request_forecast().await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn request_tag_costs_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            tag_costs: Some(vec![
+                InputServiceCost::new("Project$frontend", "12.34"),
+                InputServiceCost::new("Project$backend", "56.78"),
+            ]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_tag_costs = vec![
+            TagCost {
+                tag_value: String::from("frontend"),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: String::from("USD"),
+                },
+            },
+            TagCost {
+                tag_value: String::from("backend"),
+                cost: Cost {
+                    amount: 56.78.into(),
+                    unit: String::from("USD"),
+                },
+            },
+        ];
+
+        let actual_tag_costs = explorer.request_tag_costs("Project").await.unwrap();
+
+        assert_eq!(expected_tag_costs, actual_tag_costs);
+    }
+
+    #[tokio::test]
+    async fn request_usage_type_costs_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            usage_type_costs: Some(vec![
+                InputServiceCost::new("APN1-DataTransfer-Out-Bytes", "12.34")
+                    .with_usage_quantity("512.0", "GB"),
+                InputServiceCost::new("APN1-BoxUsage:t3.micro", "56.78"),
+            ]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_usage_type_costs = vec![
+            UsageTypeCost {
+                usage_type: String::from("APN1-DataTransfer-Out-Bytes"),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: String::from("USD"),
+                },
+                usage_quantity: Some(Cost {
+                    amount: 512.0.into(),
+                    unit: String::from("GB"),
+                }),
+            },
+            UsageTypeCost {
+                usage_type: String::from("APN1-BoxUsage:t3.micro"),
+                cost: Cost {
+                    amount: 56.78.into(),
+                    unit: String::from("USD"),
+                },
+                usage_quantity: None,
+            },
+        ];
+
+        let actual_usage_type_costs = explorer.request_usage_type_costs().await.unwrap();
+
+        assert_eq!(expected_usage_type_costs, actual_usage_type_costs);
+    }
+
+    #[tokio::test]
+    async fn request_region_costs_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            region_costs: Some(vec![
+                InputServiceCost::new("ap-northeast-1", "12.34"),
+                InputServiceCost::new("NoRegion", "5.67"),
+            ]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_region_costs = vec![
+            RegionCost {
+                region: String::from("ap-northeast-1"),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: String::from("USD"),
+                },
+            },
+            RegionCost {
+                region: String::from("NoRegion"),
+                cost: Cost {
+                    amount: 5.67.into(),
+                    unit: String::from("USD"),
+                },
+            },
+        ];
+
+        let actual_region_costs = explorer.request_region_costs().await.unwrap();
+
+        assert_eq!(expected_region_costs, actual_region_costs);
+    }
+
+    #[tokio::test]
+    async fn request_account_costs_with_names_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            account_costs: Some(vec![
+                InputServiceCost::new("123456789012", "12.34"),
+                InputServiceCost::new("210987654321", "5.67"),
+            ]),
+            account_names: vec![("123456789012".to_string(), "production".to_string())],
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let actual_account_costs = explorer.request_account_costs().await.unwrap();
+
+        assert_eq!(
+            Some("production".to_string()),
+            actual_account_costs[0].account_name
+        );
+        assert_eq!(None, actual_account_costs[1].account_name);
+    }
+
+    /// Prepare `CostExplorerService` of DAILY granularity over two days,
+    /// on each of which `client_stub` returns its grouped costs.
+    fn prepare_two_day_explorer(
+        client_stub: CostAndUsageClientStub,
+    ) -> CostExplorerService<CostAndUsageClientStub, Local> {
+        let client_stub = CostAndUsageClientStub {
+            daily_costs: Some(vec![String::from("0"), String::from("0")]),
+            ..client_stub
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 3));
+        CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized)
+            .granularity(Granularity::Daily)
+    }
+
+    #[tokio::test]
+    async fn sum_up_tag_costs_over_periods() {
+        let explorer = prepare_two_day_explorer(CostAndUsageClientStub {
+            tag_costs: Some(vec![
+                InputServiceCost::new("Project$frontend", "1.25"),
+                InputServiceCost::new("Project$backend", "0.5"),
+            ]),
+            ..Default::default()
+        });
+
+        let actual_tag_costs = explorer.request_tag_costs("Project").await.unwrap();
+
+        assert_eq!(
+            vec![
+                TagCost {
+                    tag_value: String::from("frontend"),
+                    cost: Cost {
+                        amount: 2.5.into(),
+                        unit: String::from("USD"),
+                    },
+                },
+                TagCost {
+                    tag_value: String::from("backend"),
+                    cost: Cost {
+                        amount: 1.0.into(),
+                        unit: String::from("USD"),
+                    },
+                },
+            ],
+            actual_tag_costs
+        );
+    }
+
+    #[tokio::test]
+    async fn sum_up_usage_type_costs_over_periods() {
+        let explorer = prepare_two_day_explorer(CostAndUsageClientStub {
+            usage_type_costs: Some(vec![
+                InputServiceCost::new("APN1-DataTransfer-Out-Bytes", "1.25")
+                    .with_usage_quantity("256.0", "GB"),
+                InputServiceCost::new("APN1-BoxUsage:t3.micro", "0.5"),
+            ]),
+            ..Default::default()
+        });
+
+        let actual_usage_type_costs = explorer.request_usage_type_costs().await.unwrap();
+
+        assert_eq!(
+            vec![
+                UsageTypeCost {
+                    usage_type: String::from("APN1-DataTransfer-Out-Bytes"),
+                    cost: Cost {
+                        amount: 2.5.into(),
+                        unit: String::from("USD"),
+                    },
+                    usage_quantity: Some(Cost {
+                        amount: 512.0.into(),
+                        unit: String::from("GB"),
+                    }),
+                },
+                UsageTypeCost {
+                    usage_type: String::from("APN1-BoxUsage:t3.micro"),
+                    cost: Cost {
+                        amount: 1.0.into(),
+                        unit: String::from("USD"),
+                    },
+                    usage_quantity: None,
+                },
+            ],
+            actual_usage_type_costs
+        );
+    }
+
+    #[tokio::test]
+    async fn sum_up_region_costs_over_periods() {
+        let explorer = prepare_two_day_explorer(CostAndUsageClientStub {
+            region_costs: Some(vec![
+                InputServiceCost::new("ap-northeast-1", "1.25"),
+                InputServiceCost::new("NoRegion", "0.5"),
+            ]),
+            ..Default::default()
+        });
+
+        let actual_region_costs = explorer.request_region_costs().await.unwrap();
+
+        assert_eq!(
+            vec![
+                RegionCost {
+                    region: String::from("ap-northeast-1"),
+                    cost: Cost {
+                        amount: 2.5.into(),
+                        unit: String::from("USD"),
+                    },
+                },
+                RegionCost {
+                    region: String::from("NoRegion"),
+                    cost: Cost {
+                        amount: 1.0.into(),
+                        unit: String::from("USD"),
+                    },
+                },
+            ],
+            actual_region_costs
+        );
+    }
+
+    #[tokio::test]
+    async fn sum_up_account_costs_over_periods() {
+        let explorer = prepare_two_day_explorer(CostAndUsageClientStub {
+            account_costs: Some(vec![
+                InputServiceCost::new("123456789012", "1.25"),
+                InputServiceCost::new("210987654321", "0.5"),
+            ]),
+            account_names: vec![("123456789012".to_string(), "production".to_string())],
+            ..Default::default()
+        });
+
+        let actual_account_costs = explorer.request_account_costs().await.unwrap();
+
+        assert_eq!(
+            vec![
+                AccountCost {
+                    account_id: String::from("123456789012"),
+                    account_name: Some(String::from("production")),
+                    cost: Cost {
+                        amount: 2.5.into(),
+                        unit: String::from("USD"),
+                    },
+                },
+                AccountCost {
+                    account_id: String::from("210987654321"),
+                    account_name: None,
+                    cost: Cost {
+                        amount: 1.0.into(),
+                        unit: String::from("USD"),
+                    },
+                },
+            ],
+            actual_account_costs
+        );
+    }
+
+    #[tokio::test]
+    async fn request_monthly_costs_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            monthly_costs: Some(vec![
+                String::from("10.00"),
+                String::from("12.00"),
+                String::from("5.00"),
+            ]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let actual_monthly_costs = explorer.request_monthly_costs(3).await.unwrap();
+        let actual_periods: Vec<(Date<Local>, Date<Local>)> = actual_monthly_costs
+            .iter()
+            .map(|x| (x.start_date, x.end_date))
+            .collect();
+
+        assert_eq!(
+            vec![
+                (Local.ymd(2021, 5, 1), Local.ymd(2021, 6, 1)),
+                (Local.ymd(2021, 6, 1), Local.ymd(2021, 7, 1)),
+                (Local.ymd(2021, 7, 1), Local.ymd(2021, 7, 23)),
+            ],
+            actual_periods
+        );
+        assert_eq!(Amount::from(5.0), actual_monthly_costs[2].cost.amount);
+    }
+
+    #[tokio::test]
+    async fn request_utilization_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            savings_plans_utilization: Some(String::from("95.0")),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let actual_savings_plans_utilization =
+            explorer.request_savings_plans_utilization().await.unwrap();
+        let actual_reservation_utilization =
+            explorer.request_reservation_utilization().await.unwrap();
+
+        assert_eq!(
+            Some(Utilization { percentage: 95.0 }),
+            actual_savings_plans_utilization
+        );
+        assert_eq!(None, actual_reservation_utilization);
+    }
+
+    #[tokio::test]
+    async fn return_error_when_request_fails() {
+        let client_stub = CostAndUsageClientStub {
+            total_cost: Some(String::from("1.00")),
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "1.00")]),
+            failures: AtomicU32::new(2),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let total_cost_error = explorer.request_total_cost().await.unwrap_err();
+        let service_costs_error = explorer.request_service_costs().await.unwrap_err();
+
+        assert!(matches!(total_cost_error, CostNotificationError::Api(_)));
+        assert!(matches!(service_costs_error, CostNotificationError::Api(_)));
+    }
+
+    #[tokio::test]
+    async fn request_rightsizing_savings_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            rightsizing_savings: Some(String::from("42.5")),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let actual_savings = explorer.request_rightsizing_savings().await.unwrap();
+
+        assert_eq!(
+            Some(SavingsOpportunity {
+                cost: Cost {
+                    amount: 42.5.into(),
+                    unit: String::from("USD"),
+                },
+            }),
+            actual_savings
+        );
+    }
+
+    #[tokio::test]
+    async fn request_daily_costs_correctly() {
+        let client_stub = CostAndUsageClientStub {
+            daily_costs: Some(vec![
+                String::from("1.23"),
+                String::from("4.56"),
+                String::from("7.89"),
+            ]),
+            ..Default::default()
+        };
+        let report_date_range = ReportDateRange::new(Local.ymd(2021, 7, 4));
+        let explorer =
+            CostExplorerService::new(client_stub, report_date_range, CostMetric::Amortized);
+
+        let expected_daily_costs = vec![
+            DailyCost {
+                date: Local.ymd(2021, 7, 1),
+                cost: Cost {
+                    amount: 1.23.into(),
+                    unit: String::from("USD"),
+                },
+                estimated: false,
+            },
+            DailyCost {
+                date: Local.ymd(2021, 7, 2),
+                cost: Cost {
+                    amount: 4.56.into(),
+                    unit: String::from("USD"),
+                },
+                estimated: false,
+            },
+            DailyCost {
+                date: Local.ymd(2021, 7, 3),
+                cost: Cost {
+                    amount: 7.89.into(),
+                    unit: String::from("USD"),
+                },
+                estimated: false,
+            },
+        ];
+
+        let actual_daily_costs = explorer.request_daily_costs().await.unwrap();
+
+        assert_eq!(expected_daily_costs, actual_daily_costs);
+    }
+}
+
+#[cfg(test)]
+mod test_build_request {
+    use super::*;
+    use crate::reporting_date::ReportDateRange;
+    use chrono::{Local, TimeZone};
+    use rusoto_ce::DateInterval;
+
+    #[test]
+    fn build_total_cost_request_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let expected_request = GetCostAndUsageRequest {
+            filter: None,
+            granularity: String::from("MONTHLY"),
+            group_by: None,
+            metrics: vec![String::from("AmortizedCost")],
+            next_page_token: None,
+            time_period: DateInterval {
+                start: "2021-07-01".to_string(),
+                end: "2021-07-23".to_string(),
+            },
+        };
+        let actual_request = build_cost_and_usage_request(
+            &input_date_range,
+            None,
+            Granularity::Monthly,
+            CostMetric::Amortized,
+            None,
+        );
+        assert_eq!(expected_request, actual_request);
+    }
+
+    #[test]
+    fn build_service_costs_request_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let expected_request = GetCostAndUsageRequest {
+            filter: None,
+            granularity: String::from("MONTHLY"),
+            group_by: Some(vec![GroupDefinition {
+                type_: Some("DIMENSION".to_string()),
+                key: Some("SERVICE".to_string()),
+            }]),
+            metrics: vec![String::from("AmortizedCost")],
+            next_page_token: None,
+            time_period: DateInterval {
+                start: "2021-07-01".to_string(),
+                end: "2021-07-23".to_string(),
+            },
+        };
+        let actual_request = build_cost_and_usage_request(
+            &input_date_range,
+            Some(&GroupBy::Service),
+            Granularity::Monthly,
+            CostMetric::Amortized,
+            None,
+        );
+
+        assert_eq!(expected_request, actual_request);
+    }
+
+    #[test]
+    fn build_tag_costs_request_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let expected_request = GetCostAndUsageRequest {
+            filter: None,
+            granularity: String::from("MONTHLY"),
+            group_by: Some(vec![GroupDefinition {
+                type_: Some("TAG".to_string()),
+                key: Some("Project".to_string()),
+            }]),
+            metrics: vec![String::from("AmortizedCost")],
+            next_page_token: None,
+            time_period: DateInterval {
+                start: "2021-07-01".to_string(),
+                end: "2021-07-23".to_string(),
+            },
+        };
+        let actual_request = build_cost_and_usage_request(
+            &input_date_range,
+            Some(&GroupBy::Tag("Project".to_string())),
+            Granularity::Monthly,
+            CostMetric::Amortized,
+            None,
+        );
+
+        assert_eq!(expected_request, actual_request);
+    }
+
+    #[test]
+    fn build_usage_type_costs_request_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let expected_request = GetCostAndUsageRequest {
+            filter: None,
+            granularity: String::from("MONTHLY"),
+            group_by: Some(vec![GroupDefinition {
+                type_: Some("DIMENSION".to_string()),
+                key: Some("USAGE_TYPE".to_string()),
+            }]),
+            metrics: vec![String::from("AmortizedCost")],
+            next_page_token: None,
+            time_period: DateInterval {
+                start: "2021-07-01".to_string(),
+                end: "2021-07-23".to_string(),
+            },
+        };
+        let actual_request = build_cost_and_usage_request(
+            &input_date_range,
+            Some(&GroupBy::UsageType),
+            Granularity::Monthly,
+            CostMetric::Amortized,
+            None,
+        );
+
+        assert_eq!(expected_request, actual_request);
+    }
+
+    #[test]
+    fn build_daily_cost_request_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let expected_request = GetCostAndUsageRequest {
+            filter: None,
+            granularity: String::from("DAILY"),
+            group_by: None,
+            metrics: vec![String::from("AmortizedCost")],
+            next_page_token: None,
+            time_period: DateInterval {
+                start: "2021-07-01".to_string(),
+                end: "2021-07-23".to_string(),
+            },
+        };
+        let actual_request = build_cost_and_usage_request(
+            &input_date_range,
+            None,
+            Granularity::Daily,
+            CostMetric::Amortized,
+            None,
+        );
+
+        assert_eq!(expected_request, actual_request);
+    }
+
+    #[test]
+    fn build_account_filter_correctly() {
+        let expected_filter = Expression {
+            dimensions: Some(DimensionValues {
+                key: Some("LINKED_ACCOUNT".to_string()),
+                values: Some(vec!["123456789012".to_string()]),
+                match_options: None,
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(expected_filter, build_account_filter("123456789012"));
+    }
+
+    #[test]
+    fn build_credits_exclusion_filter_correctly() {
+        let expected_filter = Expression {
+            not: Box::new(Some(Expression {
+                dimensions: Some(DimensionValues {
+                    key: Some("RECORD_TYPE".to_string()),
+                    values: Some(vec!["Credit".to_string(), "Refund".to_string()]),
+                    match_options: None,
+                }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some(expected_filter.clone()),
+            combine_filters(&[expected_filter])
+        );
+        assert_eq!(None, combine_filters(&[]));
+    }
+
+    #[test]
+    fn combine_account_and_credits_filters_with_and() {
+        let account_filter = build_account_filter("123456789012");
+        let credits_filter = build_credits_exclusion_filter();
+        let expected_filter = Expression {
+            and: Some(vec![account_filter.clone(), credits_filter.clone()]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some(expected_filter),
+            combine_filters(&[account_filter, credits_filter])
+        );
+    }
+
+    #[test]
+    fn build_request_with_filter_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+        let filter = build_account_filter("123456789012");
+
+        let actual_request = build_cost_and_usage_request(
+            &input_date_range,
+            Some(&GroupBy::Service),
+            Granularity::Monthly,
+            CostMetric::Amortized,
+            Some(&filter),
+        );
+
+        assert_eq!(Some(filter), actual_request.filter);
+    }
+
+    #[test]
+    fn build_request_with_each_metric_correctly() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+
+        for (metric, expected_metric) in [
+            (CostMetric::Amortized, "AmortizedCost"),
+            (CostMetric::Unblended, "UnblendedCost"),
+            (CostMetric::Blended, "BlendedCost"),
+            (CostMetric::NetAmortized, "NetAmortizedCost"),
+            (CostMetric::NetUnblended, "NetUnblendedCost"),
+        ] {
+            let actual_request = build_cost_and_usage_request(
+                &input_date_range,
+                None,
+                Granularity::Monthly,
+                metric,
+                None,
+            );
+
+            assert_eq!(vec![expected_metric.to_string()], actual_request.metrics);
+        }
+    }
+
+    #[test]
+    fn parse_cost_metric_correctly() {
+        assert_eq!(Ok(CostMetric::NetUnblended), "NetUnblendedCost".parse());
+        assert_eq!(
+            "NET_UNBLENDED_COST",
+            CostMetric::NetUnblended.forecast_metric()
+        );
+        assert!("UNBLENDED_COST".parse::<CostMetric>().is_err());
     }
 }
@@ -0,0 +1,276 @@
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// Number of the decimal places held by `Amount`,
+/// which covers those of the amounts in the Cost Explorer responses.
+const SCALE: u32 = 10;
+
+/// `10^SCALE`, the number of the minor units in one.
+const ONE: i128 = 10i128.pow(SCALE);
+
+/// Fixed-point decimal amount held as an integer number of the minor units (`1e-10`).
+///
+/// Unlike floating point numbers, the amounts parsed from the API responses
+/// are held exactly, so adding them up never gains or loses a cent.
+///
+/// The precision of `Display` rounds the amount half away from zero:
+/// ```
+/// let amount: Amount = "31415.925".parse().unwrap();
+/// assert_eq!("31415.93", format!("{:.2}", amount));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i128);
+impl Amount {
+    /// Zero amount.
+    pub const ZERO: Amount = Amount(0);
+
+    /// Amount of `num` scaled down by `decimals` decimal places (e.g. `new(5, 3)` is `0.005`).
+    /// `decimals` must not be greater than 10.
+    pub const fn new(num: i64, decimals: u32) -> Amount {
+        Amount(num as i128 * 10i128.pow(SCALE - decimals))
+    }
+
+    /// Convert to the nearest floating point number,
+    /// which is used to calculate ratios and to compare with settings.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / ONE as f64
+    }
+
+    /// Absolute value of the amount.
+    pub fn abs(self) -> Amount {
+        Amount(self.0.abs())
+    }
+
+    /// Whether the amount is below zero.
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    /// Round the amount half away from zero to `decimals` decimal places.
+    pub fn round_dp(self, decimals: u32) -> Amount {
+        if decimals >= SCALE {
+            return self;
+        }
+        let unit = 10i128.pow(SCALE - decimals);
+        let rounded = (self.0.abs() + unit / 2) / unit * unit;
+        Amount(rounded * self.0.signum())
+    }
+}
+
+/// Convert the floating point number rounded to `1e-10`.
+/// Non-finite numbers, which are rejected in parsing the responses, are regarded as zero.
+impl From<f64> for Amount {
+    fn from(value: f64) -> Amount {
+        match value.is_finite() {
+            true => Amount((value * ONE as f64).round() as i128),
+            false => Amount::ZERO,
+        }
+    }
+}
+
+/// Error in parsing an invalid decimal string into `Amount`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseAmountError(String);
+impl fmt::Display for ParseAmountError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid decimal amount {:?}", self.0)
+    }
+}
+impl std::error::Error for ParseAmountError {}
+
+impl FromStr for Amount {
+    type Err = ParseAmountError;
+
+    /// Parse the decimal string (e.g. `-31415.92`) exactly.
+    ///
+    /// The digits beyond `1e-10` are rounded, and the exponent notation (e.g. `1.5E-7`),
+    /// which the API may use for tiny amounts, is parsed through `f64`.
+    fn from_str(s: &str) -> Result<Amount, ParseAmountError> {
+        let invalid = || ParseAmountError(s.to_string());
+        let trimmed = s.trim();
+        if trimmed.contains(['e', 'E']) {
+            return trimmed
+                .parse::<f64>()
+                .ok()
+                .filter(|x| x.is_finite())
+                .map(Amount::from)
+                .ok_or_else(invalid);
+        }
+
+        let (negative, digits) = match trimmed.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|x| x.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let mut minor_units = 0i128;
+        for digit in integer
+            .chars()
+            .chain(fraction.chars())
+            .take(integer.len() + SCALE as usize)
+        {
+            minor_units = minor_units
+                .checked_mul(10)
+                .and_then(|x| x.checked_add(digit.to_digit(10).unwrap() as i128))
+                .ok_or_else(invalid)?;
+        }
+        let scale = fraction.len().min(SCALE as usize) as u32;
+        minor_units *= 10i128.pow(SCALE - scale);
+        if fraction[scale as usize..].starts_with(['5', '6', '7', '8', '9']) {
+            minor_units += 1;
+        }
+
+        Ok(Amount(if negative { -minor_units } else { minor_units }))
+    }
+}
+
+/// Display the amount in the decimal notation.
+///
+/// Without the precision, the trailing zeros of the decimal places are omitted (e.g. `0.3`).
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (amount, decimals) = match f.precision() {
+            Some(decimals) => (self.round_dp(decimals as u32), decimals),
+            None => (*self, SCALE as usize),
+        };
+        let sign = if amount.is_negative() { "-" } else { "" };
+        let integer = amount.0.abs() / ONE;
+        let fraction = format!("{:0width$}", amount.0.abs() % ONE, width = SCALE as usize);
+        let fraction = match f.precision() {
+            Some(_) => format!("{:0<width$}", fraction, width = decimals)[..decimals].to_string(),
+            None => fraction.trim_end_matches('0').to_string(),
+        };
+        match fraction.is_empty() {
+            true => write!(f, "{}{}", sign, integer),
+            false => write!(f, "{}{}.{}", sign, integer, fraction),
+        }
+    }
+}
+
+/// Serialize the amount as a JSON number.
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_f64())
+    }
+}
+
+impl Add for Amount {
+    type Output = Amount;
+    fn add(self, other: Amount) -> Amount {
+        Amount(self.0 + other.0)
+    }
+}
+impl AddAssign for Amount {
+    fn add_assign(&mut self, other: Amount) {
+        self.0 += other.0;
+    }
+}
+impl Sub for Amount {
+    type Output = Amount;
+    fn sub(self, other: Amount) -> Amount {
+        Amount(self.0 - other.0)
+    }
+}
+impl SubAssign for Amount {
+    fn sub_assign(&mut self, other: Amount) {
+        self.0 -= other.0;
+    }
+}
+impl Neg for Amount {
+    type Output = Amount;
+    fn neg(self) -> Amount {
+        Amount(-self.0)
+    }
+}
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, Add::add)
+    }
+}
+impl<'a> Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Amount {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(test)]
+mod test_amount {
+    use super::*;
+
+    fn amount(s: &str) -> Amount {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_decimal_strings_exactly() {
+        assert_eq!(Amount(314_159_200_000_000), amount("31415.92"));
+        assert_eq!(Amount(-1_230_000_000), amount("-0.123"));
+        assert_eq!(Amount(5_000_000_000), amount(".5"));
+        assert_eq!(Amount(1), amount("0.00000000005"));
+        assert_eq!(Amount(1_500), amount("1.5E-7"));
+        assert_eq!(Amount::ZERO, amount("0"));
+    }
+
+    #[test]
+    fn return_error_for_invalid_strings() {
+        for invalid in ["", "-", ".", "NaN", "inf", "1.2.3", "12a", "1e400"] {
+            assert!(invalid.parse::<Amount>().is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn sum_up_many_cents_exactly() {
+        let total: Amount = (0..100_000).map(|_| amount("0.01")).sum();
+        assert_eq!(amount("1000"), total);
+
+        let total: Amount = ["0.1", "0.2"].iter().map(|x| amount(x)).sum();
+        assert_eq!("0.3", total.to_string());
+    }
+
+    #[test]
+    fn display_large_amount_with_exact_cents() {
+        let large = amount("31415.92");
+
+        assert_eq!("31415.92", format!("{:.2}", large));
+        assert_eq!("31415.92", large.to_string());
+        assert_eq!(
+            "31415.93",
+            format!("{:.2}", large + amount("0.005") + amount("0.005"))
+        );
+    }
+
+    #[test]
+    fn round_half_away_from_zero() {
+        assert_eq!("0.13", format!("{:.2}", amount("0.125")));
+        assert_eq!("-0.13", format!("{:.2}", amount("-0.125")));
+        assert_eq!("0.00", format!("{:.2}", amount("-0.001")));
+        assert_eq!("3", format!("{:.0}", amount("2.5")));
+        assert_eq!("1.2345000000", format!("{:.10}", amount("1.2345")));
+        assert_eq!("1.234500000000", format!("{:.12}", amount("1.2345")));
+    }
+
+    #[test]
+    fn scale_down_number_by_decimals() {
+        assert_eq!(amount("0.005"), Amount::new(5, 3));
+        assert_eq!(amount("-31415.92"), Amount::new(-3141592, 2));
+        assert_eq!(amount("7"), Amount::new(7, 0));
+    }
+
+    #[test]
+    fn convert_from_and_to_f64() {
+        assert_eq!(amount("31.41"), Amount::from(31.41));
+        assert_eq!(31.41, amount("31.41").to_f64());
+        assert_eq!(Amount::ZERO, Amount::from(f64::NAN));
+    }
+}
@@ -1,103 +1,725 @@
-use chrono::{Date, Local, NaiveDate, TimeZone};
-use rusoto_ce::{GetCostAndUsageResponse, Group, MetricValue};
+use chrono::{Date, NaiveDate, TimeZone};
+use rusoto_ce::{
+    GetCostAndUsageResponse, GetCostForecastResponse, GetReservationUtilizationResponse,
+    GetRightsizingRecommendationResponse, GetSavingsPlansUtilizationResponse, Group, MetricValue,
+    ResultByTime,
+};
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use crate::cost_explorer::amount::Amount;
+use crate::cost_explorer::error::ParseError;
+use crate::cost_explorer::CostMetric;
 
 /// AWS Cost
-#[derive(Debug, PartialEq, Clone, PartialOrd)]
+///
+/// The costs are ordered by amount and then by unit.
+#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Serialize)]
 pub struct Cost {
-    pub amount: f32,
+    pub amount: Amount,
     pub unit: String,
 }
-impl From<MetricValue> for Cost {
-    fn from(from: MetricValue) -> Cost {
-        let parsed_amount = from.amount.as_ref().unwrap().parse::<f32>().unwrap();
+impl TryFrom<MetricValue> for Cost {
+    type Error = ParseError;
 
-        let parsed_unit = from.unit.as_ref().unwrap().to_string();
+    /// Parse `MetricValue` in the API response into `Cost`.
+    ///
+    /// The amount is parsed exactly as a decimal.
+    /// Returns `ParseError::InvalidMetric` with the raw amount if the amount or unit
+    /// is missing or invalid. Non-finite amounts such as `NaN` and `inf` are also invalid,
+    /// since they cannot be summed up or displayed.
+    fn try_from(from: MetricValue) -> Result<Cost, ParseError> {
+        let invalid_metric = || ParseError::InvalidMetric(from.amount.clone().unwrap_or_default());
+        let amount = from
+            .amount
+            .as_ref()
+            .and_then(|x| x.parse::<Amount>().ok())
+            .ok_or_else(invalid_metric)?;
+        let unit = from.unit.clone().ok_or_else(invalid_metric)?;
+        Ok(Cost { amount, unit })
+    }
+}
 
+impl Cost {
+    /// Zero cost in USD, in which Cost Explorer reports the costs.
+    pub fn zero() -> Cost {
         Cost {
-            amount: parsed_amount,
-            unit: parsed_unit,
+            amount: Amount::ZERO,
+            unit: "USD".to_string(),
         }
     }
 }
 
 /// Period of cost aggregation in the API response.
-#[derive(Debug, PartialEq)]
-pub struct ReportedDateRange {
-    pub start_date: Date<Local>,
-    pub end_date: Date<Local>,
+///
+/// The dates are in the timezone of the reporting date.
+#[derive(Debug, Clone, Serialize)]
+#[serde(bound = "")]
+pub struct ReportedDateRange<T: TimeZone> {
+    #[serde(serialize_with = "serialize_date")]
+    pub start_date: Date<T>,
+    #[serde(serialize_with = "serialize_date")]
+    pub end_date: Date<T>,
+}
+impl<T: TimeZone> PartialEq for ReportedDateRange<T> {
+    fn eq(&self, other: &ReportedDateRange<T>) -> bool {
+        self.start_date == other.start_date && self.end_date == other.end_date
+    }
+}
+impl<T: TimeZone> ReportedDateRange<T> {
+    /// Parse the period from the first to the last result of the API response,
+    /// regarding the dates as the dates in `timezone`.
+    fn try_from_response(
+        from: &GetCostAndUsageResponse,
+        timezone: &T,
+    ) -> Result<ReportedDateRange<T>, ParseError> {
+        let results_by_time = from
+            .results_by_time
+            .as_deref()
+            .filter(|x| !x.is_empty())
+            .ok_or(ParseError::MissingResultsByTime)?;
+        let time_period_of = |result_by_time: &ResultByTime| {
+            result_by_time
+                .time_period
+                .clone()
+                .ok_or(ParseError::MissingTimePeriod)
+        };
+        let first_time_period = time_period_of(&results_by_time[0])?;
+        let last_time_period = time_period_of(&results_by_time[results_by_time.len() - 1])?;
+
+        Ok(ReportedDateRange {
+            start_date: parse_timestamp_into_local_date(&first_time_period.start, timezone)?,
+            end_date: parse_timestamp_into_local_date(&last_time_period.end, timezone)?,
+        })
+    }
+}
+
+/// Serialize the date formatted as `%Y-%m-%d`.
+fn serialize_date<T: TimeZone, S: Serializer>(
+    date: &Date<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&date.naive_local().to_string())
 }
 
 /// Total AWS cost during `date_range`.
-#[derive(Debug, PartialEq)]
-pub struct TotalCost {
-    pub date_range: ReportedDateRange,
+#[derive(Debug, Serialize)]
+#[serde(bound = "")]
+pub struct TotalCost<T: TimeZone> {
+    pub date_range: ReportedDateRange<T>,
     pub cost: Cost,
 }
-impl From<GetCostAndUsageResponse> for TotalCost {
-    /// Parse the API response into `TotalCost`
-    fn from(from: GetCostAndUsageResponse) -> TotalCost {
-        let result_by_time = &from.results_by_time.as_ref().unwrap()[0];
-        let time_period = result_by_time.time_period.as_ref().unwrap();
+impl<T: TimeZone> PartialEq for TotalCost<T> {
+    fn eq(&self, other: &TotalCost<T>) -> bool {
+        self.date_range == other.date_range && self.cost == other.cost
+    }
+}
+impl<T: TimeZone> TotalCost<T> {
+    /// Encode the total cost and `service_costs` into JSON for downstream consumers.
+    ///
+    /// # Example
+    /// `{"total_cost":{"date_range":{...},"cost":{...}},"service_costs":[...]}`
+    pub fn to_json(&self, service_costs: &[ServiceCost]) -> Value {
+        json!({
+            "total_cost": self,
+            "service_costs": service_costs,
+        })
+    }
 
-        let parsed_start_date = parse_timestamp_into_local_date(&time_period.start).unwrap();
-        let parsed_end_date = parse_timestamp_into_local_date(&time_period.end).unwrap();
+    /// Parse the API response into `TotalCost`.
+    ///
+    /// The dates of the response are regarded as the dates in `timezone`
+    /// and the cost is read from `metric`.
+    /// If the response has multiple results (e.g. of DAILY granularity,
+    /// or of MONTHLY granularity over the turn of a month),
+    /// their costs are summed up over the period from the first to the last result.
+    /// Returns `ParseError` if the response lacks any of the fields
+    /// or the costs are in different units.
+    pub fn try_from_response(
+        from: &GetCostAndUsageResponse,
+        timezone: &T,
+        metric: CostMetric,
+    ) -> Result<TotalCost<T>, ParseError> {
+        let date_range = ReportedDateRange::try_from_response(from, timezone)?;
 
-        let amortized_cost = result_by_time
-            .total
-            .as_ref()
-            .unwrap()
-            .get("AmortizedCost")
-            .unwrap()
-            .clone();
+        let costs = from
+            .results_by_time
+            .iter()
+            .flatten()
+            .map(|result_by_time| try_parse_total(result_by_time, metric))
+            .collect::<Result<Vec<Cost>, ParseError>>()?;
 
+        Ok(TotalCost {
+            date_range,
+            cost: sum_costs(costs)?,
+        })
+    }
+
+    /// Zero total cost during `date_range`, for the response without any results
+    /// (e.g. of an account with no spend yet).
+    pub fn zero(date_range: ReportedDateRange<T>) -> TotalCost<T> {
         TotalCost {
-            date_range: ReportedDateRange {
-                start_date: parsed_start_date,
-                end_date: parsed_end_date,
-            },
-            cost: amortized_cost.into(),
+            date_range,
+            cost: Cost::zero(),
         }
     }
+
+    /// Sum up `service_costs` parsed from the API response grouped by service
+    /// into `TotalCost` during `date_range`,
+    /// since the grouped response has no total of the period.
+    ///
+    /// The total is zero if no service has costs.
+    /// Returns `ParseError` if the costs are in different units.
+    pub fn try_from_service_costs(
+        date_range: ReportedDateRange<T>,
+        service_costs: &[ServiceCost],
+    ) -> Result<TotalCost<T>, ParseError> {
+        if service_costs.is_empty() {
+            return Ok(TotalCost::zero(date_range));
+        }
+        let cost = sum_costs(service_costs.iter().map(|x| x.cost.clone()).collect())?;
+        Ok(TotalCost { date_range, cost })
+    }
+}
+
+/// AWS cost of a day.
+#[derive(Debug)]
+pub struct DailyCost<T: TimeZone> {
+    pub date: Date<T>,
+    pub cost: Cost,
+    /// Whether the cost of the day is still estimated (not finalized).
+    pub estimated: bool,
+}
+impl<T: TimeZone> PartialEq for DailyCost<T> {
+    fn eq(&self, other: &DailyCost<T>) -> bool {
+        self.date == other.date && self.cost == other.cost && self.estimated == other.estimated
+    }
 }
+impl<T: TimeZone> DailyCost<T> {
+    /// Parse the API response of DAILY granularity into a vector of `DailyCost`.
+    ///
+    /// The dates of the response are regarded as the dates in `timezone`
+    /// and the costs are read from `metric`.
+    /// The `estimated` flag of each day is preserved.
+    /// The response without results is parsed into no days,
+    /// and the day without the total into zero cost.
+    ///
+    /// Returns `ParseError` if the date or the cost of any day is missing or invalid.
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        timezone: &T,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        res.results_by_time
+            .iter()
+            .flatten()
+            .map(|result_by_time| {
+                let time_period = result_by_time
+                    .time_period
+                    .as_ref()
+                    .ok_or(ParseError::MissingTimePeriod)?;
+                let cost = try_parse_total(result_by_time, metric)?;
 
-/// Parse the timestamp in the `time_period` field of the API response.
-fn parse_timestamp_into_local_date(timestamp: &str) -> chrono::LocalResult<Date<Local>> {
-    let parsed_start_date = NaiveDate::parse_from_str(timestamp, "%Y-%m-%d")
+                Ok(DailyCost {
+                    date: parse_timestamp_into_local_date(&time_period.start, timezone)?,
+                    cost,
+                    estimated: result_by_time.estimated.unwrap_or(false),
+                })
+            })
+            .collect()
+    }
+}
+
+/// AWS total cost of a month.
+#[derive(Debug)]
+pub struct MonthlyCost<T: TimeZone> {
+    /// The first date of the month.
+    pub start_date: Date<T>,
+    /// The first date of the next month, or the reporting date if the month is in progress.
+    pub end_date: Date<T>,
+    pub cost: Cost,
+}
+impl<T: TimeZone> PartialEq for MonthlyCost<T> {
+    fn eq(&self, other: &MonthlyCost<T>) -> bool {
+        self.start_date == other.start_date
+            && self.end_date == other.end_date
+            && self.cost == other.cost
+    }
+}
+impl<T: TimeZone> MonthlyCost<T> {
+    /// Parse the API response of MONTHLY granularity into a vector of `MonthlyCost`.
+    ///
+    /// The dates of the response are regarded as the dates in `timezone`
+    /// and the costs are read from `metric`.
+    /// The response without results is parsed into no months,
+    /// and the month without the total into zero cost.
+    ///
+    /// Returns `ParseError` if the date or the cost of any month is missing or invalid.
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        timezone: &T,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        res.results_by_time
+            .iter()
+            .flatten()
+            .map(|result_by_time| {
+                let time_period = result_by_time
+                    .time_period
+                    .as_ref()
+                    .ok_or(ParseError::MissingTimePeriod)?;
+                let cost = try_parse_total(result_by_time, metric)?;
+
+                Ok(MonthlyCost {
+                    start_date: parse_timestamp_into_local_date(&time_period.start, timezone)?,
+                    end_date: parse_timestamp_into_local_date(&time_period.end, timezone)?,
+                    cost,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parse the timestamp in the `time_period` field of the API response
+/// into the date in `timezone`.
+///
+/// Returns `ParseError::InvalidTimePeriod` with the timestamp
+/// if it is not formatted as `%Y-%m-%d` or the date is ambiguous in `timezone`.
+fn parse_timestamp_into_local_date<T: TimeZone>(
+    timestamp: &str,
+    timezone: &T,
+) -> Result<Date<T>, ParseError> {
+    NaiveDate::parse_from_str(timestamp, "%Y-%m-%d")
         .ok()
-        .unwrap();
-    Local.from_local_date(&parsed_start_date)
+        .and_then(|parsed_date| timezone.from_local_date(&parsed_date).single())
+        .ok_or_else(|| ParseError::InvalidTimePeriod(timestamp.to_string()))
+}
+
+/// Sum up `costs`, returning `ParseError` if they are in different units.
+fn sum_costs(costs: Vec<Cost>) -> Result<Cost, ParseError> {
+    let mut costs = costs.into_iter();
+    let first = costs.next().ok_or(ParseError::MissingResultsByTime)?;
+    costs.try_fold(first, |sum, cost| match sum.unit == cost.unit {
+        true => Ok(Cost {
+            amount: sum.amount + cost.amount,
+            unit: sum.unit,
+        }),
+        false => Err(ParseError::MixedUnits(sum.unit, cost.unit)),
+    })
+}
+
+/// Parse the total of `metric` in `result_by_time` into `Cost`,
+/// which is zero if the result has no total (e.g. of an account with no spend yet).
+fn try_parse_total(result_by_time: &ResultByTime, metric: CostMetric) -> Result<Cost, ParseError> {
+    match &result_by_time.total {
+        Some(total) => try_parse_cost(total, metric),
+        None => Ok(Cost::zero()),
+    }
+}
+
+/// Parse the value of `metric` among `metrics` into `Cost`,
+/// returning `ParseError` if it is missing or invalid.
+fn try_parse_cost(
+    metrics: &HashMap<String, MetricValue>,
+    metric: CostMetric,
+) -> Result<Cost, ParseError> {
+    let value = metrics
+        .get(metric.as_str())
+        .ok_or_else(|| ParseError::MissingMetric(metric.as_str().to_string()))?;
+    try_parse_metric(value, metric.as_str())
+}
+
+/// Parse the value of `metric` into `Cost`,
+/// returning `ParseError::InvalidMetric` with the name of `metric`
+/// if its amount (e.g. `NaN`) or unit is missing or invalid.
+fn try_parse_metric(value: &MetricValue, metric: &str) -> Result<Cost, ParseError> {
+    Cost::try_from(value.clone()).map_err(|_| ParseError::InvalidMetric(metric.to_string()))
+}
+
+/// Parse the value of `metric` in `group` into `Cost`,
+/// returning `ParseError` if it is missing or invalid.
+fn try_parse_group_cost(group: &Group, metric: CostMetric) -> Result<Cost, ParseError> {
+    match &group.metrics {
+        Some(metrics) => try_parse_cost(metrics, metric),
+        None => Err(ParseError::MissingMetric(metric.as_str().to_string())),
+    }
+}
+
+/// Groups of all the results in the API response, which are empty if it has no results.
+fn groups_of_all_results(res: &GetCostAndUsageResponse) -> impl Iterator<Item = &Group> {
+    res.results_by_time
+        .iter()
+        .flatten()
+        .flat_map(|result_by_time| result_by_time.groups.iter().flatten())
+}
+
+/// Cost of a group (e.g. a region) in the API response grouped by a dimension or a tag.
+trait GroupCost {
+    /// Whether `other` is the cost of the same group in the same unit.
+    fn is_same_group(&self, other: &Self) -> bool;
+
+    /// Add the cost of `other` of the same group.
+    fn add(&mut self, other: Self);
+}
+
+/// Parse the groups of all the results in the API response with `try_from_group`
+/// and sum up the costs of the same group across the results (e.g. of DAILY granularity)
+/// in the order of appearance.
+///
+/// Costs of a group in different units are not summed but kept as separate entries.
+/// Returns `ParseError` if any of the groups cannot be parsed.
+fn try_sum_groups<C: GroupCost>(
+    res: &GetCostAndUsageResponse,
+    try_from_group: impl Fn(&Group) -> Result<C, ParseError>,
+) -> Result<Vec<C>, ParseError> {
+    let mut summed: Vec<C> = vec![];
+    for group in groups_of_all_results(res) {
+        let cost = try_from_group(group)?;
+        match summed.iter_mut().find(|x| x.is_same_group(&cost)) {
+            Some(x) => x.add(cost),
+            None => summed.push(cost),
+        }
+    }
+    Ok(summed)
 }
 
 /// The cost of a service.
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Serialize)]
 pub struct ServiceCost {
     pub service_name: String,
     pub cost: Cost,
 }
-impl From<Group> for ServiceCost {
-    /// Parse `Group` in the API response into ServiceCost.
-    fn from(from: Group) -> ServiceCost {
+impl ServiceCost {
+    /// Parse `Group` in the API response into ServiceCost,
+    /// returning `ParseError` if the cost is missing or invalid.
+    fn try_from_group(from: &Group, metric: CostMetric) -> Result<ServiceCost, ParseError> {
         let service_name = &from.keys.as_ref().unwrap()[0];
-        let amortized_cost = from
+        let cost = try_parse_group_cost(from, metric)?;
+
+        Ok(ServiceCost {
+            service_name: service_name.to_string(),
+            cost,
+        })
+    }
+
+    /// Parse the API response into a vector of `ServiceCost` with the costs of `metric`
+    ///
+    /// If the response has multiple results (e.g. of DAILY granularity),
+    /// the costs of each service are summed up across them in the order of appearance.
+    /// Costs of a service in different units are not summed but kept as separate entries.
+    /// Results without groups (e.g. of an account with no spend yet) are skipped.
+    /// Returns `ParseError` if the cost of any service is missing or invalid (e.g. `NaN`).
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        try_sum_groups(res, |x| Self::try_from_group(x, metric))
+    }
+}
+impl GroupCost for ServiceCost {
+    fn is_same_group(&self, other: &Self) -> bool {
+        self.service_name == other.service_name && self.cost.unit == other.cost.unit
+    }
+
+    fn add(&mut self, other: Self) {
+        self.cost.amount += other.cost.amount;
+    }
+}
+
+/// Check that all of `service_costs` are in the same unit as `total`,
+/// returning `ParseError` for the first service in a different unit
+/// since their costs cannot be compared or summed up.
+pub fn check_units(total: &Cost, service_costs: &[ServiceCost]) -> Result<(), ParseError> {
+    match service_costs.iter().find(|x| x.cost.unit != total.unit) {
+        Some(x) => Err(ParseError::CurrencyMismatch(
+            total.unit.clone(),
+            x.service_name.clone(),
+            x.cost.unit.clone(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// The cost of a value of a cost allocation tag.
+#[derive(Debug, PartialEq, Clone)]
+pub struct TagCost {
+    /// Tag value. It is empty for resources without the tag.
+    pub tag_value: String,
+    pub cost: Cost,
+}
+impl TagCost {
+    /// Parse `Group` in the API response into TagCost,
+    /// returning `ParseError` if the cost is missing or invalid.
+    ///
+    /// The key of the group is formatted as `{tag key}${tag value}` (e.g. `Project$frontend`).
+    fn try_from_group(from: &Group, metric: CostMetric) -> Result<TagCost, ParseError> {
+        let key = &from.keys.as_ref().unwrap()[0];
+        let tag_value = match key.split_once('$') {
+            Some((_, value)) => value,
+            None => key.as_str(),
+        };
+        let cost = try_parse_group_cost(from, metric)?;
+
+        Ok(TagCost {
+            tag_value: tag_value.to_string(),
+            cost,
+        })
+    }
+
+    /// Parse the API response into a vector of `TagCost` with the costs of `metric`
+    ///
+    /// The costs of each tag value are summed up across the results.
+    /// Returns `ParseError` if the cost of any tag value is missing or invalid (e.g. `NaN`).
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        try_sum_groups(res, |x| Self::try_from_group(x, metric))
+    }
+}
+impl GroupCost for TagCost {
+    fn is_same_group(&self, other: &Self) -> bool {
+        self.tag_value == other.tag_value && self.cost.unit == other.cost.unit
+    }
+
+    fn add(&mut self, other: Self) {
+        self.cost.amount += other.cost.amount;
+    }
+}
+
+/// The cost of a usage type.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UsageTypeCost {
+    /// Usage type (e.g. `APN1-DataTransfer-Out-Bytes`)
+    pub usage_type: String,
+    pub cost: Cost,
+    /// Usage amount with its unit (e.g. `120.5 GB`), if `UsageQuantity` is requested
+    pub usage_quantity: Option<Cost>,
+}
+impl UsageTypeCost {
+    /// Parse `Group` in the API response into UsageTypeCost,
+    /// returning `ParseError` if the cost or the usage amount is invalid.
+    fn try_from_group(from: &Group, metric: CostMetric) -> Result<UsageTypeCost, ParseError> {
+        let usage_type = &from.keys.as_ref().unwrap()[0];
+        let usage_quantity = from
             .metrics
             .as_ref()
-            .unwrap()
-            .get("AmortizedCost")
-            .unwrap()
-            .clone();
+            .and_then(|x| x.get("UsageQuantity"))
+            .map(|x| try_parse_metric(x, "UsageQuantity"))
+            .transpose()?;
 
-        ServiceCost {
-            service_name: service_name.to_string(),
-            cost: amortized_cost.into(),
+        Ok(UsageTypeCost {
+            usage_type: usage_type.to_string(),
+            cost: try_parse_group_cost(from, metric)?,
+            usage_quantity,
+        })
+    }
+
+    /// Parse the API response into a vector of `UsageTypeCost` with the costs of `metric`
+    ///
+    /// The costs and usage amounts of each usage type are summed up across the results.
+    /// Returns `ParseError` if the cost of any usage type is missing or invalid (e.g. `NaN`).
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        try_sum_groups(res, |x| Self::try_from_group(x, metric))
+    }
+}
+impl GroupCost for UsageTypeCost {
+    fn is_same_group(&self, other: &Self) -> bool {
+        self.usage_type == other.usage_type && self.cost.unit == other.cost.unit
+    }
+
+    fn add(&mut self, other: Self) {
+        self.cost.amount += other.cost.amount;
+        self.usage_quantity = match (self.usage_quantity.take(), other.usage_quantity) {
+            (Some(mut sum), Some(quantity)) if sum.unit == quantity.unit => {
+                sum.amount += quantity.amount;
+                Some(sum)
+            }
+            (sum, quantity) => sum.or(quantity),
+        };
+    }
+}
+
+/// The cost of a region.
+#[derive(Debug, PartialEq, Clone)]
+pub struct RegionCost {
+    /// Region code (e.g. `ap-northeast-1`).
+    /// Costs of global services belong to pseudo-regions such as `NoRegion` and `global`.
+    pub region: String,
+    pub cost: Cost,
+}
+impl RegionCost {
+    /// Parse `Group` in the API response into RegionCost,
+    /// returning `ParseError` if the cost is missing or invalid.
+    fn try_from_group(from: &Group, metric: CostMetric) -> Result<RegionCost, ParseError> {
+        let region = &from.keys.as_ref().unwrap()[0];
+        let cost = try_parse_group_cost(from, metric)?;
+
+        Ok(RegionCost {
+            region: region.to_string(),
+            cost,
+        })
+    }
+
+    /// Parse the API response into a vector of `RegionCost` with the costs of `metric`
+    ///
+    /// The costs of each region are summed up across the results.
+    /// Returns `ParseError` if the cost of any region is missing or invalid (e.g. `NaN`).
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        try_sum_groups(res, |x| Self::try_from_group(x, metric))
+    }
+}
+impl GroupCost for RegionCost {
+    fn is_same_group(&self, other: &Self) -> bool {
+        self.region == other.region && self.cost.unit == other.cost.unit
+    }
+
+    fn add(&mut self, other: Self) {
+        self.cost.amount += other.cost.amount;
+    }
+}
+
+/// AWS cost of each linked account.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AccountCost {
+    /// Account ID (e.g. `123456789012`)
+    pub account_id: String,
+    /// Account name given in `DimensionValueAttributes` of the API response, if any
+    pub account_name: Option<String>,
+    pub cost: Cost,
+}
+impl AccountCost {
+    /// Parse `Group` in the API response into AccountCost without the account name,
+    /// returning `ParseError` if the cost is missing or invalid.
+    fn try_from_group(from: &Group, metric: CostMetric) -> Result<AccountCost, ParseError> {
+        let account_id = &from.keys.as_ref().unwrap()[0];
+        let cost = try_parse_group_cost(from, metric)?;
+
+        Ok(AccountCost {
+            account_id: account_id.to_string(),
+            account_name: None,
+            cost,
+        })
+    }
+
+    /// Parse the API response into a vector of `AccountCost` with the costs of `metric`.
+    ///
+    /// The costs of each account are summed up across the results,
+    /// and the account IDs are mapped to their names by the `description` attributes
+    /// in `dimension_value_attributes` of the response.
+    /// Returns `ParseError` if the cost of any account is missing or invalid (e.g. `NaN`).
+    pub fn try_from_response(
+        res: &GetCostAndUsageResponse,
+        metric: CostMetric,
+    ) -> Result<Vec<Self>, ParseError> {
+        let account_names: HashMap<&str, &str> = res
+            .dimension_value_attributes
+            .iter()
+            .flatten()
+            .filter_map(|x| {
+                let description = x.attributes.as_ref()?.get("description")?;
+                Some((x.value.as_deref()?, description.as_str()))
+            })
+            .collect();
+
+        let mut account_costs = try_sum_groups(res, |x| Self::try_from_group(x, metric))?;
+        for account_cost in account_costs.iter_mut() {
+            account_cost.account_name = account_names
+                .get(account_cost.account_id.as_str())
+                .map(|x| x.to_string());
         }
+        Ok(account_costs)
     }
 }
-impl ServiceCost {
-    /// Parse the API response into a vector of `ServiceCost`
-    pub fn from_response(res: &GetCostAndUsageResponse) -> Vec<Self> {
-        let result_by_time = &res.results_by_time.as_ref().unwrap()[0];
-        let groups = result_by_time.groups.as_ref().unwrap();
-        groups.iter().map(|x| x.clone().into()).collect()
+impl GroupCost for AccountCost {
+    fn is_same_group(&self, other: &Self) -> bool {
+        self.account_id == other.account_id && self.cost.unit == other.cost.unit
+    }
+
+    fn add(&mut self, other: Self) {
+        self.cost.amount += other.cost.amount;
+    }
+}
+
+/// Forecast of the AWS cost from the reporting date to the end of the month.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ForecastCost {
+    pub cost: Cost,
+}
+impl ForecastCost {
+    /// Parse the GetCostForecast API response into `ForecastCost`.
+    ///
+    /// The forecast is zero if the response has no total.
+    /// Returns `ParseError` if the total is invalid (e.g. `NaN`).
+    pub fn try_from_response(res: &GetCostForecastResponse) -> Result<Self, ParseError> {
+        let cost = match &res.total {
+            Some(total) => try_parse_metric(total, "Total")?,
+            None => Cost::zero(),
+        };
+        Ok(ForecastCost { cost })
+    }
+}
+
+/// Utilization of the commitments (Savings Plans or Reserved Instances) in the reported period.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Utilization {
+    /// Percentage of the commitments which has been used (e.g. `95.0`).
+    pub percentage: f64,
+}
+impl Utilization {
+    /// Parse the GetSavingsPlansUtilization API response into `Utilization`.
+    ///
+    /// Returns None if the percentage is missing.
+    pub fn from_savings_plans_response(res: &GetSavingsPlansUtilizationResponse) -> Option<Self> {
+        parse_percentage(res.total.utilization.utilization_percentage.as_deref())
+    }
+
+    /// Parse the GetReservationUtilization API response into `Utilization`.
+    ///
+    /// Returns None if the percentage is missing.
+    pub fn from_reservation_response(res: &GetReservationUtilizationResponse) -> Option<Self> {
+        let total = res.total.as_ref()?;
+        parse_percentage(total.utilization_percentage.as_deref())
+    }
+}
+
+/// Parse the percentage in the utilization response (e.g. `"95.1234"`).
+fn parse_percentage(percentage: Option<&str>) -> Option<Utilization> {
+    let percentage = percentage?.parse::<f64>().ok()?;
+    Some(Utilization { percentage })
+}
+
+/// Estimated monthly savings of the account by following the rightsizing recommendations.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SavingsOpportunity {
+    pub cost: Cost,
+}
+impl SavingsOpportunity {
+    /// Parse the summary of GetRightsizingRecommendation API response into `SavingsOpportunity`.
+    ///
+    /// Returns None if the summary or its savings amount is missing.
+    pub fn from_response(res: &GetRightsizingRecommendationResponse) -> Option<Self> {
+        let summary = res.summary.as_ref()?;
+        let amount = summary
+            .estimated_total_monthly_savings_amount
+            .as_ref()?
+            .parse::<Amount>()
+            .ok()?;
+        let unit = summary
+            .savings_currency_code
+            .clone()
+            .unwrap_or_else(|| String::from("USD"));
+        Some(SavingsOpportunity {
+            cost: Cost { amount, unit },
+        })
     }
 }
 
@@ -105,19 +727,64 @@ impl ServiceCost {
 mod test_parsers {
 
     use super::*;
+    use chrono::Local;
+    use chrono_tz::Asia::Tokyo;
     use rusoto_ce::*;
+    use std::cmp::Ordering;
+
+    use crate::cost_explorer::test_utils::{
+        prepare_daily_sample_response, prepare_dimension_value_attributes,
+        prepare_forecast_sample_response, prepare_monthly_sample_response,
+        prepare_reservation_utilization_response, prepare_rightsizing_recommendation_response,
+        prepare_sample_response, prepare_savings_plans_utilization_response, replace_cost_metric,
+        InputServiceCost,
+    };
 
-    use crate::cost_explorer::test_utils::{prepare_sample_response, InputServiceCost};
+    #[test]
+    fn compare_costs_by_amount_and_unit() {
+        let cost = |amount: f64, unit: &str| Cost {
+            amount: amount.into(),
+            unit: unit.to_string(),
+        };
+
+        assert_eq!(Ordering::Less, cost(1.0, "USD").cmp(&cost(2.0, "USD")));
+        assert_eq!(Ordering::Equal, cost(1.0, "USD").cmp(&cost(1.0, "USD")));
+        assert_eq!(Ordering::Less, cost(1.0, "JPY").cmp(&cost(1.0, "USD")));
+    }
 
     #[test]
     fn parse_timestamp_into_local_date_correctly() {
         let input_timestamp = "2021-07-22";
         let expected_parsed_date = Local.ymd(2021, 7, 22);
 
-        let actual_parsed_date = parse_timestamp_into_local_date(input_timestamp).unwrap();
+        let actual_parsed_date = parse_timestamp_into_local_date(input_timestamp, &Local).unwrap();
         assert_eq!(expected_parsed_date, actual_parsed_date);
     }
 
+    #[test]
+    fn parse_timestamp_into_date_in_designated_timezone() {
+        let input_timestamp = "2021-07-22";
+        let expected_parsed_date = Tokyo.ymd(2021, 7, 22);
+
+        let actual_parsed_date = parse_timestamp_into_local_date(input_timestamp, &Tokyo).unwrap();
+        assert_eq!(expected_parsed_date, actual_parsed_date);
+        assert_eq!("2021-07-22JST", format!("{}", actual_parsed_date));
+    }
+
+    #[test]
+    fn return_error_for_malformed_timestamp() {
+        let actual_error = parse_timestamp_into_local_date("2021/07/01", &Local).unwrap_err();
+
+        assert_eq!(
+            ParseError::InvalidTimePeriod("2021/07/01".to_string()),
+            actual_error
+        );
+        assert_eq!(
+            "invalid date \"2021/07/01\" in time_period (expected YYYY-MM-DD)",
+            actual_error.to_string()
+        );
+    }
+
     #[test]
     fn parse_cost_from_metric_value_correctly() {
         let input_metric_value = MetricValue {
@@ -126,15 +793,167 @@ mod test_parsers {
         };
 
         let expected_cost = Cost {
-            amount: 123.56,
+            amount: 123.56.into(),
             unit: "USD".to_string(),
         };
 
-        let actual_cost: Cost = input_metric_value.into();
+        let actual_cost = Cost::try_from(input_metric_value).unwrap();
 
         assert_eq!(expected_cost, actual_cost);
     }
 
+    #[test]
+    fn return_error_for_non_finite_amount() {
+        for amount in ["NaN", "inf", "-inf"].iter() {
+            let input_metric_value = MetricValue {
+                amount: Some(amount.to_string()),
+                unit: Some("USD".to_string()),
+            };
+
+            assert_eq!(
+                Err(ParseError::InvalidMetric(amount.to_string())),
+                Cost::try_from(input_metric_value.clone())
+            );
+            assert_eq!(
+                Err(ParseError::InvalidMetric("AmortizedCost".to_string())),
+                try_parse_metric(&input_metric_value, "AmortizedCost")
+            );
+        }
+    }
+
+    #[test]
+    fn sum_up_many_cents_exactly() {
+        let cent = MetricValue {
+            amount: Some("0.01".to_string()),
+            unit: Some("USD".to_string()),
+        };
+        let costs = vec![Cost::try_from(cent).unwrap(); 100_000];
+
+        let actual_sum = sum_costs(costs).unwrap();
+
+        assert_eq!(Amount::new(1000, 0), actual_sum.amount);
+        assert_eq!("1000", actual_sum.amount.to_string());
+    }
+
+    #[test]
+    fn parse_large_amount_with_exact_cents() {
+        let large = MetricValue {
+            amount: Some("31415.92".to_string()),
+            unit: Some("USD".to_string()),
+        };
+        let cent = MetricValue {
+            amount: Some("0.01".to_string()),
+            unit: Some("USD".to_string()),
+        };
+
+        let actual_cost = Cost::try_from(large).unwrap();
+        let actual_sum = sum_costs(vec![actual_cost.clone(), Cost::try_from(cent).unwrap()]);
+
+        assert_eq!(Amount::new(3141592, 2), actual_cost.amount);
+        assert_eq!("31415.92", actual_cost.amount.to_string());
+        assert_eq!("31415.93", actual_sum.unwrap().amount.to_string());
+    }
+
+    #[test]
+    fn return_error_for_breakdown_costs_of_non_finite_amount() {
+        for amount in ["NaN", "inf"].iter() {
+            let input_response: GetCostAndUsageResponse = prepare_sample_response(
+                Some(DateInterval {
+                    start: String::from("2021-07-01"),
+                    end: String::from("2021-07-18"),
+                }),
+                None,
+                Some(vec![
+                    InputServiceCost::new("ap-northeast-1", "2.34"),
+                    InputServiceCost::new("us-east-1", amount),
+                ]),
+            );
+            let expected_error = || ParseError::InvalidMetric("AmortizedCost".to_string());
+
+            assert_eq!(
+                Err(expected_error()),
+                TagCost::try_from_response(&input_response, CostMetric::Amortized)
+            );
+            assert_eq!(
+                Err(expected_error()),
+                UsageTypeCost::try_from_response(&input_response, CostMetric::Amortized)
+            );
+            assert_eq!(
+                Err(expected_error()),
+                RegionCost::try_from_response(&input_response, CostMetric::Amortized)
+            );
+            assert_eq!(
+                Err(expected_error()),
+                AccountCost::try_from_response(&input_response, CostMetric::Amortized)
+            );
+        }
+    }
+
+    #[test]
+    fn return_error_for_usage_quantity_of_nan() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            None,
+            Some(vec![InputServiceCost::new(
+                "APN1-DataTransfer-Out-Bytes",
+                "1.23",
+            )
+            .with_usage_quantity("NaN", "GB")]),
+        );
+
+        assert_eq!(
+            Err(ParseError::InvalidMetric("UsageQuantity".to_string())),
+            UsageTypeCost::try_from_response(&input_response, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn return_error_for_daily_monthly_and_forecast_costs_of_nan() {
+        let daily_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![String::from("1.23"), String::from("NaN")],
+        );
+        let monthly_response: GetCostAndUsageResponse = prepare_monthly_sample_response(
+            NaiveDate::from_ymd(2021, 6, 1),
+            NaiveDate::from_ymd(2021, 7, 23),
+            vec![String::from("inf"), String::from("5.67")],
+        );
+        let expected_error = || ParseError::InvalidMetric("AmortizedCost".to_string());
+
+        assert_eq!(
+            Err(expected_error()),
+            DailyCost::try_from_response(&daily_response, &Local, CostMetric::Amortized)
+        );
+        assert_eq!(
+            Err(expected_error()),
+            MonthlyCost::try_from_response(&monthly_response, &Local, CostMetric::Amortized)
+        );
+        assert_eq!(
+            Err(ParseError::InvalidMetric("Total".to_string())),
+            ForecastCost::try_from_response(&prepare_forecast_sample_response(String::from("NaN")))
+        );
+    }
+
+    #[test]
+    fn return_error_for_service_cost_of_nan() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            None,
+            Some(vec![
+                InputServiceCost::new("AWS CloudTrail", "2.34"),
+                InputServiceCost::new("AWS Lambda", "NaN"),
+            ]),
+        );
+
+        assert_eq!(
+            Err(ParseError::InvalidMetric("AmortizedCost".to_string())),
+            ServiceCost::try_from_response(&input_response, CostMetric::Amortized)
+        );
+    }
+
     #[test]
     fn parse_total_cost_correctly() {
         let input_response: GetCostAndUsageResponse = prepare_sample_response(
@@ -152,44 +971,810 @@ mod test_parsers {
                 end_date: Local.ymd(2021, 7, 18),
             },
             cost: Cost {
-                amount: 1234.56,
+                amount: 1234.56.into(),
                 unit: String::from("USD"),
             },
         };
 
-        let actual_parsed_total_cost: TotalCost = input_response.into();
+        let actual_parsed_total_cost =
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized).unwrap();
 
         assert_eq!(expected_parsed_total_cost, actual_parsed_total_cost);
     }
 
     #[test]
-    fn parse_service_costs_correctly() {
-        let input_response: GetCostAndUsageResponse = prepare_sample_response(
-            None,
+    fn sum_up_total_of_no_service_costs_as_zero() {
+        let input_date_range = ReportedDateRange {
+            start_date: Local.ymd(2021, 7, 1),
+            end_date: Local.ymd(2021, 7, 18),
+        };
+
+        let actual_total_cost =
+            TotalCost::try_from_service_costs(input_date_range.clone(), &[]).unwrap();
+
+        assert_eq!(TotalCost::zero(input_date_range), actual_total_cost);
+        assert_eq!(Cost::zero(), actual_total_cost.cost);
+    }
+
+    #[test]
+    fn parse_response_without_groups_into_no_service_costs() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
             None,
-            Some(vec![
-                InputServiceCost::new("Amazon Simple Storage Service", "1234.56"),
-                InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
-            ]),
+            Some(vec![]),
         );
-        let expected_parsed_service_costs = vec![
-            ServiceCost {
-                service_name: String::from("Amazon Simple Storage Service"),
-                cost: Cost {
-                    amount: 1234.56,
-                    unit: String::from("USD"),
-                },
-            },
-            ServiceCost {
-                service_name: String::from("Amazon Elastic Compute Cloud"),
+        input_response.results_by_time.as_mut().unwrap()[0].groups = None;
+
+        assert!(
+            ServiceCost::try_from_response(&input_response, CostMetric::Amortized)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(ServiceCost::try_from_response(
+            &GetCostAndUsageResponse::default(),
+            CostMetric::Amortized
+        )
+        .unwrap()
+        .is_empty());
+    }
+
+    #[test]
+    fn parse_response_without_groups_into_no_breakdown_costs() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            None,
+            None,
+        );
+        input_response.results_by_time.as_mut().unwrap()[0].groups = None;
+
+        for response in [input_response, GetCostAndUsageResponse::default()] {
+            assert!(TagCost::try_from_response(&response, CostMetric::Amortized)
+                .unwrap()
+                .is_empty());
+            assert!(
+                UsageTypeCost::try_from_response(&response, CostMetric::Amortized)
+                    .unwrap()
+                    .is_empty()
+            );
+            assert!(
+                RegionCost::try_from_response(&response, CostMetric::Amortized)
+                    .unwrap()
+                    .is_empty()
+            );
+            assert!(
+                AccountCost::try_from_response(&response, CostMetric::Amortized)
+                    .unwrap()
+                    .is_empty()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_response_without_results_into_no_days_or_months() {
+        let input_response = GetCostAndUsageResponse::default();
+
+        assert_eq!(
+            Ok(vec![]),
+            DailyCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+        assert_eq!(
+            Ok(vec![]),
+            MonthlyCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn parse_day_and_month_without_total_as_zero() {
+        let mut daily_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![String::from("1.23")],
+        );
+        daily_response.results_by_time.as_mut().unwrap()[0].total = None;
+        let mut monthly_response: GetCostAndUsageResponse = prepare_monthly_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            NaiveDate::from_ymd(2021, 7, 23),
+            vec![String::from("12.34")],
+        );
+        monthly_response.results_by_time.as_mut().unwrap()[0].total = None;
+
+        assert_eq!(
+            Cost::zero(),
+            DailyCost::try_from_response(&daily_response, &Local, CostMetric::Amortized).unwrap()
+                [0]
+            .cost
+        );
+        assert_eq!(
+            Cost::zero(),
+            MonthlyCost::try_from_response(&monthly_response, &Local, CostMetric::Amortized)
+                .unwrap()[0]
+                .cost
+        );
+    }
+
+    #[test]
+    fn return_error_for_day_without_time_period() {
+        let mut input_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![String::from("1.23")],
+        );
+        input_response.results_by_time.as_mut().unwrap()[0].time_period = None;
+
+        assert_eq!(
+            Err(ParseError::MissingTimePeriod),
+            DailyCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn parse_result_without_total_as_zero() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            Some(String::from("1234.56")),
+            None,
+        );
+        input_response.results_by_time.as_mut().unwrap()[0].total = None;
+
+        let actual_total_cost =
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized).unwrap();
+
+        assert_eq!(Cost::zero(), actual_total_cost.cost);
+        assert_eq!(
+            Local.ymd(2021, 7, 18),
+            actual_total_cost.date_range.end_date
+        );
+    }
+
+    #[test]
+    fn parse_total_cost_in_non_local_timezone_correctly() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            Some(String::from("1234.56")),
+            None,
+        );
+
+        let actual_parsed_total_cost =
+            TotalCost::try_from_response(&input_response, &Tokyo, CostMetric::Amortized).unwrap();
+
+        assert_eq!(
+            Tokyo.ymd(2021, 7, 1),
+            actual_parsed_total_cost.date_range.start_date
+        );
+        assert_eq!(
+            Tokyo.ymd(2021, 7, 18),
+            actual_parsed_total_cost.date_range.end_date
+        );
+    }
+
+    #[test]
+    fn sum_up_total_costs_of_multiple_results() {
+        let input_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![
+                String::from("1.25"),
+                String::from("2.5"),
+                String::from("3.0"),
+            ],
+        );
+
+        let expected_parsed_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 4),
+            },
+            cost: Cost {
+                amount: 6.75.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        let actual_parsed_total_cost =
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized).unwrap();
+
+        assert_eq!(expected_parsed_total_cost, actual_parsed_total_cost);
+    }
+
+    #[test]
+    fn sum_up_total_costs_of_multiple_months() {
+        let input_response: GetCostAndUsageResponse = prepare_monthly_sample_response(
+            NaiveDate::from_ymd(2021, 6, 1),
+            NaiveDate::from_ymd(2021, 7, 2),
+            vec![String::from("1200.5"), String::from("34.25")],
+        );
+
+        let expected_parsed_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 6, 1),
+                end_date: Local.ymd(2021, 7, 2),
+            },
+            cost: Cost {
+                amount: 1234.75.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        let actual_parsed_total_cost =
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized).unwrap();
+
+        assert_eq!(expected_parsed_total_cost, actual_parsed_total_cost);
+    }
+
+    #[test]
+    fn return_error_for_total_costs_in_different_units() {
+        let mut input_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![String::from("1.25"), String::from("300")],
+        );
+        input_response.results_by_time.as_mut().unwrap()[1]
+            .total
+            .as_mut()
+            .unwrap()
+            .get_mut("AmortizedCost")
+            .unwrap()
+            .unit = Some(String::from("JPY"));
+
+        assert_eq!(
+            Err(ParseError::MixedUnits("USD".to_string(), "JPY".to_string())),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn return_error_for_service_cost_in_other_unit() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            Some(String::from("1.62")),
+            Some(vec![
+                InputServiceCost::new("AWS CloudTrail", "1.62"),
+                InputServiceCost::new("AWS Marketplace", "500").with_unit("JPY"),
+            ]),
+        );
+        let total_cost = Cost {
+            amount: 1.62.into(),
+            unit: String::from("USD"),
+        };
+        let service_costs =
+            ServiceCost::try_from_response(&input_response, CostMetric::Amortized).unwrap();
+
+        assert_eq!(
+            Err(ParseError::CurrencyMismatch(
+                "USD".to_string(),
+                "AWS Marketplace".to_string(),
+                "JPY".to_string()
+            )),
+            check_units(&total_cost, &service_costs)
+        );
+        assert_eq!(Ok(()), check_units(&total_cost, &service_costs[..1]));
+    }
+
+    #[test]
+    fn return_error_for_missing_results_by_time() {
+        let mut input_response: GetCostAndUsageResponse =
+            prepare_sample_response(None, Some(String::from("1234.56")), None);
+        let empty_response = GetCostAndUsageResponse::default();
+        input_response.results_by_time = Some(vec![]);
+
+        assert_eq!(
+            Err(ParseError::MissingResultsByTime),
+            TotalCost::try_from_response(&empty_response, &Local, CostMetric::Amortized)
+        );
+        assert_eq!(
+            Err(ParseError::MissingResultsByTime),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn return_error_for_missing_time_period() {
+        let input_response: GetCostAndUsageResponse =
+            prepare_sample_response(None, Some(String::from("1234.56")), None);
+
+        assert_eq!(
+            Err(ParseError::MissingTimePeriod),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn return_error_for_invalid_time_period() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021/07/01"),
+                end: String::from("2021-07-18"),
+            }),
+            Some(String::from("1234.56")),
+            None,
+        );
+
+        assert_eq!(
+            Err(ParseError::InvalidTimePeriod("2021/07/01".to_string())),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn return_error_for_missing_metric() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            Some(String::from("1234.56")),
+            None,
+        );
+        input_response.results_by_time.as_mut().unwrap()[0].total = Some(HashMap::new());
+
+        assert_eq!(
+            Err(ParseError::MissingMetric("AmortizedCost".to_string())),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn return_error_for_missing_amount() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            None,
+            None,
+        );
+
+        assert_eq!(
+            Err(ParseError::InvalidMetric("AmortizedCost".to_string())),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn parse_service_costs_correctly() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            None,
+            Some(vec![
+                InputServiceCost::new("Amazon Simple Storage Service", "1234.56"),
+                InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
+            ]),
+        );
+        let expected_parsed_service_costs = vec![
+            ServiceCost {
+                service_name: String::from("Amazon Simple Storage Service"),
                 cost: Cost {
-                    amount: 31415.92,
+                    amount: 1234.56.into(),
+                    unit: String::from("USD"),
+                },
+            },
+            ServiceCost {
+                service_name: String::from("Amazon Elastic Compute Cloud"),
+                cost: Cost {
+                    amount: 31415.92.into(),
                     unit: String::from("USD"),
                 },
             },
         ];
-        let actual_parsed_service_costs = ServiceCost::from_response(&input_response);
+        let actual_parsed_service_costs =
+            ServiceCost::try_from_response(&input_response, CostMetric::Amortized).unwrap();
 
         assert_eq!(expected_parsed_service_costs, actual_parsed_service_costs);
     }
+
+    #[test]
+    fn sum_up_service_costs_of_multiple_results() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            None,
+            Some(vec![
+                InputServiceCost::new("AWS CloudTrail", "1.25"),
+                InputServiceCost::new("AWS Lambda", "0.5"),
+            ]),
+        );
+        let next_day_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            None,
+            Some(vec![
+                InputServiceCost::new("AWS Lambda", "2.0"),
+                InputServiceCost::new("Amazon Simple Storage Service", "3.0"),
+            ]),
+        );
+        input_response
+            .results_by_time
+            .as_mut()
+            .unwrap()
+            .extend(next_day_response.results_by_time.unwrap());
+
+        let actual_parsed_service_costs: Vec<(String, Amount)> =
+            ServiceCost::try_from_response(&input_response, CostMetric::Amortized)
+                .unwrap()
+                .into_iter()
+                .map(|x| (x.service_name, x.cost.amount))
+                .collect();
+
+        assert_eq!(
+            vec![
+                ("AWS CloudTrail".to_string(), Amount::new(125, 2)),
+                ("AWS Lambda".to_string(), Amount::new(25, 1)),
+                (
+                    "Amazon Simple Storage Service".to_string(),
+                    Amount::new(3, 0)
+                ),
+            ],
+            actual_parsed_service_costs
+        );
+    }
+
+    #[test]
+    fn parse_costs_of_designated_metric() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            Some(DateInterval {
+                start: String::from("2021-07-01"),
+                end: String::from("2021-07-18"),
+            }),
+            Some(String::from("1234.56")),
+            Some(vec![InputServiceCost::new("AWS CloudTrail", "2.34")]),
+        );
+        replace_cost_metric(&mut input_response, "UnblendedCost");
+
+        let actual_total_cost =
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Unblended).unwrap();
+        let actual_service_costs =
+            ServiceCost::try_from_response(&input_response, CostMetric::Unblended).unwrap();
+
+        assert_eq!(Amount::from(1234.56), actual_total_cost.cost.amount);
+        assert_eq!(Amount::from(2.34), actual_service_costs[0].cost.amount);
+        assert_eq!(
+            Err(ParseError::MissingMetric("AmortizedCost".to_string())),
+            TotalCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn parse_tag_costs_correctly() {
+        let input_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            None,
+            Some(vec![
+                InputServiceCost::new("Project$frontend", "12.34"),
+                InputServiceCost::new("Project$", "5.67"),
+            ]),
+        );
+        let expected_parsed_tag_costs = vec![
+            TagCost {
+                tag_value: String::from("frontend"),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: String::from("USD"),
+                },
+            },
+            TagCost {
+                tag_value: String::from(""),
+                cost: Cost {
+                    amount: 5.67.into(),
+                    unit: String::from("USD"),
+                },
+            },
+        ];
+        let actual_parsed_tag_costs =
+            TagCost::try_from_response(&input_response, CostMetric::Amortized).unwrap();
+
+        assert_eq!(expected_parsed_tag_costs, actual_parsed_tag_costs);
+    }
+
+    #[test]
+    fn parse_usage_type_costs_with_usage_quantity_correctly() {
+        let metric_value = |amount: &str, unit: &str| MetricValue {
+            amount: Some(amount.to_string()),
+            unit: Some(unit.to_string()),
+        };
+        let input_group = Group {
+            keys: Some(vec!["APN1-DataTransfer-Out-Bytes".to_string()]),
+            metrics: Some(
+                vec![
+                    ("AmortizedCost".to_string(), metric_value("12.34", "USD")),
+                    ("UsageQuantity".to_string(), metric_value("120.5", "GB")),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+        };
+        let expected_parsed_usage_type_cost = UsageTypeCost {
+            usage_type: String::from("APN1-DataTransfer-Out-Bytes"),
+            cost: Cost {
+                amount: 12.34.into(),
+                unit: String::from("USD"),
+            },
+            usage_quantity: Some(Cost {
+                amount: 120.5.into(),
+                unit: String::from("GB"),
+            }),
+        };
+
+        assert_eq!(
+            expected_parsed_usage_type_cost,
+            UsageTypeCost::try_from_group(&input_group, CostMetric::Amortized).unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_account_costs_with_names_correctly() {
+        let mut input_response: GetCostAndUsageResponse = prepare_sample_response(
+            None,
+            None,
+            Some(vec![
+                InputServiceCost::new("123456789012", "12.34"),
+                InputServiceCost::new("210987654321", "5.67"),
+            ]),
+        );
+        input_response.dimension_value_attributes =
+            prepare_dimension_value_attributes(&[("123456789012", "production")]);
+        let expected_parsed_account_costs = vec![
+            AccountCost {
+                account_id: String::from("123456789012"),
+                account_name: Some(String::from("production")),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: String::from("USD"),
+                },
+            },
+            AccountCost {
+                account_id: String::from("210987654321"),
+                account_name: None,
+                cost: Cost {
+                    amount: 5.67.into(),
+                    unit: String::from("USD"),
+                },
+            },
+        ];
+        let actual_parsed_account_costs =
+            AccountCost::try_from_response(&input_response, CostMetric::Amortized).unwrap();
+
+        assert_eq!(expected_parsed_account_costs, actual_parsed_account_costs);
+    }
+
+    #[test]
+    fn parse_daily_costs_correctly() {
+        let input_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 31),
+            vec![String::from("1.23"), String::from("4.56")],
+        );
+        let expected_parsed_daily_costs = vec![
+            DailyCost {
+                date: Local.ymd(2021, 7, 31),
+                cost: Cost {
+                    amount: 1.23.into(),
+                    unit: String::from("USD"),
+                },
+                estimated: false,
+            },
+            DailyCost {
+                date: Local.ymd(2021, 8, 1),
+                cost: Cost {
+                    amount: 4.56.into(),
+                    unit: String::from("USD"),
+                },
+                estimated: false,
+            },
+        ];
+        let actual_parsed_daily_costs =
+            DailyCost::try_from_response(&input_response, &Local, CostMetric::Amortized).unwrap();
+
+        assert_eq!(expected_parsed_daily_costs, actual_parsed_daily_costs);
+    }
+
+    #[test]
+    fn preserve_estimated_flag_of_each_day() {
+        let mut input_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![
+                String::from("1.23"),
+                String::from("4.56"),
+                String::from("7.89"),
+            ],
+        );
+        let results_by_time = input_response.results_by_time.as_mut().unwrap();
+        results_by_time[1].estimated = Some(true);
+        results_by_time[2].estimated = None;
+
+        let actual_estimated_flags: Vec<bool> =
+            DailyCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+                .unwrap()
+                .iter()
+                .map(|x| x.estimated)
+                .collect();
+
+        assert_eq!(vec![false, true, false], actual_estimated_flags);
+    }
+
+    #[test]
+    fn return_error_for_malformed_date_of_day() {
+        let mut input_response: GetCostAndUsageResponse = prepare_daily_sample_response(
+            NaiveDate::from_ymd(2021, 7, 1),
+            vec![String::from("1.23"), String::from("4.56")],
+        );
+        input_response.results_by_time.as_mut().unwrap()[1]
+            .time_period
+            .as_mut()
+            .unwrap()
+            .start = String::from("2021/07/02");
+
+        assert_eq!(
+            Err(ParseError::InvalidTimePeriod("2021/07/02".to_string())),
+            DailyCost::try_from_response(&input_response, &Local, CostMetric::Amortized)
+        );
+    }
+
+    #[test]
+    fn parse_monthly_costs_correctly() {
+        let input_response: GetCostAndUsageResponse = prepare_monthly_sample_response(
+            NaiveDate::from_ymd(2021, 6, 1),
+            NaiveDate::from_ymd(2021, 7, 23),
+            vec![String::from("12.34"), String::from("5.67")],
+        );
+        let expected_parsed_monthly_costs = vec![
+            MonthlyCost {
+                start_date: Local.ymd(2021, 6, 1),
+                end_date: Local.ymd(2021, 7, 1),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: String::from("USD"),
+                },
+            },
+            MonthlyCost {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 23),
+                cost: Cost {
+                    amount: 5.67.into(),
+                    unit: String::from("USD"),
+                },
+            },
+        ];
+        let actual_parsed_monthly_costs =
+            MonthlyCost::try_from_response(&input_response, &Local, CostMetric::Amortized).unwrap();
+
+        assert_eq!(expected_parsed_monthly_costs, actual_parsed_monthly_costs);
+    }
+
+    #[test]
+    fn parse_utilization_correctly() {
+        let savings_plans_response = prepare_savings_plans_utilization_response("95.1234");
+        let reservation_response = prepare_reservation_utilization_response("62.5");
+
+        assert_eq!(
+            Some(Utilization {
+                percentage: 95.1234
+            }),
+            Utilization::from_savings_plans_response(&savings_plans_response)
+        );
+        assert_eq!(
+            Some(Utilization { percentage: 62.5 }),
+            Utilization::from_reservation_response(&reservation_response)
+        );
+        assert_eq!(
+            None,
+            Utilization::from_reservation_response(&GetReservationUtilizationResponse::default())
+        );
+    }
+
+    #[test]
+    fn parse_savings_opportunity_correctly() {
+        let input_response = prepare_rightsizing_recommendation_response("123.456");
+        let response_without_currency = GetRightsizingRecommendationResponse {
+            summary: Some(RightsizingRecommendationSummary {
+                estimated_total_monthly_savings_amount: Some(String::from("0")),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Some(SavingsOpportunity {
+                cost: Cost {
+                    amount: 123.456.into(),
+                    unit: String::from("USD"),
+                },
+            }),
+            SavingsOpportunity::from_response(&input_response)
+        );
+        assert_eq!(
+            Some(SavingsOpportunity {
+                cost: Cost {
+                    amount: 0.0.into(),
+                    unit: String::from("USD"),
+                },
+            }),
+            SavingsOpportunity::from_response(&response_without_currency)
+        );
+        assert_eq!(
+            None,
+            SavingsOpportunity::from_response(&GetRightsizingRecommendationResponse::default())
+        );
+    }
+
+    #[test]
+    fn parse_forecast_cost_correctly() {
+        let input_response = prepare_forecast_sample_response(String::from("123.456"));
+
+        let expected_forecast_cost = ForecastCost {
+            cost: Cost {
+                amount: 123.456.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        let actual_forecast_cost = ForecastCost::try_from_response(&input_response).unwrap();
+
+        assert_eq!(expected_forecast_cost, actual_forecast_cost);
+    }
+
+    #[test]
+    fn parse_forecast_without_total_as_zero() {
+        assert_eq!(
+            ForecastCost { cost: Cost::zero() },
+            ForecastCost::try_from_response(&GetCostForecastResponse::default()).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_total_cost_correctly() {
+        let input_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Tokyo.ymd(2021, 7, 1),
+                end_date: Tokyo.ymd(2021, 7, 18),
+            },
+            cost: Cost {
+                amount: 1234.5.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        assert_eq!(
+            r#"{"date_range":{"start_date":"2021-07-01","end_date":"2021-07-18"},"cost":{"amount":1234.5,"unit":"USD"}}"#,
+            serde_json::to_string(&input_total_cost).unwrap()
+        );
+    }
+
+    #[test]
+    fn encode_report_as_json() {
+        let input_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Tokyo.ymd(2021, 7, 1),
+                end_date: Tokyo.ymd(2021, 7, 9),
+            },
+            cost: Cost {
+                amount: 1.5.into(),
+                unit: String::from("USD"),
+            },
+        };
+        let input_service_costs = vec![ServiceCost {
+            service_name: "AWS Lambda".to_string(),
+            cost: Cost {
+                amount: 1.5.into(),
+                unit: String::from("USD"),
+            },
+        }];
+
+        assert_eq!(
+            json!({
+                "total_cost": {
+                    "date_range": {"start_date": "2021-07-01", "end_date": "2021-07-09"},
+                    "cost": {"amount": 1.5, "unit": "USD"},
+                },
+                "service_costs": [
+                    {"service_name": "AWS Lambda", "cost": {"amount": 1.5, "unit": "USD"}},
+                ],
+            }),
+            input_total_cost.to_json(&input_service_costs)
+        );
+    }
 }
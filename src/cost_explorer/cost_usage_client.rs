@@ -1,6 +1,11 @@
 use rusoto_ce::{
     CostExplorer, CostExplorerClient, GetCostAndUsageError, GetCostAndUsageRequest,
-    GetCostAndUsageResponse,
+    GetCostAndUsageResponse, GetCostForecastError, GetCostForecastRequest, GetCostForecastResponse,
+    GetReservationUtilizationError, GetReservationUtilizationRequest,
+    GetReservationUtilizationResponse, GetRightsizingRecommendationError,
+    GetRightsizingRecommendationRequest, GetRightsizingRecommendationResponse,
+    GetSavingsPlansUtilizationError, GetSavingsPlansUtilizationRequest,
+    GetSavingsPlansUtilizationResponse,
 };
 use rusoto_core::{Region, RusotoError};
 
@@ -16,17 +21,74 @@ pub trait GetCostAndUsage {
     ) -> Result<GetCostAndUsageResponse, RusotoError<GetCostAndUsageError>>;
 }
 
+/// Trait which picks up [get_cost_forecast](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html#tymethod.get_cost_forecast) method from [rusoto_ce::CostExplorer](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html) trait.
+#[async_trait]
+pub trait GetCostForecast {
+    /// Retrieves the forecast of AWS cost. [See this](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_cost_forecast)
+    async fn get_cost_forecast(
+        &self,
+        input: GetCostForecastRequest,
+    ) -> Result<GetCostForecastResponse, RusotoError<GetCostForecastError>>;
+}
+
+/// Trait which picks up [get_savings_plans_utilization](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html#tymethod.get_savings_plans_utilization) and [get_reservation_utilization](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html#tymethod.get_reservation_utilization) methods from [rusoto_ce::CostExplorer](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html) trait.
+#[async_trait]
+pub trait GetUtilization {
+    /// Retrieves the utilization of Savings Plans. [See this](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_savings_plans_utilization)
+    async fn get_savings_plans_utilization(
+        &self,
+        input: GetSavingsPlansUtilizationRequest,
+    ) -> Result<GetSavingsPlansUtilizationResponse, RusotoError<GetSavingsPlansUtilizationError>>;
+
+    /// Retrieves the utilization of Reserved Instances. [See this](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_reservation_utilization)
+    async fn get_reservation_utilization(
+        &self,
+        input: GetReservationUtilizationRequest,
+    ) -> Result<GetReservationUtilizationResponse, RusotoError<GetReservationUtilizationError>>;
+}
+
+/// Trait which picks up [get_rightsizing_recommendation](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html#tymethod.get_rightsizing_recommendation) method from [rusoto_ce::CostExplorer](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/trait.CostExplorer.html) trait.
+#[async_trait]
+pub trait GetRightsizingRecommendation {
+    /// Retrieves the rightsizing recommendations of EC2 instances. [See this](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_rightsizing_recommendation)
+    async fn get_rightsizing_recommendation(
+        &self,
+        input: GetRightsizingRecommendationRequest,
+    ) -> Result<GetRightsizingRecommendationResponse, RusotoError<GetRightsizingRecommendationError>>;
+}
+
 /// Wrapper of [rusoto_ce::CostExplorerClient](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html).
-/// It implements only [get_cost_and_usage](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_anomaly_subscriptions) method
-/// to send a request to [GetCostAndUsage endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetCostAndUsage.html)
+/// It implements only [get_cost_and_usage](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_anomaly_subscriptions),
+/// [get_cost_forecast](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_cost_forecast)
+/// the utilization methods and [get_rightsizing_recommendation](https://docs.rs/rusoto_ce/0.47.0/rusoto_ce/struct.CostExplorerClient.html#method.get_rightsizing_recommendation)
+/// to send requests to [GetCostAndUsage endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetCostAndUsage.html)
+/// and [GetCostForecast endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetCostForecast.html)
 /// of CostExplorer API.
 pub struct CostAndUsageClient(CostExplorerClient);
 
 impl CostAndUsageClient {
-    pub fn new() -> Self {
-        // NOTE: Region must not be ap-northeast-1
-        // because endpoint https://ce.ap-northeast1.amazonaws.com/ does not exist
-        CostAndUsageClient(CostExplorerClient::new(Region::UsEast1))
+    /// Constructor with the region to which the requests are sent.
+    /// See `cost_explorer_region`.
+    pub fn new(region: Region) -> Self {
+        CostAndUsageClient(CostExplorerClient::new(region))
+    }
+}
+
+/// Region of Cost Explorer to which the requests are sent.
+///
+/// The requests are sent to `endpoint_url` (e.g. `http://localhost:4566` of LocalStack)
+/// if given, signed for `region`.
+///
+/// NOTE: Region should be us-east-1 except for testing,
+/// because the endpoint of Cost Explorer does not exist in the other regions
+/// (e.g. https://ce.ap-northeast-1.amazonaws.com/).
+pub fn cost_explorer_region(region: &Region, endpoint_url: Option<&str>) -> Region {
+    match endpoint_url {
+        Some(endpoint) => Region::Custom {
+            name: region.name().to_string(),
+            endpoint: endpoint.to_string(),
+        },
+        None => region.clone(),
     }
 }
 
@@ -38,6 +100,78 @@ impl GetCostAndUsage for CostAndUsageClient {
         &self,
         input: GetCostAndUsageRequest,
     ) -> Result<GetCostAndUsageResponse, RusotoError<GetCostAndUsageError>> {
-        (&self.0).get_cost_and_usage(input).await
+        self.0.get_cost_and_usage(input).await
+    }
+}
+
+#[async_trait]
+impl GetCostForecast for CostAndUsageClient {
+    /// Send a request to [GetCostForecast endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetCostForecast.html)
+    /// of CostExplorer API.
+    async fn get_cost_forecast(
+        &self,
+        input: GetCostForecastRequest,
+    ) -> Result<GetCostForecastResponse, RusotoError<GetCostForecastError>> {
+        self.0.get_cost_forecast(input).await
+    }
+}
+
+#[async_trait]
+impl GetUtilization for CostAndUsageClient {
+    /// Send a request to [GetSavingsPlansUtilization endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetSavingsPlansUtilization.html)
+    /// of CostExplorer API.
+    async fn get_savings_plans_utilization(
+        &self,
+        input: GetSavingsPlansUtilizationRequest,
+    ) -> Result<GetSavingsPlansUtilizationResponse, RusotoError<GetSavingsPlansUtilizationError>>
+    {
+        self.0.get_savings_plans_utilization(input).await
+    }
+
+    /// Send a request to [GetReservationUtilization endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetReservationUtilization.html)
+    /// of CostExplorer API.
+    async fn get_reservation_utilization(
+        &self,
+        input: GetReservationUtilizationRequest,
+    ) -> Result<GetReservationUtilizationResponse, RusotoError<GetReservationUtilizationError>>
+    {
+        self.0.get_reservation_utilization(input).await
+    }
+}
+
+#[async_trait]
+impl GetRightsizingRecommendation for CostAndUsageClient {
+    /// Send a request to [GetRightsizingRecommendation endpoint](https://docs.aws.amazon.com/aws-cost-management/latest/APIReference/API_GetRightsizingRecommendation.html)
+    /// of CostExplorer API.
+    async fn get_rightsizing_recommendation(
+        &self,
+        input: GetRightsizingRecommendationRequest,
+    ) -> Result<GetRightsizingRecommendationResponse, RusotoError<GetRightsizingRecommendationError>>
+    {
+        self.0.get_rightsizing_recommendation(input).await
+    }
+}
+
+#[cfg(test)]
+mod test_cost_usage_client {
+    use super::*;
+
+    #[test]
+    fn send_requests_to_custom_region_and_endpoint() {
+        assert_eq!(
+            Region::UsEast1,
+            cost_explorer_region(&Region::UsEast1, None)
+        );
+        assert_eq!(
+            Region::UsWest2,
+            cost_explorer_region(&Region::UsWest2, None)
+        );
+        assert_eq!(
+            Region::Custom {
+                name: "us-east-1".to_string(),
+                endpoint: "http://localhost:4566".to_string(),
+            },
+            cost_explorer_region(&Region::UsEast1, Some("http://localhost:4566"))
+        );
     }
 }
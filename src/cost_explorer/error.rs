@@ -0,0 +1,135 @@
+use rusoto_core::RusotoError;
+use std::error;
+use std::fmt;
+
+/// Error in retrieving the AWS costs.
+#[derive(Debug, PartialEq)]
+pub enum CostNotificationError {
+    /// AWS credentials could not be resolved.
+    Auth(String),
+    /// Request to CostExplorer API failed.
+    Api(String),
+    /// Response of CostExplorer API could not be parsed.
+    Parse(ParseError),
+}
+impl fmt::Display for CostNotificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CostNotificationError::Auth(message) => write!(
+                f,
+                "Failed to resolve AWS credentials: {}. Check the IAM role of the Lambda function and its permissions for ce:GetCostAndUsage (and ce:GetCostForecast).",
+                message
+            ),
+            CostNotificationError::Api(message) => {
+                write!(f, "CostExplorer API request failed: {}", message)
+            }
+            CostNotificationError::Parse(e) => {
+                write!(f, "Unexpected CostExplorer API response: {}", e)
+            }
+        }
+    }
+}
+impl error::Error for CostNotificationError {}
+
+impl<E: error::Error + 'static> From<RusotoError<E>> for CostNotificationError {
+    fn from(from: RusotoError<E>) -> CostNotificationError {
+        match from {
+            RusotoError::Credentials(e) => CostNotificationError::Auth(e.to_string()),
+            e => CostNotificationError::Api(e.to_string()),
+        }
+    }
+}
+
+impl From<ParseError> for CostNotificationError {
+    fn from(from: ParseError) -> CostNotificationError {
+        CostNotificationError::Parse(from)
+    }
+}
+
+/// Field missing or invalid in the response of CostExplorer API.
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// `results_by_time` is missing or empty.
+    MissingResultsByTime,
+    /// `time_period` of the result is missing.
+    MissingTimePeriod,
+    /// Date of `time_period` is not formatted as `%Y-%m-%d`.
+    InvalidTimePeriod(String),
+    /// The metric (e.g. `AmortizedCost`) is missing in the result.
+    MissingMetric(String),
+    /// Amount or unit of the metric is missing or invalid.
+    InvalidMetric(String),
+    /// Costs to be summed across the results are in different units (e.g. `USD` and `JPY`).
+    MixedUnits(String, String),
+    /// Cost of the service (the second) is in a unit (the third)
+    /// different from that of the total cost (the first).
+    CurrencyMismatch(String, String, String),
+}
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::MissingResultsByTime => write!(f, "results_by_time is empty"),
+            ParseError::MissingTimePeriod => write!(f, "time_period is missing"),
+            ParseError::InvalidTimePeriod(date) => write!(
+                f,
+                "invalid date {:?} in time_period (expected YYYY-MM-DD)",
+                date
+            ),
+            ParseError::MissingMetric(metric) => write!(f, "metric {} is missing", metric),
+            ParseError::InvalidMetric(metric) => write!(f, "metric {} is invalid", metric),
+            ParseError::MixedUnits(unit, other) => {
+                write!(f, "costs in {} and {} cannot be summed", unit, other)
+            }
+            ParseError::CurrencyMismatch(total_unit, service_name, unit) => write!(
+                f,
+                "cost of {} is in {} while the total cost is in {}",
+                service_name, unit, total_unit
+            ),
+        }
+    }
+}
+impl error::Error for ParseError {}
+
+#[cfg(test)]
+mod test_error {
+    use super::*;
+    use rusoto_ce::GetCostAndUsageError;
+    use rusoto_core::credential::CredentialsError;
+
+    #[test]
+    fn map_credentials_error_into_auth_error() {
+        let input_error: RusotoError<GetCostAndUsageError> =
+            RusotoError::Credentials(CredentialsError::new("no credentials found"));
+
+        let actual_error = CostNotificationError::from(input_error);
+
+        assert_eq!(
+            CostNotificationError::Auth("no credentials found".to_string()),
+            actual_error
+        );
+        assert!(actual_error.to_string().contains("ce:GetCostAndUsage"));
+    }
+
+    #[test]
+    fn map_other_errors_into_api_error() {
+        let input_error: RusotoError<GetCostAndUsageError> = RusotoError::Service(
+            GetCostAndUsageError::LimitExceeded("Rate exceeded".to_string()),
+        );
+
+        assert_eq!(
+            CostNotificationError::Api("Rate exceeded".to_string()),
+            CostNotificationError::from(input_error)
+        );
+    }
+
+    #[test]
+    fn display_parse_error_correctly() {
+        let actual_error =
+            CostNotificationError::from(ParseError::MissingMetric("AmortizedCost".to_string()));
+
+        assert_eq!(
+            "Unexpected CostExplorer API response: metric AmortizedCost is missing",
+            actual_error.to_string()
+        );
+    }
+}
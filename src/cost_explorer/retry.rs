@@ -0,0 +1,373 @@
+use rusoto_ce::{
+    GetCostAndUsageError, GetCostAndUsageRequest, GetCostAndUsageResponse, GetCostForecastError,
+    GetCostForecastRequest, GetCostForecastResponse, GetReservationUtilizationError,
+    GetReservationUtilizationRequest, GetReservationUtilizationResponse,
+    GetRightsizingRecommendationError, GetRightsizingRecommendationRequest,
+    GetRightsizingRecommendationResponse, GetSavingsPlansUtilizationError,
+    GetSavingsPlansUtilizationRequest, GetSavingsPlansUtilizationResponse,
+};
+use rusoto_core::RusotoError;
+
+use async_trait::async_trait;
+use rand::{Rng, RngCore};
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::cost_explorer::cost_usage_client::{
+    GetCostAndUsage, GetCostForecast, GetRightsizingRecommendation, GetUtilization,
+};
+
+/// Upper limit of the backoff, which keeps the retries within the Lambda timeout.
+const MAX_BACKOFF: Duration = Duration::from_secs(20);
+
+/// Retry policy whose retries are shared by all the requests of a run.
+///
+/// The first attempt of each request is always sent,
+/// while every retry consumes one of the remaining retries.
+/// It keeps the number of API calls, which are charged per request,
+/// and the execution time within the Lambda timeout.
+pub struct RetryBudget {
+    /// The number of retries left.
+    remaining: AtomicU32,
+    /// Waiting time before the first retry of a request, which doubles on each retry.
+    backoff: Duration,
+    /// Source of randomness of the jitter. The backoff is not jittered if None.
+    jitter: Option<Mutex<Box<dyn RngCore + Send>>>,
+}
+impl RetryBudget {
+    pub fn new(max_retries: u32, backoff: Duration) -> Self {
+        RetryBudget {
+            remaining: AtomicU32::new(max_retries),
+            backoff,
+            jitter: None,
+        }
+    }
+
+    /// Wait a random time between zero and the backoff before each retry ("full jitter"),
+    /// drawn from `rng`.
+    ///
+    /// It spreads the retries of concurrent executions, which would otherwise
+    /// be throttled again at the same time.
+    /// A seeded `rng` makes the waiting times reproducible.
+    pub fn with_jitter<R: RngCore + Send + 'static>(mut self, rng: R) -> Self {
+        self.jitter = Some(Mutex::new(Box::new(rng)));
+        self
+    }
+
+    /// Consume one retry. Returns false if the budget has been exhausted.
+    pub fn try_consume(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| x.checked_sub(1))
+            .is_ok()
+    }
+
+    /// The number of retries left.
+    pub fn remaining(&self) -> u32 {
+        self.remaining.load(Ordering::SeqCst)
+    }
+
+    /// Waiting time before the `retries`-th retry (starting from 0) of a request,
+    /// which is capped at `MAX_BACKOFF`.
+    fn backoff(&self, retries: u32) -> Duration {
+        let backoff = self
+            .backoff
+            .checked_mul(2u32.saturating_pow(retries))
+            .map_or(MAX_BACKOFF, |x| x.min(MAX_BACKOFF));
+        match &self.jitter {
+            Some(rng) => backoff.mul_f64(rng.lock().unwrap().gen::<f64>()),
+            None => backoff,
+        }
+    }
+}
+impl fmt::Debug for RetryBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryBudget")
+            .field("remaining", &self.remaining)
+            .field("backoff", &self.backoff)
+            .field("jitter", &self.jitter.is_some())
+            .finish()
+    }
+}
+
+/// Client which retries the failed requests of `client` within `budget`.
+///
+/// Only transient errors (throttling, HTTP dispatch failures and 5xx responses) are retried.
+pub struct RetryingClient<C> {
+    client: C,
+    budget: Arc<RetryBudget>,
+}
+impl<C> RetryingClient<C> {
+    pub fn new(client: C, budget: Arc<RetryBudget>) -> Self {
+        RetryingClient { client, budget }
+    }
+
+    /// Send the request by `send` and retry while the error is transient
+    /// and the budget remains.
+    async fn send_with_retry<R, E, F, Fut>(
+        &self,
+        send: F,
+        is_throttled: fn(&E) -> bool,
+    ) -> Result<R, RusotoError<E>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<R, RusotoError<E>>>,
+    {
+        let mut retries = 0;
+        loop {
+            match send().await {
+                Err(e) if is_transient(&e, is_throttled) && self.budget.try_consume() => {
+                    let wait = self.budget.backoff(retries);
+                    println!(
+                        "Cost Explorer request failed. Retry after {:?} ({} retries left)",
+                        wait,
+                        self.budget.remaining()
+                    );
+                    tokio::time::sleep(wait).await;
+                    retries += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Whether the error is expected to be resolved by retrying.
+fn is_transient<E>(error: &RusotoError<E>, is_throttled: fn(&E) -> bool) -> bool {
+    match error {
+        RusotoError::Service(e) => is_throttled(e),
+        RusotoError::HttpDispatch(_) => true,
+        RusotoError::Unknown(res) => res.status.is_server_error() || res.status.as_u16() == 429,
+        _ => false,
+    }
+}
+
+#[async_trait]
+impl<C: GetCostAndUsage + Send + Sync> GetCostAndUsage for RetryingClient<C> {
+    async fn get_cost_and_usage(
+        &self,
+        input: GetCostAndUsageRequest,
+    ) -> Result<GetCostAndUsageResponse, RusotoError<GetCostAndUsageError>> {
+        self.send_with_retry(
+            || self.client.get_cost_and_usage(input.clone()),
+            |e| matches!(e, GetCostAndUsageError::LimitExceeded(_)),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<C: GetCostForecast + Send + Sync> GetCostForecast for RetryingClient<C> {
+    async fn get_cost_forecast(
+        &self,
+        input: GetCostForecastRequest,
+    ) -> Result<GetCostForecastResponse, RusotoError<GetCostForecastError>> {
+        self.send_with_retry(
+            || self.client.get_cost_forecast(input.clone()),
+            |e| matches!(e, GetCostForecastError::LimitExceeded(_)),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<C: GetUtilization + Send + Sync> GetUtilization for RetryingClient<C> {
+    async fn get_savings_plans_utilization(
+        &self,
+        input: GetSavingsPlansUtilizationRequest,
+    ) -> Result<GetSavingsPlansUtilizationResponse, RusotoError<GetSavingsPlansUtilizationError>>
+    {
+        self.send_with_retry(
+            || self.client.get_savings_plans_utilization(input.clone()),
+            |e| matches!(e, GetSavingsPlansUtilizationError::LimitExceeded(_)),
+        )
+        .await
+    }
+
+    async fn get_reservation_utilization(
+        &self,
+        input: GetReservationUtilizationRequest,
+    ) -> Result<GetReservationUtilizationResponse, RusotoError<GetReservationUtilizationError>>
+    {
+        self.send_with_retry(
+            || self.client.get_reservation_utilization(input.clone()),
+            |e| matches!(e, GetReservationUtilizationError::LimitExceeded(_)),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<C: GetRightsizingRecommendation + Send + Sync> GetRightsizingRecommendation
+    for RetryingClient<C>
+{
+    async fn get_rightsizing_recommendation(
+        &self,
+        input: GetRightsizingRecommendationRequest,
+    ) -> Result<GetRightsizingRecommendationResponse, RusotoError<GetRightsizingRecommendationError>>
+    {
+        self.send_with_retry(
+            || self.client.get_rightsizing_recommendation(input.clone()),
+            |e| matches!(e, GetRightsizingRecommendationError::LimitExceeded(_)),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test_retry {
+    use super::*;
+    use crate::cost_explorer::test_utils::CostAndUsageClientStub;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use rusoto_ce::DateInterval;
+
+    fn sample_request() -> GetCostAndUsageRequest {
+        GetCostAndUsageRequest {
+            granularity: "MONTHLY".to_string(),
+            metrics: vec!["AmortizedCost".to_string()],
+            time_period: DateInterval {
+                start: "2021-07-01".to_string(),
+                end: "2021-07-23".to_string(),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn retry_until_success_within_budget() {
+        let client = RetryingClient::new(
+            CostAndUsageClientStub {
+                total_cost: Some("1.0".to_string()),
+                failures: AtomicU32::new(2),
+                ..Default::default()
+            },
+            Arc::new(RetryBudget::new(3, Duration::ZERO)),
+        );
+
+        let res = client.get_cost_and_usage(sample_request()).await;
+
+        assert!(res.is_ok());
+        assert_eq!(3, client.client.attempts.load(Ordering::SeqCst));
+        assert_eq!(1, client.budget.remaining());
+    }
+
+    #[tokio::test]
+    async fn exhaust_budget_shared_across_calls() {
+        let client = RetryingClient::new(
+            CostAndUsageClientStub {
+                total_cost: Some("1.0".to_string()),
+                forecast: Some("1.0".to_string()),
+                failures: AtomicU32::new(2),
+                ..Default::default()
+            },
+            Arc::new(RetryBudget::new(3, Duration::ZERO)),
+        );
+
+        let first_res = client.get_cost_and_usage(sample_request()).await;
+        client.client.failures.store(2, Ordering::SeqCst);
+        let second_res = client
+            .get_cost_forecast(GetCostForecastRequest::default())
+            .await;
+
+        // 1st call: succeeds after 2 failures, consuming 2 retries.
+        // 2nd call: fails twice, consuming the last retry.
+        assert!(first_res.is_ok());
+        assert!(second_res.is_err());
+        assert_eq!(5, client.client.attempts.load(Ordering::SeqCst));
+        assert_eq!(0, client.budget.remaining());
+    }
+
+    #[tokio::test]
+    async fn fail_immediately_on_non_throttling_error() {
+        let client = RetryingClient::new(
+            CostAndUsageClientStub {
+                credentials_error: true,
+                ..Default::default()
+            },
+            Arc::new(RetryBudget::new(3, Duration::ZERO)),
+        );
+
+        let res = client.get_cost_and_usage(sample_request()).await;
+
+        assert!(matches!(res, Err(RusotoError::Credentials(_))));
+        assert_eq!(3, client.budget.remaining());
+    }
+
+    #[test]
+    fn regard_only_throttling_service_errors_as_transient() {
+        let is_throttled =
+            |e: &GetCostAndUsageError| matches!(e, GetCostAndUsageError::LimitExceeded(_));
+
+        assert!(is_transient(
+            &RusotoError::Service(GetCostAndUsageError::LimitExceeded(
+                "Rate exceeded".to_string()
+            )),
+            is_throttled
+        ));
+        assert!(!is_transient(
+            &RusotoError::Service(GetCostAndUsageError::DataUnavailable("No data".to_string())),
+            is_throttled
+        ));
+        assert!(!is_transient(
+            &RusotoError::<GetCostAndUsageError>::Validation("Invalid".to_string()),
+            is_throttled
+        ));
+    }
+
+    #[test]
+    fn double_backoff_on_each_retry() {
+        let budget = RetryBudget::new(3, Duration::from_millis(500));
+
+        assert_eq!(Duration::from_millis(500), budget.backoff(0));
+        assert_eq!(Duration::from_millis(2000), budget.backoff(2));
+    }
+
+    #[test]
+    fn cap_backoff_without_overflow() {
+        let budget = RetryBudget::new(100, Duration::from_millis(500));
+
+        assert_eq!(Duration::from_secs(16), budget.backoff(5));
+        assert_eq!(MAX_BACKOFF, budget.backoff(6));
+        assert_eq!(MAX_BACKOFF, budget.backoff(99));
+        assert_eq!(
+            MAX_BACKOFF,
+            RetryBudget::new(3, Duration::from_secs(u64::MAX)).backoff(1)
+        );
+    }
+
+    #[test]
+    fn jitter_backoff_by_seeded_rng() {
+        let budget =
+            RetryBudget::new(3, Duration::from_millis(500)).with_jitter(StdRng::seed_from_u64(42));
+
+        let actual_backoffs: Vec<Duration> = (0..3).map(|x| budget.backoff(x)).collect();
+
+        assert_eq!(
+            vec![
+                Duration::from_nanos(486_444_044),
+                Duration::from_nanos(524_092_124),
+                Duration::from_nanos(245_107_276),
+            ],
+            actual_backoffs
+        );
+    }
+
+    #[test]
+    fn reproduce_jitter_with_same_seed() {
+        let backoffs_of_seed = |seed: u64| -> Vec<Duration> {
+            let budget = RetryBudget::new(3, Duration::from_millis(500))
+                .with_jitter(StdRng::seed_from_u64(seed));
+            (0..5).map(|x| budget.backoff(x)).collect()
+        };
+
+        assert_eq!(backoffs_of_seed(1), backoffs_of_seed(1));
+        assert_ne!(backoffs_of_seed(1), backoffs_of_seed(2));
+        assert!(backoffs_of_seed(1)
+            .iter()
+            .zip(0..)
+            .all(|(backoff, retries)| *backoff < Duration::from_millis(500) * 2u32.pow(retries)));
+    }
+}
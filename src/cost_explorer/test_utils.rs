@@ -1,23 +1,47 @@
 use async_trait::async_trait;
+use chrono::{Datelike, Duration, NaiveDate};
 use rusoto_ce::*;
+use rusoto_core::credential::CredentialsError;
 use rusoto_core::RusotoError;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
-use crate::cost_explorer::cost_usage_client::GetCostAndUsage;
+use crate::cost_explorer::cost_usage_client::{
+    GetCostAndUsage, GetCostForecast, GetRightsizingRecommendation, GetUtilization,
+};
 
 /// Object used in tests to set the service name and its cost.
 #[derive(Clone)]
 pub struct InputServiceCost {
     service_name: String,
     cost: String,
+    /// Unit of the cost, which is `USD` by default.
+    unit: String,
+    /// Usage amount and its unit returned as `UsageQuantity` metric.
+    usage_quantity: Option<(String, String)>,
 }
 impl InputServiceCost {
     pub fn new(service_name: &str, cost: &str) -> Self {
         InputServiceCost {
             service_name: String::from(service_name),
             cost: String::from(cost),
+            unit: String::from("USD"),
+            usage_quantity: None,
         }
     }
+
+    /// Set the unit of the cost (e.g. `JPY`).
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = String::from(unit);
+        self
+    }
+
+    /// Set the usage amount returned together with the cost.
+    pub fn with_usage_quantity(mut self, amount: &str, unit: &str) -> Self {
+        self.usage_quantity = Some((String::from(amount), String::from(unit)));
+        self
+    }
 }
 impl From<InputServiceCost> for Group {
     /// Convert the `InputServiceCost` object into Group object,
@@ -28,9 +52,18 @@ impl From<InputServiceCost> for Group {
             String::from("AmortizedCost"),
             MetricValue {
                 amount: Some(from.cost.clone()),
-                unit: Some(String::from("USD")),
+                unit: Some(from.unit.clone()),
             },
         );
+        if let Some((amount, unit)) = from.usage_quantity {
+            metrics.insert(
+                String::from("UsageQuantity"),
+                MetricValue {
+                    amount: Some(amount),
+                    unit: Some(unit),
+                },
+            );
+        }
         Group {
             keys: Some(vec![from.service_name.clone()]),
             metrics: Some(metrics),
@@ -52,10 +85,8 @@ pub fn prepare_sample_response(
             unit: Some(String::from("USD")),
         },
     );
-    let input_grouped_costs: Option<Vec<Group>> = match service_costs {
-        Some(service_costs) => Some(service_costs.iter().map(|x| x.clone().into()).collect()),
-        None => None,
-    };
+    let input_grouped_costs: Option<Vec<Group>> =
+        service_costs.map(|service_costs| service_costs.iter().map(|x| x.clone().into()).collect());
 
     GetCostAndUsageResponse {
         dimension_value_attributes: None,
@@ -70,39 +101,495 @@ pub fn prepare_sample_response(
     }
 }
 
+/// Replace the key of the costs (`AmortizedCost`) in the sample response with `metric`.
+pub fn replace_cost_metric(response: &mut GetCostAndUsageResponse, metric: &str) {
+    let replace = |metrics: &mut HashMap<String, MetricValue>| {
+        if let Some(value) = metrics.remove("AmortizedCost") {
+            metrics.insert(metric.to_string(), value);
+        }
+    };
+    for result_by_time in response.results_by_time.iter_mut().flatten() {
+        result_by_time.total.iter_mut().for_each(replace);
+        for group in result_by_time.groups.iter_mut().flatten() {
+            group.metrics.iter_mut().for_each(replace);
+        }
+    }
+}
+
+/// Prepare sample object of Cost Explorer API response of DAILY granularity.
+/// The total cost of each day is set from `daily_costs` in order starting from `start_date`.
+pub fn prepare_daily_sample_response(
+    start_date: NaiveDate,
+    daily_costs: Vec<String>,
+) -> GetCostAndUsageResponse {
+    let results_by_time = daily_costs
+        .into_iter()
+        .enumerate()
+        .map(|(i, daily_cost)| {
+            let date = start_date + Duration::days(i as i64);
+            let mut total = HashMap::new();
+            total.insert(
+                String::from("AmortizedCost"),
+                MetricValue {
+                    amount: Some(daily_cost),
+                    unit: Some(String::from("USD")),
+                },
+            );
+            ResultByTime {
+                estimated: Some(false),
+                groups: None,
+                time_period: Some(DateInterval {
+                    start: date.format("%Y-%m-%d").to_string(),
+                    end: date.succ().format("%Y-%m-%d").to_string(),
+                }),
+                total: Some(total),
+            }
+        })
+        .collect();
+
+    GetCostAndUsageResponse {
+        dimension_value_attributes: None,
+        group_definitions: None,
+        next_page_token: None,
+        results_by_time: Some(results_by_time),
+    }
+}
+
+/// Prepare sample object of Cost Explorer API response of MONTHLY granularity
+/// for the period from `start_date` to `end_date`.
+/// The total cost of each month is set from `monthly_costs` in order,
+/// and the period of the last month ends on `end_date`.
+pub fn prepare_monthly_sample_response(
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    monthly_costs: Vec<String>,
+) -> GetCostAndUsageResponse {
+    let mut month_start = start_date;
+    let results_by_time = monthly_costs
+        .into_iter()
+        .map(|monthly_cost| {
+            let next_month_start = (month_start.with_day(28).unwrap() + Duration::days(4))
+                .with_day(1)
+                .unwrap();
+            let month_end = next_month_start.min(end_date);
+            let mut total = HashMap::new();
+            total.insert(
+                String::from("AmortizedCost"),
+                MetricValue {
+                    amount: Some(monthly_cost),
+                    unit: Some(String::from("USD")),
+                },
+            );
+            let result_by_time = ResultByTime {
+                estimated: Some(false),
+                groups: None,
+                time_period: Some(DateInterval {
+                    start: month_start.format("%Y-%m-%d").to_string(),
+                    end: month_end.format("%Y-%m-%d").to_string(),
+                }),
+                total: Some(total),
+            };
+            month_start = next_month_start;
+            result_by_time
+        })
+        .collect();
+
+    GetCostAndUsageResponse {
+        dimension_value_attributes: None,
+        group_definitions: None,
+        next_page_token: None,
+        results_by_time: Some(results_by_time),
+    }
+}
+
+/// Prepare sample object of GetSavingsPlansUtilization API response
+/// with the total `utilization_percentage`.
+pub fn prepare_savings_plans_utilization_response(
+    utilization_percentage: &str,
+) -> GetSavingsPlansUtilizationResponse {
+    GetSavingsPlansUtilizationResponse {
+        savings_plans_utilizations_by_time: None,
+        total: SavingsPlansUtilizationAggregates {
+            utilization: SavingsPlansUtilization {
+                utilization_percentage: Some(utilization_percentage.to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    }
+}
+
+/// Prepare sample object of GetReservationUtilization API response
+/// with the total `utilization_percentage`.
+pub fn prepare_reservation_utilization_response(
+    utilization_percentage: &str,
+) -> GetReservationUtilizationResponse {
+    GetReservationUtilizationResponse {
+        next_page_token: None,
+        total: Some(ReservationAggregates {
+            utilization_percentage: Some(utilization_percentage.to_string()),
+            ..Default::default()
+        }),
+        utilizations_by_time: vec![],
+    }
+}
+
+/// Prepare sample object of GetRightsizingRecommendation API response
+/// whose summary has the estimated monthly savings of `savings_amount` USD.
+pub fn prepare_rightsizing_recommendation_response(
+    savings_amount: &str,
+) -> GetRightsizingRecommendationResponse {
+    GetRightsizingRecommendationResponse {
+        summary: Some(RightsizingRecommendationSummary {
+            estimated_total_monthly_savings_amount: Some(savings_amount.to_string()),
+            savings_currency_code: Some(String::from("USD")),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Prepare sample `dimension_value_attributes` which maps
+/// the account IDs to their names as `description` attributes.
+pub fn prepare_dimension_value_attributes(
+    account_names: &[(&str, &str)],
+) -> Option<Vec<DimensionValuesWithAttributes>> {
+    Some(
+        account_names
+            .iter()
+            .map(|(id, name)| DimensionValuesWithAttributes {
+                value: Some(id.to_string()),
+                attributes: Some(
+                    vec![("description".to_string(), name.to_string())]
+                        .into_iter()
+                        .collect(),
+                ),
+            })
+            .collect(),
+    )
+}
+
+/// Prepare sample object of GetCostForecast API response with the forecast `total`.
+pub fn prepare_forecast_sample_response(total: String) -> GetCostForecastResponse {
+    GetCostForecastResponse {
+        forecast_results_by_time: None,
+        total: Some(MetricValue {
+            amount: Some(total),
+            unit: Some(String::from("USD")),
+        }),
+    }
+}
+
+/// `next_page_token` returned by `CostAndUsageClientStub` with the first page of service costs.
+const NEXT_PAGE_TOKEN: &str = "page-2";
+
+/// Costs returned by `CostAndUsageClientStub` for the request
+/// whose period starts on `start_date` (formatted as `%Y-%m-%d`).
+#[derive(Clone, Default)]
+pub struct PeriodCosts {
+    pub start_date: String,
+    pub total_cost: Option<String>,
+    pub service_costs: Option<Vec<InputServiceCost>>,
+    pub usage_type_costs: Option<Vec<InputServiceCost>>,
+}
+
 /// A Stub of `CostAndUsageClient` used for testing functions and methods
 /// which call CostExplorer API.
-/// `service_costs` and `total_cost` fields are used in
+/// `service_costs`, `total_cost` and `tag_costs` fields are used in
 /// the mock API response.
+#[derive(Default)]
 pub struct CostAndUsageClientStub {
     pub service_costs: Option<Vec<InputServiceCost>>,
     pub total_cost: Option<String>,
+    /// Service costs returned as the second page.
+    /// If set, `service_costs` is returned as the first page with `next_page_token`.
+    pub next_page_service_costs: Option<Vec<InputServiceCost>>,
+    /// Costs grouped by tag values, whose names are formatted as `{tag key}${tag value}`.
+    pub tag_costs: Option<Vec<InputServiceCost>>,
+    /// Costs grouped by usage types.
+    pub usage_type_costs: Option<Vec<InputServiceCost>>,
+    /// Costs grouped by regions.
+    pub region_costs: Option<Vec<InputServiceCost>>,
+    /// Costs grouped by linked account IDs.
+    pub account_costs: Option<Vec<InputServiceCost>>,
+    /// Names of the linked accounts returned as `dimension_value_attributes`.
+    pub account_names: Vec<(String, String)>,
+    /// Total costs of each day from the start date, returned for DAILY granularity.
+    pub daily_costs: Option<Vec<String>>,
+    /// Total costs of each month from the start date, returned for the total
+    /// of MONTHLY granularity over multiple months.
+    pub monthly_costs: Option<Vec<String>>,
+    /// Total cost, service and usage type costs of periods other than the reporting period
+    /// (e.g. the previous period), used instead of `total_cost`, `service_costs`
+    /// and `usage_type_costs`.
+    pub other_periods: Vec<PeriodCosts>,
+    /// Total cost of the second metric of the request, if any,
+    /// which is returned along with `total_cost`.
+    pub cross_check_total: Option<String>,
+    /// Forecast of the cost returned by GetCostForecast.
+    pub forecast: Option<String>,
+    /// Utilization percentage of Savings Plans.
+    /// If None, the request fails with `DataUnavailable` as no Savings Plans are active.
+    pub savings_plans_utilization: Option<String>,
+    /// Utilization percentage of Reserved Instances.
+    /// If None, the request fails with `DataUnavailable` as no reservations are active.
+    pub reservation_utilization: Option<String>,
+    /// Estimated monthly savings by the rightsizing recommendations.
+    /// If None, the response has no summary.
+    pub rightsizing_savings: Option<String>,
+    /// The number of the requests which fail with `LimitExceeded` before succeeding.
+    pub failures: AtomicU32,
+    /// The number of the requests received so far.
+    /// Shared so that it can be read after the stub is moved.
+    pub attempts: Arc<AtomicU32>,
+    /// If true, every request fails as AWS credentials could not be resolved.
+    pub credentials_error: bool,
 }
-#[async_trait]
-impl GetCostAndUsage for CostAndUsageClientStub {
-    /// Return the mock of CostExplorer API response.
-    /// If `group_by` field of the request object is empty,
-    /// it returns a response object which has total cost.
-    /// Otherwise, the response object has service costs
-    /// and its total cost is None.
-    async fn get_cost_and_usage(
+impl CostAndUsageClientStub {
+    /// Build the mock of CostExplorer API response for `input`,
+    /// whose costs are keyed by `AmortizedCost`.
+    fn prepare_cost_and_usage_response(
         &self,
         input: GetCostAndUsageRequest,
-    ) -> Result<GetCostAndUsageResponse, RusotoError<GetCostAndUsageError>> {
+    ) -> GetCostAndUsageResponse {
+        if input.granularity == "DAILY" {
+            let start_date =
+                NaiveDate::parse_from_str(&input.time_period.start, "%Y-%m-%d").unwrap();
+            let mut response = prepare_daily_sample_response(
+                start_date,
+                self.daily_costs.clone().unwrap_or_default(),
+            );
+            if let Some(group_by) = &input.group_by {
+                let grouped_costs = self.daily_grouped_costs(&group_by[0]);
+                for result_by_time in response.results_by_time.iter_mut().flatten() {
+                    result_by_time.groups = grouped_costs
+                        .as_ref()
+                        .map(|x| x.iter().map(|x| x.clone().into()).collect());
+                    result_by_time.total = None;
+                }
+                if group_by[0].key.as_deref() == Some("LINKED_ACCOUNT") {
+                    response.dimension_value_attributes = self.account_name_attributes();
+                }
+            }
+            return response;
+        }
+
+        if let (Some(monthly_costs), None) = (&self.monthly_costs, &input.group_by) {
+            let start_date =
+                NaiveDate::parse_from_str(&input.time_period.start, "%Y-%m-%d").unwrap();
+            let end_date = NaiveDate::parse_from_str(&input.time_period.end, "%Y-%m-%d").unwrap();
+            if end_date.pred().with_day(1).unwrap() > start_date {
+                return prepare_monthly_sample_response(
+                    start_date,
+                    end_date,
+                    monthly_costs.clone(),
+                );
+            }
+        }
+
+        let period_costs = self
+            .other_periods
+            .iter()
+            .find(|x| x.start_date == input.time_period.start);
+
         let service_costs: Option<Vec<InputServiceCost>>;
         let total_cost: Option<String>;
+        let mut dimension_value_attributes = None;
+        let mut next_page_token = None;
         match input.group_by {
+            Some(group_by) if group_by[0].type_.as_deref() == Some("TAG") => {
+                service_costs = self.tag_costs.clone();
+                total_cost = None;
+            }
+            Some(group_by) if group_by[0].key.as_deref() == Some("USAGE_TYPE") => {
+                service_costs = match period_costs {
+                    Some(x) => x.usage_type_costs.clone(),
+                    None => self.usage_type_costs.clone(),
+                };
+                total_cost = None;
+            }
+            Some(group_by) if group_by[0].key.as_deref() == Some("REGION") => {
+                service_costs = self.region_costs.clone();
+                total_cost = None;
+            }
+            Some(group_by) if group_by[0].key.as_deref() == Some("LINKED_ACCOUNT") => {
+                service_costs = self.account_costs.clone();
+                total_cost = None;
+                dimension_value_attributes = self.account_name_attributes();
+            }
+            Some(_) if input.next_page_token.as_deref() == Some(NEXT_PAGE_TOKEN) => {
+                service_costs = self.next_page_service_costs.clone();
+                total_cost = None;
+            }
             Some(_) => {
-                service_costs = self.service_costs.clone();
+                service_costs = match period_costs {
+                    Some(x) => x.service_costs.clone(),
+                    None => self.service_costs.clone(),
+                };
                 total_cost = None;
+                if self.next_page_service_costs.is_some() {
+                    next_page_token = Some(NEXT_PAGE_TOKEN.to_string());
+                }
             }
             None => {
                 service_costs = None;
-                total_cost = self.total_cost.clone();
+                total_cost = match period_costs {
+                    Some(x) => x.total_cost.clone(),
+                    None => self.total_cost.clone(),
+                };
             }
         }
-        let response: GetCostAndUsageResponse =
+        let mut response: GetCostAndUsageResponse =
             prepare_sample_response(Some(input.time_period), total_cost, service_costs);
+        response.dimension_value_attributes = dimension_value_attributes;
+        response.next_page_token = next_page_token;
+        response
+    }
+
+    /// Costs returned on each day of DAILY granularity for the request grouped by `group_by`.
+    fn daily_grouped_costs(&self, group_by: &GroupDefinition) -> Option<Vec<InputServiceCost>> {
+        match (group_by.type_.as_deref(), group_by.key.as_deref()) {
+            (Some("TAG"), _) => self.tag_costs.clone(),
+            (_, Some("USAGE_TYPE")) => self.usage_type_costs.clone(),
+            (_, Some("REGION")) => self.region_costs.clone(),
+            (_, Some("LINKED_ACCOUNT")) => self.account_costs.clone(),
+            _ => self.service_costs.clone(),
+        }
+    }
+
+    /// `account_names` as `dimension_value_attributes` of the response.
+    fn account_name_attributes(&self) -> Option<Vec<DimensionValuesWithAttributes>> {
+        let account_names: Vec<(&str, &str)> = self
+            .account_names
+            .iter()
+            .map(|(id, name)| (id.as_str(), name.as_str()))
+            .collect();
+        prepare_dimension_value_attributes(&account_names)
+    }
+
+    /// Count the request and return whether it should fail.
+    fn fails(&self) -> bool {
+        self.attempts.fetch_add(1, Ordering::SeqCst);
+        self.failures
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| x.checked_sub(1))
+            .is_ok()
+    }
+}
+#[async_trait]
+impl GetCostAndUsage for CostAndUsageClientStub {
+    /// Return the mock of CostExplorer API response.
+    /// The first `failures` requests fail with `LimitExceeded`
+    /// and all the requests fail if `credentials_error` is true.
+    /// The costs are keyed by the metric of the request.
+    /// If the request has the second metric, the total is also keyed by it
+    /// with the amount of `cross_check_total`.
+    /// If `granularity` of the request is DAILY, it returns the daily total costs,
+    /// or the service (or tag, usage type, region and account) costs on each day if it is grouped.
+    /// If `monthly_costs` is set and the total cost over multiple months is requested,
+    /// it returns the monthly total costs.
+    /// If `group_by` field of the request object is empty,
+    /// it returns a response object which has total cost.
+    /// Otherwise, the response object has service costs
+    /// (or tag, usage type, region and account costs if it is grouped by them)
+    /// and its total cost is None.
+    /// The account costs are returned with `account_names` as `dimension_value_attributes`.
+    /// If `next_page_service_costs` is set, the service costs are returned in two pages.
+    /// The total cost, service and usage type costs are taken from `other_periods`
+    /// if the start date of the request matches.
+    async fn get_cost_and_usage(
+        &self,
+        input: GetCostAndUsageRequest,
+    ) -> Result<GetCostAndUsageResponse, RusotoError<GetCostAndUsageError>> {
+        if self.credentials_error {
+            return Err(RusotoError::Credentials(CredentialsError::new(
+                "No credentials found",
+            )));
+        }
+        if self.fails() {
+            return Err(RusotoError::Service(GetCostAndUsageError::LimitExceeded(
+                "Rate exceeded".to_string(),
+            )));
+        }
+        let metrics = input.metrics.clone();
+        let mut response = self.prepare_cost_and_usage_response(input);
+        replace_cost_metric(&mut response, &metrics[0]);
+        if let (Some(metric), Some(amount)) = (metrics.get(1), &self.cross_check_total) {
+            for result_by_time in response.results_by_time.iter_mut().flatten() {
+                result_by_time.total.iter_mut().for_each(|total| {
+                    total.insert(
+                        metric.to_string(),
+                        MetricValue {
+                            amount: Some(amount.to_string()),
+                            unit: Some(String::from("USD")),
+                        },
+                    );
+                });
+            }
+        }
         Ok(response)
     }
 }
+#[async_trait]
+impl GetCostForecast for CostAndUsageClientStub {
+    /// Return the mock of GetCostForecast API response which has `forecast` as its total.
+    async fn get_cost_forecast(
+        &self,
+        _input: GetCostForecastRequest,
+    ) -> Result<GetCostForecastResponse, RusotoError<GetCostForecastError>> {
+        if self.fails() {
+            return Err(RusotoError::Service(GetCostForecastError::LimitExceeded(
+                "Rate exceeded".to_string(),
+            )));
+        }
+        Ok(prepare_forecast_sample_response(
+            self.forecast.clone().unwrap(),
+        ))
+    }
+}
+#[async_trait]
+impl GetUtilization for CostAndUsageClientStub {
+    /// Return the mock of GetSavingsPlansUtilization API response
+    /// which has `savings_plans_utilization` as its total.
+    async fn get_savings_plans_utilization(
+        &self,
+        _input: GetSavingsPlansUtilizationRequest,
+    ) -> Result<GetSavingsPlansUtilizationResponse, RusotoError<GetSavingsPlansUtilizationError>>
+    {
+        match &self.savings_plans_utilization {
+            Some(percentage) => Ok(prepare_savings_plans_utilization_response(percentage)),
+            None => Err(RusotoError::Service(
+                GetSavingsPlansUtilizationError::DataUnavailable("No Savings Plans".to_string()),
+            )),
+        }
+    }
+
+    /// Return the mock of GetReservationUtilization API response
+    /// which has `reservation_utilization` as its total.
+    async fn get_reservation_utilization(
+        &self,
+        _input: GetReservationUtilizationRequest,
+    ) -> Result<GetReservationUtilizationResponse, RusotoError<GetReservationUtilizationError>>
+    {
+        match &self.reservation_utilization {
+            Some(percentage) => Ok(prepare_reservation_utilization_response(percentage)),
+            None => Err(RusotoError::Service(
+                GetReservationUtilizationError::DataUnavailable("No reservations".to_string()),
+            )),
+        }
+    }
+}
+#[async_trait]
+impl GetRightsizingRecommendation for CostAndUsageClientStub {
+    /// Return the mock of GetRightsizingRecommendation API response
+    /// whose summary has `rightsizing_savings` as the estimated monthly savings.
+    async fn get_rightsizing_recommendation(
+        &self,
+        _input: GetRightsizingRecommendationRequest,
+    ) -> Result<GetRightsizingRecommendationResponse, RusotoError<GetRightsizingRecommendationError>>
+    {
+        match &self.rightsizing_savings {
+            Some(amount) => Ok(prepare_rightsizing_recommendation_response(amount)),
+            None => Ok(GetRightsizingRecommendationResponse::default()),
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::message_builder::NotificationMessage;
+use crate::slack_notifier::SendMessage;
+
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use slack_hook::Error;
+
+/// Maximum number of characters of the title of an embed.
+const MAX_TITLE_CHARS: usize = 256;
+
+/// Maximum number of characters of the description of an embed.
+const MAX_DESCRIPTION_CHARS: usize = 4096;
+
+/// Maximum number of characters of the footer text of an embed.
+const MAX_FOOTER_CHARS: usize = 2048;
+
+/// Maximum number of characters of the titles, descriptions and footers of all embeds.
+const MAX_TOTAL_CHARS: usize = 6000;
+
+/// Color of the embed of the comparison with the previous period.
+const COMPARISON_COLOR: u32 = 0x43_9f_e0;
+
+/// Truncate `text` to `max_chars` characters, ending with `…` if truncated.
+pub(crate) fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+impl NotificationMessage {
+    /// Create the payload of Discord Webhook from `NotificationMessage` object.
+    ///
+    /// The header is displayed as the title and the body as the description
+    /// of the embed colored by the severity, which are truncated to 256 and 4096 characters.
+    /// The comparison with the previous period, if any, follows as the second embed,
+    /// and the footer is displayed in the footer of the last embed.
+    ///
+    /// Since Discord rejects the embeds longer than 6000 characters in total,
+    /// the descriptions are truncated further to the characters left by the others.
+    fn as_discord_payload(&self) -> Value {
+        let color = u32::from_str_radix(self.severity.color().trim_start_matches('#'), 16).unwrap();
+        let title = truncate_with_ellipsis(&self.header, MAX_TITLE_CHARS);
+        let footer = self
+            .footer
+            .as_ref()
+            .map(|x| truncate_with_ellipsis(x, MAX_FOOTER_CHARS));
+        let mut remaining_chars = MAX_TOTAL_CHARS
+            - title.chars().count()
+            - footer.as_ref().map_or(0, |x| x.chars().count());
+
+        let description =
+            truncate_with_ellipsis(&self.body, MAX_DESCRIPTION_CHARS.min(remaining_chars));
+        remaining_chars -= description.chars().count();
+        let mut embeds = vec![json!({
+            "title": title,
+            "description": description,
+            "color": color,
+        })];
+        if let Some(comparison) = self.comparison.as_ref().filter(|_| remaining_chars > 0) {
+            embeds.push(json!({
+                "description": truncate_with_ellipsis(
+                    comparison,
+                    MAX_DESCRIPTION_CHARS.min(remaining_chars)
+                ),
+                "color": COMPARISON_COLOR,
+            }));
+        }
+        if let (Some(footer), Some(last)) = (footer, embeds.last_mut()) {
+            last["footer"] = json!({ "text": footer });
+        }
+        json!({ "embeds": embeds })
+    }
+}
+
+/// An object to send notification message to Discord via Webhook.
+pub struct DiscordNotifier {
+    url: Url,
+    client: Client,
+}
+impl DiscordNotifier {
+    pub fn new(webhook_url: &str) -> Result<Self, Error> {
+        Ok(DiscordNotifier {
+            url: Url::parse(webhook_url)?,
+            client: Client::new(),
+        })
+    }
+}
+impl SendMessage for DiscordNotifier {
+    /// Send message to Discord as embeds.
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(&message.as_discord_payload())
+            .send()?;
+        match response.status().as_u16() {
+            200..=299 => Ok(()),
+            status => Err(Error::from(format!("HTTP error {}", status))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_discord_payload {
+    use super::*;
+    use crate::message_builder::Severity;
+
+    #[test]
+    fn build_embed_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 0.01 USD\n・AWS Cost Explorer: 0.18 USD".to_string(),
+            severity: Severity::Critical,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            json!({
+                "embeds": [{
+                    "title": "07/01~07/11の請求額は、1.62 USDです。",
+                    "description": "・AWS CloudTrail: 0.01 USD\n・AWS Cost Explorer: 0.18 USD",
+                    "color": 0xd00000,
+                }]
+            }),
+            sample_message.as_discord_payload()
+        );
+    }
+
+    #[test]
+    fn append_comparison_embed_with_footer() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            comparison: Some("【前月同期比較】\n合計: 1.00 USD → 1.62 USD".to_string()),
+            footer: Some("Dashboard: https://example.com/cost".to_string()),
+            ..Default::default()
+        };
+
+        let actual_payload = sample_message.as_discord_payload();
+
+        assert_eq!(
+            json!({
+                "description": "【前月同期比較】\n合計: 1.00 USD → 1.62 USD",
+                "color": COMPARISON_COLOR,
+                "footer": {"text": "Dashboard: https://example.com/cost"},
+            }),
+            actual_payload["embeds"][1]
+        );
+        assert_eq!(Value::Null, actual_payload["embeds"][0]["footer"]);
+    }
+
+    #[test]
+    fn truncate_long_description_with_ellipsis() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS Lambda: 1.00 USD\n".repeat(300),
+            ..Default::default()
+        };
+
+        let actual_description = sample_message.as_discord_payload()["embeds"][0]["description"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(MAX_DESCRIPTION_CHARS, actual_description.chars().count());
+        assert!(actual_description.starts_with("・AWS Lambda: 1.00 USD\n・AWS Lambda"));
+        assert!(actual_description.ends_with('…'));
+    }
+
+    #[test]
+    fn truncate_long_title_with_ellipsis() {
+        let sample_message = NotificationMessage {
+            header: format!("Total cost for {} is 1.62 USD.", "07/01~07/11 ".repeat(30)),
+            body: "- AWS Lambda: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+
+        let actual_title = sample_message.as_discord_payload()["embeds"][0]["title"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        assert_eq!(MAX_TITLE_CHARS, actual_title.chars().count());
+        assert!(actual_title.starts_with("Total cost for 07/01~07/11 07/01~07/11"));
+        assert!(actual_title.ends_with('…'));
+    }
+
+    #[test]
+    fn keep_all_embeds_within_total_limit() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS Lambda: 1.00 USD\n".repeat(300),
+            comparison: Some("・AWS Lambda: 1.00 USD → 1.62 USD\n".repeat(300)),
+            footer: Some("Dashboard: https://example.com/cost".to_string()),
+            ..Default::default()
+        };
+
+        let actual_payload = sample_message.as_discord_payload();
+        let chars = |value: &Value| value.as_str().map_or(0, |x| x.chars().count());
+        let embeds = actual_payload["embeds"].as_array().unwrap();
+        let total_chars: usize = embeds
+            .iter()
+            .map(|x| chars(&x["title"]) + chars(&x["description"]) + chars(&x["footer"]["text"]))
+            .sum();
+
+        assert_eq!(2, embeds.len());
+        assert_eq!(MAX_TOTAL_CHARS, total_chars);
+        assert_eq!(MAX_DESCRIPTION_CHARS, chars(&embeds[0]["description"]));
+        assert!(embeds[1]["description"].as_str().unwrap().ends_with('…'));
+        assert_eq!(
+            "Dashboard: https://example.com/cost",
+            embeds[1]["footer"]["text"]
+        );
+    }
+
+    #[test]
+    fn keep_text_within_limit() {
+        assert_eq!("12345", truncate_with_ellipsis("12345", 5));
+        assert_eq!("123…", truncate_with_ellipsis("12345", 4));
+        assert_eq!("請求…", truncate_with_ellipsis("請求額です", 3));
+    }
+}
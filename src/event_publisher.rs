@@ -0,0 +1,246 @@
+use async_trait::async_trait;
+use rusoto_core::signature::SignedRequest;
+use rusoto_core::{Client, Region};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::error;
+
+/// Error in publishing the event.
+pub type PublishError = Box<dyn error::Error + Send + Sync>;
+
+/// Entry of the request of [PutEvents](https://docs.aws.amazon.com/eventbridge/latest/APIReference/API_PutEvents.html)
+/// of EventBridge API.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PutEventsRequestEntry {
+    /// Source of the event (e.g. `aws-cost-notification`).
+    pub source: String,
+    /// Type of the event, which is matched by the rules together with `source`.
+    pub detail_type: String,
+    /// JSON object of the event encoded as a string.
+    pub detail: String,
+    /// Event bus to which the event is sent. The default bus is used if None.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_bus_name: Option<String>,
+}
+
+/// Trait to call PutEvents method of EventBridge API.
+#[async_trait]
+pub trait PutEvents {
+    async fn put_events(&self, entries: Vec<PutEventsRequestEntry>) -> Result<(), PublishError>;
+}
+
+/// Client to send requests to EventBridge API of the region of the Lambda function.
+///
+/// The requests are signed by the shared client of rusoto.
+pub struct EventBridgeClient {
+    client: Client,
+    region: Region,
+}
+impl EventBridgeClient {
+    pub fn new() -> Self {
+        EventBridgeClient {
+            client: Client::shared(),
+            region: Region::default(),
+        }
+    }
+}
+#[async_trait]
+impl PutEvents for EventBridgeClient {
+    async fn put_events(&self, entries: Vec<PutEventsRequestEntry>) -> Result<(), PublishError> {
+        let mut request = SignedRequest::new("POST", "events", &self.region, "/");
+        request.set_content_type("application/x-amz-json-1.1".to_string());
+        request.add_header("x-amz-target", "AWSEvents.PutEvents");
+        request.set_payload(Some(serde_json::to_vec(&json!({ "Entries": entries }))?));
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|e| format!("Failed to send PutEvents request: {:?}", e))?;
+        let response = response.buffer().await?;
+        if !response.status.is_success() {
+            return Err(format!(
+                "PutEvents failed with HTTP status {}: {}",
+                response.status,
+                String::from_utf8_lossy(&response.body)
+            )
+            .into());
+        }
+        parse_put_events_response(&serde_json::from_slice(&response.body)?)
+    }
+}
+
+/// Check `FailedEntryCount` of the response of PutEvents,
+/// since the request succeeds even if the entries are rejected.
+///
+/// The error is of the first rejected entry, which has `ErrorCode`
+/// while the accepted ones have `EventId`.
+fn parse_put_events_response(response: &Value) -> Result<(), PublishError> {
+    match response["FailedEntryCount"].as_i64() {
+        Some(0) | None => Ok(()),
+        Some(count) => {
+            let entry = response["Entries"]
+                .as_array()
+                .and_then(|entries| entries.iter().find(|x| x["ErrorCode"].is_string()))
+                .unwrap_or(&Value::Null);
+            Err(format!(
+                "PutEvents rejected {} entries: {} {}",
+                count,
+                entry["ErrorCode"].as_str().unwrap_or_default(),
+                entry["ErrorMessage"].as_str().unwrap_or_default()
+            )
+            .into())
+        }
+    }
+}
+
+/// Trait to publish the cost report as an event.
+///
+/// It is shared across the await points of the run, so it must be `Sync`.
+#[async_trait]
+pub trait PublishReport: Sync {
+    async fn publish(&self, report: &Value) -> Result<(), PublishError>;
+}
+
+/// Publisher which sends the report to EventBridge as the detail of a custom event.
+pub struct EventBridgePublisher<C: PutEvents = EventBridgeClient> {
+    client: C,
+    source: String,
+    detail_type: String,
+    event_bus_name: Option<String>,
+}
+impl<C: PutEvents> EventBridgePublisher<C> {
+    pub fn new(client: C, source: &str, detail_type: &str) -> Self {
+        EventBridgePublisher {
+            client,
+            source: source.to_string(),
+            detail_type: detail_type.to_string(),
+            event_bus_name: None,
+        }
+    }
+
+    /// Send the events to `event_bus_name` instead of the default bus.
+    pub fn event_bus_name(mut self, event_bus_name: Option<&str>) -> Self {
+        self.event_bus_name = event_bus_name.map(|x| x.to_string());
+        self
+    }
+
+    /// Build the entry whose detail is `report`.
+    fn build_entry(&self, report: &Value) -> PutEventsRequestEntry {
+        PutEventsRequestEntry {
+            source: self.source.clone(),
+            detail_type: self.detail_type.clone(),
+            detail: report.to_string(),
+            event_bus_name: self.event_bus_name.clone(),
+        }
+    }
+}
+#[async_trait]
+impl<C: PutEvents + Sync + Send> PublishReport for EventBridgePublisher<C> {
+    async fn publish(&self, report: &Value) -> Result<(), PublishError> {
+        self.client.put_events(vec![self.build_entry(report)]).await
+    }
+}
+
+/// Stub of the client which keeps the entries in memory.
+#[cfg(test)]
+#[derive(Default)]
+pub struct PutEventsStub {
+    pub fail: bool,
+    /// Entries put so far.
+    /// Shared so that they can be read after the stub is moved.
+    pub entries: std::sync::Arc<std::sync::Mutex<Vec<PutEventsRequestEntry>>>,
+}
+#[cfg(test)]
+#[async_trait]
+impl PutEvents for PutEventsStub {
+    async fn put_events(&self, entries: Vec<PutEventsRequestEntry>) -> Result<(), PublishError> {
+        if self.fail {
+            return Err("AccessDeniedException".into());
+        }
+        self.entries.lock().unwrap().extend(entries);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_event_publisher {
+    use super::*;
+
+    #[tokio::test]
+    async fn publish_report_as_event_detail() {
+        let client_stub = PutEventsStub::default();
+        let entries = client_stub.entries.clone();
+        let publisher =
+            EventBridgePublisher::new(client_stub, "aws-cost-notification", "AWS Cost Report")
+                .event_bus_name(Some("cost-events"));
+        let report = json!({"total_cost": {"cost": {"amount": 1.5, "unit": "USD"}}});
+
+        let res = publisher.publish(&report).await;
+
+        assert!(res.is_ok());
+        let entries = entries.lock().unwrap();
+        assert_eq!(
+            vec![PutEventsRequestEntry {
+                source: "aws-cost-notification".to_string(),
+                detail_type: "AWS Cost Report".to_string(),
+                detail: r#"{"total_cost":{"cost":{"amount":1.5,"unit":"USD"}}}"#.to_string(),
+                event_bus_name: Some("cost-events".to_string()),
+            }],
+            *entries
+        );
+        assert_eq!(
+            report,
+            serde_json::from_str::<Value>(&entries[0].detail).unwrap()
+        );
+    }
+
+    #[test]
+    fn serialize_entry_in_api_format() {
+        let entry = PutEventsRequestEntry {
+            source: "aws-cost-notification".to_string(),
+            detail_type: "AWS Cost Report".to_string(),
+            detail: "{}".to_string(),
+            event_bus_name: None,
+        };
+
+        assert_eq!(
+            json!({
+                "Source": "aws-cost-notification",
+                "DetailType": "AWS Cost Report",
+                "Detail": "{}",
+            }),
+            serde_json::to_value(&entry).unwrap()
+        );
+    }
+
+    #[test]
+    fn return_error_for_failed_entries() {
+        assert!(parse_put_events_response(
+            &json!({"FailedEntryCount": 0, "Entries": [{"EventId": "1"}]})
+        )
+        .is_ok());
+        assert!(parse_put_events_response(&json!({
+            "FailedEntryCount": 1,
+            "Entries": [{"ErrorCode": "InternalFailure", "ErrorMessage": "Internal error"}]
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn report_error_of_rejected_entry() {
+        let res = parse_put_events_response(&json!({
+            "FailedEntryCount": 1,
+            "Entries": [
+                {"EventId": "11710aed-b79e-4468-a20b-bb3c0c3b4860"},
+                {"ErrorCode": "AccessDeniedException", "ErrorMessage": "Not authorized"},
+            ]
+        }));
+
+        assert_eq!(
+            "PutEvents rejected 1 entries: AccessDeniedException Not authorized",
+            res.unwrap_err().to_string()
+        );
+    }
+}
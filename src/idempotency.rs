@@ -0,0 +1,556 @@
+use crate::cost_explorer::cost_response_parser::Cost;
+
+use async_trait::async_trait;
+use rusoto_core::signature::SignedRequest;
+use rusoto_core::{Client, Region};
+use serde_json::{json, Value};
+use std::error;
+use std::future::Future;
+#[cfg(test)]
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Error in reading or writing the store.
+pub type StoreError = Box<dyn error::Error + Send + Sync>;
+
+/// Trait of the storage which records the reports already notified.
+///
+/// `try_acquire` must be a conditional write (optimistic lock):
+/// it records `key` only if it is not recorded yet and returns whether the write succeeded.
+/// Therefore, only one of the invocations running at the same time acquires the key.
+///
+/// The acquired key is locked only for a limited time, so that the report
+/// is retried if the invocation is killed (e.g. by the timeout) before `complete` or `release`.
+#[async_trait]
+pub trait IdempotencyStore: Sync {
+    /// Record `key` if it is not recorded yet or its lock has expired.
+    /// Returns false if it has been already recorded by another invocation.
+    async fn try_acquire(&self, key: &str) -> Result<bool, StoreError>;
+
+    /// Record `key` without expiration since the report has been notified.
+    async fn complete(&self, key: &str) -> Result<(), StoreError>;
+
+    /// Remove `key` so that the report can be notified again.
+    async fn release(&self, key: &str) -> Result<(), StoreError>;
+}
+
+/// Name of the partition key (string) of the DynamoDB table.
+const REPORT_KEY_ATTRIBUTE: &str = "report_key";
+
+/// Name of the attribute of the time (Unix seconds) when the lock of the key expires.
+/// It can also be set as the TTL attribute of the table.
+const EXPIRES_AT_ATTRIBUTE: &str = "expires_at";
+
+/// Duration of the lock of an acquired key, which is the maximum timeout of Lambda.
+const LOCK_DURATION: Duration = Duration::from_secs(15 * 60);
+
+/// `IdempotencyStore` and `NotifiedTotalStore` which record the keys
+/// and the notified totals in a DynamoDB table,
+/// so that they are shared by all the execution environments.
+///
+/// The key is put with the condition that it does not exist or its lock has expired,
+/// and the table must have the partition key `report_key` of the string type.
+pub struct DynamoDbStore {
+    client: Client,
+    region: Region,
+    table_name: String,
+    scope: Option<String>,
+}
+impl DynamoDbStore {
+    pub fn new(table_name: &str) -> Self {
+        DynamoDbStore {
+            client: Client::shared(),
+            region: Region::default(),
+            table_name: table_name.to_string(),
+            scope: None,
+        }
+    }
+
+    /// Prefix the keys with `scope` (e.g. the function name and the profile),
+    /// so that the functions sharing the table do not overwrite the records of each other.
+    pub fn scope(mut self, scope: Option<&str>) -> Self {
+        self.scope = scope.map(|x| x.to_string());
+        self
+    }
+
+    /// `key` prefixed with the scope, if any.
+    fn scoped_key(&self, key: &str) -> String {
+        match &self.scope {
+            Some(scope) => format!("{}#{}", scope, key),
+            None => key.to_string(),
+        }
+    }
+
+    /// Send the request of `operation` (e.g. `PutItem`) with `payload`.
+    ///
+    /// Returns whether it succeeded and the body of the response.
+    async fn dispatch(
+        &self,
+        operation: &str,
+        payload: &Value,
+    ) -> Result<(bool, Value), StoreError> {
+        let mut request = SignedRequest::new("POST", "dynamodb", &self.region, "/");
+        request.set_content_type("application/x-amz-json-1.0".to_string());
+        request.add_header("x-amz-target", &format!("DynamoDB_20120810.{}", operation));
+        request.set_payload(Some(serde_json::to_vec(payload)?));
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|e| format!("Failed to send {} request: {:?}", operation, e))?;
+        let response = response.buffer().await?;
+        let body = serde_json::from_slice(&response.body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&response.body).to_string()));
+        Ok((response.status.is_success(), body))
+    }
+}
+#[async_trait]
+impl IdempotencyStore for DynamoDbStore {
+    async fn try_acquire(&self, key: &str) -> Result<bool, StoreError> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let payload = put_item_payload(&self.table_name, &self.scoped_key(key), now);
+        let (success, body) = self.dispatch("PutItem", &payload).await?;
+        parse_put_item_response(success, &body)
+    }
+
+    async fn complete(&self, key: &str) -> Result<(), StoreError> {
+        let payload = json!({
+            "TableName": self.table_name,
+            "Item": { REPORT_KEY_ATTRIBUTE: { "S": self.scoped_key(key) } },
+        });
+        match self.dispatch("PutItem", &payload).await? {
+            (true, _) => Ok(()),
+            (false, body) => Err(format!("PutItem failed: {}", body).into()),
+        }
+    }
+
+    async fn release(&self, key: &str) -> Result<(), StoreError> {
+        let payload = json!({
+            "TableName": self.table_name,
+            "Key": { REPORT_KEY_ATTRIBUTE: { "S": self.scoped_key(key) } },
+        });
+        match self.dispatch("DeleteItem", &payload).await? {
+            (true, _) => Ok(()),
+            (false, body) => Err(format!("DeleteItem failed: {}", body).into()),
+        }
+    }
+}
+
+/// Payload of PutItem which locks `key` for `LOCK_DURATION` from `now` (Unix seconds)
+/// only if it does not exist in the table or its lock has expired.
+///
+/// The completed keys have no expiration, so they are never put again.
+fn put_item_payload(table_name: &str, key: &str, now: u64) -> Value {
+    json!({
+        "TableName": table_name,
+        "Item": {
+            REPORT_KEY_ATTRIBUTE: { "S": key },
+            EXPIRES_AT_ATTRIBUTE: { "N": (now + LOCK_DURATION.as_secs()).to_string() },
+        },
+        "ConditionExpression": "attribute_not_exists(#key) OR #expires_at < :now",
+        "ExpressionAttributeNames": {
+            "#key": REPORT_KEY_ATTRIBUTE,
+            "#expires_at": EXPIRES_AT_ATTRIBUTE,
+        },
+        "ExpressionAttributeValues": { ":now": { "N": now.to_string() } },
+    })
+}
+
+/// Whether the key has been put, which is false if the condition of PutItem failed
+/// because another invocation has already put it.
+fn parse_put_item_response(success: bool, body: &Value) -> Result<bool, StoreError> {
+    if success {
+        return Ok(true);
+    }
+    match body["__type"].as_str() {
+        Some(error_type) if error_type.ends_with("ConditionalCheckFailedException") => Ok(false),
+        _ => Err(format!("PutItem failed: {}", body).into()),
+    }
+}
+
+/// Total cost sent in the last notification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotifiedTotal {
+    /// Start date of the reported period (e.g. `2021-07-01`),
+    /// with which the totals of the same period are compared.
+    pub period_start: String,
+    /// Total cost in the original currency.
+    pub cost: Cost,
+}
+
+/// Trait of the storage which records the total cost of the last notification
+/// of each period.
+///
+/// It is shared across the await points of the run, so it must be `Sync`.
+#[async_trait]
+pub trait NotifiedTotalStore: Sync {
+    /// The total cost of the last notification of the period starting at `period_start`, if any.
+    async fn last_notified_total(
+        &self,
+        period_start: &str,
+    ) -> Result<Option<NotifiedTotal>, StoreError>;
+
+    /// Record `total` as that of the last notification of its period.
+    async fn record_notified_total(&self, total: &NotifiedTotal) -> Result<(), StoreError>;
+}
+
+#[async_trait]
+impl NotifiedTotalStore for DynamoDbStore {
+    async fn last_notified_total(
+        &self,
+        period_start: &str,
+    ) -> Result<Option<NotifiedTotal>, StoreError> {
+        let payload = json!({
+            "TableName": self.table_name,
+            "Key": { REPORT_KEY_ATTRIBUTE: { "S": self.scoped_key(&notified_total_key(period_start)) } },
+            "ConsistentRead": true,
+        });
+        match self.dispatch("GetItem", &payload).await? {
+            (true, body) => parse_notified_total(period_start, &body),
+            (false, body) => Err(format!("GetItem failed: {}", body).into()),
+        }
+    }
+
+    async fn record_notified_total(&self, total: &NotifiedTotal) -> Result<(), StoreError> {
+        let key = self.scoped_key(&notified_total_key(&total.period_start));
+        match self
+            .dispatch(
+                "PutItem",
+                &notified_total_payload(&self.table_name, &key, total),
+            )
+            .await?
+        {
+            (true, _) => Ok(()),
+            (false, body) => Err(format!("PutItem failed: {}", body).into()),
+        }
+    }
+}
+
+/// Key under which the last notified total of the period starting at `period_start` is recorded.
+fn notified_total_key(period_start: &str) -> String {
+    format!("notified_total#{}", period_start)
+}
+
+/// Payload of PutItem which overwrites the notified total under `key`.
+fn notified_total_payload(table_name: &str, key: &str, total: &NotifiedTotal) -> Value {
+    json!({
+        "TableName": table_name,
+        "Item": {
+            REPORT_KEY_ATTRIBUTE: { "S": key },
+            "amount": { "N": total.cost.amount.to_string() },
+            "unit": { "S": total.cost.unit },
+        },
+    })
+}
+
+/// Parse the notified total of the period starting at `period_start`
+/// from the response of GetItem, which has no `Item` if it has not been recorded.
+fn parse_notified_total(
+    period_start: &str,
+    body: &Value,
+) -> Result<Option<NotifiedTotal>, StoreError> {
+    let item = match body.get("Item") {
+        Some(item) => item,
+        None => return Ok(None),
+    };
+    let (amount, unit) = match (item["amount"]["N"].as_str(), item["unit"]["S"].as_str()) {
+        (Some(amount), Some(unit)) => (amount, unit),
+        _ => return Err(format!("Invalid notified total: {}", item).into()),
+    };
+    Ok(Some(NotifiedTotal {
+        period_start: period_start.to_string(),
+        cost: Cost {
+            amount: amount.parse()?,
+            unit: unit.to_string(),
+        },
+    }))
+}
+
+/// Stub of `IdempotencyStore` and `NotifiedTotalStore` which records in memory.
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryStore {
+    keys: Mutex<std::collections::HashSet<String>>,
+    completed_keys: Mutex<std::collections::HashSet<String>>,
+    notified_totals: Mutex<std::collections::HashMap<String, NotifiedTotal>>,
+}
+#[cfg(test)]
+#[async_trait]
+impl IdempotencyStore for InMemoryStore {
+    async fn try_acquire(&self, key: &str) -> Result<bool, StoreError> {
+        let mut keys = self.keys.lock().map_err(|e| e.to_string())?;
+        Ok(keys.insert(key.to_string()))
+    }
+
+    async fn complete(&self, key: &str) -> Result<(), StoreError> {
+        let mut completed_keys = self.completed_keys.lock().map_err(|e| e.to_string())?;
+        completed_keys.insert(key.to_string());
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), StoreError> {
+        let mut keys = self.keys.lock().map_err(|e| e.to_string())?;
+        keys.remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl NotifiedTotalStore for InMemoryStore {
+    async fn last_notified_total(
+        &self,
+        period_start: &str,
+    ) -> Result<Option<NotifiedTotal>, StoreError> {
+        let totals = self.notified_totals.lock().map_err(|e| e.to_string())?;
+        Ok(totals.get(period_start).cloned())
+    }
+
+    async fn record_notified_total(&self, total: &NotifiedTotal) -> Result<(), StoreError> {
+        let mut totals = self.notified_totals.lock().map_err(|e| e.to_string())?;
+        totals.insert(total.period_start.clone(), total.clone());
+        Ok(())
+    }
+}
+
+/// Run `task` only if `key` is acquired in `store`.
+///
+/// Returns false without running `task` if another invocation has already acquired the key.
+/// If `task` succeeds, the key is completed so that its lock never expires.
+/// If `task` fails, the key is released so that the report can be retried.
+pub async fn run_once<S, F>(store: &S, key: &str, task: F) -> Result<bool, Box<dyn error::Error>>
+where
+    S: IdempotencyStore,
+    F: Future<Output = Result<(), Box<dyn error::Error>>>,
+{
+    if !store.try_acquire(key).await.map_err(|e| e.to_string())? {
+        println!("Report {} has been already notified. Skipped.", key);
+        return Ok(false);
+    }
+
+    // The error is not Send, so it is held as a message while releasing the key.
+    match task.await.map_err(|e| e.to_string()) {
+        Ok(_) => {
+            // The report has been notified, so the failure is only logged.
+            if let Err(e) = store.complete(key).await {
+                println!("Failed to complete report {}: {}", key, e);
+            }
+            Ok(true)
+        }
+        Err(message) => {
+            store.release(key).await.map_err(|e| e.to_string())?;
+            Err(message.into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_idempotency {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn only_one_of_concurrent_writes_acquires_key() {
+        let store = Arc::new(InMemoryStore::default());
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let store = store.clone();
+                thread::spawn(move || {
+                    futures::executor::block_on(store.try_acquire("2021-07-23")).unwrap()
+                })
+            })
+            .collect();
+        let acquired = handles
+            .into_iter()
+            .map(|x| x.join().unwrap())
+            .filter(|x| *x)
+            .count();
+
+        assert_eq!(1, acquired);
+    }
+
+    #[tokio::test]
+    async fn loser_of_race_skips_task() {
+        let store = InMemoryStore::default();
+        let executed = AtomicUsize::new(0);
+        let task = || async {
+            executed.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        };
+
+        let (first, second) = futures::join!(
+            run_once(&store, "2021-07-23", task()),
+            run_once(&store, "2021-07-23", task()),
+        );
+
+        assert_eq!(1, executed.load(Ordering::SeqCst));
+        assert_ne!(first.unwrap(), second.unwrap());
+    }
+
+    fn notified_total(period_start: &str, amount: f64) -> NotifiedTotal {
+        NotifiedTotal {
+            period_start: period_start.to_string(),
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn overwrite_last_notified_total_of_same_period() {
+        let store = InMemoryStore::default();
+
+        assert_eq!(None, store.last_notified_total("2021-07-01").await.unwrap());
+        for total in [
+            notified_total("2021-06-01", 90.0),
+            notified_total("2021-07-01", 100.0),
+            notified_total("2021-07-01", 115.0),
+        ] {
+            store.record_notified_total(&total).await.unwrap();
+        }
+
+        assert_eq!(
+            Some(notified_total("2021-06-01", 90.0)),
+            store.last_notified_total("2021-06-01").await.unwrap()
+        );
+        assert_eq!(
+            Some(notified_total("2021-07-01", 115.0)),
+            store.last_notified_total("2021-07-01").await.unwrap()
+        );
+    }
+
+    #[test]
+    fn prefix_key_with_scope() {
+        let store = DynamoDbStore::new("cost-notification-reports");
+        assert_eq!("2021-07-23", store.scoped_key("2021-07-23"));
+
+        let store = store.scope(Some("CostNotifier/prod"));
+        assert_eq!(
+            "CostNotifier/prod#2021-07-23",
+            store.scoped_key("2021-07-23")
+        );
+    }
+
+    #[test]
+    fn put_notified_total_of_period() {
+        assert_eq!(
+            json!({
+                "TableName": "cost-notification-reports",
+                "Item": {
+                    "report_key": { "S": "prod#notified_total#2021-07-01" },
+                    "amount": { "N": "115.5" },
+                    "unit": { "S": "USD" },
+                },
+            }),
+            notified_total_payload(
+                "cost-notification-reports",
+                "prod#notified_total#2021-07-01",
+                &notified_total("2021-07-01", 115.5)
+            )
+        );
+    }
+
+    #[test]
+    fn parse_notified_total_correctly() {
+        let body = json!({
+            "Item": {
+                "report_key": { "S": "prod#notified_total#2021-07-01" },
+                "amount": { "N": "115.5" },
+                "unit": { "S": "USD" },
+            },
+        });
+
+        assert_eq!(
+            Some(notified_total("2021-07-01", 115.5)),
+            parse_notified_total("2021-07-01", &body).unwrap()
+        );
+        assert_eq!(
+            None,
+            parse_notified_total("2021-07-01", &json!({})).unwrap()
+        );
+        assert!(parse_notified_total(
+            "2021-07-01",
+            &json!({ "Item": { "amount": { "S": "115.5" } } })
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn release_key_when_task_fails() {
+        let store = InMemoryStore::default();
+
+        let failed = run_once(&store, "2021-07-23", async {
+            Err("Something Wrong!".into())
+        })
+        .await;
+        let retried = run_once(&store, "2021-07-23", async { Ok(()) }).await;
+
+        assert!(failed.is_err());
+        assert!(retried.unwrap());
+    }
+
+    #[tokio::test]
+    async fn complete_key_only_when_task_succeeds() {
+        let store = InMemoryStore::default();
+
+        let _ = run_once(&store, "2021-07-22", async {
+            Err("Something Wrong!".into())
+        })
+        .await;
+        let _ = run_once(&store, "2021-07-23", async { Ok(()) }).await;
+
+        let completed_keys = store.completed_keys.lock().unwrap();
+        assert!(!completed_keys.contains("2021-07-22"));
+        assert!(completed_keys.contains("2021-07-23"));
+    }
+
+    #[test]
+    fn put_key_only_if_not_exists_or_expired() {
+        assert_eq!(
+            json!({
+                "TableName": "cost-notification-reports",
+                "Item": {
+                    "report_key": { "S": "CostNotifier/prod#2021-07-23" },
+                    "expires_at": { "N": "1627001100" },
+                },
+                "ConditionExpression": "attribute_not_exists(#key) OR #expires_at < :now",
+                "ExpressionAttributeNames": {
+                    "#key": "report_key",
+                    "#expires_at": "expires_at",
+                },
+                "ExpressionAttributeValues": { ":now": { "N": "1627000200" } },
+            }),
+            put_item_payload(
+                "cost-notification-reports",
+                "CostNotifier/prod#2021-07-23",
+                1627000200
+            )
+        );
+    }
+
+    #[test]
+    fn parse_put_item_response_correctly() {
+        assert!(parse_put_item_response(true, &json!({})).unwrap());
+        assert!(!parse_put_item_response(
+            false,
+            &json!({
+                "__type": "com.amazonaws.dynamodb.v20120810#ConditionalCheckFailedException",
+                "message": "The conditional request failed",
+            })
+        )
+        .unwrap());
+        assert!(parse_put_item_response(
+            false,
+            &json!({
+                "__type": "com.amazonaws.dynamodb.v20120810#ResourceNotFoundException",
+                "message": "Requested resource not found",
+            })
+        )
+        .is_err());
+    }
+}
@@ -1,32 +1,76 @@
 //! # AWS Cost Notifier
 //!
 //! A Lambda function to retrieve AWS costs from Cost Explorer
-//! and notify them to Slack.
+//! and notify them to Slack, Microsoft Teams, Discord or Amazon SNS.
 
+/// Read the settings of the notification.
+mod config;
 /// Call AWS CostExplorer API and retrieve total cost and costs for each service.
 mod cost_explorer;
+/// Send a message to notify the AWS costs to Discord.
+mod discord_notifier;
+/// Publish the cost report to EventBridge.
+mod event_publisher;
+/// Prevent the same report from being notified more than once.
+mod idempotency;
 /// Build notification message from API responses
 mod message_builder;
 /// Set the period to retrieve the AWS costs.
 mod reporting_date;
+/// Read the secrets of the notification from Secrets Manager.
+mod secret_provider;
 /// Send a message to notify the AWS costs to Slack.
 mod slack_notifier;
+/// Send a message to notify the AWS costs to a Slack thread via Web API.
+mod slack_thread_notifier;
+/// Publish a message to notify the AWS costs to Amazon SNS.
+mod sns_notifier;
+/// Send a message to notify the AWS costs to Microsoft Teams.
+mod teams_notifier;
+/// Record a span of each run and export it to OpenTelemetry collector.
+mod telemetry;
 
-use cost_explorer::cost_usage_client::{CostAndUsageClient, GetCostAndUsage};
-use cost_explorer::CostExplorerService;
-use message_builder::NotificationMessage;
-use reporting_date::{date_in_specified_timezone, ReportDateRange};
-use slack_notifier::{SendMessage, SlackNotifier};
+use config::Config;
+use cost_explorer::amount::Amount;
+use cost_explorer::cost_response_parser::check_units;
+use cost_explorer::cost_usage_client::{
+    cost_explorer_region, CostAndUsageClient, GetCostAndUsage, GetCostForecast,
+    GetRightsizingRecommendation, GetUtilization,
+};
+use cost_explorer::error::CostNotificationError;
+use cost_explorer::retry::{RetryBudget, RetryingClient};
+use cost_explorer::{CostExplorerService, Granularity};
+use discord_notifier::DiscordNotifier;
+use event_publisher::{EventBridgeClient, EventBridgePublisher, PublishReport};
+use idempotency::{run_once, DynamoDbStore, NotifiedTotal, NotifiedTotalStore};
+use message_builder::comparison::{diff_service_costs, is_unchanged, ChangeTolerance};
+use message_builder::csv::service_costs_to_csv;
+use message_builder::{projected_overage_date, projected_total, NotificationMessage};
+use reporting_date::{datetime_in_specified_timezone, ReportDateRange, WindowAlignment};
+use secret_provider::{resolve_webhook_url, SecretsManagerClient};
+use slack_notifier::{
+    sleep_off_worker, BroadcastNotifier, DryRunNotifier, FailoverNotifier, NotifierKind,
+    RateLimitPolicy, SendMessage, SeverityColors, SlackNotifier, WebhookClient,
+};
+use slack_thread_notifier::{ChatApiClient, SlackThreadNotifier};
+use sns_notifier::{SnsClient, SnsNotifier};
+use teams_notifier::TeamsNotifier;
+use telemetry::{ExportSpan, OtlpExporter, SpanRecorder, RUN_SPAN_NAME};
 
-use chrono::{Date, Local, TimeZone};
+use chrono::{DateTime, Local, TimeZone};
 use dotenv::dotenv;
 use lambda_runtime::{handler_fn, Context, Error};
+use rand::rngs::StdRng;
+use rand::{FromEntropy, SeedableRng};
 use serde_json::Value;
 use std::error;
 use std::fmt::Display;
-use tokio;
+use std::sync::Arc;
+use std::time::Duration;
 
-#[tokio::main]
+// The notifiers block in place to wait for the retries and the requests to SNS,
+// which needs the multi-threaded runtime.
+#[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<(), Error> {
     let func = handler_fn(lambda_handler);
     lambda_runtime::run(func).await?;
@@ -34,57 +78,729 @@ async fn main() -> Result<(), Error> {
 }
 
 /// The function executed in AWS Lambda.
-async fn lambda_handler(_: Value, _: Context) -> Result<(), Error> {
-    let cost_usage_client = CostAndUsageClient::new();
-    let slack_notifier = SlackNotifier::new();
-
+///
+/// If the event is `{"action":"debug"}`, it returns the parsed costs
+/// as the result without sending any notification.
+///
+/// `unit_metric_value` of the event (e.g. `{"unit_metric_value":1200}`)
+/// overrides `UNIT_METRIC_VALUE`.
+async fn lambda_handler(event: Value, _: Context) -> Result<Value, Error> {
     dotenv().ok();
-    let tz_string = dotenv::var("REPORTING_TIMEZONE").expect("REPORTING_TIMEZONE not found");
+    let mut config = Config::from_env().map_err(|e| e.to_string())?;
+    if let Some(value) = event["unit_metric_value"].as_f64() {
+        config.unit_metric_value = Some(value);
+    }
+
+    let mut retry_budget = RetryBudget::new(
+        config.aws_retry_budget,
+        Duration::from_millis(config.aws_retry_backoff_millis),
+    );
+    if config.aws_retry_jitter {
+        retry_budget = retry_budget.with_jitter(match config.aws_retry_jitter_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        });
+    }
+    let region = cost_explorer_region(&config.ce_region, config.ce_endpoint_url.as_deref());
+    let cost_usage_client =
+        RetryingClient::new(CostAndUsageClient::new(region), Arc::new(retry_budget));
+    let primary_notifier: Box<dyn SendMessage + Send + Sync> = match config.notifier {
+        _ if config.dry_run => Box::new(DryRunNotifier),
+        NotifierKind::Slack if !config.slack_webhook_urls.is_empty() => {
+            let mut destinations = vec![];
+            for url in &config.slack_webhook_urls {
+                let webhook = WebhookClient::new(url).map_err(|e| e.to_string())?;
+                let notifier =
+                    configure_slack_notifier(SlackNotifier::with_webhook(webhook), &config);
+                destinations.push((url.clone(), notifier));
+            }
+            Box::new(BroadcastNotifier::new(destinations))
+        }
+        NotifierKind::Slack => {
+            let url = resolve_webhook_url(&config, &SecretsManagerClient::new())
+                .await
+                .map_err(|e| e.to_string())?;
+            Box::new(build_slack_notifier(
+                url.as_deref().unwrap_or_default(),
+                &config,
+            )?)
+        }
+        NotifierKind::SlackThread => {
+            let token = config.slack_bot_token.as_deref().unwrap_or_default();
+            let channel = config.slack_channel.as_deref().unwrap_or_default();
+            Box::new(
+                SlackThreadNotifier::new(ChatApiClient::new(token), channel)
+                    .severity_colors(severity_colors(&config)),
+            )
+        }
+        NotifierKind::Teams => {
+            let url = config.teams_webhook_url.as_deref().unwrap_or_default();
+            Box::new(TeamsNotifier::new(url).map_err(|e| e.to_string())?)
+        }
+        NotifierKind::Discord => {
+            let url = config.discord_webhook_url.as_deref().unwrap_or_default();
+            Box::new(DiscordNotifier::new(url).map_err(|e| e.to_string())?)
+        }
+        NotifierKind::Sns => {
+            let topic_arn = config.sns_topic_arn.as_deref().unwrap_or_default();
+            Box::new(SnsNotifier::new(SnsClient::new(), topic_arn))
+        }
+    };
+    let notifier = FailoverNotifier::new(primary_notifier, config.fallback_sink);
+
+    let tz_string = config.reporting_timezone.clone().unwrap_or_default();
     let now = Local::now();
-    let reporting_date = date_in_specified_timezone(now, tz_string).unwrap();
+    let reporting_datetime =
+        datetime_in_specified_timezone(now, tz_string).map_err(|e| e.to_string())?;
 
     println!(
         "Launched lambda handler with reporting date {}",
-        reporting_date
+        reporting_datetime.date()
     );
 
-    let res = request_cost_and_notify(cost_usage_client, slack_notifier, reporting_date).await;
+    if event["action"] == "debug" {
+        return request_parsed_costs(cost_usage_client, reporting_datetime, &config)
+            .await
+            .map_err(|e| e.to_string().into());
+    }
+
+    let exporter = match &config.otlp_endpoint {
+        Some(endpoint) => Some(OtlpExporter::new(endpoint).map_err(|e| e.to_string())?),
+        None => None,
+    };
+
+    let publisher = match config.publish_report_event {
+        true => Some(
+            EventBridgePublisher::new(
+                EventBridgeClient::new(),
+                &config.event_source,
+                &config.event_detail_type,
+            )
+            .event_bus_name(config.event_bus_name.as_deref()),
+        ),
+        false => None,
+    };
+
+    let store = config
+        .idempotency_table_name
+        .as_deref()
+        .map(|table_name| DynamoDbStore::new(table_name).scope(config.store_scope().as_deref()));
+    let report_key = reporting_datetime.date().format("%Y-%m-%d").to_string();
+    let task = request_cost_and_notify(
+        cost_usage_client,
+        notifier,
+        reporting_datetime,
+        &config,
+        exporter.as_ref().map(|x| x as &dyn ExportSpan),
+        store.as_ref().map(|x| x as &dyn NotifiedTotalStore),
+        publisher.as_ref().map(|x| x as &dyn PublishReport),
+    );
+    let res = match (config.idempotency_enabled, &store) {
+        (true, Some(store)) => run_once(store, &report_key, task).await.map(|_| ()),
+        _ => task.await,
+    };
     match res {
-        Ok(_) => Ok(()),
+        Ok(_) => Ok(Value::Null),
         Err(e) => Err(e.to_string().into()),
     }
 }
 
+/// Build the notifier to Slack with the Webhook `url` and the retry policy and colors in `config`.
+fn build_slack_notifier(url: &str, config: &Config) -> Result<SlackNotifier, Error> {
+    let slack_notifier =
+        SlackNotifier::with_webhook(WebhookClient::new(url).map_err(|e| e.to_string())?);
+    Ok(configure_slack_notifier(slack_notifier, config))
+}
+
+/// Set the retry policy, colors and format in `config` to `slack_notifier`.
+fn configure_slack_notifier(slack_notifier: SlackNotifier, config: &Config) -> SlackNotifier {
+    slack_notifier
+        .rate_limit_policy(RateLimitPolicy {
+            max_retries: config.slack_rate_limit_max_retries,
+            max_wait: Duration::from_secs(config.slack_rate_limit_max_wait_secs),
+        })
+        .severity_colors(severity_colors(config))
+        .format(config.slack_format)
+        .sleep(sleep_off_worker)
+}
+
+/// Colors of the Slack attachment for each severity, overridden by `config`.
+fn severity_colors(config: &Config) -> SeverityColors {
+    let default_colors = SeverityColors::default();
+    SeverityColors {
+        normal: config.color_normal.clone().unwrap_or(default_colors.normal),
+        warning: config
+            .color_warning
+            .clone()
+            .unwrap_or(default_colors.warning),
+        critical: config
+            .color_critical
+            .clone()
+            .unwrap_or(default_colors.critical),
+    }
+}
+
+/// Set the period of the cost aggregation from the reporting date.
+fn report_date_range<T>(reporting_datetime: &DateTime<T>, config: &Config) -> ReportDateRange<T>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    if let WindowAlignment::Rolling(days) = config.window_alignment {
+        return ReportDateRange::trailing_days(reporting_datetime.date(), days);
+    }
+    match config.fiscal_year_start_month {
+        Some(month) => ReportDateRange::fiscal_year_to_date(reporting_datetime.date(), month),
+        None => ReportDateRange::new(reporting_datetime.date()),
+    }
+}
+
+/// Time granularity in which the total and service costs are requested.
+///
+/// The rolling window is requested in DAILY granularity and summed up,
+/// since it does not align with the months.
+fn cost_granularity(config: &Config) -> Granularity {
+    match config.window_alignment {
+        WindowAlignment::Calendar => config.cost_granularity,
+        WindowAlignment::Rolling(_) => Granularity::Daily,
+    }
+}
+
+/// Retrieve the total cost and service costs and return them
+/// as JSON for troubleshooting the parsers.
+///
+/// # Example
+/// `{"total_cost":{"date_range":{...},"cost":{...}},"service_costs":[...]}`
+async fn request_parsed_costs<C: GetCostAndUsage, T>(
+    cost_usage_client: C,
+    reporting_datetime: DateTime<T>,
+    config: &Config,
+) -> Result<Value, Box<dyn error::Error>>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    let report_date_range = report_date_range(&reporting_datetime, config);
+    let cost_explorer =
+        CostExplorerService::new(cost_usage_client, report_date_range, config.cost_metric)
+            .granularity(cost_granularity(config))
+            .linked_account(config.filter_linked_account.as_deref())
+            .exclude_credits(config.exclude_credits);
+    let total_cost = cost_explorer.request_total_cost().await?;
+    let service_costs = cost_explorer.request_service_costs().await?;
+
+    Ok(total_cost.to_json(&service_costs))
+}
+
 /// The core function of the whole process.
 /// `cost_usage_client` retrieves AWS costs via CostExplorer API
 /// and `notifier` sends a message to Slack.
 ///
 /// The period of the cost aggregation is from the first date
-/// of the month upto the `reporting_date`.
-/// If the `reporting_date` is the first date of the month,
+/// of the month upto the date of `reporting_datetime`.
+/// If the reporting date is the first date of the month,
 /// the start date is set to the first date of the previous month.
 ///
+/// If `fiscal_year_start_month` is set in `config`, the period is
+/// from the first date of the fiscal year instead.
+///
+/// If `show_service_breakdown` is unset in `config`, the service costs
+/// are not requested and only the total is sent.
+///
+/// If `unchanged_summary` is set in `config` and the costs have hardly changed
+/// since the same period of the previous month, only a terse summary is sent
+/// instead of the full breakdown.
+///
+/// If `alert_increase_percent` is set in `config` and the total cost has increased
+/// by more than the percentage since the previous period,
+/// the message is escalated with `alert_mention`.
+///
+/// If `comparison_attachment` is set in `config`, the comparison with
+/// the previous period is sent as a separate attachment.
+///
+/// If `monthly_credit` is set in `config`, the total cost net of the credit
+/// is appended to the header.
+/// If `unit_metric_value` is set, the cost per unit
+/// of the business metric is appended to the header.
+///
+/// If `notify_threshold` is set in `config`, the notification is skipped
+/// while the total cost is below it.
+///
+/// If `notify_only_over_budget` is set in `config`, the notification is skipped
+/// unless the projected total at the end of the month exceeds `monthly_budget`,
+/// in which case the message is escalated with `alert_mention`.
+///
+/// If `show_forecast` is set in `config`, the projected total at the end
+/// of the month is appended to the header.
+/// If `display_currency` is set, the costs are converted into it.
+///
+/// If `ranking_tag_key` is set in `config`, the ranking of the costs
+/// for each value of the tag is appended to the message.
+/// If `group_by_tag` is set, the costs for all the values of the tag are appended.
+/// If `report_data_transfer` is set, the total cost of data transfer is appended.
+/// If `show_top_increasing_usage_type` is set, the usage type whose cost
+/// has increased the most since the previous period is appended.
+/// If `top_usage_types` is set, the top usage types by cost are appended
+/// with their usage amounts if `show_usage_quantity` is set.
+/// If `show_region_costs` is set, the costs for each region are appended.
+/// If `show_account_costs` is set, the costs for each linked account are appended.
+/// If `show_utilization` is set, the utilization of Savings Plans
+/// and Reserved Instances is appended.
+/// If `show_rightsizing_savings` is set, the estimated monthly savings
+/// by the rightsizing recommendations are appended.
+/// If `show_overage_date` is set, the date on which the total cost
+/// is projected to exceed `monthly_budget` is appended.
+/// If `show_daily_costs` is set, the total costs of each day are appended.
+/// If `daily_comparison_baseline` is set, the change of the cost
+/// of the latest day from the baseline day is appended.
+/// If `monthly_table_periods` is set, the table of the total costs
+/// of the recent months is appended.
+/// If `show_generated_at` is set, `reporting_datetime` is displayed
+/// as the time when the report was generated.
+/// If `message_footer` is set, it is displayed at the bottom of the message.
+/// If `split_messages` is set, the total cost and its breakdown
+/// are sent as two separate messages.
+///
+/// If `show_change_since_last_notification` is set in `config`, the change
+/// of the total cost since the last notification of the same period recorded
+/// in `notified_total_store` (the DynamoDB table `idempotency_table_name`)
+/// is appended to the header.
+/// The notified total is recorded in `notified_total_store`, if given, after sending.
+///
+/// If retrieving the costs fails, the error is notified by `notifier`
+/// before it is returned, so that the failure is noticed in the same channel.
+///
+/// If `exporter` is given, the span of the run, which records the durations
+/// of fetching the costs, building and sending the message and the outcome,
+/// is exported by it. Failure of the export does not fail the run.
+///
+/// If `publisher` is given, the total cost and service costs are published
+/// as the report once they are retrieved. Failure of the publication does not fail the run.
+///
 /// You can execute integration tests by using stubs and designating
 /// the reporting date.
-async fn request_cost_and_notify<C: GetCostAndUsage, N: SendMessage, T>(
+async fn request_cost_and_notify<
+    C: GetCostAndUsage + GetCostForecast + GetUtilization + GetRightsizingRecommendation,
+    N: SendMessage,
+    T,
+>(
     cost_usage_client: C,
     notifier: N,
-    reporting_date: Date<T>,
+    reporting_datetime: DateTime<T>,
+    config: &Config,
+    exporter: Option<&dyn ExportSpan>,
+    notified_total_store: Option<&dyn NotifiedTotalStore>,
+    publisher: Option<&dyn PublishReport>,
 ) -> Result<(), Box<dyn error::Error>>
 where
     T: TimeZone,
     <T as chrono::TimeZone>::Offset: Display,
 {
-    let report_date_range = ReportDateRange::new(reporting_date);
+    let mut span = SpanRecorder::start(RUN_SPAN_NAME);
+    let res = notify_costs(
+        cost_usage_client,
+        &notifier,
+        reporting_datetime,
+        config,
+        notified_total_store,
+        publisher,
+        &mut span,
+    )
+    .await;
+
+    if let Err(e) = &res {
+        if let Some(e) = e.downcast_ref::<CostNotificationError>() {
+            if let Err(send_error) = notifier.send(NotificationMessage::error(&e.to_string())) {
+                println!("Failed to notify the error: {}", send_error);
+            }
+        }
+    }
+
+    if let Some(exporter) = exporter {
+        if let Err(e) = exporter.export(&span.finish(res.is_ok())) {
+            println!("Failed to export the span: {}", e);
+        }
+    }
+    res
+}
+
+/// Retrieve the costs, build the message and send it,
+/// recording the end of each phase in `span`.
+async fn notify_costs<
+    C: GetCostAndUsage + GetCostForecast + GetUtilization + GetRightsizingRecommendation,
+    N: SendMessage,
+    T,
+>(
+    cost_usage_client: C,
+    notifier: &N,
+    reporting_datetime: DateTime<T>,
+    config: &Config,
+    notified_total_store: Option<&dyn NotifiedTotalStore>,
+    publisher: Option<&dyn PublishReport>,
+    span: &mut SpanRecorder,
+) -> Result<(), Box<dyn error::Error>>
+where
+    T: TimeZone,
+    <T as chrono::TimeZone>::Offset: Display,
+{
+    let report_date_range = report_date_range(&reporting_datetime, config);
+
+    let cost_explorer =
+        CostExplorerService::new(cost_usage_client, report_date_range, config.cost_metric)
+            .granularity(cost_granularity(config))
+            .linked_account(config.filter_linked_account.as_deref())
+            .exclude_credits(config.exclude_credits);
+    let (total_cost, service_costs) = match config.show_service_breakdown {
+        true if config.single_cost_request => cost_explorer.request_all().await?,
+        true => (
+            cost_explorer.request_total_cost().await?,
+            cost_explorer.request_service_costs().await?,
+        ),
+        false => (cost_explorer.request_total_cost().await?, vec![]),
+    };
+    check_units(&total_cost.cost, &service_costs).map_err(CostNotificationError::from)?;
+    if config.output_json {
+        println!("{}", total_cost.to_json(&service_costs));
+    }
+    if config.output_csv {
+        println!("{}", service_costs_to_csv(&service_costs));
+    }
+    if let Some(publisher) = publisher {
+        if let Err(e) = publisher.publish(&total_cost.to_json(&service_costs)).await {
+            println!("Failed to publish the report: {}", e);
+        }
+    }
+    if let Some(threshold) = config.notify_threshold {
+        if total_cost.cost.amount < Amount::from(threshold) {
+            println!(
+                "Total cost {} is below the threshold {}. Skip the notification.",
+                total_cost.cost, threshold
+            );
+            return Ok(());
+        }
+    }
+    let notified_total = NotifiedTotal {
+        period_start: total_cost
+            .date_range
+            .start_date
+            .format("%Y-%m-%d")
+            .to_string(),
+        cost: total_cost.cost.clone(),
+    };
+
+    let compares_services =
+        config.unchanged_summary || config.comparison_attachment || config.alert_dropped_services;
+    let previous_total = match compares_services
+        || config.alert_increase_percent.is_some()
+        || config.show_month_over_month
+    {
+        true => Some(cost_explorer.request_previous_period_total().await?),
+        false => None,
+    };
+    let changes = match compares_services {
+        true => {
+            let previous_service_costs = match config.show_service_breakdown {
+                true => {
+                    cost_explorer
+                        .request_previous_period_service_costs()
+                        .await?
+                }
+                false => vec![],
+            };
+            Some(diff_service_costs(
+                &previous_service_costs,
+                &service_costs,
+                &config.service_aliases,
+            ))
+        }
+        false => None,
+    };
+    let forecast = match config.show_forecast || config.notify_only_over_budget {
+        true => cost_explorer.request_forecast().await?,
+        false => None,
+    };
+    span.end_phase("fetch");
+
+    let projected_total = projected_total(&total_cost.cost, forecast.as_ref());
+    let over_budget = match config.monthly_budget {
+        Some(budget) => projected_total.amount > Amount::from(budget),
+        None => false,
+    };
+    if config.notify_only_over_budget && !over_budget {
+        println!(
+            "Projected total {} is within the budget. Skip the notification.",
+            projected_total
+        );
+        return Ok(());
+    }
+
+    if let (true, false, Some(previous_total), Some(changes)) = (
+        config.unchanged_summary,
+        config.notify_only_over_budget,
+        &previous_total,
+        &changes,
+    ) {
+        if is_unchanged(
+            &previous_total.cost,
+            &total_cost.cost,
+            changes,
+            &ChangeTolerance::from_config(config),
+        ) {
+            let message = NotificationMessage::unchanged(&total_cost, config)
+                .with_unit_labels(&config.unit_labels)
+                .with_footer(config.message_footer.as_deref());
+            span.end_phase("build");
+            let res = notifier.send(message);
+            span.end_phase("send");
+            if res.is_ok() {
+                record_notified_total(notified_total_store, &notified_total).await;
+            }
+            return notification_result(res);
+        }
+    }
+
+    let actual_cost = total_cost.cost.clone();
+    let reported_date_range = total_cost.date_range.clone();
+    let mut notification_message = NotificationMessage::new(total_cost, service_costs, config)
+        .with_spend_severity(&actual_cost, config);
+
+    if let (Some(threshold), Some(previous_total)) =
+        (config.alert_increase_percent, &previous_total)
+    {
+        notification_message = notification_message.with_increase_alert(
+            &previous_total.cost,
+            &actual_cost,
+            threshold,
+            config.alert_mention.as_deref(),
+        );
+    }
+
+    if let (true, Some(budget)) = (config.notify_only_over_budget, config.monthly_budget) {
+        notification_message = notification_message.with_budget_alert(
+            &projected_total,
+            budget,
+            config.alert_mention.as_deref(),
+            config,
+        );
+    }
+
+    if let (true, Some(changes)) = (config.alert_dropped_services, &changes) {
+        notification_message = notification_message.with_dropped_services(
+            changes,
+            config.escape_slack_text,
+            config.locale,
+        );
+    }
 
-    let cost_explorer = CostExplorerService::new(cost_usage_client, report_date_range);
-    let total_cost = cost_explorer.request_total_cost().await;
-    let service_costs = cost_explorer.request_service_costs().await;
+    if let (true, Some(previous_total), Some(changes)) =
+        (config.comparison_attachment, &previous_total, &changes)
+    {
+        notification_message = notification_message.with_comparison(
+            &previous_total.cost,
+            &actual_cost,
+            changes,
+            config,
+        );
+    }
+
+    if let Some(credit) = config.monthly_credit {
+        notification_message = notification_message.with_net_cost(&actual_cost, credit, config);
+    }
+
+    if let (true, Some(store)) = (
+        config.show_change_since_last_notification,
+        notified_total_store,
+    ) {
+        match store
+            .last_notified_total(&notified_total.period_start)
+            .await
+        {
+            Ok(Some(last)) => {
+                notification_message = notification_message.with_change_since_last_notification(
+                    &last.cost,
+                    &actual_cost,
+                    config,
+                );
+            }
+            Ok(_) => {}
+            Err(e) => println!("Failed to read the last notified total: {}", e),
+        }
+    }
+
+    if config.unit_metric_value.is_some() {
+        notification_message = notification_message.with_unit_cost(
+            &actual_cost,
+            &config.unit_metric_name,
+            config.unit_metric_value,
+            config,
+        );
+    }
+
+    if config.progress_header {
+        notification_message = notification_message.with_progress_header(
+            &reported_date_range,
+            &actual_cost,
+            forecast.as_ref().filter(|_| config.show_forecast),
+            config.monthly_budget,
+            config,
+        );
+    } else if let (true, Some(forecast)) = (config.show_forecast, &forecast) {
+        notification_message = notification_message.with_forecast(&actual_cost, forecast, config);
+    }
+
+    if let Some(tolerance) = config.metric_divergence_tolerance_percent {
+        let (amortized, unblended) = cost_explorer
+            .request_amortized_and_unblended_totals()
+            .await?;
+        notification_message = notification_message.with_metric_divergence(
+            &amortized.cost,
+            &unblended.cost,
+            tolerance,
+            config,
+        );
+    }
+
+    if let (true, Some(previous_total)) = (config.show_month_over_month, &previous_total) {
+        notification_message =
+            notification_message.with_month_over_month(&previous_total.cost, &actual_cost);
+    }
+
+    if let Some(tag_key) = &config.ranking_tag_key {
+        let tag_costs = cost_explorer.request_tag_costs(tag_key).await?;
+        notification_message = notification_message.with_tag_ranking(
+            tag_key,
+            tag_costs,
+            config.ranking_top_n,
+            config.escape_slack_text,
+        );
+    }
+
+    if let Some(tag_key) = &config.group_by_tag {
+        let tag_costs = cost_explorer.request_tag_costs(tag_key).await?;
+        notification_message =
+            notification_message.with_tag_costs(tag_key, tag_costs, config.escape_slack_text);
+    }
+
+    if config.report_data_transfer {
+        let usage_type_costs = cost_explorer.request_usage_type_costs().await?;
+        notification_message =
+            notification_message.with_data_transfer_cost(usage_type_costs, config);
+    }
+
+    if config.show_top_increasing_usage_type {
+        let previous_usage_type_costs = cost_explorer
+            .request_previous_period_usage_type_costs()
+            .await?;
+        let usage_type_costs = cost_explorer.request_usage_type_costs().await?;
+        notification_message = notification_message.with_top_increasing_usage_type(
+            &previous_usage_type_costs,
+            &usage_type_costs,
+            config,
+        );
+    }
+
+    if let Some(top_n) = config.top_usage_types {
+        let usage_type_costs = cost_explorer.request_usage_type_costs().await?;
+        notification_message = notification_message.with_top_usage_types(
+            usage_type_costs,
+            top_n,
+            config.show_usage_quantity,
+            config,
+        );
+    }
 
-    let notification_message = NotificationMessage::new(total_cost, service_costs);
+    if config.show_region_costs {
+        let region_costs = cost_explorer.request_region_costs().await?;
+        notification_message =
+            notification_message.with_region_costs(region_costs, config.group_global_regions);
+    }
+
+    if config.show_account_costs {
+        let account_costs = cost_explorer.request_account_costs().await?;
+        notification_message = notification_message.with_account_costs(account_costs);
+    }
 
-    let res = notifier.send(notification_message);
+    if config.show_utilization {
+        let savings_plans_utilization = cost_explorer.request_savings_plans_utilization().await?;
+        let reservation_utilization = cost_explorer.request_reservation_utilization().await?;
+        notification_message = notification_message.with_utilization(
+            savings_plans_utilization.as_ref(),
+            reservation_utilization.as_ref(),
+            config.low_utilization_percent,
+        );
+    }
 
+    if config.show_rightsizing_savings {
+        let savings = cost_explorer.request_rightsizing_savings().await?;
+        notification_message =
+            notification_message.with_savings_opportunity(savings.as_ref(), config);
+    }
+
+    if let (true, Some(budget)) = (config.show_overage_date, config.monthly_budget) {
+        let daily_costs = cost_explorer.request_daily_costs().await?;
+        let overage_date = projected_overage_date(&daily_costs, budget);
+        notification_message = notification_message.with_overage_date(overage_date.as_ref());
+    }
+
+    if config.show_daily_costs {
+        let daily_costs = cost_explorer.request_daily_costs().await?;
+        let today = reporting_datetime.date();
+        notification_message = notification_message
+            .with_daily_costs(daily_costs, config.exclude_incomplete_day.then_some(&today));
+    }
+
+    if let Some(baseline) = config.daily_comparison_baseline {
+        let (baseline_total, latest_total) =
+            cost_explorer.request_daily_comparison(baseline).await?;
+        notification_message = notification_message.with_daily_change(
+            baseline,
+            &baseline_total,
+            &latest_total,
+            config,
+        );
+    }
+
+    if let Some(months) = config.monthly_table_periods {
+        let monthly_costs = cost_explorer.request_monthly_costs(months).await?;
+        notification_message = notification_message.with_monthly_table(monthly_costs, config);
+    }
+
+    if config.show_generated_at {
+        notification_message = notification_message.with_generated_at(&reporting_datetime);
+    }
+
+    notification_message = notification_message
+        .with_unit_labels(&config.unit_labels)
+        .with_footer(config.message_footer.as_deref());
+
+    span.end_phase("build");
+    let res = if config.split_messages {
+        let (total_message, breakdown_message) = notification_message.split();
+        notifier
+            .send(total_message)
+            .and_then(|_| notifier.send(breakdown_message))
+    } else {
+        notifier.send(notification_message)
+    };
+    span.end_phase("send");
+    if res.is_ok() {
+        record_notified_total(notified_total_store, &notified_total).await;
+    }
+    notification_result(res)
+}
+
+/// Record the notified total in `store`, if given.
+/// Failure of the record does not fail the run since the notification has been already sent.
+async fn record_notified_total(store: Option<&dyn NotifiedTotalStore>, total: &NotifiedTotal) {
+    if let Some(store) = store {
+        if let Err(e) = store.record_notified_total(total).await {
+            println!("Failed to record the notified total: {}", e);
+        }
+    }
+}
+
+/// Convert the result of the Slack notification into that of the whole process.
+fn notification_result(res: Result<(), slack_hook::Error>) -> Result<(), Box<dyn error::Error>> {
     match res {
         Ok(_) => {
             println!("Notification Successfully Completed!");
@@ -96,22 +812,45 @@ where
 
 #[cfg(test)]
 mod integration_tests {
-    use super::request_cost_and_notify;
-    use crate::cost_explorer::test_utils::{CostAndUsageClientStub, InputServiceCost};
-    use crate::message_builder::NotificationMessage;
+    use super::{
+        cost_granularity, report_date_range, request_cost_and_notify, request_parsed_costs,
+    };
+    use crate::config::Config;
+    use crate::cost_explorer::cost_response_parser::Cost;
+    use crate::cost_explorer::error::{CostNotificationError, ParseError};
+    use crate::cost_explorer::test_utils::{CostAndUsageClientStub, InputServiceCost, PeriodCosts};
+    use crate::cost_explorer::Granularity;
+    use crate::event_publisher::{EventBridgePublisher, PutEventsStub};
+    use crate::idempotency::{InMemoryStore, NotifiedTotal, NotifiedTotalStore};
+    use crate::message_builder::{NotificationMessage, Severity};
+    use crate::reporting_date::{ReportDateRange, WindowAlignment};
     use crate::slack_notifier::SendMessage;
+    use crate::telemetry::{AttributeValue, InMemoryExporter, RUN_SPAN_NAME};
     use chrono::{Local, TimeZone};
+    use serde_json::{json, Value};
     use slack_hook::Error;
-    use tokio;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicU32, Ordering};
 
+    #[derive(Default)]
     struct SlackNotifierStub {
         fail: bool,
+        /// Headers and bodies of the sent messages.
+        sent: Rc<RefCell<Vec<(String, String)>>>,
+        /// Severities of the sent messages.
+        severities: Rc<RefCell<Vec<Severity>>>,
+        /// Comparisons of the sent messages.
+        comparisons: Rc<RefCell<Vec<Option<String>>>>,
     }
     impl SendMessage for SlackNotifierStub {
-        fn send(self, _message: NotificationMessage) -> Result<(), Error> {
+        fn send(&self, message: NotificationMessage) -> Result<(), Error> {
             if self.fail {
                 Err(Error::from("Something Wrong!"))
             } else {
+                self.severities.borrow_mut().push(message.severity);
+                self.comparisons.borrow_mut().push(message.comparison);
+                self.sent.borrow_mut().push((message.header, message.body));
                 Ok(())
             }
         }
@@ -125,15 +864,26 @@ mod integration_tests {
                 InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
             ]),
             total_cost: Some(String::from("1234.56")),
+            ..Default::default()
         };
 
-        let slack_notifier_stub = SlackNotifierStub { fail: false };
+        let slack_notifier_stub = SlackNotifierStub {
+            fail: false,
+            ..Default::default()
+        };
 
-        let reporting_date = Local.ymd(2021, 8, 1);
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
 
-        let res =
-            request_cost_and_notify(cost_usage_client_stub, slack_notifier_stub, reporting_date)
-                .await;
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
 
         assert!(res.is_ok());
     }
@@ -146,52 +896,1085 @@ mod integration_tests {
                 InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
             ]),
             total_cost: Some(String::from("1234.56")),
+            ..Default::default()
         };
 
-        let slack_notifier_stub = SlackNotifierStub { fail: true };
+        let slack_notifier_stub = SlackNotifierStub {
+            fail: true,
+            ..Default::default()
+        };
 
-        let reporting_date = Local.ymd(2021, 8, 1);
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
 
-        let res =
-            request_cost_and_notify(cost_usage_client_stub, slack_notifier_stub, reporting_date)
-                .await;
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
         assert!(res.is_err());
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn panic_when_total_cost_is_empty() {
+    async fn notify_error_when_cost_request_fails() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "1.00")]),
+            total_cost: Some(String::from("1.00")),
+            failures: AtomicU32::new(1),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+        let severities = slack_notifier_stub.severities.clone();
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            Local.ymd(2021, 8, 1).and_hms(9, 0, 0),
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        let error = res.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<CostNotificationError>(),
+            Some(CostNotificationError::Api(_))
+        ));
+        assert_eq!(
+            vec![("コスト取得に失敗しました".to_string(), error.to_string())],
+            *sent.borrow()
+        );
+        assert_eq!(vec![Severity::Critical], *severities.borrow());
+    }
+
+    #[tokio::test]
+    async fn publish_report_to_event_bridge() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "1.5")]),
+            total_cost: Some(String::from("1.5")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+        let put_events_stub = PutEventsStub::default();
+        let entries = put_events_stub.entries.clone();
+        let publisher =
+            EventBridgePublisher::new(put_events_stub, "aws-cost-notification", "AWS Cost Report");
+        let failing_publisher = EventBridgePublisher::new(
+            PutEventsStub {
+                fail: true,
+                ..Default::default()
+            },
+            "aws-cost-notification",
+            "AWS Cost Report",
+        );
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            Local.ymd(2021, 7, 23).and_hms(9, 0, 0),
+            &Config::default(),
+            None,
+            None,
+            Some(&publisher),
+        )
+        .await;
+        let res_with_failing_publisher = request_cost_and_notify(
+            CostAndUsageClientStub {
+                service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "1.5")]),
+                total_cost: Some(String::from("1.5")),
+                ..Default::default()
+            },
+            SlackNotifierStub::default(),
+            Local.ymd(2021, 7, 23).and_hms(9, 0, 0),
+            &Config::default(),
+            None,
+            None,
+            Some(&failing_publisher),
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert!(res_with_failing_publisher.is_ok());
+        assert_eq!(1, sent.borrow().len());
+        let entries = entries.lock().unwrap();
+        assert_eq!(1, entries.len());
+        assert_eq!("aws-cost-notification", entries[0].source);
+        assert_eq!("AWS Cost Report", entries[0].detail_type);
+        assert_eq!(
+            json!({
+                "total_cost": {
+                    "date_range": {"start_date": "2021-07-01", "end_date": "2021-07-23"},
+                    "cost": {"amount": 1.5, "unit": "USD"},
+                },
+                "service_costs": [
+                    {"service_name": "AWS CloudTrail", "cost": {"amount": 1.5, "unit": "USD"}},
+                ],
+            }),
+            serde_json::from_str::<Value>(&entries[0].detail).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn return_cost_error_even_if_error_notification_fails() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            credentials_error: true,
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub {
+            fail: true,
+            ..Default::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            Local.ymd(2021, 8, 1).and_hms(9, 0, 0),
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(matches!(
+            res.unwrap_err().downcast_ref::<CostNotificationError>(),
+            Some(CostNotificationError::Auth(_))
+        ));
+    }
+
+    fn notified_total(period_start: &str, amount: f64) -> NotifiedTotal {
+        NotifiedTotal {
+            period_start: period_start.to_string(),
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn append_change_since_last_notification() {
+        let store = InMemoryStore::default();
+        store
+            .record_notified_total(&notified_total("2021-07-01", 100.0))
+            .await
+            .unwrap();
+        let config = Config {
+            show_change_since_last_notification: true,
+            ..Config::default()
+        };
+
+        for total_cost in ["115.00", "112.00"] {
+            let cost_usage_client_stub = CostAndUsageClientStub {
+                service_costs: Some(vec![InputServiceCost::new("AWS Lambda", total_cost)]),
+                total_cost: Some(String::from(total_cost)),
+                ..Default::default()
+            };
+            let slack_notifier_stub = SlackNotifierStub::default();
+            let sent = slack_notifier_stub.sent.clone();
+
+            let res = request_cost_and_notify(
+                cost_usage_client_stub,
+                slack_notifier_stub,
+                Local.ymd(2021, 7, 23).and_hms(9, 0, 0),
+                &config,
+                None,
+                Some(&store),
+                None,
+            )
+            .await;
+
+            assert!(res.is_ok());
+            let expected_change = match total_cost {
+                "115.00" => "+15.00 USD",
+                _ => "-3.00 USD",
+            };
+            assert_eq!(
+                format!(
+                    "07/01~07/23の請求額は、{} USDです。 (前回通知から {})",
+                    total_cost, expected_change
+                ),
+                sent.borrow()[0].0
+            );
+        }
+        assert_eq!(
+            Some(notified_total("2021-07-01", 112.0)),
+            store.last_notified_total("2021-07-01").await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_change_since_notification_of_other_period() {
+        let store = InMemoryStore::default();
+        store
+            .record_notified_total(&notified_total("2021-06-01", 100.0))
+            .await
+            .unwrap();
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS Lambda", "5.00")]),
+            total_cost: Some(String::from("5.00")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+        let config = Config {
+            show_change_since_last_notification: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            Local.ymd(2021, 7, 2).and_hms(9, 0, 0),
+            &config,
+            None,
+            Some(&store),
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!("07/01~07/02の請求額は、5.00 USDです。", sent.borrow()[0].0);
+        assert_eq!(
+            Some(notified_total("2021-06-01", 100.0)),
+            store.last_notified_total("2021-06-01").await.unwrap()
+        );
+        assert_eq!(
+            Some(notified_total("2021-07-01", 5.0)),
+            store.last_notified_total("2021-07-01").await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn export_span_of_run() {
+        let exporter = InMemoryExporter::default();
+
+        for fail in [false, true] {
+            let cost_usage_client_stub = CostAndUsageClientStub {
+                service_costs: Some(vec![InputServiceCost::new(
+                    "Amazon Simple Storage Service",
+                    "1234.56",
+                )]),
+                total_cost: Some(String::from("1234.56")),
+                ..Default::default()
+            };
+            let slack_notifier_stub = SlackNotifierStub {
+                fail,
+                ..Default::default()
+            };
+
+            let _ = request_cost_and_notify(
+                cost_usage_client_stub,
+                slack_notifier_stub,
+                Local.ymd(2021, 8, 1).and_hms(9, 0, 0),
+                &Config::default(),
+                Some(&exporter),
+                None,
+                None,
+            )
+            .await;
+        }
+
+        let spans = exporter.spans.lock().unwrap();
+        assert_eq!(2, spans.len());
+        for span in spans.iter() {
+            assert_eq!(RUN_SPAN_NAME, span.name);
+            for key in ["fetch.duration_ms", "build.duration_ms", "send.duration_ms"] {
+                assert!(matches!(span.attribute(key), Some(AttributeValue::Int(_))));
+            }
+        }
+        assert_eq!(
+            Some(&AttributeValue::String("success".to_string())),
+            spans[0].attribute("outcome")
+        );
+        assert_eq!(
+            Some(&AttributeValue::String("failure".to_string())),
+            spans[1].attribute("outcome")
+        );
+    }
+
+    #[tokio::test]
+    async fn return_auth_error_when_credentials_are_not_resolved() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            credentials_error: true,
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            Local.ymd(2021, 8, 1).and_hms(9, 0, 0),
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        let error = res.unwrap_err();
+        assert_eq!(
+            Some(&CostNotificationError::Auth(
+                "No credentials found".to_string()
+            )),
+            error.downcast_ref::<CostNotificationError>()
+        );
+        assert_eq!("コスト取得に失敗しました", sent.borrow()[0].0);
+    }
+
+    #[tokio::test]
+    async fn return_parse_error_when_total_cost_is_empty() {
         let cost_usage_client_stub = CostAndUsageClientStub {
             service_costs: Some(vec![
                 InputServiceCost::new("Amazon Simple Storage Service", "1234.56"),
                 InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
             ]),
             total_cost: None,
+            ..Default::default()
+        };
+
+        let slack_notifier_stub = SlackNotifierStub {
+            fail: false,
+            ..Default::default()
+        };
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        let err = res.unwrap_err();
+        assert_eq!(
+            Some(&CostNotificationError::Parse(ParseError::InvalidMetric(
+                "AmortizedCost".to_string()
+            ))),
+            err.downcast_ref::<CostNotificationError>()
+        );
+        assert_eq!("コスト取得に失敗しました", sent.borrow()[0].0);
+    }
+
+    #[test]
+    fn aggregate_costs_in_calendar_month_or_rolling_30_days() {
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let rolling_config = Config {
+            window_alignment: WindowAlignment::Rolling(30),
+            ..Config::default()
+        };
+
+        assert_eq!(
+            ReportDateRange::new(Local.ymd(2021, 7, 23)),
+            report_date_range(&reporting_datetime, &Config::default())
+        );
+        assert_eq!(Granularity::Monthly, cost_granularity(&Config::default()));
+        assert_eq!(
+            ReportDateRange::trailing_days(Local.ymd(2021, 7, 23), 30),
+            report_date_range(&reporting_datetime, &rolling_config)
+        );
+        assert_eq!(Granularity::Daily, cost_granularity(&rolling_config));
+    }
+
+    #[tokio::test]
+    async fn return_currency_mismatch_error_for_service_costs_in_other_unit() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("Amazon Simple Storage Service", "12.34"),
+                InputServiceCost::new("AWS Marketplace", "1500").with_unit("JPY"),
+            ]),
+            total_cost: Some(String::from("12.34")),
+            ..Default::default()
         };
 
-        let slack_notifier_stub = SlackNotifierStub { fail: false };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
 
-        let reporting_date = Local.ymd(2021, 8, 1);
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
 
-        let _res =
-            request_cost_and_notify(cost_usage_client_stub, slack_notifier_stub, reporting_date)
-                .await;
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        let err = res.unwrap_err();
+        assert_eq!(
+            Some(&CostNotificationError::Parse(ParseError::CurrencyMismatch(
+                "USD".to_string(),
+                "AWS Marketplace".to_string(),
+                "JPY".to_string()
+            ))),
+            err.downcast_ref::<CostNotificationError>()
+        );
+        assert_eq!("コスト取得に失敗しました", sent.borrow()[0].0);
     }
 
     #[tokio::test]
-    #[should_panic]
-    async fn panic_when_service_costs_is_empty() {
+    async fn send_total_only_when_service_costs_are_missing() {
         let cost_usage_client_stub = CostAndUsageClientStub {
             service_costs: None,
             total_cost: Some(String::from("1234.56")),
+            ..Default::default()
         };
 
-        let slack_notifier_stub = SlackNotifierStub { fail: false };
+        let slack_notifier_stub = SlackNotifierStub {
+            fail: false,
+            ..Default::default()
+        };
+        let sent = slack_notifier_stub.sent.clone();
 
-        let reporting_date = Local.ymd(2021, 8, 1);
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
 
-        let _res =
-            request_cost_and_notify(cost_usage_client_stub, slack_notifier_stub, reporting_date)
-                .await;
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &Config::default(),
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            vec![(
+                "07/01~08/01の請求額は、1234.56 USDです。".to_string(),
+                "".to_string()
+            )],
+            *sent.borrow()
+        );
+    }
+
+    #[tokio::test]
+    async fn sum_up_total_from_service_costs_in_single_request() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("AWS CloudTrail", "0.5"),
+                InputServiceCost::new("AWS Lambda", "1.0"),
+            ]),
+            total_cost: Some(String::from("1234.56")),
+            ..Default::default()
+        };
+        let attempts = cost_usage_client_stub.attempts.clone();
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            single_cost_request: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+        assert_eq!(
+            "07/01~07/23の請求額は、1.50 USDです。".to_string(),
+            sent.borrow()[0].0
+        );
+    }
+
+    #[tokio::test]
+    async fn send_header_only_without_service_breakdown() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: None,
+            total_cost: Some(String::from("1234.56")),
+            ..Default::default()
+        };
+        let attempts = cost_usage_client_stub.attempts.clone();
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            show_service_breakdown: false,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        // Only the total cost is requested.
+        assert_eq!(1, attempts.load(Ordering::SeqCst));
+        assert_eq!(
+            vec![(
+                "07/01~07/23の請求額は、1234.56 USDです。".to_string(),
+                "".to_string()
+            )],
+            *sent.borrow()
+        );
+    }
+
+    #[tokio::test]
+    async fn send_total_and_breakdown_separately_when_split() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("Amazon Simple Storage Service", "1234.56"),
+                InputServiceCost::new("Amazon Elastic Compute Cloud", "31415.92"),
+            ]),
+            total_cost: Some(String::from("32650.48")),
+            ..Default::default()
+        };
+
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
+        let config = Config {
+            split_messages: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            vec![
+                (
+                    "07/01~08/01の請求額は、32650.48 USDです。".to_string(),
+                    "".to_string()
+                ),
+                (
+                    "内訳".to_string(),
+                    "・Amazon Elastic Compute Cloud: 31415.92 USD\n・Amazon Simple Storage Service: 1234.56 USD".to_string()
+                ),
+            ],
+            *sent.borrow()
+        );
+    }
+
+    fn stub_with_previous_period(previous_ec2_cost: &str) -> CostAndUsageClientStub {
+        CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("Amazon Simple Storage Service", "10.00"),
+                InputServiceCost::new("Amazon Elastic Compute Cloud", "100.00"),
+            ]),
+            total_cost: Some(String::from("110.00")),
+            other_periods: vec![PeriodCosts {
+                start_date: String::from("2021-06-01"),
+                total_cost: Some(String::from("108.00")),
+                service_costs: Some(vec![
+                    InputServiceCost::new("Amazon Simple Storage Service", "10.00"),
+                    InputServiceCost::new("Amazon Elastic Compute Cloud", previous_ec2_cost),
+                ]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn send_terse_summary_when_costs_are_stable() {
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
+        let config = Config {
+            unchanged_summary: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            stub_with_previous_period("98.00"),
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            vec![(
+                "大きな変化はありません (合計 110.00 USD)".to_string(),
+                "".to_string()
+            )],
+            *sent.borrow()
+        );
+    }
+
+    #[tokio::test]
+    async fn send_full_breakdown_when_costs_have_changed() {
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
+        let config = Config {
+            unchanged_summary: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            stub_with_previous_period("50.00"),
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            vec![(
+                "07/01~08/01の請求額は、110.00 USDです。".to_string(),
+                "・Amazon Elastic Compute Cloud: 100.00 USD\n・Amazon Simple Storage Service: 10.00 USD".to_string()
+            )],
+            *sent.borrow()
+        );
+    }
+
+    #[tokio::test]
+    async fn append_month_over_month_change_to_header() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "112.30")]),
+            total_cost: Some(String::from("112.30")),
+            other_periods: vec![PeriodCosts {
+                start_date: String::from("2021-06-01"),
+                total_cost: Some(String::from("100.00")),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            show_month_over_month: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            "07/01~07/23の請求額は、112.30 USDです。 (前月比 +12.3%)",
+            sent.borrow()[0].0
+        );
+    }
+
+    #[tokio::test]
+    async fn flag_services_dropped_to_zero() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![
+                InputServiceCost::new("AWS CloudTrail", "10.00"),
+                InputServiceCost::new("AWS Glue", "0.00"),
+            ]),
+            total_cost: Some(String::from("10.00")),
+            other_periods: vec![PeriodCosts {
+                start_date: String::from("2021-06-01"),
+                total_cost: Some(String::from("30.00")),
+                service_costs: Some(vec![
+                    InputServiceCost::new("AWS CloudTrail", "8.00"),
+                    InputServiceCost::new("AWS Glue", "22.00"),
+                ]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            alert_dropped_services: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            "・AWS CloudTrail: 10.00 USD\n\n⚠️0になったサービス: AWS Glue",
+            sent.borrow()[0].1
+        );
+    }
+
+    #[tokio::test]
+    async fn append_forecast_to_header() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "10.00")]),
+            total_cost: Some(String::from("10.00")),
+            forecast: Some(String::from("5.00")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            show_forecast: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            "07/01~07/23の請求額は、10.00 USDです。 (月末予測 15.00 USD)",
+            sent.borrow()[0].0
+        );
+    }
+
+    #[tokio::test]
+    async fn skip_notification_when_forecast_is_within_budget() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "60.00")]),
+            total_cost: Some(String::from("60.00")),
+            forecast: Some(String::from("40.00")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            monthly_budget: Some(100.0),
+            notify_only_over_budget: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert!(sent.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn notify_only_when_total_reaches_threshold() {
+        for (total, expected_sent) in [("49.99", 0), ("50.00", 1), ("60.00", 1)] {
+            let cost_usage_client_stub = CostAndUsageClientStub {
+                service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", total)]),
+                total_cost: Some(total.to_string()),
+                ..Default::default()
+            };
+            let slack_notifier_stub = SlackNotifierStub::default();
+            let sent = slack_notifier_stub.sent.clone();
+
+            let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+            let config = Config {
+                notify_threshold: Some(50.0),
+                ..Config::default()
+            };
+
+            let res = request_cost_and_notify(
+                cost_usage_client_stub,
+                slack_notifier_stub,
+                reporting_datetime,
+                &config,
+                None,
+                None,
+                None,
+            )
+            .await;
+
+            assert!(res.is_ok());
+            assert_eq!(expected_sent, sent.borrow().len());
+        }
+    }
+
+    #[tokio::test]
+    async fn alert_when_forecast_exceeds_budget() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "60.00")]),
+            total_cost: Some(String::from("60.00")),
+            forecast: Some(String::from("50.00")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+        let severities = slack_notifier_stub.severities.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            monthly_budget: Some(100.0),
+            notify_only_over_budget: true,
+            alert_mention: Some("<!channel>".to_string()),
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(vec![Severity::Critical], *severities.borrow());
+        assert_eq!(
+            "<!channel>\n・AWS CloudTrail: 60.00 USD\n\n⚠️月末予測 110.00 USD が予算 100.00 USD を超過する見込みです",
+            sent.borrow()[0].1
+        );
+    }
+
+    #[tokio::test]
+    async fn warn_when_utilization_is_low() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("Amazon EC2", "60.00")]),
+            total_cost: Some(String::from("60.00")),
+            reservation_utilization: Some(String::from("62.5")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+        let severities = slack_notifier_stub.severities.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            show_utilization: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(vec![Severity::Warning], *severities.borrow());
+        assert_eq!(
+            "・Amazon EC2: 60.00 USD\n\n⚠️RI稼働率: 62% (80%未満)",
+            sent.borrow()[0].1
+        );
+    }
+
+    #[tokio::test]
+    async fn append_rightsizing_savings() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("Amazon EC2", "60.00")]),
+            total_cost: Some(String::from("60.00")),
+            rightsizing_savings: Some(String::from("12.3456")),
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            show_rightsizing_savings: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            "・Amazon EC2: 60.00 USD\n\n推定節約可能額: 12.35 USD/月",
+            sent.borrow()[0].1
+        );
+    }
+
+    #[tokio::test]
+    async fn return_parsed_costs_for_debugging() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "1.5")]),
+            total_cost: Some(String::from("1.5")),
+            ..Default::default()
+        };
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+
+        let actual_output = request_parsed_costs(
+            cost_usage_client_stub,
+            reporting_datetime,
+            &Config::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            serde_json::json!({
+                "total_cost": {
+                    "date_range": {"start_date": "2021-07-01", "end_date": "2021-07-23"},
+                    "cost": {"amount": 1.5, "unit": "USD"},
+                },
+                "service_costs": [
+                    {"service_name": "AWS CloudTrail", "cost": {"amount": 1.5, "unit": "USD"}},
+                ],
+            }),
+            actual_output
+        );
+    }
+
+    #[tokio::test]
+    async fn escalate_when_total_increases_beyond_threshold() {
+        let cost_usage_client_stub = CostAndUsageClientStub {
+            service_costs: Some(vec![InputServiceCost::new("AWS CloudTrail", "3.00")]),
+            total_cost: Some(String::from("3.00")),
+            other_periods: vec![PeriodCosts {
+                start_date: String::from("2021-06-01"),
+                total_cost: Some(String::from("1.50")),
+                service_costs: None,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let sent = slack_notifier_stub.sent.clone();
+        let severities = slack_notifier_stub.severities.clone();
+
+        let reporting_datetime = Local.ymd(2021, 7, 23).and_hms(9, 0, 0);
+        let config = Config {
+            alert_increase_percent: Some(50.0),
+            alert_mention: Some(String::from("<!here>")),
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            cost_usage_client_stub,
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(vec![Severity::Critical], *severities.borrow());
+        assert_eq!(
+            "<!here>\n・AWS CloudTrail: 3.00 USD\n\n⚠️前月同期比 +100.0%",
+            sent.borrow()[0].1
+        );
+    }
+
+    #[tokio::test]
+    async fn send_comparison_with_previous_period() {
+        let slack_notifier_stub = SlackNotifierStub::default();
+        let comparisons = slack_notifier_stub.comparisons.clone();
+
+        let reporting_datetime = Local.ymd(2021, 8, 1).and_hms(9, 0, 0);
+        let config = Config {
+            comparison_attachment: true,
+            ..Config::default()
+        };
+
+        let res = request_cost_and_notify(
+            stub_with_previous_period("50.00"),
+            slack_notifier_stub,
+            reporting_datetime,
+            &config,
+            None,
+            None,
+            None,
+        )
+        .await;
+
+        assert!(res.is_ok());
+        assert_eq!(
+            vec![Some("【前月同期比較】\n合計: 108.00 USD → 110.00 USD (+2.00 USD, +1.9%)\n・Amazon Elastic Compute Cloud: 50.00 USD → 100.00 USD (+50.00 USD, +100.0%)\n・Amazon Simple Storage Service: 10.00 USD → 10.00 USD (+0.00 USD, +0.0%)".to_string())],
+            *comparisons.borrow()
+        );
     }
 }
@@ -1,19 +1,88 @@
-use crate::cost_explorer::cost_response_parser::{Cost, ReportedDateRange, ServiceCost, TotalCost};
-use chrono::Datelike;
+/// Group the service costs by higher-level categories.
+pub mod category;
+/// Compare the costs with those of the previous period.
+pub mod comparison;
+/// Export the service costs as CSV.
+pub mod csv;
+/// Convert the costs into the display currency.
+pub mod currency;
+/// Escape the text for Slack formatting.
+pub mod escape;
+/// Restrict the reported services by their names.
+pub mod filter;
+/// Templates of the messages in each language.
+pub mod locale;
+/// Format the values of metrics such as costs and usage amounts.
+pub mod metric;
+/// Render plain-text tables.
+pub mod table;
+
+use crate::config::Config;
+use crate::cost_explorer::amount::Amount;
+use crate::cost_explorer::cost_response_parser::{
+    AccountCost, Cost, DailyCost, ForecastCost, MonthlyCost, RegionCost, ReportedDateRange,
+    SavingsOpportunity, ServiceCost, TagCost, TotalCost, UsageTypeCost, Utilization,
+};
+use crate::reporting_date::DailyBaseline;
+use category::group_by_category;
+use chrono::{Date, DateTime, Datelike, Duration, TimeZone};
+use comparison::{
+    format_change, percentage_change, top_increasing_usage_type, ChangeTolerance,
+    ServiceCostChange, Trend, NEGLIGIBLE_AMOUNT,
+};
+use currency::{convert_cost, group_thousands, localize_units, zero_decimal_symbol};
+use escape::escape_mrkdwn;
+use filter::{is_listed_service, is_reported_service};
+use locale::Locale;
+use metric::{format_metric, format_metrics, USAGE_METRIC};
+use std::collections::HashMap;
 use std::fmt;
+use table::{align_rows, render_table, Align, ServiceLayout};
+
+/// Number of decimals of the costs displayed by default.
+pub const DEFAULT_COST_DECIMALS: usize = 2;
+
+impl Cost {
+    /// Format the cost with `decimals` decimals.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let input_cost = Cost {
+    ///     amount: 0.0123.into(),
+    ///     unit: "USD".to_string(),
+    /// };
+    /// assert_eq!("0.0123 USD", input_cost.format_with_precision(4));
+    /// ```
+    ///
+    /// Currencies without minor units (e.g. JPY) are displayed with their symbols,
+    /// without decimals and grouped by three digits regardless of `decimals`.
+    /// (e.g. `¥1,234,567`)
+    pub fn format_with_precision(&self, decimals: usize) -> String {
+        match zero_decimal_symbol(&self.unit) {
+            Some(symbol) => match self.amount.round_dp(0).is_negative() {
+                true => format!("-{}{}", symbol, group_thousands(-self.amount)),
+                false => format!("{}{}", symbol, group_thousands(self.amount)),
+            },
+            None => format!("{:.*} {}", decimals, self.amount, self.unit),
+        }
+    }
+}
 
 /// # Example
 ///
 /// ```
 /// let input_cost = Cost {
-///     amount: 132.2345,
+///     amount: 132.2345.into(),
 ///     unit: "USD".to_string(),
 /// };
 /// assert_eq!("132.23 USD", format!("{}", input_cost));
 /// ```
+///
+/// The cost is formatted with `DEFAULT_COST_DECIMALS` decimals.
 impl fmt::Display for Cost {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:.2} {}", self.amount, self.unit)
+        write!(f, "{}", self.format_with_precision(DEFAULT_COST_DECIMALS))
     }
 }
 
@@ -26,8 +95,23 @@ impl fmt::Display for Cost {
 /// };
 /// assert_eq!("07/01~07/23", format!("{}", sample_date_range))
 /// ```
-impl fmt::Display for ReportedDateRange {
+///
+/// The years are displayed if the range crosses a year boundary.
+/// (e.g. `2021/12/01~2022/01/01`)
+impl<T: TimeZone> fmt::Display for ReportedDateRange<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.start_date.year() != self.end_date.year() {
+            return write!(
+                f,
+                "{}/{:02}/{:02}~{}/{:02}/{:02}",
+                self.start_date.year(),
+                self.start_date.month(),
+                self.start_date.day(),
+                self.end_date.year(),
+                self.end_date.month(),
+                self.end_date.day(),
+            );
+        }
         write!(
             f,
             "{:02}/{:02}~{:02}/{:02}",
@@ -39,6 +123,27 @@ impl fmt::Display for ReportedDateRange {
     }
 }
 
+impl<T: TimeZone> ReportedDateRange<T> {
+    /// Format the start and end dates with the strftime-style `fmt`, joined by `~`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let sample_date_range = ReportedDateRange {
+    ///     start_date: Local.ymd(2021, 7, 1),
+    ///     end_date: Local.ymd(2021, 7, 23),
+    /// };
+    /// assert_eq!("2021-07-01~2021-07-23", sample_date_range.format_range("%Y-%m-%d"))
+    /// ```
+    pub fn format_range(&self, fmt: &str) -> String {
+        format!(
+            "{}~{}",
+            self.start_date.naive_local().format(fmt),
+            self.end_date.naive_local().format(fmt)
+        )
+    }
+}
+
 impl ServiceCost {
     /// # Example
     ///
@@ -46,274 +151,4178 @@ impl ServiceCost {
     /// let sample_service_cost = ServiceCost {
     ///     service_name: "AWS CloudTrail".to_string(),
     ///     cost: Cost {
-    ///         amount: 0.0123,
+    ///         amount: 0.0123.into(),
     ///         unit: "USD".to_string(),
     ///     },
     /// };
-    /// let actual_line = sample_service_cost.to_message_line();
+    /// let actual_line = sample_service_cost.to_message_line(true, 2, Locale::Ja);
     ///
     /// assert_eq!("・AWS CloudTrail: 0.01 USD", actual_line);
     /// ```
+    ///
+    /// If `escape` is true, the service name is escaped for Slack formatting.
+    /// The cost is formatted with `decimals` decimals,
+    /// and the line starts with the bullet of the `locale`.
+    fn to_message_line(&self, escape: bool, decimals: usize, locale: Locale) -> String {
+        format!(
+            "{}{}: {}",
+            locale.bullet(),
+            format_label(&self.service_name, escape),
+            self.cost.format_with_precision(decimals)
+        )
+    }
+
+    /// Format the line followed by the share of the service in the `total` cost.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let sample_service_cost = ServiceCost {
+    ///     service_name: "Amazon EC2".to_string(),
+    ///     cost: Cost {
+    ///         amount: 31.41.into(),
+    ///         unit: "USD".to_string(),
+    ///     },
+    /// };
+    /// let actual_line =
+    ///     sample_service_cost.to_message_line_with_share(74.8, true, 2, Locale::Ja);
+    ///
+    /// assert_eq!("・Amazon EC2: 31.41 USD (42.0%)", actual_line);
+    /// ```
+    fn to_message_line_with_share(
+        &self,
+        total: f64,
+        escape: bool,
+        decimals: usize,
+        locale: Locale,
+    ) -> String {
+        format!(
+            "{}{}",
+            self.to_message_line(escape, decimals, locale),
+            self.share_remark(total)
+        )
+    }
+
+    /// The share of the service in the `total` cost. (e.g. ` (42.0%)`)
+    /// It is `0.0%` if the total is zero.
+    fn share_remark(&self, total: f64) -> String {
+        let share = match total > 0.0 {
+            true => self.cost.amount.to_f64() / total * 100.0,
+            false => 0.0,
+        };
+        format!(" ({:.1}%)", share)
+    }
+
+    /// Format the line with the consumption against the `budget` of the service.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let sample_service_cost = ServiceCost {
+    ///     service_name: "Amazon Elastic Compute Cloud - Compute".to_string(),
+    ///     cost: Cost {
+    ///         amount: 80.0.into(),
+    ///         unit: "USD".to_string(),
+    ///     },
+    /// };
+    /// let actual_line =
+    ///     sample_service_cost.to_message_line_with_budget(100.0, true, 2, Locale::Ja);
+    ///
+    /// assert_eq!("・Amazon Elastic Compute Cloud - Compute: 80.00/100.00 USD (80%)", actual_line);
+    /// ```
+    fn to_message_line_with_budget(
+        &self,
+        budget: f64,
+        escape: bool,
+        decimals: usize,
+        locale: Locale,
+    ) -> String {
+        let (consumption, remark) = self.budget_consumption(budget, decimals);
+        format!(
+            "{}{}: {}{}",
+            locale.bullet(),
+            format_label(&self.service_name, escape),
+            consumption,
+            remark
+        )
+    }
+
+    /// The consumption against the `budget` of the service (e.g. `80.00/100.00 USD`)
+    /// and the remark following it (e.g. ` (80%)`, ` (120%) ⚠️予算超過`).
+    /// The amounts are formatted with `decimals` decimals.
+    fn budget_consumption(&self, budget: f64, decimals: usize) -> (String, String) {
+        let consumption = format!(
+            "{:.*}/{:.*} {}",
+            decimals, self.cost.amount, decimals, budget, self.cost.unit
+        );
+        let remark = format!(" ({:.0}%)", self.cost.amount.to_f64() / budget * 100.0);
+        match self.cost.amount > Amount::from(budget) {
+            true => (consumption, format!("{} ⚠️予算超過", remark)),
+            false => (consumption, remark),
+        }
+    }
+}
+
+impl TagCost {
+    /// # Example
+    ///
+    /// ```
+    /// let sample_tag_cost = TagCost {
+    ///     tag_value: "frontend".to_string(),
+    ///     cost: Cost {
+    ///         amount: 0.0123.into(),
+    ///         unit: "USD".to_string(),
+    ///     },
+    /// };
+    /// let actual_line = sample_tag_cost.to_message_line(true);
+    ///
+    /// assert_eq!("・frontend: 0.01 USD", actual_line);
+    /// ```
+    ///
+    /// If `escape` is true, the tag value is escaped for Slack formatting.
+    /// The cost of resources without the tag is labeled as `(untagged)`.
+    fn to_message_line(&self, escape: bool) -> String {
+        let label = match self.tag_value.is_empty() {
+            true => UNTAGGED_LABEL.to_string(),
+            false => format_label(&self.tag_value, escape),
+        };
+        to_ranking_line(&label, &self.cost)
+    }
+}
+
+impl<T: TimeZone> DailyCost<T> {
+    /// The cost of the day which is still estimated is marked with `(見込み)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let sample_daily_cost = DailyCost {
+    ///     date: Local.ymd(2021, 7, 1),
+    ///     cost: Cost {
+    ///         amount: 1.234.into(),
+    ///         unit: "USD".to_string(),
+    ///     },
+    ///     estimated: true,
+    /// };
+    /// let actual_line = sample_daily_cost.to_message_line();
+    ///
+    /// assert_eq!("・07/01: 1.23 USD (見込み)", actual_line);
+    /// ```
     fn to_message_line(&self) -> String {
-        format!("・{}: {}", self.service_name, self.cost)
+        let line = format!(
+            "・{:02}/{:02}: {}",
+            self.date.month(),
+            self.date.day(),
+            self.cost
+        );
+        match self.estimated {
+            true => format!("{} (見込み)", line),
+            false => line,
+        }
+    }
+}
+
+/// Escape the `label` (e.g. a service name) for Slack formatting if `escape` is true.
+fn format_label(label: &str, escape: bool) -> String {
+    match escape {
+        true => escape_mrkdwn(label),
+        false => label.to_string(),
+    }
+}
+
+/// Format a line of the cost ranking. (e.g. `・AWS CloudTrail: 0.01 USD`)
+fn to_ranking_line(label: &str, cost: &Cost) -> String {
+    format!("・{}: {}", label, cost)
+}
+
+/// Build the lines of the cost ranking from `items`,
+/// whose cost and message line are obtained by `cost_of` and `to_line`.
+///
+/// The items are displayed in descending order by amount,
+/// skipping items whose amounts are below `min_amount`, or which are displayed
+/// as zero (e.g. `0.00 USD`, `¥0`) if `min_amount` is None,
+/// unless `always_show` returns true.
+/// If `limit` is given, only the top `limit` items are displayed.
+fn build_ranking_lines<T, L, F>(
+    items: &[T],
+    cost_of: fn(&T) -> &Cost,
+    to_line: L,
+    always_show: F,
+    min_amount: Option<f64>,
+    limit: Option<usize>,
+) -> Vec<String>
+where
+    L: Fn(&T) -> String,
+    F: Fn(&T) -> bool,
+{
+    rank_items(items, cost_of, always_show, min_amount)
+        .into_iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .map(&to_line)
+        .collect()
+}
+
+/// Sort `items` in descending order by amount and filter out those not displayed
+/// in the ranking built by `build_ranking_lines`.
+fn rank_items<T, F>(
+    items: &[T],
+    cost_of: fn(&T) -> &Cost,
+    always_show: F,
+    min_amount: Option<f64>,
+) -> Vec<&T>
+where
+    F: Fn(&T) -> bool,
+{
+    let mut sorted_items: Vec<&T> = items.iter().collect();
+    sorted_items.sort_by(|a, b| cost_of(b).cmp(cost_of(a)));
+
+    sorted_items
+        .into_iter()
+        .filter(|x| {
+            always_show(x)
+                || match min_amount {
+                    Some(min_amount) => cost_of(x).amount >= Amount::from(min_amount),
+                    None => !is_displayed_as_zero(cost_of(x)),
+                }
+        })
+        .collect()
+}
+
+/// Label and formatted total cost of the services collapsed out of the ranking.
+/// (e.g. `その他 (3 services)`, `1.23 USD`)
+///
+/// The number of the services is omitted from the label
+/// unless `others_show_count` of `config` is true.
+/// The total is formatted by `format_others_cost` with `others_rounding`
+/// and `cost_decimals` of `config`.
+///
+/// Returns None if there are no such services or their total is displayed as zero.
+fn collapse_others(others: &[&ServiceCost], config: &Config) -> Option<(String, String)> {
+    let first = others.first()?;
+    let total = Cost {
+        amount: others.iter().map(|x| x.cost.amount).sum(),
+        unit: first.cost.unit.clone(),
+    };
+    if is_displayed_as_zero(&total) {
+        return None;
+    }
+    let label = config
+        .locale
+        .others(Some(others.len()).filter(|_| config.others_show_count));
+    Some((
+        label,
+        format_others_cost(&total, config.others_rounding, config.cost_decimals),
+    ))
+}
+
+/// Format the total cost of the collapsed services with `decimals` decimals.
+///
+/// If `rounding` is set, the amount is rounded to the nearest multiple of it
+/// and marked with `~` (e.g. `~10.00 USD`), or displayed as less than `rounding`
+/// if it is rounded to zero (e.g. `<10.00 USD`).
+fn format_others_cost(total: &Cost, rounding: Option<f64>, decimals: usize) -> String {
+    let rounding = match rounding {
+        Some(rounding) => rounding,
+        None => return total.format_with_precision(decimals),
+    };
+    let rounded = (total.amount.to_f64() / rounding).round() * rounding;
+    let cost_of = |amount: f64| Cost {
+        amount: Amount::from(amount),
+        unit: total.unit.clone(),
+    };
+    match rounded == 0.0 {
+        true => format!("<{}", cost_of(rounding).format_with_precision(decimals)),
+        false => format!("~{}", cost_of(rounded).format_with_precision(decimals)),
+    }
+}
+
+/// Whether `cost` is displayed as zero, i.e. 0.005 or less,
+/// or less than 0.5 in currencies without decimals.
+fn is_displayed_as_zero(cost: &Cost) -> bool {
+    match zero_decimal_symbol(&cost.unit) {
+        Some(_) => cost.amount.abs() < Amount::new(5, 1),
+        None => cost.amount.abs() <= NEGLIGIBLE_AMOUNT,
+    }
+}
+
+/// Label of the costs of resources without the cost allocation tag.
+const UNTAGGED_LABEL: &str = "(untagged)";
+
+/// Pseudo-regions to which the costs of global services belong.
+const GLOBAL_PSEUDO_REGIONS: &[&str] = &["NoRegion", "global", ""];
+
+/// Label of the costs of global services.
+const GLOBAL_REGION_LABEL: &str = "グローバル";
+
+/// Relabel the pseudo-regions of global services (e.g. `NoRegion`) as `グローバル`
+/// and sum up their costs into one.
+fn group_global_regions(region_costs: Vec<RegionCost>) -> Vec<RegionCost> {
+    let mut grouped: Vec<RegionCost> = vec![];
+    for region_cost in region_costs {
+        let region = match GLOBAL_PSEUDO_REGIONS.contains(&region_cost.region.as_str()) {
+            true => GLOBAL_REGION_LABEL.to_string(),
+            false => region_cost.region,
+        };
+        match grouped.iter_mut().find(|x| x.region == region) {
+            Some(x) => x.cost.amount += region_cost.cost.amount,
+            None => grouped.push(RegionCost {
+                region,
+                cost: region_cost.cost,
+            }),
+        }
+    }
+    grouped
+}
+
+/// Whether `usage_type` contains any of `patterns`.
+fn matches_any_pattern(usage_type: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| usage_type.contains(pattern.as_str()))
+}
+
+/// Sum up the costs of usage types which contain any of `patterns`.
+///
+/// The unit of the sum is taken from the matched costs (USD if nothing matches).
+fn sum_matching_usage_types(usage_type_costs: &[UsageTypeCost], patterns: &[String]) -> Cost {
+    let matched: Vec<&UsageTypeCost> = usage_type_costs
+        .iter()
+        .filter(|x| matches_any_pattern(&x.usage_type, patterns))
+        .collect();
+
+    Cost {
+        amount: matched.iter().map(|x| x.cost.amount).sum(),
+        unit: match matched.first() {
+            Some(x) => x.cost.unit.clone(),
+            None => "USD".to_string(),
+        },
+    }
+}
+
+/// Severity of the message by the band of the total `amount`:
+/// `Severity::Critical` from `critical_threshold`, `Severity::Warning` from `warning_threshold`
+/// and `Severity::Normal` below them.
+/// The color of the message is that of the severity (e.g. `Severity::Warning.color()`).
+pub fn severity_of_spend(
+    amount: Amount,
+    warning_threshold: Option<f64>,
+    critical_threshold: Option<f64>,
+) -> Severity {
+    if critical_threshold.is_some_and(|x| amount >= Amount::from(x)) {
+        return Severity::Critical;
+    }
+    if warning_threshold.is_some_and(|x| amount >= Amount::from(x)) {
+        return Severity::Warning;
+    }
+    Severity::Normal
+}
+
+/// The projected total at the end of the month, which is the sum of
+/// the `actual` cost so far and the `forecast` of the rest.
+///
+/// It is the `actual` cost if there is no forecast (i.e. the month has ended).
+pub fn projected_total(actual: &Cost, forecast: Option<&ForecastCost>) -> Cost {
+    Cost {
+        amount: actual.amount + forecast.map_or(Amount::ZERO, |x| x.cost.amount),
+        unit: actual.unit.clone(),
+    }
+}
+
+/// The number of the latest days whose average cost is regarded as the daily burn rate.
+const TRAILING_DAYS: usize = 7;
+
+/// The date on which the total cost of the month is projected to exceed `budget`,
+/// extrapolating the average cost of the last `TRAILING_DAYS` days of `daily_costs`.
+///
+/// Returns the actual date if the budget has already been exceeded,
+/// or None if it is not projected to be exceeded within the month.
+/// Only the costs of the month of the last day are summed up.
+pub fn projected_overage_date<T: TimeZone>(
+    daily_costs: &[DailyCost<T>],
+    budget: f64,
+) -> Option<Date<T>> {
+    let last = daily_costs.last()?;
+    let daily_costs: Vec<&DailyCost<T>> = daily_costs
+        .iter()
+        .filter(|x| x.date.year() == last.date.year() && x.date.month() == last.date.month())
+        .collect();
+
+    let budget = Amount::from(budget);
+    let mut spent = Amount::ZERO;
+    for x in daily_costs.iter() {
+        spent += x.cost.amount;
+        if spent > budget {
+            return Some(x.date.clone());
+        }
+    }
+
+    let trailing = &daily_costs[daily_costs.len().saturating_sub(TRAILING_DAYS)..];
+    let burn_rate = trailing
+        .iter()
+        .map(|x| x.cost.amount)
+        .sum::<Amount>()
+        .to_f64()
+        / trailing.len() as f64;
+    if burn_rate <= 0.0 {
+        return None;
+    }
+    let days = ((budget - spent).to_f64() / burn_rate).floor() as i64 + 1;
+    let overage_date = last.date.clone() + Duration::days(days);
+    match overage_date.month() == last.date.month() {
+        true => Some(overage_date),
+        false => None,
+    }
+}
+
+/// Sum up the usage amounts of usage types which contain any of `patterns`.
+///
+/// Returns None if none of them has the usage amount.
+fn sum_matching_usage_quantities(
+    usage_type_costs: &[UsageTypeCost],
+    patterns: &[String],
+) -> Option<Cost> {
+    let matched: Vec<&Cost> = usage_type_costs
+        .iter()
+        .filter(|x| matches_any_pattern(&x.usage_type, patterns))
+        .filter_map(|x| x.usage_quantity.as_ref())
+        .collect();
+
+    matched.first().map(|first| Cost {
+        amount: matched.iter().map(|x| x.amount).sum(),
+        unit: first.unit.clone(),
+    })
+}
+
+impl<T: TimeZone> TotalCost<T> {
+    /// # Example
+    ///
+    /// ```
+    /// let sample_total_cost = TotalCost {
+    ///     date_range: ReportedDateRange {
+    ///         start_date: Local.ymd(2021, 7, 1),
+    ///         end_date: Local.ymd(2021, 7, 11),
+    ///     },
+    ///     cost: Cost {
+    ///         amount: 1.6234.into(),
+    ///         unit: "USD".to_string(),
+    ///     },
+    /// };
+    /// let actual_header = sample_total_cost.to_message_header(2, Locale::Ja, None);
+    ///
+    /// assert_eq!("07/01~07/11の請求額は、1.62 USDです。", actual_header);
+    /// ```
+    ///
+    /// The cost is formatted with `decimals` decimals in the template of the `locale`.
+    /// The dates are formatted with `date_format` if given,
+    /// otherwise as `MM/DD~MM/DD` (with the years across a year boundary).
+    fn to_message_header(
+        &self,
+        decimals: usize,
+        locale: Locale,
+        date_format: Option<&str>,
+    ) -> String {
+        let period = match date_format {
+            Some(fmt) => self.date_range.format_range(fmt),
+            None => self.date_range.to_string(),
+        };
+        locale.header(&period, &self.cost.format_with_precision(decimals))
+    }
+}
+
+/// Severity of the notification, which is displayed as the color of the message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Severity {
+    #[default]
+    Normal,
+    /// Something needs attention (e.g. a service exceeds its budget).
+    Warning,
+    Critical,
+}
+impl Severity {
+    /// Default hex color of the Slack attachment.
+    pub fn color(&self) -> &'static str {
+        match self {
+            Severity::Normal => "#36a64f",
+            Severity::Warning => "#daa038",
+            Severity::Critical => "#d00000",
+        }
     }
 }
 
-impl TotalCost {
-    /// # Example
-    ///
-    /// ```
-    /// let sample_total_cost = TotalCost {
-    ///     date_range: ReportedDateRange {
-    ///         start_date: Local.ymd(2021, 7, 1),
-    ///         end_date: Local.ymd(2021, 7, 11),
-    ///     },
-    ///     cost: Cost {
-    ///         amount: 1.6234,
-    ///         unit: "USD".to_string(),
-    ///     },
-    /// };
-    /// let actual_header = sample_total_cost.to_message_header();
-    ///
-    /// assert_eq!("07/01~07/11の請求額は、1.62 USDです。", actual_header);
-    /// ```
-    fn to_message_header(&self) -> String {
-        format!("{}の請求額は、{}です。", self.date_range, self.cost)
+/// Cost notification message to send to Slack.
+#[derive(Default, Clone)]
+pub struct NotificationMessage {
+    /// Headline message to display the total cost
+    ///
+    /// # Example
+    /// `07/01~07/11の請求額は、1.62 USDです。`
+    pub header: String,
+    /// Body of the message to display costs for each service
+    ///
+    /// # Example
+    /// `・AWS CloudTrail: 1.23 USD\n・AWS Cost Explorer: 0.12 USD`
+    pub body: String,
+    /// Severity of the notification
+    pub severity: Severity,
+    /// Comparison with the previous period, which is displayed separately from the body
+    ///
+    /// # Example
+    /// `【前月同期比較】\n合計: 100.00 USD → 120.00 USD (+20.00 USD, +20.0%)`
+    pub comparison: Option<String>,
+    /// Static text displayed at the bottom of the message
+    ///
+    /// # Example
+    /// `Dashboard: https://example.com/cost`
+    pub footer: Option<String>,
+}
+impl NotificationMessage {
+    /// Build Slack notification message from parsed total cost and service costs.
+    ///
+    /// The service costs are displayed in descending order by amount,
+    /// skipping services which are displayed as zero (e.g. `0.00 USD`, `0.00 JPY`),
+    /// or whose costs are below `min_service_cost` of `config` if it is set.
+    /// Services listed in `always_show_services` of `config` are displayed
+    /// regardless of the amount.
+    /// Services which have budgets in `service_budgets` of `config` are displayed
+    /// with their consumption against the budgets,
+    /// and the message is raised to `Severity::Warning` if any of them exceeds its budget.
+    ///
+    /// Services whose costs exceed `owner_mention_threshold` of `config` are displayed
+    /// with the mentions of their owners in `service_owners`.
+    /// (e.g. `・AWS Lambda: 123.45 USD <@U012AB3CD>`)
+    ///
+    /// If `max_services` of `config` is set, only the top `max_services` services
+    /// (in each category if grouped) are listed and the rest are collapsed into a line
+    /// of their total cost. (e.g. `・その他 (3 services): 1.23 USD`)
+    ///
+    /// If `service_layout` of `config` is `ServiceLayout::Aligned`, the service names
+    /// are padded so that the colons line up and the costs are right-aligned in a code block.
+    /// (e.g. `・AWS Lambda: 1.50 USD` -> `AWS Lambda :  1.50 USD`)
+    /// The owner mentions follow the code block, in which they would not notify the owners.
+    ///
+    /// If `group_by_category` of `config` is true, the services are nested
+    /// under their categories with the subtotals.
+    /// (e.g. `【Compute】12.50 USD\n  ・AWS Lambda: 2.50 USD`)
+    ///
+    /// Only services matching `service_name_prefix`, `service_name_pattern`
+    /// and `service_allowlist` of `config` and not matching `service_blocklist` are displayed,
+    /// while the total cost still includes the excluded services.
+    ///
+    /// If `display_currency` of `config` is set, the costs are converted into it.
+    ///
+    /// If `show_percentage` of `config` is true, each service line shows its share
+    /// of the total cost except for the services with budgets.
+    /// (e.g. `・Amazon EC2: 31.41 USD (42.0%)`)
+    pub fn new<T: TimeZone>(
+        mut total_cost: TotalCost<T>,
+        service_costs: Vec<ServiceCost>,
+        config: &Config,
+    ) -> Self {
+        total_cost.cost = convert_cost(&total_cost.cost, config);
+        let service_costs: Vec<ServiceCost> = service_costs
+            .into_iter()
+            .filter(|x| {
+                is_reported_service(
+                    &x.service_name,
+                    config.service_name_prefix.as_deref(),
+                    config.service_name_pattern.as_ref(),
+                ) && is_listed_service(
+                    &x.service_name,
+                    &config.service_allowlist,
+                    &config.service_blocklist,
+                )
+            })
+            .map(|x| ServiceCost {
+                cost: convert_cost(&x.cost, config),
+                ..x
+            })
+            .collect();
+
+        let total_amount = total_cost.cost.amount.to_f64();
+        let owner_mention = |x: &ServiceCost| match config.service_owners.get(&x.service_name) {
+            Some(owner) if x.cost.amount > Amount::from(config.owner_mention_threshold) => {
+                format!(" <@{}>", owner)
+            }
+            _ => String::new(),
+        };
+        let to_service_line = |x: &ServiceCost| {
+            let line = match config.service_budgets.get(&x.service_name) {
+                Some(budget) if *budget > 0.0 => x.to_message_line_with_budget(
+                    *budget,
+                    config.escape_slack_text,
+                    config.cost_decimals,
+                    config.locale,
+                ),
+                _ if config.show_percentage => x.to_message_line_with_share(
+                    total_amount,
+                    config.escape_slack_text,
+                    config.cost_decimals,
+                    config.locale,
+                ),
+                _ => x.to_message_line(
+                    config.escape_slack_text,
+                    config.cost_decimals,
+                    config.locale,
+                ),
+            };
+            format!("{}{}", line, owner_mention(x))
+        };
+        // The label, cost and remark of the line in `ServiceLayout::Aligned`,
+        // whose label is escaped after the alignment so that the width is of the displayed one.
+        let to_service_row = |x: &ServiceCost| {
+            let (cost, remark) = match config.service_budgets.get(&x.service_name) {
+                Some(budget) if *budget > 0.0 => {
+                    x.budget_consumption(*budget, config.cost_decimals)
+                }
+                _ if config.show_percentage => (
+                    x.cost.format_with_precision(config.cost_decimals),
+                    x.share_remark(total_amount),
+                ),
+                _ => (
+                    x.cost.format_with_precision(config.cost_decimals),
+                    String::new(),
+                ),
+            };
+            (x.service_name.clone(), cost, remark)
+        };
+        // The lines of the services and, in `ServiceLayout::Aligned`,
+        // the owner mentions which are put after the code block to be notified.
+        let build_service_lines = |service_costs: &[ServiceCost]| -> (Vec<String>, Vec<String>) {
+            let ranked_services = rank_items(
+                service_costs,
+                |x| &x.cost,
+                |x| config.always_show_services.contains(&x.service_name),
+                config.min_service_cost,
+            );
+            let (listed, others) = ranked_services.split_at(
+                config
+                    .max_services
+                    .unwrap_or(usize::MAX)
+                    .min(ranked_services.len()),
+            );
+            let others = collapse_others(others, config);
+            match config.service_layout {
+                ServiceLayout::Bullet => {
+                    let mut lines: Vec<String> =
+                        listed.iter().map(|x| to_service_line(x)).collect();
+                    lines.extend(others.map(|(label, total)| {
+                        format!("{}{}: {}", config.locale.bullet(), label, total)
+                    }));
+                    (lines, vec![])
+                }
+                ServiceLayout::Aligned => {
+                    let mut rows: Vec<(String, String, String)> =
+                        listed.iter().map(|x| to_service_row(x)).collect();
+                    rows.extend(others.map(|(label, total)| (label, total, String::new())));
+                    let aligned_lines = align_rows(
+                        &rows
+                            .iter()
+                            .map(|(label, cost, _)| (label.clone(), cost.clone()))
+                            .collect::<Vec<(String, String)>>(),
+                    );
+                    let lines = aligned_lines
+                        .into_iter()
+                        .zip(rows.iter())
+                        .map(|(line, (_, _, remark))| {
+                            format!(
+                                "{}{}",
+                                format_label(&line, config.escape_slack_text),
+                                remark
+                            )
+                        })
+                        .collect();
+                    let mentions = listed
+                        .iter()
+                        .map(|x| owner_mention(x).trim().to_string())
+                        .filter(|x| !x.is_empty())
+                        .collect();
+                    (lines, mentions)
+                }
+            }
+        };
+
+        let (lines, mentions) = if config.group_by_category {
+            let mut lines = vec![];
+            let mut mentions = vec![];
+            for x in group_by_category(&service_costs, &config.service_categories) {
+                let (service_lines, service_mentions) = build_service_lines(&x.service_costs);
+                if service_lines.is_empty() {
+                    continue;
+                }
+                lines.push(format!("【{}】{}", x.category, x.subtotal));
+                lines.extend(service_lines.iter().map(|line| format!("  {}", line)));
+                mentions.extend(service_mentions);
+            }
+            (lines, mentions)
+        } else {
+            build_service_lines(&service_costs)
+        };
+
+        let exceeds_budget =
+            service_costs
+                .iter()
+                .any(|x| match config.service_budgets.get(&x.service_name) {
+                    Some(budget) => *budget > 0.0 && x.cost.amount > Amount::from(*budget),
+                    None => false,
+                });
+
+        let body = match config.service_layout {
+            ServiceLayout::Aligned if !lines.is_empty() => {
+                let code_block = format!("```\n{}\n```", lines.join("\n"));
+                match mentions.is_empty() {
+                    true => code_block,
+                    false => format!("{}\n{}", code_block, mentions.join(" ")),
+                }
+            }
+            _ => lines.join("\n"),
+        };
+
+        NotificationMessage {
+            header: total_cost.to_message_header(
+                config.cost_decimals,
+                config.locale,
+                config.date_format.as_deref(),
+            ),
+            body,
+            severity: match exceeds_budget {
+                true => Severity::Warning,
+                false => Severity::Normal,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Build the terse message sent instead of the full breakdown
+    /// when nothing notable happened since the previous period.
+    ///
+    /// # Example
+    /// `大きな変化はありません (合計 1.62 USD)`
+    pub fn unchanged<T: TimeZone>(total_cost: &TotalCost<T>, config: &Config) -> Self {
+        NotificationMessage {
+            header: format!(
+                "大きな変化はありません (合計 {})",
+                convert_cost(&total_cost.cost, config)
+            ),
+            ..Default::default()
+        }
+    }
+
+    /// Build the message notifying that retrieving the costs failed
+    /// with the detail of the error `msg`.
+    ///
+    /// # Example
+    /// `コスト取得に失敗しました` (header), `Failed to resolve AWS credentials: ...` (body)
+    pub fn error(msg: &str) -> Self {
+        NotificationMessage {
+            header: "コスト取得に失敗しました".to_string(),
+            body: msg.to_string(),
+            severity: Severity::Critical,
+            ..Default::default()
+        }
+    }
+
+    /// Append the projected total at the end of the month to the header,
+    /// which is the sum of the `actual` cost so far and the `forecast` of the rest.
+    ///
+    /// It is converted into `display_currency` of `config` in the same way as the actual costs.
+    ///
+    /// # Example
+    /// `07/01~07/23の請求額は、1.62 USDです。 (月末予測 2.34 USD)`
+    pub fn with_forecast(
+        mut self,
+        actual: &Cost,
+        forecast: &ForecastCost,
+        config: &Config,
+    ) -> Self {
+        let projected_total = projected_total(actual, Some(forecast));
+        self.header = format!(
+            "{} ({})",
+            self.header,
+            config
+                .locale
+                .forecast(&convert_cost(&projected_total, config).to_string())
+        );
+        self
+    }
+
+    /// Replace the header with the progress of the month, which shows
+    /// the `actual` cost so far, the projected total at the end of the month
+    /// and the `budget` side by side.
+    ///
+    /// The projected total is omitted without `forecast`, and so is the budget without `budget`.
+    /// If `date_range` spans multiple months (e.g. from the beginning of the fiscal year),
+    /// the actual cost is labeled with the range instead of the month.
+    /// The costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `7月 MTD 620.00 USD → 月末予測 1240.00 USD (予算 1500.00 USD)`
+    pub fn with_progress_header<T: TimeZone>(
+        mut self,
+        date_range: &ReportedDateRange<T>,
+        actual: &Cost,
+        forecast: Option<&ForecastCost>,
+        budget: Option<f64>,
+        config: &Config,
+    ) -> Self {
+        let last_day = date_range.end_date.clone() - Duration::days(1);
+        let label = match date_range.start_date.month() == last_day.month()
+            && date_range.start_date.year() == last_day.year()
+        {
+            true => format!("{}月 MTD", date_range.start_date.month()),
+            false => format!("{} 累計", date_range),
+        };
+        let mut header = format!("{} {}", label, convert_cost(actual, config));
+        if let Some(forecast) = forecast {
+            let projected_total = convert_cost(&projected_total(actual, Some(forecast)), config);
+            header = format!(
+                "{} → {}",
+                header,
+                config.locale.forecast(&projected_total.to_string())
+            );
+        }
+        if let Some(budget) = budget {
+            let budget = Cost {
+                amount: Amount::from(budget),
+                unit: actual.unit.clone(),
+            };
+            header = format!("{} (予算 {})", header, convert_cost(&budget, config));
+        }
+        self.header = header;
+        self
+    }
+
+    /// Escalate the message to `Severity::Critical` with the warning that
+    /// the `projected_total` at the end of the month exceeds `budget`.
+    ///
+    /// The costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `<!channel>\n...\n\n⚠️月末予測 120.00 USD が予算 100.00 USD を超過する見込みです`
+    pub fn with_budget_alert(
+        mut self,
+        projected_total: &Cost,
+        budget: f64,
+        mention: Option<&str>,
+        config: &Config,
+    ) -> Self {
+        let budget = Cost {
+            amount: Amount::from(budget),
+            unit: projected_total.unit.clone(),
+        };
+        self.append_section(&config.locale.budget_alert(
+            &convert_cost(projected_total, config).to_string(),
+            &convert_cost(&budget, config).to_string(),
+        ));
+        self.escalate(Severity::Critical, mention)
+    }
+
+    /// Append the date on which the total cost is projected to exceed the budget
+    /// to the body, or that it will stay within the budget if `overage_date` is None.
+    ///
+    /// # Example
+    /// `予算超過予測日: 07/28`
+    pub fn with_overage_date<T: TimeZone>(mut self, overage_date: Option<&Date<T>>) -> Self {
+        self.append_section(&match overage_date {
+            Some(date) => format!("予算超過予測日: {:02}/{:02}", date.month(), date.day()),
+            None => "今月は予算内".to_string(),
+        });
+        self
+    }
+
+    /// Append the net cost, which is the `actual` cost minus `credit`
+    /// (not less than zero), to the header.
+    ///
+    /// It is converted into `display_currency` of `config` in the same way as the actual costs.
+    ///
+    /// # Example
+    /// `07/01~07/23の請求額は、120.00 USDです。 (実質負担: 20.00 USD)`
+    pub fn with_net_cost(mut self, actual: &Cost, credit: f64, config: &Config) -> Self {
+        let net_cost = Cost {
+            amount: (actual.amount - Amount::from(credit)).max(Amount::ZERO),
+            unit: actual.unit.clone(),
+        };
+        self.header = format!(
+            "{} (実質負担: {})",
+            self.header,
+            convert_cost(&net_cost, config)
+        );
+        self
+    }
+
+    /// Append the change of the total cost since the last notification to the header.
+    ///
+    /// Unlike the comparison with the previous period, it is the change from
+    /// the `last_notified` total, which may have been reported at any time.
+    /// It is converted into `display_currency` of `config` in the same way as the actual costs.
+    ///
+    /// # Example
+    /// `07/01~07/23の請求額は、115.00 USDです。 (前回通知から +15.00 USD)`
+    pub fn with_change_since_last_notification(
+        mut self,
+        last_notified: &Cost,
+        actual: &Cost,
+        config: &Config,
+    ) -> Self {
+        let difference = Cost {
+            amount: actual.amount - last_notified.amount,
+            unit: actual.unit.clone(),
+        };
+        let sign = if difference.amount.is_negative() {
+            ""
+        } else {
+            "+"
+        };
+        self.header = format!(
+            "{} (前回通知から {}{})",
+            self.header,
+            sign,
+            convert_cost(&difference, config)
+        );
+        self
+    }
+
+    /// Append the cost per unit of the business metric to the header,
+    /// which is the `actual` cost divided by `metric_value`.
+    ///
+    /// Nothing is appended if `metric_value` is not given or zero.
+    /// It is converted into `display_currency` of `config` in the same way as the actual costs.
+    ///
+    /// # Example
+    /// `07/01~07/23の請求額は、120.00 USDです。 (1ユーザーあたり 0.12 USD)`
+    pub fn with_unit_cost(
+        mut self,
+        actual: &Cost,
+        metric_name: &str,
+        metric_value: Option<f64>,
+        config: &Config,
+    ) -> Self {
+        let metric_value = match metric_value {
+            Some(value) if value != 0.0 => value,
+            _ => return self,
+        };
+        let unit_cost = Cost {
+            amount: Amount::from(actual.amount.to_f64() / metric_value),
+            unit: actual.unit.clone(),
+        };
+        self.header = format!(
+            "{} (1{}あたり {})",
+            self.header,
+            metric_name,
+            convert_cost(&unit_cost, config)
+        );
+        self
+    }
+
+    /// Raise the severity to `severity` (never lowers it)
+    /// and put `mention` (e.g. `<!channel>`) at the top of the body.
+    pub fn escalate(mut self, severity: Severity, mention: Option<&str>) -> Self {
+        self.severity = self.severity.max(severity);
+        if let Some(mention) = mention {
+            self.body = match self.body.is_empty() {
+                true => mention.to_string(),
+                false => format!("{}\n{}", mention, self.body),
+            };
+        }
+        self
+    }
+
+    /// Replace the units of the costs in the header, body and comparison
+    /// with their labels in `labels` (e.g. `USD` -> `ドル`, `JPY` -> `円`).
+    pub fn with_unit_labels(mut self, labels: &HashMap<String, String>) -> Self {
+        if labels.is_empty() {
+            return self;
+        }
+        self.header = localize_units(&self.header, labels);
+        self.body = localize_units(&self.body, labels);
+        self.comparison = self.comparison.map(|x| localize_units(&x, labels));
+        self
+    }
+
+    /// Escalate the message to the severity of the band of the `total` cost
+    /// between `cost_warn_threshold` and `cost_crit_threshold` of `config`,
+    /// which are compared with the cost in `display_currency`.
+    pub fn with_spend_severity(self, total: &Cost, config: &Config) -> Self {
+        let severity = severity_of_spend(
+            convert_cost(total, config).amount,
+            config.cost_warn_threshold,
+            config.cost_crit_threshold,
+        );
+        self.escalate(severity, None)
+    }
+
+    /// Escalate the message to `Severity::Critical` if the total cost has increased
+    /// by more than `threshold_percent` since the previous period,
+    /// regardless of the amount of the increase.
+    ///
+    /// # Example
+    /// `<!channel>\n...\n\n⚠️前月同期比 +80.0%`
+    pub fn with_increase_alert(
+        mut self,
+        previous_total: &Cost,
+        current_total: &Cost,
+        threshold_percent: f64,
+        mention: Option<&str>,
+    ) -> Self {
+        match percentage_change(previous_total.amount, current_total.amount) {
+            Some(percentage) if percentage > threshold_percent => {
+                self.append_section(&format!("⚠️前月同期比 +{:.1}%", percentage));
+                self.escalate(Severity::Critical, mention)
+            }
+            _ => self,
+        }
+    }
+
+    /// Append the change rate of the total cost since the same period
+    /// of the previous month to the header.
+    ///
+    /// It is displayed as `N/A` if the cost of the previous period is zero.
+    ///
+    /// # Example
+    /// `07/01~07/23の請求額は、112.30 USDです。 (前月比 +12.3%)`
+    pub fn with_month_over_month(mut self, previous_total: &Cost, current_total: &Cost) -> Self {
+        let change = match percentage_change(previous_total.amount, current_total.amount) {
+            Some(percentage) => format!("{:+.1}%", percentage),
+            None => "N/A".to_string(),
+        };
+        self.header = format!("{} (前月比 {})", self.header, change);
+        self
+    }
+
+    /// Append a warning if the totals of AmortizedCost and UnblendedCost differ
+    /// by more than `tolerance_percent` of UnblendedCost,
+    /// which indicates the significant effect of the amortization of RI/SP upfront fees.
+    /// The costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `⚠️AmortizedCost と UnblendedCost が乖離しています: 120.00 USD / 100.00 USD (+20.0%)`
+    pub fn with_metric_divergence(
+        mut self,
+        amortized: &Cost,
+        unblended: &Cost,
+        tolerance_percent: f64,
+        config: &Config,
+    ) -> Self {
+        let difference = match percentage_change(unblended.amount, amortized.amount) {
+            Some(percentage) if percentage.abs() > tolerance_percent => {
+                format!(" ({:+.1}%)", percentage)
+            }
+            Some(_) => return self,
+            None if amortized.amount.abs() >= NEGLIGIBLE_AMOUNT => String::new(),
+            None => return self,
+        };
+        self.append_section(&format!(
+            "⚠️AmortizedCost と UnblendedCost が乖離しています: {} / {}{}",
+            convert_cost(amortized, config),
+            convert_cost(unblended, config),
+            difference
+        ));
+        self
+    }
+
+    /// Append the services which were charged in the previous period
+    /// but are not charged in the current period, which may indicate that
+    /// something has stopped unexpectedly (e.g. a data pipeline).
+    ///
+    /// Nothing is appended if there are no such services.
+    /// If `escape` is true, the service names are escaped for Slack formatting.
+    ///
+    /// # Example
+    /// `⚠️0になったサービス: AWS Glue, Amazon Kinesis`
+    pub fn with_dropped_services(
+        mut self,
+        changes: &[ServiceCostChange],
+        escape: bool,
+        locale: Locale,
+    ) -> Self {
+        let dropped_services: Vec<String> = changes
+            .iter()
+            .filter(|x| x.is_dropped_to_zero())
+            .map(|x| format_label(&x.service_name, escape))
+            .collect();
+        if !dropped_services.is_empty() {
+            self.append_section(&locale.dropped_services(&dropped_services.join(", ")));
+        }
+        self
+    }
+
+    /// Set the comparison of the total and service costs with the previous period,
+    /// which is displayed separately from the body.
+    ///
+    /// The services are displayed in descending order by the size of the change,
+    /// skipping services which are not charged in both periods.
+    /// Services charged only in the previous period are also skipped
+    /// unless `include_dropped_services` of `config` is true.
+    /// If `group_by_trend` of `config` is true, they are grouped under
+    /// `【新規】`, `【増加】`, `【減少】` and `【横ばい】`, where changes within
+    /// `unchanged_tolerance_percent` or `unchanged_tolerance_amount` are regarded as `横ばい`.
+    /// The costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `【前月同期比較】\n合計: 100.00 USD → 120.00 USD (+20.00 USD, +20.0%)\n・AWS Lambda: 1.00 USD → 2.00 USD (+1.00 USD, +100.0%)`
+    pub fn with_comparison(
+        mut self,
+        previous_total: &Cost,
+        current_total: &Cost,
+        changes: &[ServiceCostChange],
+        config: &Config,
+    ) -> Self {
+        let mut changes: Vec<&ServiceCostChange> = changes
+            .iter()
+            .filter(|x| !x.is_negligible())
+            .filter(|x| config.include_dropped_services || !x.is_dropped_to_zero())
+            .collect();
+        changes.sort_by_key(|x| std::cmp::Reverse(x.difference().abs()));
+
+        let mut lines = vec![
+            config.locale.comparison_title().to_string(),
+            config.locale.total_change(&format_change(
+                &convert_cost(previous_total, config),
+                &convert_cost(current_total, config),
+            )),
+        ];
+        let to_line = |x: &&ServiceCostChange| {
+            format!(
+                "{}{}: {}",
+                config.locale.bullet(),
+                format_label(&x.service_name, config.escape_slack_text),
+                format_change(
+                    &convert_cost(&x.previous, config),
+                    &convert_cost(&x.current, config)
+                )
+            )
+        };
+        if config.group_by_trend {
+            let tolerance = ChangeTolerance::from_config(config);
+            for trend in Trend::ALL.iter() {
+                let trend_lines: Vec<String> = changes
+                    .iter()
+                    .filter(|x| x.trend(&tolerance) == *trend)
+                    .map(to_line)
+                    .collect();
+                if !trend_lines.is_empty() {
+                    lines.push(format!("【{}】", trend.label()));
+                    lines.extend(trend_lines);
+                }
+            }
+        } else {
+            lines.extend(changes.iter().map(to_line));
+        }
+
+        self.comparison = Some(lines.join("\n"));
+        self
+    }
+
+    /// Append the ranking of the costs for each value of the tag `tag_key` to the body.
+    ///
+    /// The top `top_n` tag values are displayed in descending order by amount
+    /// in the same way as the service costs.
+    /// If `escape` is true, the tag key and values are escaped for Slack formatting.
+    ///
+    /// # Example
+    /// `【Project別 上位3件】\n・frontend: 12.34 USD\n・backend: 5.67 USD`
+    pub fn with_tag_ranking(
+        mut self,
+        tag_key: &str,
+        tag_costs: Vec<TagCost>,
+        top_n: usize,
+        escape: bool,
+    ) -> Self {
+        let lines = build_ranking_lines(
+            &tag_costs,
+            |x| &x.cost,
+            |x| x.to_message_line(escape),
+            |_| false,
+            None,
+            Some(top_n),
+        );
+
+        let ranking = format!(
+            "【{}別 上位{}件】\n{}",
+            format_label(tag_key, escape),
+            top_n,
+            lines.join("\n")
+        );
+        self.append_section(&ranking);
+        self
+    }
+
+    /// Append the costs for all the values of the tag `tag_key` to the body.
+    ///
+    /// The tag values are displayed in descending order by amount
+    /// in the same way as the service costs.
+    /// If `escape` is true, the tag key and values are escaped for Slack formatting.
+    ///
+    /// # Example
+    /// `【Project別】\n・frontend: 12.34 USD\n・(untagged): 5.67 USD`
+    pub fn with_tag_costs(mut self, tag_key: &str, tag_costs: Vec<TagCost>, escape: bool) -> Self {
+        let lines = build_ranking_lines(
+            &tag_costs,
+            |x| &x.cost,
+            |x| x.to_message_line(escape),
+            |_| false,
+            None,
+            None,
+        );
+
+        let section = format!(
+            "【{}別】\n{}",
+            format_label(tag_key, escape),
+            lines.join("\n")
+        );
+        self.append_section(&section);
+        self
+    }
+
+    /// Append the total cost of data transfer to the body.
+    ///
+    /// The cost is the sum of usage types which contain any of `data_transfer_patterns`
+    /// of `config` (e.g. `DataTransfer`, `-AWS-Out-Bytes`).
+    /// The transferred amount is displayed next to the cost if it has been requested,
+    /// and each value is formatted by `metric_decimals` of `config`,
+    /// in which the cost is of `cost_metric` (e.g. `UnblendedCost`).
+    ///
+    /// # Example
+    /// `データ転送料: 12.34 USD / 120.5 GB`
+    pub fn with_data_transfer_cost(
+        mut self,
+        usage_type_costs: Vec<UsageTypeCost>,
+        config: &Config,
+    ) -> Self {
+        let patterns = &config.data_transfer_patterns;
+        let data_transfer_cost = sum_matching_usage_types(&usage_type_costs, patterns);
+        let data_transfer_quantity = sum_matching_usage_quantities(&usage_type_costs, patterns);
+
+        let mut values = vec![(config.cost_metric.as_str(), &data_transfer_cost)];
+        if let Some(quantity) = &data_transfer_quantity {
+            values.push((USAGE_METRIC, quantity));
+        }
+        self.append_section(&format!(
+            "データ転送料: {}",
+            format_metrics(&values, &config.metric_decimals)
+        ));
+        self
+    }
+
+    /// Append the usage type whose cost has increased the most
+    /// since the previous period to the body.
+    ///
+    /// Nothing is appended if no usage type has increased.
+    /// The increase is converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `最大増加の使用タイプ: APN1-DataTransfer-Out-Bytes +30.00 USD`
+    pub fn with_top_increasing_usage_type(
+        mut self,
+        previous: &[UsageTypeCost],
+        current: &[UsageTypeCost],
+        config: &Config,
+    ) -> Self {
+        if let Some(x) = top_increasing_usage_type(previous, current) {
+            self.append_section(&format!(
+                "最大増加の使用タイプ: {} +{}",
+                x.usage_type,
+                convert_cost(&x.cost, config)
+            ));
+        }
+        self
+    }
+
+    /// Append the top `top_n` usage types to the body in descending order by cost.
+    ///
+    /// If `show_usage_quantity` is true, the usage amounts are displayed next to the costs,
+    /// formatted by `metric_decimals` of `config`.
+    /// The costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `【使用タイプ別 上位3件】\n・APN1-DataTransfer-Out-Bytes: 45.00 USD (512.0 GB)`
+    pub fn with_top_usage_types(
+        mut self,
+        usage_type_costs: Vec<UsageTypeCost>,
+        top_n: usize,
+        show_usage_quantity: bool,
+        config: &Config,
+    ) -> Self {
+        let usage_type_costs: Vec<UsageTypeCost> = usage_type_costs
+            .into_iter()
+            .map(|x| UsageTypeCost {
+                cost: convert_cost(&x.cost, config),
+                ..x
+            })
+            .collect();
+        let lines = build_ranking_lines(
+            &usage_type_costs,
+            |x| &x.cost,
+            |x| {
+                let line = to_ranking_line(&x.usage_type, &x.cost);
+                match (show_usage_quantity, &x.usage_quantity) {
+                    (true, Some(quantity)) => format!(
+                        "{} ({})",
+                        line,
+                        format_metric(USAGE_METRIC, quantity, &config.metric_decimals)
+                    ),
+                    _ => line,
+                }
+            },
+            |_| false,
+            None,
+            Some(top_n),
+        );
+
+        self.append_section(&format!(
+            "【使用タイプ別 上位{}件】\n{}",
+            top_n,
+            lines.join("\n")
+        ));
+        self
+    }
+
+    /// Set the static `footer` (e.g. a link to the cost dashboard)
+    /// displayed at the bottom of the message.
+    pub fn with_footer(mut self, footer: Option<&str>) -> Self {
+        self.footer = footer.map(|x| x.to_string());
+        self
+    }
+
+    /// Append the costs for each region to the body
+    /// in descending order by amount.
+    ///
+    /// If `group_global` is true, the pseudo-regions of global services
+    /// (`NoRegion`, `global`) are displayed together as `グローバル`.
+    ///
+    /// # Example
+    /// `【リージョン別】\n・ap-northeast-1: 12.34 USD\n・グローバル: 5.67 USD`
+    pub fn with_region_costs(mut self, region_costs: Vec<RegionCost>, group_global: bool) -> Self {
+        let region_costs = match group_global {
+            true => group_global_regions(region_costs),
+            false => region_costs,
+        };
+        let lines = build_ranking_lines(
+            &region_costs,
+            |x| &x.cost,
+            |x| to_ranking_line(&x.region, &x.cost),
+            |_| false,
+            None,
+            None,
+        );
+
+        self.append_section(&format!("【リージョン別】\n{}", lines.join("\n")));
+        self
+    }
+
+    /// Append the costs for each linked account to the body
+    /// in descending order by amount.
+    ///
+    /// The accounts are labeled with their names if available, otherwise with their IDs.
+    ///
+    /// # Example
+    /// `【アカウント別】\n・production (123456789012): 12.34 USD\n・210987654321: 5.67 USD`
+    pub fn with_account_costs(mut self, account_costs: Vec<AccountCost>) -> Self {
+        let lines = build_ranking_lines(
+            &account_costs,
+            |x| &x.cost,
+            |x| match &x.account_name {
+                Some(name) => to_ranking_line(&format!("{} ({})", name, x.account_id), &x.cost),
+                None => to_ranking_line(&x.account_id, &x.cost),
+            },
+            |_| false,
+            None,
+            None,
+        );
+
+        self.append_section(&format!("【アカウント別】\n{}", lines.join("\n")));
+        self
+    }
+
+    /// Append the utilization of Savings Plans and Reserved Instances to the body.
+    ///
+    /// Commitments without utilization (i.e. not purchased) are omitted.
+    /// If any utilization is below `low_threshold_percent`, it is marked
+    /// and the message is escalated to `Severity::Warning`,
+    /// since the unused commitments are charged anyway.
+    ///
+    /// # Example
+    /// `SP稼働率: 95%\n⚠️RI稼働率: 62% (80%未満)`
+    pub fn with_utilization(
+        mut self,
+        savings_plans: Option<&Utilization>,
+        reservation: Option<&Utilization>,
+        low_threshold_percent: f64,
+    ) -> Self {
+        let utilizations: Vec<(&str, &Utilization)> =
+            vec![("SP", savings_plans), ("RI", reservation)]
+                .into_iter()
+                .filter_map(|(label, utilization)| utilization.map(|x| (label, x)))
+                .collect();
+        if utilizations.is_empty() {
+            return self;
+        }
+
+        let is_low = |x: &Utilization| x.percentage < low_threshold_percent;
+        let lines: Vec<String> = utilizations
+            .iter()
+            .map(|(label, utilization)| match is_low(utilization) {
+                true => format!(
+                    "⚠️{}稼働率: {:.0}% ({:.0}%未満)",
+                    label, utilization.percentage, low_threshold_percent
+                ),
+                false => format!("{}稼働率: {:.0}%", label, utilization.percentage),
+            })
+            .collect();
+        self.append_section(&lines.join("\n"));
+
+        match utilizations.iter().any(|(_, x)| is_low(x)) {
+            true => self.escalate(Severity::Warning, None),
+            false => self,
+        }
+    }
+
+    /// Append the estimated monthly savings by the rightsizing recommendations to the body.
+    ///
+    /// Nothing is appended if the savings are not available.
+    ///
+    /// # Example
+    /// `推定節約可能額: 42.50 USD/月`
+    pub fn with_savings_opportunity(
+        mut self,
+        savings: Option<&SavingsOpportunity>,
+        config: &Config,
+    ) -> Self {
+        if let Some(savings) = savings {
+            self.append_section(&format!(
+                "推定節約可能額: {}/月",
+                convert_cost(&savings.cost, config).format_with_precision(config.cost_decimals)
+            ));
+        }
+        self
+    }
+
+    /// Append the total costs of each day and their average to the body.
+    ///
+    /// If `today` is given, the days from it on are excluded from both the displayed costs
+    /// and the average since their costs are not fixed yet.
+    /// The day before `today` is kept because the end date of Cost Explorer is exclusive.
+    ///
+    /// # Example
+    /// `【日別】(本日分を除く)\n・07/01: 1.23 USD\n・07/02: 4.56 USD\n平均: 2.90 USD`
+    pub fn with_daily_costs<T: TimeZone>(
+        mut self,
+        mut daily_costs: Vec<DailyCost<T>>,
+        today: Option<&Date<T>>,
+    ) -> Self {
+        if let Some(today) = today {
+            daily_costs.retain(|x| x.date < *today);
+        }
+
+        let mut lines = vec![match today {
+            Some(_) => "【日別】(本日分を除く)".to_string(),
+            None => "【日別】".to_string(),
+        }];
+        lines.extend(daily_costs.iter().map(|x| x.to_message_line()));
+        if let Some(first) = daily_costs.first() {
+            let average = Cost {
+                amount: Amount::from(
+                    daily_costs
+                        .iter()
+                        .map(|x| x.cost.amount)
+                        .sum::<Amount>()
+                        .to_f64()
+                        / daily_costs.len() as f64,
+                ),
+                unit: first.cost.unit.clone(),
+            };
+            lines.push(format!("平均: {}", average));
+        }
+
+        self.append_section(&lines.join("\n"));
+        self
+    }
+
+    /// Append the change of the cost of the latest day from the `baseline` day to the body.
+    ///
+    /// The costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `前週同曜日比 (07/15 → 07/22): 2.00 USD → 3.00 USD (+1.00 USD, +50.0%)`
+    pub fn with_daily_change<T: TimeZone>(
+        mut self,
+        baseline: DailyBaseline,
+        baseline_total: &TotalCost<T>,
+        latest_total: &TotalCost<T>,
+        config: &Config,
+    ) -> Self {
+        let format_date = |x: &TotalCost<T>| {
+            format!(
+                "{:02}/{:02}",
+                x.date_range.start_date.month(),
+                x.date_range.start_date.day()
+            )
+        };
+        self.append_section(&format!(
+            "{} ({} → {}): {}",
+            baseline.label(),
+            format_date(baseline_total),
+            format_date(latest_total),
+            format_change(
+                &convert_cost(&baseline_total.cost, config),
+                &convert_cost(&latest_total.cost, config)
+            )
+        ));
+        self
+    }
+
+    /// Append the table of the total costs of recent months to the body,
+    /// with the change from the previous month.
+    ///
+    /// Leading months without costs (e.g. before the account was created) are skipped,
+    /// so the table may have fewer months than requested.
+    /// The month in progress is displayed with its end date,
+    /// and the costs are converted into `display_currency` of `config`.
+    ///
+    /// # Example
+    /// `【直近3か月の推移】\n```\n月 | 合計 | 前月比\n...\n2021/06 | 12.00 USD | +20.0%\n2021/07 (~07/23) | 5.00 USD | -58.3%\n```` (padded to align the columns)
+    pub fn with_monthly_table<T>(
+        mut self,
+        monthly_costs: Vec<MonthlyCost<T>>,
+        config: &Config,
+    ) -> Self
+    where
+        T: TimeZone,
+        <T as TimeZone>::Offset: fmt::Display,
+    {
+        let monthly_costs: Vec<MonthlyCost<T>> = monthly_costs
+            .into_iter()
+            .skip_while(|x| x.cost.amount.abs() <= NEGLIGIBLE_AMOUNT)
+            .collect();
+        if monthly_costs.is_empty() {
+            return self;
+        }
+
+        let rows: Vec<Vec<String>> = monthly_costs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let month = match x.end_date.day() {
+                    1 => format!("{}", x.start_date.format("%Y/%m")),
+                    _ => format!(
+                        "{} (~{})",
+                        x.start_date.format("%Y/%m"),
+                        x.end_date.format("%m/%d")
+                    ),
+                };
+                let change = match i.checked_sub(1).map(|j| &monthly_costs[j]) {
+                    Some(previous) => {
+                        match percentage_change(previous.cost.amount, x.cost.amount) {
+                            Some(percentage) if percentage < 0.0 => format!("{:.1}%", percentage),
+                            Some(percentage) => format!("+{:.1}%", percentage),
+                            None => "-".to_string(),
+                        }
+                    }
+                    None => "".to_string(),
+                };
+                vec![month, convert_cost(&x.cost, config).to_string(), change]
+            })
+            .collect();
+
+        let table = render_table(
+            &[
+                ("月", Align::Left),
+                ("合計", Align::Right),
+                ("前月比", Align::Right),
+            ],
+            &rows,
+        );
+        self.append_section(&format!("【直近{}か月の推移】\n{}", rows.len(), table));
+        self
+    }
+
+    /// Append the time when the report was generated to the body.
+    ///
+    /// # Example
+    /// `生成時刻: 2021-07-23 09:00 JST`
+    pub fn with_generated_at<T>(mut self, generated_at: &DateTime<T>) -> Self
+    where
+        T: TimeZone,
+        <T as TimeZone>::Offset: fmt::Display,
+    {
+        self.append_section(&format!(
+            "生成時刻: {}",
+            generated_at.format("%Y-%m-%d %H:%M %Z")
+        ));
+        self
+    }
+
+    /// Append `section` to the body separated by a blank line.
+    fn append_section(&mut self, section: &str) {
+        self.body = if self.body.is_empty() {
+            section.to_string()
+        } else {
+            format!("{}\n\n{}", self.body, section)
+        };
+    }
+
+    /// Split the message into the total cost message
+    /// and the message of the cost breakdown.
+    ///
+    /// The former has only the header and the latter has the body, the comparison
+    /// and the footer.
+    pub fn split(self) -> (NotificationMessage, NotificationMessage) {
+        let total_message = NotificationMessage {
+            header: self.header,
+            body: String::new(),
+            severity: self.severity,
+            comparison: None,
+            footer: None,
+        };
+        let breakdown_message = NotificationMessage {
+            header: "内訳".to_string(),
+            body: self.body,
+            severity: self.severity,
+            comparison: self.comparison,
+            footer: self.footer,
+        };
+        (total_message, breakdown_message)
+    }
+}
+
+#[cfg(test)]
+mod test_cost_representation {
+    use crate::cost_explorer::cost_response_parser::Cost;
+
+    #[test]
+    fn display_correctly() {
+        let input_cost = Cost {
+            amount: 132.2345.into(),
+            unit: "USD".to_string(),
+        };
+        assert_eq!("132.23 USD", format!("{}", input_cost));
+    }
+
+    #[test]
+    fn display_jpy_without_decimals_correctly() {
+        let jpy = |amount: f64| Cost {
+            amount: amount.into(),
+            unit: "JPY".to_string(),
+        };
+
+        assert_eq!("¥0", format!("{}", jpy(0.2)));
+        assert_eq!("¥980", format!("{}", jpy(980.0)));
+        assert_eq!("¥12,346", format!("{}", jpy(12345.6)));
+        assert_eq!("¥1,234,567", format!("{}", jpy(1234567.0)));
+        assert_eq!("-¥5,000", format!("{}", jpy(-5000.0)));
+        assert!(!format!("{}", jpy(1234567.0)).contains('.'));
+    }
+
+    #[test]
+    fn format_with_designated_precision() {
+        let usd = Cost {
+            amount: 1.2345.into(),
+            unit: "USD".to_string(),
+        };
+        let jpy = Cost {
+            amount: 12345.6.into(),
+            unit: "JPY".to_string(),
+        };
+
+        assert_eq!("1 USD", usd.format_with_precision(0));
+        assert_eq!("1.23 USD", usd.format_with_precision(2));
+        assert_eq!("1.2345 USD", usd.format_with_precision(4));
+        assert_eq!("¥12,346", jpy.format_with_precision(4));
+    }
+}
+
+#[cfg(test)]
+mod test_date_range_representation {
+    use crate::cost_explorer::cost_response_parser::ReportedDateRange;
+    use chrono::{Local, TimeZone};
+    use chrono_tz::America::Los_Angeles;
+
+    #[test]
+    fn test_display_correctly() {
+        let sample_date_range = ReportedDateRange {
+            start_date: Local.ymd(2021, 7, 1),
+            end_date: Local.ymd(2021, 7, 23),
+        };
+        assert_eq!("07/01~07/23", format!("{}", sample_date_range))
+    }
+
+    #[test]
+    fn display_dates_in_non_local_timezone_correctly() {
+        let sample_date_range = ReportedDateRange {
+            start_date: Los_Angeles.ymd(2021, 7, 1),
+            end_date: Los_Angeles.ymd(2021, 7, 31),
+        };
+        assert_eq!("07/01~07/31", format!("{}", sample_date_range))
+    }
+
+    #[test]
+    fn display_years_across_year_boundary() {
+        let sample_date_range = ReportedDateRange {
+            start_date: Local.ymd(2021, 12, 1),
+            end_date: Local.ymd(2022, 1, 1),
+        };
+        assert_eq!("2021/12/01~2022/01/01", format!("{}", sample_date_range))
+    }
+
+    #[test]
+    fn format_date_range_with_custom_format() {
+        let sample_date_range = ReportedDateRange {
+            start_date: Local.ymd(2021, 12, 1),
+            end_date: Local.ymd(2022, 1, 1),
+        };
+        assert_eq!(
+            "2021-12-01~2022-01-01",
+            sample_date_range.format_range("%Y-%m-%d")
+        );
+        assert_eq!("01.12~01.01", sample_date_range.format_range("%d.%m"));
+        assert_eq!("12/01~01/01", sample_date_range.format_range("%m/%d"));
+    }
+}
+#[cfg(test)]
+mod test_build_message {
+    use super::*;
+    use crate::config::Config;
+    use crate::cost_explorer::cost_response_parser::{Cost, ReportedDateRange};
+    use crate::cost_explorer::CostMetric;
+    use chrono::{Local, TimeZone, Utc};
+    use chrono_tz::Asia::Tokyo;
+
+    #[test]
+    fn convert_total_cost_into_message_header_correctly() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 1.6234.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let expected_header = "07/01~07/11の請求額は、1.62 USDです。";
+        let actual_header =
+            sample_total_cost.to_message_header(DEFAULT_COST_DECIMALS, Locale::Ja, None);
+
+        assert_eq!(expected_header, actual_header);
+        assert_eq!(
+            "Total cost for 07/01~07/11 is 1.62 USD.",
+            sample_total_cost.to_message_header(DEFAULT_COST_DECIMALS, Locale::En, None)
+        );
+        assert_eq!(
+            "2021-07-01~2021-07-11の請求額は、1.62 USDです。",
+            sample_total_cost.to_message_header(
+                DEFAULT_COST_DECIMALS,
+                Locale::Ja,
+                Some("%Y-%m-%d")
+            )
+        );
+    }
+    #[test]
+    fn convert_service_cost_into_message_line_correctly() {
+        let sample_service_cost = ServiceCost {
+            service_name: "AWS CloudTrail".to_string(),
+            cost: Cost {
+                amount: 0.0123.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let expected_line = "・AWS CloudTrail: 0.01 USD";
+        let actual_line =
+            sample_service_cost.to_message_line(true, DEFAULT_COST_DECIMALS, Locale::Ja);
+
+        assert_eq!(expected_line, actual_line);
+        assert_eq!(
+            "- AWS CloudTrail: 0.01 USD",
+            sample_service_cost.to_message_line(true, DEFAULT_COST_DECIMALS, Locale::En)
+        );
+    }
+
+    #[test]
+    fn escape_service_names_for_slack_correctly() {
+        let sample_service_cost = |service_name: &str| ServiceCost {
+            service_name: service_name.to_string(),
+            cost: Cost {
+                amount: 1.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        assert_eq!(
+            "・R&amp;D Tools: 1.00 USD",
+            sample_service_cost("R&D Tools").to_message_line(
+                true,
+                DEFAULT_COST_DECIMALS,
+                Locale::Ja
+            )
+        );
+        assert_eq!(
+            "・&lt;unknown: 1.00 USD",
+            sample_service_cost("<unknown").to_message_line(
+                true,
+                DEFAULT_COST_DECIMALS,
+                Locale::Ja
+            )
+        );
+        assert_eq!(
+            "・Service -&gt; Renamed: 1.00/2.00 USD (50%)",
+            sample_service_cost("Service -> Renamed").to_message_line_with_budget(
+                2.0,
+                true,
+                DEFAULT_COST_DECIMALS,
+                Locale::Ja
+            )
+        );
+        assert_eq!(
+            "・R&D Tools: 1.00 USD",
+            sample_service_cost("R&D Tools").to_message_line(
+                false,
+                DEFAULT_COST_DECIMALS,
+                Locale::Ja
+            )
+        );
+    }
+
+    #[test]
+    fn construct_notification_message_correctly() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 1.357.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let sample_service_costs = vec![
+            ServiceCost {
+                service_name: "AWS CloudTrail".to_string(),
+                cost: Cost {
+                    amount: 1.234.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Cost Explorer".to_string(),
+                cost: Cost {
+                    amount: 0.123.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &Config::default());
+
+        assert_eq!(
+            "07/01~07/11の請求額は、1.36 USDです。",
+            actual_message.header,
+        );
+
+        assert_eq!(
+            "・AWS CloudTrail: 1.23 USD\n・AWS Cost Explorer: 0.12 USD",
+            actual_message.body,
+        );
+    }
+
+    #[test]
+    fn sort_service_costs_by_descending_order_correctly() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 1.6234.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let sample_service_costs = vec![
+            ServiceCost {
+                service_name: "AWS Service A".to_string(),
+                cost: Cost {
+                    amount: 1.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Service B".to_string(),
+                cost: Cost {
+                    amount: 3.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Service C".to_string(),
+                cost: Cost {
+                    amount: 2.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &Config::default());
+
+        assert_eq!(
+            "・AWS Service B: 3.00 USD\n・AWS Service C: 2.00 USD\n・AWS Service A: 1.00 USD",
+            actual_message.body,
+        );
+    }
+
+    #[test]
+    fn message_line_is_not_displayed_when_cost_is_zero() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 0.01.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let sample_service_costs = vec![
+            ServiceCost {
+                service_name: "AWS CloudTrail".to_string(),
+                cost: Cost {
+                    amount: 0.01.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Cost Explorer".to_string(),
+                cost: Cost {
+                    amount: 0.001.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Dummy Service".to_string(),
+                cost: Cost {
+                    amount: 0.005.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &Config::default());
+
+        assert_eq!(
+            "07/01~07/11の請求額は、0.01 USDです。",
+            actual_message.header,
+        );
+
+        assert_eq!("・AWS CloudTrail: 0.01 USD", actual_message.body,);
+    }
+
+    #[test]
+    fn show_share_of_total_in_service_lines() {
+        let config = Config {
+            show_percentage: true,
+            ..Config::default()
+        };
+
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(8.0),
+            vec![
+                sample_service_cost("Amazon EC2", 6.0),
+                sample_service_cost("AWS Lambda", 1.0),
+            ],
+            &config,
+        );
+        assert_eq!(
+            "・Amazon EC2: 6.00 USD (75.0%)\n・AWS Lambda: 1.00 USD (12.5%)",
+            actual_message.body
+        );
+
+        let aligned_message = NotificationMessage::new(
+            sample_total_cost(8.0),
+            vec![sample_service_cost("Amazon EC2", 6.0)],
+            &Config {
+                service_layout: ServiceLayout::Aligned,
+                ..config.clone()
+            },
+        );
+        assert_eq!(
+            "```\nAmazon EC2 : 6.00 USD (75.0%)\n```",
+            aligned_message.body
+        );
+
+        assert_eq!(
+            "・Amazon EC2: 0.00 USD (0.0%)",
+            sample_service_cost("Amazon EC2", 0.0).to_message_line_with_share(
+                0.0,
+                true,
+                DEFAULT_COST_DECIMALS,
+                Locale::Ja
+            )
+        );
+    }
+
+    #[test]
+    fn build_message_in_english() {
+        let config = Config {
+            locale: Locale::En,
+            service_budgets: vec![("AWS Lambda".to_string(), 4.0)].into_iter().collect(),
+            ..Config::default()
+        };
+
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(3.0),
+            vec![
+                sample_service_cost("AWS Lambda", 2.0),
+                sample_service_cost("AWS CloudTrail", 1.0),
+            ],
+            &config,
+        );
+
+        assert_eq!(
+            "Total cost for 07/01~07/11 is 3.00 USD.",
+            actual_message.header
+        );
+        assert_eq!(
+            "- AWS Lambda: 2.00/4.00 USD (50%)\n- AWS CloudTrail: 1.00 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn build_sections_in_english() {
+        let config = Config {
+            locale: Locale::En,
+            max_services: Some(1),
+            ..Config::default()
+        };
+        let changes = vec![
+            ServiceCostChange {
+                service_name: "AWS Glue".to_string(),
+                previous: usd(5.0),
+                current: usd(0.0),
+            },
+            ServiceCostChange {
+                service_name: "AWS Lambda".to_string(),
+                previous: usd(1.0),
+                current: usd(2.0),
+            },
+        ];
+        let forecast = ForecastCost { cost: usd(117.0) };
+
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(3.0),
+            vec![
+                sample_service_cost("AWS Lambda", 2.0),
+                sample_service_cost("AWS CloudTrail", 0.5),
+                sample_service_cost("AWS Config", 0.5),
+            ],
+            &config,
+        )
+        .with_forecast(&usd(3.0), &forecast, &config)
+        .with_budget_alert(&usd(120.0), 100.0, None, &config)
+        .with_dropped_services(&changes, false, config.locale)
+        .with_comparison(&usd(6.0), &usd(3.0), &changes, &config);
+
+        assert_eq!(
+            "Total cost for 07/01~07/11 is 3.00 USD. (forecast 120.00 USD)",
+            actual_message.header
+        );
+        assert_eq!(
+            "- AWS Lambda: 2.00 USD\n\
+             - Others (2 services): 1.00 USD\n\n\
+             ⚠️The forecast 120.00 USD is expected to exceed the budget 100.00 USD\n\n\
+             ⚠️Services dropped to zero: AWS Glue",
+            actual_message.body
+        );
+        assert_eq!(
+            Some(
+                "[Compared with the same period of the previous month]\n\
+                 Total: 6.00 USD → 3.00 USD (-3.00 USD, -50.0%)\n\
+                 - AWS Glue: 5.00 USD → 0.00 USD (-5.00 USD, -100.0%)\n\
+                 - AWS Lambda: 1.00 USD → 2.00 USD (+1.00 USD, +100.0%)"
+                    .to_string()
+            ),
+            actual_message.comparison
+        );
+    }
+
+    #[test]
+    fn skip_zero_costs_regardless_of_unit() {
+        for (unit, expected_line) in [
+            ("USD", "・AWS CloudTrail: 120.00 USD"),
+            ("JPY", "・AWS CloudTrail: ¥120"),
+            ("EUR", "・AWS CloudTrail: 120.00 EUR"),
+        ] {
+            let cost = |amount: f64| Cost {
+                amount: amount.into(),
+                unit: unit.to_string(),
+            };
+            let sample_total_cost = TotalCost {
+                date_range: ReportedDateRange {
+                    start_date: Local.ymd(2021, 7, 1),
+                    end_date: Local.ymd(2021, 7, 11),
+                },
+                cost: cost(120.0),
+            };
+            let sample_service_costs = vec![
+                ServiceCost {
+                    service_name: "AWS CloudTrail".to_string(),
+                    cost: cost(120.0),
+                },
+                ServiceCost {
+                    service_name: "AWS Cost Explorer".to_string(),
+                    cost: cost(0.0),
+                },
+                ServiceCost {
+                    service_name: "AWS Key Management Service".to_string(),
+                    cost: cost(0.001),
+                },
+                ServiceCost {
+                    service_name: "Amazon Simple Notification Service".to_string(),
+                    cost: cost(match unit {
+                        "JPY" => 0.4,
+                        _ => 0.004,
+                    }),
+                },
+            ];
+
+            let actual_message = NotificationMessage::new(
+                sample_total_cost,
+                sample_service_costs,
+                &Config::default(),
+            );
+
+            assert_eq!(expected_line, actual_message.body);
+        }
+    }
+
+    #[test]
+    fn mention_owner_of_service_over_threshold() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 290.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let sample_service_costs = vec![
+            ServiceCost {
+                service_name: "AWS Lambda".to_string(),
+                cost: Cost {
+                    amount: 150.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "Amazon Simple Storage Service".to_string(),
+                cost: Cost {
+                    amount: 120.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS CloudTrail".to_string(),
+                cost: Cost {
+                    amount: 20.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+        let config = Config {
+            service_owners: vec![
+                ("AWS Lambda".to_string(), "U012AB3CD".to_string()),
+                ("AWS CloudTrail".to_string(), "U045EF6GH".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            owner_mention_threshold: 100.0,
+            ..Config::default()
+        };
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &config);
+
+        assert_eq!(
+            "・AWS Lambda: 150.00 USD <@U012AB3CD>\n・Amazon Simple Storage Service: 120.00 USD\n・AWS CloudTrail: 20.00 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn group_service_costs_by_category_correctly() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 16.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let sample_service_costs = vec![
+            ServiceCost {
+                service_name: "AWS CloudTrail".to_string(),
+                cost: Cost {
+                    amount: 1.5.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Lambda".to_string(),
+                cost: Cost {
+                    amount: 2.5.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "Amazon Elastic Compute Cloud - Compute".to_string(),
+                cost: Cost {
+                    amount: 10.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "Amazon Simple Storage Service".to_string(),
+                cost: Cost {
+                    amount: 2.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+        let config = Config {
+            group_by_category: true,
+            ..Config::default()
+        };
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &config);
+
+        assert_eq!(
+            "【Compute】12.50 USD\n  ・Amazon Elastic Compute Cloud - Compute: 10.00 USD\n  ・AWS Lambda: 2.50 USD\n【Storage】2.00 USD\n  ・Amazon Simple Storage Service: 2.00 USD\n【Other】1.50 USD\n  ・AWS CloudTrail: 1.50 USD",
+            actual_message.body,
+        );
+    }
+
+    #[test]
+    fn convert_actual_and_forecast_into_display_currency() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 23),
+            },
+            cost: Cost {
+                amount: 10.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let actual = sample_total_cost.cost.clone();
+        let sample_service_costs = vec![ServiceCost {
+            service_name: "AWS CloudTrail".to_string(),
+            cost: Cost {
+                amount: 10.0.into(),
+                unit: "USD".to_string(),
+            },
+        }];
+        let sample_forecast = ForecastCost {
+            cost: Cost {
+                amount: 5.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let config = Config {
+            display_currency: Some("JPY".to_string()),
+            exchange_rate: Some(110.0),
+            ..Config::default()
+        };
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &config)
+                .with_forecast(&actual, &sample_forecast, &config);
+
+        assert_eq!(
+            "07/01~07/23の請求額は、¥1,100です。 (月末予測 ¥1,650)",
+            actual_message.header
+        );
+        assert_eq!("・AWS CloudTrail: ¥1,100", actual_message.body);
+    }
+
+    fn daily_costs_of_july(amounts: &[f64]) -> Vec<DailyCost<Local>> {
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(i, amount)| DailyCost {
+                date: Local.ymd(2021, 7, i as u32 + 1),
+                cost: Cost {
+                    amount: (*amount).into(),
+                    unit: "USD".to_string(),
+                },
+                estimated: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn project_overage_date_of_crossing_trajectory() {
+        // 88 USD by 07/22, increasing by 4 USD per day in the last 7 days
+        let mut amounts = vec![4.0; 22];
+        amounts[0] = 10.0;
+        amounts[1] = -2.0;
+        let daily_costs = daily_costs_of_july(&amounts);
+
+        let overage_date = projected_overage_date(&daily_costs, 100.0);
+
+        assert_eq!(Some(Local.ymd(2021, 7, 26)), overage_date);
+        assert_eq!(
+            "予算超過予測日: 07/26",
+            NotificationMessage::default()
+                .with_overage_date(overage_date.as_ref())
+                .body
+        );
+    }
+
+    #[test]
+    fn project_no_overage_within_budget() {
+        let daily_costs = daily_costs_of_july(&[4.0; 22]);
+
+        let overage_date = projected_overage_date(&daily_costs, 200.0);
+
+        assert_eq!(None, overage_date);
+        assert_eq!(
+            "今月は予算内",
+            NotificationMessage::default()
+                .with_overage_date(overage_date.as_ref())
+                .body
+        );
+    }
+
+    #[test]
+    fn return_actual_date_when_budget_is_already_exceeded() {
+        let daily_costs = daily_costs_of_july(&[30.0, 30.0, 30.0, 30.0, 30.0]);
+
+        assert_eq!(
+            Some(Local.ymd(2021, 7, 4)),
+            projected_overage_date(&daily_costs, 100.0)
+        );
+    }
+
+    #[test]
+    fn append_net_cost_correctly() {
+        let sample_message = |amount: f64| NotificationMessage {
+            header: format!("07/01~07/23の請求額は、{:.2} USDです。", amount),
+            ..Default::default()
+        };
+
+        let above_credit =
+            sample_message(120.0).with_net_cost(&usd(120.0), 100.0, &Config::default());
+        let below_credit =
+            sample_message(80.0).with_net_cost(&usd(80.0), 100.0, &Config::default());
+
+        assert_eq!(
+            "07/01~07/23の請求額は、120.00 USDです。 (実質負担: 20.00 USD)",
+            above_credit.header
+        );
+        assert_eq!(
+            "07/01~07/23の請求額は、80.00 USDです。 (実質負担: 0.00 USD)",
+            below_credit.header
+        );
+    }
+
+    #[test]
+    fn append_unit_cost_correctly() {
+        let actual = Cost {
+            amount: 120.0.into(),
+            unit: "USD".to_string(),
+        };
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、120.00 USDです。".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message =
+            sample_message.with_unit_cost(&actual, "ユーザー", Some(1000.0), &Config::default());
+
+        assert_eq!(
+            "07/01~07/23の請求額は、120.00 USDです。 (1ユーザーあたり 0.12 USD)",
+            actual_message.header
+        );
+    }
+
+    #[test]
+    fn append_change_since_last_notification_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、115.00 USDです。".to_string(),
+            body: "・AWS Lambda: 115.00 USD".to_string(),
+            ..Default::default()
+        };
+        let last_notified = Cost {
+            amount: 100.0.into(),
+            unit: "USD".to_string(),
+        };
+        let actual = Cost {
+            amount: 115.0.into(),
+            unit: "USD".to_string(),
+        };
+
+        let increased_message = sample_message.with_change_since_last_notification(
+            &last_notified,
+            &actual,
+            &Config::default(),
+        );
+        let decreased_message = NotificationMessage::default().with_change_since_last_notification(
+            &actual,
+            &last_notified,
+            &Config::default(),
+        );
+
+        assert_eq!(
+            "07/01~07/23の請求額は、115.00 USDです。 (前回通知から +15.00 USD)",
+            increased_message.header
+        );
+        assert_eq!(" (前回通知から -15.00 USD)", decreased_message.header);
+    }
+
+    #[test]
+    fn append_daily_change_across_month_boundary() {
+        let total_of = |date: chrono::Date<Local>, amount: f64| TotalCost {
+            date_range: ReportedDateRange {
+                start_date: date,
+                end_date: date.succ(),
+            },
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let baseline_total = total_of(Local.ymd(2021, 7, 26), 2.0);
+        let latest_total = total_of(Local.ymd(2021, 8, 2), 3.0);
+
+        let actual_message = NotificationMessage::default().with_daily_change(
+            DailyBaseline::SameWeekdayLastWeek,
+            &baseline_total,
+            &latest_total,
+            &Config::default(),
+        );
+
+        assert_eq!(
+            "前週同曜日比 (07/26 → 08/02): 2.00 USD → 3.00 USD (+1.00 USD, +50.0%)",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn append_top_usage_types_with_quantity_correctly() {
+        let usage_type_cost =
+            |usage_type: &str, amount: f64, quantity: Option<f64>| UsageTypeCost {
+                usage_type: usage_type.to_string(),
+                cost: Cost {
+                    amount: amount.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: quantity.map(|amount| Cost {
+                    amount: amount.into(),
+                    unit: "GB".to_string(),
+                }),
+            };
+        let sample_usage_type_costs = || {
+            vec![
+                usage_type_cost("APN1-TimedStorage-ByteHrs", 3.0, Some(100.0)),
+                usage_type_cost("APN1-DataTransfer-Out-Bytes", 45.0, Some(512.0)),
+                usage_type_cost("APN1-Requests-Tier1", 1.0, None),
+            ]
+        };
+        let config = Config {
+            metric_decimals: vec![(USAGE_METRIC.to_string(), 0)].into_iter().collect(),
+            ..Config::default()
+        };
+
+        let message_with_quantity = NotificationMessage::default().with_top_usage_types(
+            sample_usage_type_costs(),
+            2,
+            true,
+            &config,
+        );
+        let message_without_quantity = NotificationMessage::default().with_top_usage_types(
+            sample_usage_type_costs(),
+            2,
+            false,
+            &config,
+        );
+
+        assert_eq!(
+            "【使用タイプ別 上位2件】\n・APN1-DataTransfer-Out-Bytes: 45.00 USD (512 GB)\n・APN1-TimedStorage-ByteHrs: 3.00 USD (100 GB)",
+            message_with_quantity.body
+        );
+        assert_eq!(
+            "【使用タイプ別 上位2件】\n・APN1-DataTransfer-Out-Bytes: 45.00 USD\n・APN1-TimedStorage-ByteHrs: 3.00 USD",
+            message_without_quantity.body
+        );
+    }
+
+    #[test]
+    fn append_utilization_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、120.00 USDです。".to_string(),
+            body: "・AWS Lambda: 120.00 USD".to_string(),
+            ..Default::default()
+        };
+        let savings_plans = Utilization { percentage: 95.2 };
+
+        let actual_message = sample_message.with_utilization(Some(&savings_plans), None, 80.0);
+
+        assert_eq!(
+            "・AWS Lambda: 120.00 USD\n\nSP稼働率: 95%",
+            actual_message.body
+        );
+        assert_eq!(Severity::Normal, actual_message.severity);
+    }
+
+    #[test]
+    fn warn_low_utilization_correctly() {
+        let savings_plans = Utilization { percentage: 95.2 };
+        let reservation = Utilization { percentage: 62.5 };
+
+        let actual_message = NotificationMessage::default().with_utilization(
+            Some(&savings_plans),
+            Some(&reservation),
+            80.0,
+        );
+        let message_without_commitments =
+            NotificationMessage::default().with_utilization(None, None, 80.0);
+
+        assert_eq!(
+            "SP稼働率: 95%\n⚠️RI稼働率: 62% (80%未満)",
+            actual_message.body
+        );
+        assert_eq!(Severity::Warning, actual_message.severity);
+        assert_eq!("", message_without_commitments.body);
+        assert_eq!(Severity::Normal, message_without_commitments.severity);
+    }
+
+    #[test]
+    fn append_savings_opportunity_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、120.00 USDです。".to_string(),
+            body: "・Amazon EC2: 120.00 USD".to_string(),
+            ..Default::default()
+        };
+        let savings = SavingsOpportunity {
+            cost: Cost {
+                amount: 42.5.into(),
+                unit: String::from("USD"),
+            },
+        };
+
+        let actual_message =
+            sample_message.with_savings_opportunity(Some(&savings), &Config::default());
+        let message_without_savings =
+            NotificationMessage::default().with_savings_opportunity(None, &Config::default());
+
+        assert_eq!(
+            "・Amazon EC2: 120.00 USD\n\n推定節約可能額: 42.50 USD/月",
+            actual_message.body
+        );
+        assert_eq!("", message_without_savings.body);
+    }
+
+    #[test]
+    fn skip_unit_cost_without_metric() {
+        let actual = Cost {
+            amount: 120.0.into(),
+            unit: "USD".to_string(),
+        };
+        let header = "07/01~07/23の請求額は、120.00 USDです。".to_string();
+        let sample_message = || NotificationMessage {
+            header: header.clone(),
+            ..Default::default()
+        };
+
+        let zero_message =
+            sample_message().with_unit_cost(&actual, "ユーザー", Some(0.0), &Config::default());
+        let absent_message =
+            sample_message().with_unit_cost(&actual, "ユーザー", None, &Config::default());
+
+        assert_eq!(header, zero_message.header);
+        assert_eq!(header, absent_message.header);
+    }
+
+    fn sample_region_costs() -> Vec<RegionCost> {
+        vec![
+            RegionCost {
+                region: "ap-northeast-1".to_string(),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            RegionCost {
+                region: "NoRegion".to_string(),
+                cost: Cost {
+                    amount: 4.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            RegionCost {
+                region: "global".to_string(),
+                cost: Cost {
+                    amount: 1.67.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ]
+    }
+
+    #[test]
+    fn group_global_pseudo_regions_correctly() {
+        let message = NotificationMessage {
+            header: "header".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message = message.with_region_costs(sample_region_costs(), true);
+
+        assert_eq!(
+            "【リージョン別】\n・ap-northeast-1: 12.34 USD\n・グローバル: 5.67 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn display_pseudo_regions_as_they_are_without_grouping() {
+        let message = NotificationMessage {
+            header: "header".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message = message.with_region_costs(sample_region_costs(), false);
+
+        assert_eq!(
+            "【リージョン別】\n・ap-northeast-1: 12.34 USD\n・NoRegion: 4.00 USD\n・global: 1.67 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn label_accounts_with_their_names_correctly() {
+        let message = NotificationMessage {
+            header: "header".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+        let account_costs = vec![
+            AccountCost {
+                account_id: "210987654321".to_string(),
+                account_name: None,
+                cost: Cost {
+                    amount: 5.67.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            AccountCost {
+                account_id: "123456789012".to_string(),
+                account_name: Some("production".to_string()),
+                cost: Cost {
+                    amount: 12.34.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+
+        let actual_message = message.with_account_costs(account_costs);
+
+        assert_eq!(
+            "【アカウント別】\n・production (123456789012): 12.34 USD\n・210987654321: 5.67 USD",
+            actual_message.body
+        );
+    }
+
+    fn sample_monthly_costs(amounts: &[f64]) -> Vec<MonthlyCost<Local>> {
+        let months = [
+            (Local.ymd(2021, 5, 1), Local.ymd(2021, 6, 1)),
+            (Local.ymd(2021, 6, 1), Local.ymd(2021, 7, 1)),
+            (Local.ymd(2021, 7, 1), Local.ymd(2021, 7, 23)),
+        ];
+        months
+            .iter()
+            .zip(amounts.iter())
+            .map(|((start_date, end_date), amount)| MonthlyCost {
+                start_date: *start_date,
+                end_date: *end_date,
+                cost: Cost {
+                    amount: (*amount).into(),
+                    unit: "USD".to_string(),
+                },
+            })
+            .collect()
+    }
+
+    #[test]
+    fn build_table_of_three_monthly_totals_correctly() {
+        let message = NotificationMessage {
+            header: "header".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message = message
+            .with_monthly_table(sample_monthly_costs(&[10.0, 12.0, 5.0]), &Config::default());
+
+        assert_eq!(
+            "【直近3か月の推移】\n```\n月               |      合計 | 前月比\n-----------------+-----------+-------\n2021/05          | 10.00 USD |\n2021/06          | 12.00 USD | +20.0%\n2021/07 (~07/23) |  5.00 USD | -58.3%\n```",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn skip_months_before_costs_are_available() {
+        let message = NotificationMessage {
+            header: "header".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message =
+            message.with_monthly_table(sample_monthly_costs(&[0.0, 12.0, 5.0]), &Config::default());
+
+        assert_eq!(
+            "【直近2か月の推移】\n```\n月               |      合計 | 前月比\n-----------------+-----------+-------\n2021/06          | 12.00 USD |\n2021/07 (~07/23) |  5.00 USD | -58.3%\n```",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn replace_units_with_labels() {
+        let labels: HashMap<String, String> = vec![
+            ("USD".to_string(), "ドル".to_string()),
+            ("JPY".to_string(), "円".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、120.00 USDです。".to_string(),
+            body: "・AWS Lambda: 120.00 USD".to_string(),
+            comparison: Some("【前月同期比較】\n合計: ¥11,000 → ¥13,200".to_string()),
+            footer: Some("USD/JPY 110".to_string()),
+            ..Default::default()
+        };
+
+        let actual_message = sample_message.with_unit_labels(&labels);
+
+        assert_eq!(
+            "07/01~07/23の請求額は、120.00 ドルです。",
+            actual_message.header
+        );
+        assert_eq!("・AWS Lambda: 120.00 ドル", actual_message.body);
+        assert_eq!(
+            Some("【前月同期比較】\n合計: 11,000円 → 13,200円".to_string()),
+            actual_message.comparison
+        );
+        assert_eq!(Some("USD/JPY 110".to_string()), actual_message.footer);
+    }
+
+    #[test]
+    fn select_severity_by_band_of_spend() {
+        let severity = |amount: f64| severity_of_spend(amount.into(), Some(100.0), Some(500.0));
+
+        assert_eq!(Severity::Normal, severity(0.0));
+        assert_eq!(Severity::Normal, severity(99.99));
+        assert_eq!(Severity::Warning, severity(100.0));
+        assert_eq!(Severity::Warning, severity(499.99));
+        assert_eq!(Severity::Critical, severity(500.0));
+        assert_eq!(Severity::Critical, severity(1000.0));
+        assert_eq!("#36a64f", severity(99.99).color());
+        assert_eq!("#daa038", severity(100.0).color());
+        assert_eq!("#d00000", severity(500.0).color());
+    }
+
+    #[test]
+    fn select_severity_with_one_of_thresholds() {
+        assert_eq!(
+            Severity::Warning,
+            severity_of_spend(1000.0.into(), Some(100.0), None)
+        );
+        assert_eq!(
+            Severity::Normal,
+            severity_of_spend(99.0.into(), None, Some(100.0))
+        );
+        assert_eq!(
+            Severity::Critical,
+            severity_of_spend(100.0.into(), None, Some(100.0))
+        );
+        assert_eq!(
+            Severity::Normal,
+            severity_of_spend(1000.0.into(), None, None)
+        );
+    }
+
+    #[test]
+    fn escalate_message_by_spend_without_lowering_severity() {
+        let config = Config {
+            cost_warn_threshold: Some(100.0),
+            cost_crit_threshold: Some(500.0),
+            ..Config::default()
+        };
+        let total = Cost {
+            amount: 120.0.into(),
+            unit: "USD".to_string(),
+        };
+        let message_of = |severity: Severity| NotificationMessage {
+            severity,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            Severity::Warning,
+            message_of(Severity::Normal)
+                .with_spend_severity(&total, &config)
+                .severity
+        );
+        assert_eq!(
+            Severity::Critical,
+            message_of(Severity::Critical)
+                .with_spend_severity(&total, &config)
+                .severity
+        );
+    }
+
+    #[test]
+    fn escalate_large_percentage_increase_even_if_amount_is_small() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、1.80 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.80 USD".to_string(),
+            ..Default::default()
+        };
+        let previous_total = Cost {
+            amount: 1.0.into(),
+            unit: "USD".to_string(),
+        };
+        let current_total = Cost {
+            amount: 1.8.into(),
+            unit: "USD".to_string(),
+        };
+
+        let actual_message = sample_message.with_increase_alert(
+            &previous_total,
+            &current_total,
+            50.0,
+            Some("<!channel>"),
+        );
+
+        assert_eq!(Severity::Critical, actual_message.severity);
+        assert_eq!(
+            "<!channel>\n・AWS CloudTrail: 1.80 USD\n\n⚠️前月同期比 +80.0%",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn escalate_forecast_over_budget_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、90.00 USDです。".to_string(),
+            body: "・AWS CloudTrail: 90.00 USD".to_string(),
+            ..Default::default()
+        };
+        let actual = Cost {
+            amount: 90.0.into(),
+            unit: "USD".to_string(),
+        };
+        let forecast = ForecastCost {
+            cost: Cost {
+                amount: 30.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let actual_message = sample_message.with_budget_alert(
+            &projected_total(&actual, Some(&forecast)),
+            100.0,
+            Some("<!channel>"),
+            &Config::default(),
+        );
+
+        assert_eq!(Severity::Critical, actual_message.severity);
+        assert_eq!(
+            "<!channel>\n・AWS CloudTrail: 90.00 USD\n\n⚠️月末予測 120.00 USD が予算 100.00 USD を超過する見込みです",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn do_not_escalate_increase_within_threshold() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、1200.00 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1200.00 USD".to_string(),
+            ..Default::default()
+        };
+        let previous_total = Cost {
+            amount: 1000.0.into(),
+            unit: "USD".to_string(),
+        };
+        let current_total = Cost {
+            amount: 1200.0.into(),
+            unit: "USD".to_string(),
+        };
+
+        let actual_message = sample_message.with_increase_alert(
+            &previous_total,
+            &current_total,
+            50.0,
+            Some("<!channel>"),
+        );
+
+        assert_eq!(Severity::Normal, actual_message.severity);
+        assert_eq!("・AWS CloudTrail: 1200.00 USD", actual_message.body);
+    }
+
+    #[test]
+    fn set_comparison_with_previous_period_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、120.00 USDです。".to_string(),
+            body: "・AWS CloudTrail: 118.00 USD\n・AWS Lambda: 2.00 USD".to_string(),
+            ..Default::default()
+        };
+        let changes = vec![
+            ServiceCostChange {
+                service_name: "AWS Lambda".to_string(),
+                previous: usd(1.0),
+                current: usd(2.0),
+            },
+            ServiceCostChange {
+                service_name: "AWS CloudTrail".to_string(),
+                previous: usd(99.0),
+                current: usd(118.0),
+            },
+            ServiceCostChange {
+                service_name: "AWS Cost Explorer".to_string(),
+                previous: usd(0.0),
+                current: usd(0.0),
+            },
+        ];
+
+        let actual_message =
+            sample_message.with_comparison(&usd(100.0), &usd(120.0), &changes, &Config::default());
+
+        assert_eq!(
+            "・AWS CloudTrail: 118.00 USD\n・AWS Lambda: 2.00 USD",
+            actual_message.body
+        );
+        assert_eq!(
+            Some("【前月同期比較】\n合計: 100.00 USD → 120.00 USD (+20.00 USD, +20.0%)\n・AWS CloudTrail: 99.00 USD → 118.00 USD (+19.00 USD, +19.2%)\n・AWS Lambda: 1.00 USD → 2.00 USD (+1.00 USD, +100.0%)".to_string()),
+            actual_message.comparison
+        );
+    }
+
+    #[test]
+    fn keep_or_omit_dropped_services_in_comparison() {
+        let changes = vec![
+            ServiceCostChange {
+                service_name: "AWS Lambda".to_string(),
+                previous: usd(1.0),
+                current: usd(2.0),
+            },
+            ServiceCostChange {
+                service_name: "AWS Glue".to_string(),
+                previous: usd(5.0),
+                current: usd(0.0),
+            },
+        ];
+        let build_comparison = |include_dropped_services: bool| {
+            let config = Config {
+                include_dropped_services,
+                ..Config::default()
+            };
+            NotificationMessage::default()
+                .with_comparison(&usd(6.0), &usd(2.0), &changes, &config)
+                .comparison
+        };
+
+        assert_eq!(
+            Some("【前月同期比較】\n合計: 6.00 USD → 2.00 USD (-4.00 USD, -66.7%)\n・AWS Glue: 5.00 USD → 0.00 USD (-5.00 USD, -100.0%)\n・AWS Lambda: 1.00 USD → 2.00 USD (+1.00 USD, +100.0%)".to_string()),
+            build_comparison(true)
+        );
+        assert_eq!(
+            Some("【前月同期比較】\n合計: 6.00 USD → 2.00 USD (-4.00 USD, -66.7%)\n・AWS Lambda: 1.00 USD → 2.00 USD (+1.00 USD, +100.0%)".to_string()),
+            build_comparison(false)
+        );
+    }
+
+    #[test]
+    fn group_comparison_by_trend_correctly() {
+        let change = |service_name: &str, previous: f64, current: f64| ServiceCostChange {
+            service_name: service_name.to_string(),
+            previous: usd(previous),
+            current: usd(current),
+        };
+        let changes = vec![
+            change("AWS Lambda", 10.0, 10.2),
+            change("Amazon Simple Storage Service", 20.0, 10.0),
+            change("AWS Glue", 0.0, 5.0),
+            change("AWS CloudTrail", 50.0, 80.0),
+        ];
+        let config = Config {
+            group_by_trend: true,
+            ..Config::default()
+        };
+
+        let actual_message = NotificationMessage::default().with_comparison(
+            &usd(80.0),
+            &usd(105.2),
+            &changes,
+            &config,
+        );
+
+        assert_eq!(
+            Some("【前月同期比較】\n合計: 80.00 USD → 105.20 USD (+25.20 USD, +31.5%)\n【新規】\n・AWS Glue: 0.00 USD → 5.00 USD (+5.00 USD, 新規)\n【増加】\n・AWS CloudTrail: 50.00 USD → 80.00 USD (+30.00 USD, +60.0%)\n【減少】\n・Amazon Simple Storage Service: 20.00 USD → 10.00 USD (-10.00 USD, -50.0%)\n【横ばい】\n・AWS Lambda: 10.00 USD → 10.20 USD (+0.20 USD, +2.0%)".to_string()),
+            actual_message.comparison
+        );
+    }
+
+    #[test]
+    fn append_top_increasing_usage_type_correctly() {
+        let usage_type_cost = |usage_type: &str, amount: f64| UsageTypeCost {
+            usage_type: usage_type.to_string(),
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+            usage_quantity: None,
+        };
+        let previous = vec![usage_type_cost("APN1-DataTransfer-Out-Bytes", 10.0)];
+        let current = vec![
+            usage_type_cost("APN1-DataTransfer-Out-Bytes", 40.0),
+            usage_type_cost("APN1-BoxUsage:t3.micro", 5.0),
+        ];
+        let sample_message = || NotificationMessage {
+            body: "・AWS CloudTrail: 1.00 USD".to_string(),
+            ..Default::default()
+        };
+
+        let increased_message = sample_message().with_top_increasing_usage_type(
+            &previous,
+            &current,
+            &Config::default(),
+        );
+        let decreased_message = sample_message().with_top_increasing_usage_type(
+            &current,
+            &previous,
+            &Config::default(),
+        );
+
+        assert_eq!(
+            "・AWS CloudTrail: 1.00 USD\n\n最大増加の使用タイプ: APN1-DataTransfer-Out-Bytes +30.00 USD",
+            increased_message.body
+        );
+        assert_eq!("・AWS CloudTrail: 1.00 USD", decreased_message.body);
+    }
+
+    #[test]
+    fn build_unchanged_message_correctly() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 1.6234.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let actual_message = NotificationMessage::unchanged(&sample_total_cost, &Config::default());
+
+        assert_eq!(
+            "大きな変化はありません (合計 1.62 USD)",
+            actual_message.header
+        );
+        assert_eq!("", actual_message.body);
+    }
+
+    #[test]
+    fn build_error_message_correctly() {
+        let actual_message =
+            NotificationMessage::error("CostExplorer API request failed: Rate exceeded");
+
+        assert_eq!("コスト取得に失敗しました", actual_message.header);
+        assert_eq!(
+            "CostExplorer API request failed: Rate exceeded",
+            actual_message.body
+        );
+        assert_eq!(Severity::Critical, actual_message.severity);
+    }
+
+    #[test]
+    fn append_tag_ranking_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+
+        let sample_tag_costs = vec![
+            TagCost {
+                tag_value: "frontend".to_string(),
+                cost: Cost {
+                    amount: 0.2.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            TagCost {
+                tag_value: "backend".to_string(),
+                cost: Cost {
+                    amount: 1.0.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            TagCost {
+                tag_value: "batch".to_string(),
+                cost: Cost {
+                    amount: 0.4.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            TagCost {
+                tag_value: "unused".to_string(),
+                cost: Cost {
+                    amount: 0.001.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+
+        let actual_message = sample_message.with_tag_ranking("Project", sample_tag_costs, 2, true);
+
+        assert_eq!(
+            "・AWS CloudTrail: 1.62 USD\n\n【Project別 上位2件】\n・backend: 1.00 USD\n・batch: 0.40 USD",
+            actual_message.body,
+        );
+    }
+
+    #[test]
+    fn append_all_tag_costs_with_untagged_label() {
+        let tag_cost = |tag_value: &str, amount: f64| TagCost {
+            tag_value: tag_value.to_string(),
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let sample_tag_costs = vec![
+            tag_cost("staging", 0.4),
+            tag_cost("", 2.5),
+            tag_cost("production", 1.0),
+            tag_cost("sandbox", 0.001),
+        ];
+
+        let actual_message =
+            NotificationMessage::default().with_tag_costs("Environment", sample_tag_costs, true);
+
+        assert_eq!(
+            "【Environment別】\n・(untagged): 2.50 USD\n・production: 1.00 USD\n・staging: 0.40 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn escape_tag_ranking_for_slack_correctly() {
+        let sample_tag_costs = vec![TagCost {
+            tag_value: "<none>".to_string(),
+            cost: Cost {
+                amount: 1.0.into(),
+                unit: "USD".to_string(),
+            },
+        }];
+
+        let actual_message =
+            NotificationMessage::default().with_tag_ranking("R&D", sample_tag_costs, 1, true);
+
+        assert_eq!(
+            "【R&amp;D別 上位1件】\n・&lt;none&gt;: 1.00 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn split_message_into_total_and_breakdown_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+
+        let (total_message, breakdown_message) = sample_message.split();
+
+        assert_eq!(
+            "07/01~07/11の請求額は、1.62 USDです。",
+            total_message.header
+        );
+        assert_eq!("", total_message.body);
+        assert_eq!("内訳", breakdown_message.header);
+        assert_eq!("・AWS CloudTrail: 1.62 USD", breakdown_message.body);
+    }
+
+    #[test]
+    fn always_show_listed_services_even_if_cost_is_zero() {
+        let sample_total_cost = TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: Cost {
+                amount: 0.01.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let sample_service_costs = vec![
+            ServiceCost {
+                service_name: "AWS CloudTrail".to_string(),
+                cost: Cost {
+                    amount: 0.01.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "Amazon Elastic Compute Cloud - Compute".to_string(),
+                cost: Cost {
+                    amount: 0.001.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+            ServiceCost {
+                service_name: "AWS Dummy Service".to_string(),
+                cost: Cost {
+                    amount: 0.005.into(),
+                    unit: "USD".to_string(),
+                },
+            },
+        ];
+        let config = Config {
+            always_show_services: vec!["Amazon Elastic Compute Cloud - Compute".to_string()],
+            ..Config::default()
+        };
+
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &config);
+
+        assert_eq!(
+            "・AWS CloudTrail: 0.01 USD\n・Amazon Elastic Compute Cloud - Compute: 0.00 USD",
+            actual_message.body,
+        );
+    }
+
+    #[test]
+    fn append_month_over_month_change_to_header() {
+        let build_header = |previous: f64, current: f64| {
+            NotificationMessage::new(sample_total_cost(current), vec![], &Config::default())
+                .with_month_over_month(&usd(previous), &usd(current))
+                .header
+        };
+
+        assert_eq!(
+            "07/01~07/11の請求額は、112.30 USDです。 (前月比 +12.3%)",
+            build_header(100.0, 112.3)
+        );
+        assert_eq!(
+            "07/01~07/11の請求額は、95.00 USDです。 (前月比 -5.0%)",
+            build_header(100.0, 95.0)
+        );
+        assert_eq!(
+            "07/01~07/11の請求額は、10.00 USDです。 (前月比 N/A)",
+            build_header(0.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn warn_diverging_amortized_and_unblended_totals() {
+        let build_body = |amortized: f64, unblended: f64| {
+            NotificationMessage::new(sample_total_cost(amortized), vec![], &Config::default())
+                .with_metric_divergence(&usd(amortized), &usd(unblended), 5.0, &Config::default())
+                .body
+        };
+
+        assert_eq!(
+            "⚠️AmortizedCost と UnblendedCost が乖離しています: 120.00 USD / 100.00 USD (+20.0%)",
+            build_body(120.0, 100.0)
+        );
+        assert_eq!(
+            "⚠️AmortizedCost と UnblendedCost が乖離しています: 80.00 USD / 100.00 USD (-20.0%)",
+            build_body(80.0, 100.0)
+        );
+        assert_eq!(
+            "⚠️AmortizedCost と UnblendedCost が乖離しています: 30.00 USD / 0.00 USD",
+            build_body(30.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn omit_warning_for_matching_amortized_and_unblended_totals() {
+        let build_body = |amortized: f64, unblended: f64| {
+            NotificationMessage::new(sample_total_cost(amortized), vec![], &Config::default())
+                .with_metric_divergence(&usd(amortized), &usd(unblended), 5.0, &Config::default())
+                .body
+        };
+
+        assert_eq!("", build_body(100.0, 100.0));
+        assert_eq!("", build_body(104.0, 100.0));
+        assert_eq!("", build_body(0.0, 0.0));
+    }
+
+    #[test]
+    fn append_services_dropped_to_zero() {
+        let change = |service_name: &str, previous: f64, current: f64| ServiceCostChange {
+            service_name: service_name.to_string(),
+            previous: usd(previous),
+            current: usd(current),
+        };
+        let build_body = |changes: &[ServiceCostChange]| {
+            NotificationMessage::new(sample_total_cost(10.0), vec![], &Config::default())
+                .with_dropped_services(changes, false, Locale::Ja)
+                .body
+        };
+
+        assert_eq!(
+            "⚠️0になったサービス: AWS Glue",
+            build_body(&[
+                change("AWS Glue", 22.0, 0.0),
+                change("AWS CloudTrail", 8.0, 10.0),
+            ])
+        );
+        assert_eq!("", build_body(&[change("AWS CloudTrail", 8.0, 10.0)]));
+    }
+
+    #[test]
+    fn build_progress_header_with_forecast_and_budget() {
+        let date_range = sample_total_cost(620.0).date_range;
+        let actual = Cost {
+            amount: 620.0.into(),
+            unit: "USD".to_string(),
+        };
+        let forecast = ForecastCost {
+            cost: Cost {
+                amount: 620.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+
+        let actual_message = NotificationMessage::default().with_progress_header(
+            &date_range,
+            &actual,
+            Some(&forecast),
+            Some(1500.0),
+            &Config::default(),
+        );
+
+        assert_eq!(
+            "7月 MTD 620.00 USD → 月末予測 1240.00 USD (予算 1500.00 USD)",
+            actual_message.header
+        );
+    }
+
+    #[test]
+    fn build_progress_header_without_forecast_or_budget() {
+        let date_range = sample_total_cost(620.0).date_range;
+        let actual = Cost {
+            amount: 620.0.into(),
+            unit: "USD".to_string(),
+        };
+        let forecast = ForecastCost {
+            cost: Cost {
+                amount: 620.0.into(),
+                unit: "USD".to_string(),
+            },
+        };
+        let build_header = |forecast: Option<&ForecastCost>, budget: Option<f64>| {
+            NotificationMessage::default()
+                .with_progress_header(&date_range, &actual, forecast, budget, &Config::default())
+                .header
+        };
+
+        assert_eq!(
+            "7月 MTD 620.00 USD → 月末予測 1240.00 USD",
+            build_header(Some(&forecast), None)
+        );
+        assert_eq!(
+            "7月 MTD 620.00 USD (予算 1500.00 USD)",
+            build_header(None, Some(1500.0))
+        );
+        assert_eq!("7月 MTD 620.00 USD", build_header(None, None));
+    }
+
+    #[test]
+    fn label_progress_header_spanning_months_with_range() {
+        let date_range = ReportedDateRange {
+            start_date: Local.ymd(2021, 4, 1),
+            end_date: Local.ymd(2021, 7, 23),
+        };
+        let month_end_range = ReportedDateRange {
+            start_date: Local.ymd(2021, 7, 1),
+            end_date: Local.ymd(2021, 8, 1),
+        };
+        let actual = Cost {
+            amount: 2000.0.into(),
+            unit: "USD".to_string(),
+        };
+
+        assert_eq!(
+            "04/01~07/23 累計 2000.00 USD (予算 1500.00 USD)",
+            NotificationMessage::default()
+                .with_progress_header(&date_range, &actual, None, Some(1500.0), &Config::default())
+                .header
+        );
+        assert_eq!(
+            "7月 MTD 2000.00 USD",
+            NotificationMessage::default()
+                .with_progress_header(&month_end_range, &actual, None, None, &Config::default())
+                .header
+        );
+    }
+
+    fn usd(amount: f64) -> Cost {
+        Cost {
+            amount: amount.into(),
+            unit: "USD".to_string(),
+        }
+    }
+
+    fn sample_total_cost(amount: f64) -> TotalCost<Local> {
+        TotalCost {
+            date_range: ReportedDateRange {
+                start_date: Local.ymd(2021, 7, 1),
+                end_date: Local.ymd(2021, 7, 11),
+            },
+            cost: usd(amount),
+        }
+    }
+
+    fn sample_service_cost(service_name: &str, amount: f64) -> ServiceCost {
+        ServiceCost {
+            service_name: service_name.to_string(),
+            cost: usd(amount),
+        }
+    }
+
+    fn build_body_with_min_service_cost(min_service_cost: Option<f64>) -> String {
+        let config = Config {
+            min_service_cost,
+            ..Config::default()
+        };
+        NotificationMessage::new(
+            sample_total_cost(1.516),
+            vec![
+                sample_service_cost("AWS Lambda", 1.5),
+                sample_service_cost("AWS CloudTrail", 0.01),
+                sample_service_cost("Amazon Simple Storage Service", 0.006),
+                sample_service_cost("AWS Glue", 0.0),
+            ],
+            &config,
+        )
+        .body
+    }
+
+    #[test]
+    fn show_all_services_with_zero_min_service_cost() {
+        assert_eq!(
+            "・AWS Lambda: 1.50 USD\n・AWS CloudTrail: 0.01 USD\n・Amazon Simple Storage Service: 0.01 USD\n・AWS Glue: 0.00 USD",
+            build_body_with_min_service_cost(Some(0.0))
+        );
+    }
+
+    #[test]
+    fn skip_services_below_min_service_cost() {
+        assert_eq!(
+            "・AWS Lambda: 1.50 USD\n・AWS CloudTrail: 0.01 USD",
+            build_body_with_min_service_cost(Some(0.01))
+        );
+        assert_eq!(
+            "・AWS Lambda: 1.50 USD",
+            build_body_with_min_service_cost(Some(1.0))
+        );
+    }
+
+    #[test]
+    fn skip_services_displayed_as_zero_without_min_service_cost() {
+        assert_eq!(
+            "・AWS Lambda: 1.50 USD\n・AWS CloudTrail: 0.01 USD\n・Amazon Simple Storage Service: 0.01 USD",
+            build_body_with_min_service_cost(None)
+        );
+    }
+
+    fn build_body_with_max_services(max_services: usize, service_costs: &[(&str, f64)]) -> String {
+        let config = Config {
+            max_services: Some(max_services),
+            ..Config::default()
+        };
+        NotificationMessage::new(
+            sample_total_cost(service_costs.iter().map(|(_, x)| x).sum()),
+            service_costs
+                .iter()
+                .map(|(name, amount)| sample_service_cost(name, *amount))
+                .collect(),
+            &config,
+        )
+        .body
+    }
+
+    const SAMPLE_SERVICE_AMOUNTS: [(&str, f64); 4] = [
+        ("AWS Lambda", 1.5),
+        ("AWS CloudTrail", 0.25),
+        ("Amazon Simple Storage Service", 2.0),
+        ("AWS Glue", 0.5),
+    ];
+
+    #[test]
+    fn collapse_services_beyond_max_services() {
+        assert_eq!(
+            "・Amazon Simple Storage Service: 2.00 USD\n・AWS Lambda: 1.50 USD\n・その他 (2 services): 0.75 USD",
+            build_body_with_max_services(2, &SAMPLE_SERVICE_AMOUNTS)
+        );
+    }
+
+    #[test]
+    fn format_header_and_service_lines_with_cost_decimals() {
+        let build_message = |cost_decimals: usize| {
+            let config = Config {
+                cost_decimals,
+                service_budgets: vec![("AWS Glue".to_string(), 1.0)].into_iter().collect(),
+                ..Config::default()
+            };
+            NotificationMessage::new(
+                sample_total_cost(1.2468),
+                vec![
+                    sample_service_cost("AWS Lambda", 1.2345),
+                    sample_service_cost("AWS Glue", 0.0123),
+                ],
+                &config,
+            )
+        };
+
+        let message = build_message(0);
+        assert_eq!("07/01~07/11の請求額は、1 USDです。", message.header);
+        assert_eq!(
+            "・AWS Lambda: 1 USD\n・AWS Glue: 0/1 USD (1%)",
+            message.body
+        );
+
+        let message = build_message(2);
+        assert_eq!("07/01~07/11の請求額は、1.25 USDです。", message.header);
+        assert_eq!(
+            "・AWS Lambda: 1.23 USD\n・AWS Glue: 0.01/1.00 USD (1%)",
+            message.body
+        );
+
+        let message = build_message(4);
+        assert_eq!("07/01~07/11の請求額は、1.2468 USDです。", message.header);
+        assert_eq!(
+            "・AWS Lambda: 1.2345 USD\n・AWS Glue: 0.0123/1.0000 USD (1%)",
+            message.body
+        );
+    }
+
+    #[test]
+    fn label_others_line_by_options() {
+        let build_body = |others_show_count: bool, others_rounding: Option<f64>| {
+            let config = Config {
+                max_services: Some(1),
+                others_show_count,
+                others_rounding,
+                ..Config::default()
+            };
+            NotificationMessage::new(
+                sample_total_cost(16.2),
+                vec![
+                    sample_service_cost("Amazon Simple Storage Service", 10.0),
+                    sample_service_cost("AWS Lambda", 3.45),
+                    sample_service_cost("AWS Glue", 2.5),
+                    sample_service_cost("AWS CloudTrail", 0.25),
+                ],
+                &config,
+            )
+            .body
+        };
+
+        assert_eq!(
+            "・Amazon Simple Storage Service: 10.00 USD\n・その他 (3 services): 6.20 USD",
+            build_body(true, None)
+        );
+        assert_eq!(
+            "・Amazon Simple Storage Service: 10.00 USD\n・その他: 6.20 USD",
+            build_body(false, None)
+        );
+        assert_eq!(
+            "・Amazon Simple Storage Service: 10.00 USD\n・その他 (3 services): ~6.00 USD",
+            build_body(true, Some(1.0))
+        );
+        assert_eq!(
+            "・Amazon Simple Storage Service: 10.00 USD\n・その他: ~10.00 USD",
+            build_body(false, Some(10.0))
+        );
+    }
+
+    #[test]
+    fn display_others_cost_rounded_to_zero_as_less_than_rounding() {
+        let cost = Cost {
+            amount: 3.45.into(),
+            unit: "USD".to_string(),
+        };
+
+        assert_eq!("3.45 USD", format_others_cost(&cost, None, 2));
+        assert_eq!("~3.50 USD", format_others_cost(&cost, Some(0.5), 2));
+        assert_eq!("<10.00 USD", format_others_cost(&cost, Some(10.0), 2));
+        assert_eq!("<10 USD", format_others_cost(&cost, Some(10.0), 0));
+    }
+
+    #[test]
+    fn list_all_services_within_max_services() {
+        let all_services = "・Amazon Simple Storage Service: 2.00 USD\n・AWS Lambda: 1.50 USD\n・AWS Glue: 0.50 USD\n・AWS CloudTrail: 0.25 USD";
+
+        assert_eq!(
+            all_services,
+            build_body_with_max_services(4, &SAMPLE_SERVICE_AMOUNTS)
+        );
+        assert_eq!(
+            all_services,
+            build_body_with_max_services(10, &SAMPLE_SERVICE_AMOUNTS)
+        );
     }
-}
 
-/// Cost notification message to send to Slack.
-pub struct NotificationMessage {
-    /// Headline message to display the total cost
-    ///
-    /// # Example
-    /// `07/01~07/11の請求額は、1.62 USDです。`
-    pub header: String,
-    /// Body of the message to display costs for each service
-    ///
-    /// # Example
-    /// `・AWS CloudTrail: 1.23 USD\n・AWS Cost Explorer: 0.12 USD`
-    pub body: String,
-}
-impl NotificationMessage {
-    /// Build Slack notification message from parsed total cost and service costs.
-    ///
-    /// The service costs are displayed in descending order by amount,
-    /// skipping services which are less than 0.01 USD.
-    pub fn new(total_cost: TotalCost, service_costs: Vec<ServiceCost>) -> Self {
-        let mut sorted_service_costs = service_costs.clone();
-        sorted_service_costs.sort_by(|a, b| b.cost.partial_cmp(&a.cost).unwrap());
+    #[test]
+    fn omit_others_line_when_remainder_is_zero() {
+        let config = Config {
+            max_services: Some(1),
+            min_service_cost: Some(0.0),
+            ..Config::default()
+        };
 
-        NotificationMessage {
-            header: total_cost.to_message_header(),
-            body: sorted_service_costs
-                .iter()
-                .filter(|x| format!("{}", x.cost) != "0.00 USD")
-                .map(|x| x.to_message_line())
-                .collect::<Vec<_>>()
-                .join("\n"),
-        }
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(2.0),
+            vec![
+                sample_service_cost("Amazon Simple Storage Service", 2.0),
+                sample_service_cost("AWS CloudTrail", 0.0),
+                sample_service_cost("AWS Glue", 0.0),
+            ],
+            &config,
+        );
+
+        assert_eq!(
+            "・Amazon Simple Storage Service: 2.00 USD",
+            actual_message.body
+        );
     }
-}
 
-#[cfg(test)]
-mod test_cost_representation {
-    use crate::cost_explorer::cost_response_parser::Cost;
+    #[test]
+    fn align_service_costs_in_aligned_layout() {
+        let config = Config {
+            service_layout: ServiceLayout::Aligned,
+            service_budgets: vec![("AWS Glue".to_string(), 10.0)].into_iter().collect(),
+            max_services: Some(3),
+            ..Config::default()
+        };
+
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(1247.0),
+            vec![
+                sample_service_cost("AWS Lambda", 1.5),
+                sample_service_cost("Amazon Simple Storage Service", 1234.0),
+                sample_service_cost("AWS Glue", 11.0),
+                sample_service_cost("AWS CloudTrail", 0.25),
+                sample_service_cost("AWS Cost Explorer", 0.25),
+            ],
+            &config,
+        );
+
+        assert_eq!(
+            "```\n\
+            Amazon Simple Storage Service :     1234.00 USD\n\
+            AWS Glue                      : 11.00/10.00 USD (110%) ⚠️予算超過\n\
+            AWS Lambda                    :        1.50 USD\n\
+            その他 (2 services)           :        0.50 USD\n\
+            ```",
+            actual_message.body
+        );
+    }
 
     #[test]
-    fn display_correctly() {
-        let input_cost = Cost {
-            amount: 132.2345,
-            unit: "USD".to_string(),
+    fn put_owner_mentions_after_code_block_in_aligned_layout() {
+        let config = Config {
+            service_layout: ServiceLayout::Aligned,
+            escape_slack_text: true,
+            service_owners: vec![
+                ("AWS Lambda".to_string(), "U012AB3CD".to_string()),
+                ("R&D Service".to_string(), "U045EF6GH".to_string()),
+            ]
+            .into_iter()
+            .collect(),
+            owner_mention_threshold: 1.0,
+            ..Config::default()
         };
-        assert_eq!("132.23 USD", format!("{}", input_cost));
+
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(13.5),
+            vec![
+                sample_service_cost("AWS Lambda", 1.5),
+                sample_service_cost("R&D Service", 12.0),
+            ],
+            &config,
+        );
+
+        assert_eq!(
+            "```\n\
+            R&amp;D Service : 12.00 USD\n\
+            AWS Lambda  :  1.50 USD\n\
+            ```\n\
+            <@U045EF6GH> <@U012AB3CD>",
+            actual_message.body
+        );
     }
-}
 
-#[cfg(test)]
-mod test_date_range_representation {
-    use crate::cost_explorer::cost_response_parser::ReportedDateRange;
-    use chrono::{Local, TimeZone};
+    #[test]
+    fn omit_code_block_in_aligned_layout_without_services() {
+        let config = Config {
+            service_layout: ServiceLayout::Aligned,
+            ..Config::default()
+        };
+
+        let actual_message = NotificationMessage::new(sample_total_cost(0.0), vec![], &config);
+
+        assert_eq!("", actual_message.body);
+    }
 
     #[test]
-    fn test_display_correctly() {
-        let sample_date_range = ReportedDateRange {
-            start_date: Local.ymd(2021, 7, 1),
-            end_date: Local.ymd(2021, 7, 23),
+    fn report_only_services_with_prefix() {
+        let config = Config {
+            service_name_prefix: Some("Amazon ".to_string()),
+            ..Config::default()
         };
-        assert_eq!("07/01~07/23", format!("{}", sample_date_range))
+
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(1.5),
+            vec![
+                sample_service_cost("AWS Lambda", 1.0),
+                sample_service_cost("Amazon Simple Storage Service", 0.5),
+            ],
+            &config,
+        );
+
+        assert_eq!(
+            "07/01~07/11の請求額は、1.50 USDです。",
+            actual_message.header
+        );
+        assert_eq!(
+            "・Amazon Simple Storage Service: 0.50 USD",
+            actual_message.body
+        );
     }
-}
-#[cfg(test)]
-mod test_build_message {
-    use super::*;
-    use crate::cost_explorer::cost_response_parser::{Cost, ReportedDateRange};
-    use chrono::{Local, TimeZone};
 
     #[test]
-    fn convert_total_cost_into_message_header_correctly() {
-        let sample_total_cost = TotalCost {
-            date_range: ReportedDateRange {
-                start_date: Local.ymd(2021, 7, 1),
-                end_date: Local.ymd(2021, 7, 11),
-            },
-            cost: Cost {
-                amount: 1.6234,
-                unit: "USD".to_string(),
-            },
+    fn report_only_services_matching_pattern() {
+        let config = Config {
+            service_name_pattern: Some("^AWS (Lambda|Glue)$".parse().unwrap()),
+            ..Config::default()
         };
-        let expected_header = "07/01~07/11の請求額は、1.62 USDです。";
-        let actual_header = sample_total_cost.to_message_header();
 
-        assert_eq!(expected_header, actual_header);
+        let actual_message = NotificationMessage::new(
+            sample_total_cost(3.5),
+            vec![
+                sample_service_cost("AWS Glue", 2.0),
+                sample_service_cost("AWS Lambda", 1.0),
+                sample_service_cost("Amazon Simple Storage Service", 0.5),
+            ],
+            &config,
+        );
+
+        assert_eq!(
+            "・AWS Glue: 2.00 USD\n・AWS Lambda: 1.00 USD",
+            actual_message.body
+        );
     }
+
     #[test]
-    fn convert_service_cost_into_message_line_correctly() {
-        let sample_service_cost = ServiceCost {
-            service_name: "AWS CloudTrail".to_string(),
-            cost: Cost {
-                amount: 0.0123,
-                unit: "USD".to_string(),
-            },
+    fn report_only_services_in_allowlist_and_not_in_blocklist() {
+        let service_costs = || {
+            vec![
+                sample_service_cost("Amazon EC2", 3.0),
+                sample_service_cost("AWS CloudTrail", 2.0),
+                sample_service_cost("Amazon Simple Storage Service", 1.0),
+                sample_service_cost("AWS Lambda", 0.5),
+            ]
+        };
+        let build_body = |allowlist: &[&str], blocklist: &[&str]| {
+            let config = Config {
+                service_allowlist: allowlist.iter().map(|x| x.to_string()).collect(),
+                service_blocklist: blocklist.iter().map(|x| x.to_string()).collect(),
+                ..Config::default()
+            };
+            NotificationMessage::new(sample_total_cost(6.5), service_costs(), &config).body
         };
-        let expected_line = "・AWS CloudTrail: 0.01 USD";
-        let actual_line = sample_service_cost.to_message_line();
 
-        assert_eq!(expected_line, actual_line);
+        assert_eq!(
+            "・Amazon EC2: 3.00 USD\n・Amazon Simple Storage Service: 1.00 USD",
+            build_body(&["ec2", "simple storage"], &[])
+        );
+        assert_eq!(
+            "・Amazon EC2: 3.00 USD\n・Amazon Simple Storage Service: 1.00 USD\n・AWS Lambda: 0.50 USD",
+            build_body(&[], &["CLOUDTRAIL"])
+        );
+        assert_eq!(
+            "・Amazon EC2: 3.00 USD",
+            build_body(&["Amazon"], &["Storage"])
+        );
     }
 
     #[test]
-    fn construct_notification_message_correctly() {
-        let sample_total_cost = TotalCost {
-            date_range: ReportedDateRange {
-                start_date: Local.ymd(2021, 7, 1),
-                end_date: Local.ymd(2021, 7, 11),
+    fn sum_only_data_transfer_usage_types() {
+        let sample_usage_type_costs = vec![
+            UsageTypeCost {
+                usage_type: "APN1-DataTransfer-Out-Bytes".to_string(),
+                cost: Cost {
+                    amount: 12.0.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: None,
             },
-            cost: Cost {
-                amount: 1.357,
+            UsageTypeCost {
+                usage_type: "APN1-BoxUsage:t3.micro".to_string(),
+                cost: Cost {
+                    amount: 30.0.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: None,
+            },
+            UsageTypeCost {
+                usage_type: "APN1-USE1-AWS-Out-Bytes".to_string(),
+                cost: Cost {
+                    amount: 4.0.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: None,
+            },
+            UsageTypeCost {
+                usage_type: "USE1-APN1-AWS-In-Bytes".to_string(),
+                cost: Cost {
+                    amount: 2.0.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: None,
+            },
+            UsageTypeCost {
+                usage_type: "DataTransfer-Regional-Bytes".to_string(),
+                cost: Cost {
+                    amount: 0.5.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: None,
+            },
+        ];
+
+        let actual_cost = sum_matching_usage_types(
+            &sample_usage_type_costs,
+            &Config::default().data_transfer_patterns,
+        );
+
+        assert_eq!(
+            Cost {
+                amount: 18.5.into(),
                 unit: "USD".to_string(),
             },
-        };
+            actual_cost
+        );
+    }
 
-        let sample_service_costs = vec![
-            ServiceCost {
-                service_name: "AWS CloudTrail".to_string(),
+    #[test]
+    fn append_data_transfer_cost_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、42.00 USDです。".to_string(),
+            body: "・Amazon Elastic Compute Cloud - Compute: 42.00 USD".to_string(),
+            ..Default::default()
+        };
+        let sample_usage_type_costs = vec![
+            UsageTypeCost {
+                usage_type: "APN1-DataTransfer-Out-Bytes".to_string(),
                 cost: Cost {
-                    amount: 1.234,
+                    amount: 12.0.into(),
                     unit: "USD".to_string(),
                 },
+                usage_quantity: None,
             },
-            ServiceCost {
-                service_name: "AWS Cost Explorer".to_string(),
+            UsageTypeCost {
+                usage_type: "APN1-BoxUsage:t3.micro".to_string(),
                 cost: Cost {
-                    amount: 0.123,
+                    amount: 30.0.into(),
                     unit: "USD".to_string(),
                 },
+                usage_quantity: None,
             },
         ];
 
-        let actual_message = NotificationMessage::new(sample_total_cost, sample_service_costs);
+        let actual_message =
+            sample_message.with_data_transfer_cost(sample_usage_type_costs, &Config::default());
 
         assert_eq!(
-            "07/01~07/11の請求額は、1.36 USDです。",
-            actual_message.header,
+            "・Amazon Elastic Compute Cloud - Compute: 42.00 USD\n\nデータ転送料: 12.00 USD",
+            actual_message.body
         );
+    }
+
+    #[test]
+    fn append_data_transfer_cost_with_usage_quantity() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、42.00 USDです。".to_string(),
+            body: "・Amazon Elastic Compute Cloud - Compute: 42.00 USD".to_string(),
+            ..Default::default()
+        };
+        let sample_usage_type_costs = vec![
+            UsageTypeCost {
+                usage_type: "APN1-DataTransfer-Out-Bytes".to_string(),
+                cost: Cost {
+                    amount: 12.0.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: Some(Cost {
+                    amount: 100.25.into(),
+                    unit: "GB".to_string(),
+                }),
+            },
+            UsageTypeCost {
+                usage_type: "DataTransfer-Regional-Bytes".to_string(),
+                cost: Cost {
+                    amount: 0.5.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: Some(Cost {
+                    amount: 20.25.into(),
+                    unit: "GB".to_string(),
+                }),
+            },
+            UsageTypeCost {
+                usage_type: "APN1-BoxUsage:t3.micro".to_string(),
+                cost: Cost {
+                    amount: 30.0.into(),
+                    unit: "USD".to_string(),
+                },
+                usage_quantity: Some(Cost {
+                    amount: 720.0.into(),
+                    unit: "Hrs".to_string(),
+                }),
+            },
+        ];
+        let config = Config {
+            metric_decimals: vec![(USAGE_METRIC.to_string(), 2)].into_iter().collect(),
+            ..Config::default()
+        };
+
+        let actual_message =
+            sample_message.with_data_transfer_cost(sample_usage_type_costs, &config);
 
         assert_eq!(
-            "・AWS CloudTrail: 1.23 USD\n・AWS Cost Explorer: 0.12 USD",
-            actual_message.body,
+            "・Amazon Elastic Compute Cloud - Compute: 42.00 USD\n\nデータ転送料: 12.50 USD / 120.50 GB",
+            actual_message.body
         );
     }
 
     #[test]
-    fn sort_service_costs_by_descending_order_correctly() {
-        let sample_total_cost = TotalCost {
-            date_range: ReportedDateRange {
-                start_date: Local.ymd(2021, 7, 1),
-                end_date: Local.ymd(2021, 7, 11),
-            },
+    fn format_data_transfer_cost_by_decimals_of_cost_metric() {
+        let sample_usage_type_costs = vec![UsageTypeCost {
+            usage_type: "APN1-DataTransfer-Out-Bytes".to_string(),
             cost: Cost {
-                amount: 1.6234,
-                unit: "USD".to_string(),
+                amount: 1234.5.into(),
+                unit: "JPY".to_string(),
             },
+            usage_quantity: None,
+        }];
+        let config = Config {
+            cost_metric: CostMetric::Unblended,
+            metric_decimals: vec![
+                ("UnblendedCost".to_string(), 0),
+                ("AmortizedCost".to_string(), 3),
+            ]
+            .into_iter()
+            .collect(),
+            ..Config::default()
         };
 
-        let sample_service_costs = vec![
-            ServiceCost {
-                service_name: "AWS Service A".to_string(),
+        let actual_message = NotificationMessage::default()
+            .with_data_transfer_cost(sample_usage_type_costs, &config);
+
+        assert_eq!("データ転送料: 1235 JPY", actual_message.body);
+    }
+
+    #[test]
+    fn append_generated_at_in_designated_timezone() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/23の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+        let generated_at = Utc.ymd(2021, 7, 23).and_hms(0, 0, 0).with_timezone(&Tokyo);
+
+        let actual_message = sample_message.with_generated_at(&generated_at);
+
+        assert_eq!(
+            "・AWS CloudTrail: 1.62 USD\n\n生成時刻: 2021-07-23 09:00 JST",
+            actual_message.body
+        );
+    }
+
+    fn sample_daily_costs() -> Vec<DailyCost<Local>> {
+        vec![
+            DailyCost {
+                date: Local.ymd(2021, 7, 1),
                 cost: Cost {
-                    amount: 1.0,
+                    amount: 1.0.into(),
                     unit: "USD".to_string(),
                 },
+                estimated: false,
             },
-            ServiceCost {
-                service_name: "AWS Service B".to_string(),
+            DailyCost {
+                date: Local.ymd(2021, 7, 2),
                 cost: Cost {
-                    amount: 3.0,
+                    amount: 2.0.into(),
                     unit: "USD".to_string(),
                 },
+                estimated: false,
             },
-            ServiceCost {
-                service_name: "AWS Service C".to_string(),
+            DailyCost {
+                date: Local.ymd(2021, 7, 3),
                 cost: Cost {
-                    amount: 2.0,
+                    amount: 0.3.into(),
                     unit: "USD".to_string(),
                 },
+                estimated: false,
             },
-        ];
+        ]
+    }
+
+    #[test]
+    fn append_daily_costs_with_incomplete_day() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/04の請求額は、3.30 USDです。".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
 
-        let actual_message = NotificationMessage::new(sample_total_cost, sample_service_costs);
+        let actual_message = sample_message.with_daily_costs(sample_daily_costs(), None);
 
         assert_eq!(
-            "・AWS Service B: 3.00 USD\n・AWS Service C: 2.00 USD\n・AWS Service A: 1.00 USD",
-            actual_message.body,
+            "【日別】\n・07/01: 1.00 USD\n・07/02: 2.00 USD\n・07/03: 0.30 USD\n平均: 1.10 USD",
+            actual_message.body
         );
     }
 
     #[test]
-    fn message_line_is_not_displayed_when_cost_is_zero() {
+    fn append_daily_costs_excluding_incomplete_day() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/04の請求額は、3.30 USDです。".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message =
+            sample_message.with_daily_costs(sample_daily_costs(), Some(&Local.ymd(2021, 7, 3)));
+
+        assert_eq!(
+            "【日別】(本日分を除く)\n・07/01: 1.00 USD\n・07/02: 2.00 USD\n平均: 1.50 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn keep_day_before_today_as_end_date_is_exclusive() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/04の請求額は、3.30 USDです。".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_message =
+            sample_message.with_daily_costs(sample_daily_costs(), Some(&Local.ymd(2021, 7, 4)));
+
+        assert_eq!(
+            "【日別】(本日分を除く)\n・07/01: 1.00 USD\n・07/02: 2.00 USD\n・07/03: 0.30 USD\n平均: 1.10 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn mark_estimated_daily_costs() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/04の請求額は、3.30 USDです。".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+        let mut daily_costs = sample_daily_costs();
+        daily_costs[1].estimated = true;
+        daily_costs[2].estimated = true;
+
+        let actual_message = sample_message.with_daily_costs(daily_costs, None);
+
+        assert_eq!(
+            "【日別】\n・07/01: 1.00 USD\n・07/02: 2.00 USD (見込み)\n・07/03: 0.30 USD (見込み)\n平均: 1.10 USD",
+            actual_message.body
+        );
+    }
+
+    #[test]
+    fn display_consumption_against_service_budgets() {
         let sample_total_cost = TotalCost {
             date_range: ReportedDateRange {
                 start_date: Local.ymd(2021, 7, 1),
                 end_date: Local.ymd(2021, 7, 11),
             },
             cost: Cost {
-                amount: 0.01,
+                amount: 215.0.into(),
                 unit: "USD".to_string(),
             },
         };
 
         let sample_service_costs = vec![
             ServiceCost {
-                service_name: "AWS CloudTrail".to_string(),
+                service_name: "Amazon Elastic Compute Cloud - Compute".to_string(),
                 cost: Cost {
-                    amount: 0.01,
+                    amount: 80.0.into(),
                     unit: "USD".to_string(),
                 },
             },
             ServiceCost {
-                service_name: "AWS Cost Explorer".to_string(),
+                service_name: "Amazon Relational Database Service".to_string(),
                 cost: Cost {
-                    amount: 0.001,
+                    amount: 120.0.into(),
                     unit: "USD".to_string(),
                 },
             },
             ServiceCost {
-                service_name: "AWS Dummy Service".to_string(),
+                service_name: "AWS Lambda".to_string(),
                 cost: Cost {
-                    amount: 0.005,
+                    amount: 15.0.into(),
                     unit: "USD".to_string(),
                 },
             },
         ];
+        let config = Config {
+            service_budgets: vec![
+                ("Amazon Elastic Compute Cloud - Compute".to_string(), 100.0),
+                ("Amazon Relational Database Service".to_string(), 100.0),
+            ]
+            .into_iter()
+            .collect(),
+            ..Config::default()
+        };
 
-        let actual_message = NotificationMessage::new(sample_total_cost, sample_service_costs);
+        let actual_message =
+            NotificationMessage::new(sample_total_cost, sample_service_costs, &config);
 
         assert_eq!(
-            "07/01~07/11の請求額は、0.01 USDです。",
-            actual_message.header,
+            "・Amazon Relational Database Service: 120.00/100.00 USD (120%) ⚠️予算超過\n\
+            ・Amazon Elastic Compute Cloud - Compute: 80.00/100.00 USD (80%)\n\
+            ・AWS Lambda: 15.00 USD",
+            actual_message.body,
         );
-
-        assert_eq!("・AWS CloudTrail: 0.01 USD", actual_message.body,);
+        assert_eq!(Severity::Warning, actual_message.severity);
     }
 }
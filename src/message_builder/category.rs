@@ -0,0 +1,176 @@
+use crate::cost_explorer::cost_response_parser::{Cost, ServiceCost};
+use std::collections::HashMap;
+
+/// Category of services which are not mapped to any category.
+pub const OTHER_CATEGORY: &str = "Other";
+
+/// Built-in mapping from service names to their categories.
+const BUILT_IN_CATEGORIES: &[(&str, &str)] = &[
+    ("Amazon Elastic Compute Cloud - Compute", "Compute"),
+    ("EC2 - Other", "Compute"),
+    ("AWS Lambda", "Compute"),
+    ("Amazon Elastic Container Service", "Compute"),
+    ("Amazon Elastic Container Service for Kubernetes", "Compute"),
+    ("Amazon EC2 Container Registry (ECR)", "Compute"),
+    ("Amazon Lightsail", "Compute"),
+    ("Amazon Simple Storage Service", "Storage"),
+    ("Amazon Elastic File System", "Storage"),
+    ("Amazon S3 Glacier", "Storage"),
+    ("AWS Backup", "Storage"),
+    ("Amazon Relational Database Service", "Database"),
+    ("Amazon DynamoDB", "Database"),
+    ("Amazon ElastiCache", "Database"),
+    ("Amazon Redshift", "Database"),
+    ("Amazon DocumentDB (with MongoDB compatibility)", "Database"),
+    ("Amazon Virtual Private Cloud", "Networking"),
+    ("Amazon CloudFront", "Networking"),
+    ("Amazon Route 53", "Networking"),
+    ("Elastic Load Balancing", "Networking"),
+    ("Amazon API Gateway", "Networking"),
+    ("AWS Direct Connect", "Networking"),
+];
+
+/// Service costs belonging to a category and their subtotal.
+#[derive(Debug, PartialEq)]
+pub struct CategoryCost {
+    pub category: String,
+    pub subtotal: Cost,
+    pub service_costs: Vec<ServiceCost>,
+}
+
+/// Category of the service.
+///
+/// `custom_categories` takes precedence over the built-in mapping
+/// and unmapped services belong to `OTHER_CATEGORY`.
+pub fn category_of<'a>(
+    service_name: &str,
+    custom_categories: &'a HashMap<String, String>,
+) -> &'a str {
+    if let Some(category) = custom_categories.get(service_name) {
+        return category;
+    }
+    BUILT_IN_CATEGORIES
+        .iter()
+        .find(|(name, _)| *name == service_name)
+        .map(|(_, category)| *category)
+        .unwrap_or(OTHER_CATEGORY)
+}
+
+/// Group the service costs by their categories and sum up the subtotals.
+///
+/// The categories are ordered by descending subtotal,
+/// except for `OTHER_CATEGORY` which is always the last.
+pub fn group_by_category(
+    service_costs: &[ServiceCost],
+    custom_categories: &HashMap<String, String>,
+) -> Vec<CategoryCost> {
+    let mut categories: Vec<CategoryCost> = vec![];
+    for service_cost in service_costs {
+        let category = category_of(&service_cost.service_name, custom_categories);
+        match categories.iter_mut().find(|x| x.category == category) {
+            Some(x) => {
+                x.subtotal.amount += service_cost.cost.amount;
+                x.service_costs.push(service_cost.clone());
+            }
+            None => categories.push(CategoryCost {
+                category: category.to_string(),
+                subtotal: service_cost.cost.clone(),
+                service_costs: vec![service_cost.clone()],
+            }),
+        }
+    }
+
+    categories.sort_by(|a, b| {
+        (a.category == OTHER_CATEGORY)
+            .cmp(&(b.category == OTHER_CATEGORY))
+            .then(b.subtotal.cmp(&a.subtotal))
+    });
+    categories
+}
+
+#[cfg(test)]
+mod test_category {
+    use super::*;
+
+    fn service_cost(service_name: &str, amount: f64) -> ServiceCost {
+        ServiceCost {
+            service_name: service_name.to_string(),
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn map_services_into_categories_correctly() {
+        let custom_categories: HashMap<String, String> =
+            vec![("AWS Lambda".to_string(), "Serverless".to_string())]
+                .into_iter()
+                .collect();
+
+        assert_eq!(
+            "Compute",
+            category_of("Amazon Elastic Compute Cloud - Compute", &custom_categories)
+        );
+        assert_eq!(
+            "Storage",
+            category_of("Amazon Simple Storage Service", &custom_categories)
+        );
+        assert_eq!("Serverless", category_of("AWS Lambda", &custom_categories));
+        assert_eq!("Other", category_of("AWS CloudTrail", &custom_categories));
+    }
+
+    #[test]
+    fn group_service_costs_by_category_correctly() {
+        let service_costs = vec![
+            service_cost("AWS CloudTrail", 50.0),
+            service_cost("Amazon Simple Storage Service", 3.0),
+            service_cost("Amazon Elastic Compute Cloud - Compute", 10.0),
+            service_cost("Amazon DynamoDB", 4.0),
+            service_cost("AWS Lambda", 2.5),
+        ];
+
+        let expected_categories = vec![
+            CategoryCost {
+                category: "Compute".to_string(),
+                subtotal: Cost {
+                    amount: 12.5.into(),
+                    unit: "USD".to_string(),
+                },
+                service_costs: vec![
+                    service_cost("Amazon Elastic Compute Cloud - Compute", 10.0),
+                    service_cost("AWS Lambda", 2.5),
+                ],
+            },
+            CategoryCost {
+                category: "Database".to_string(),
+                subtotal: Cost {
+                    amount: 4.0.into(),
+                    unit: "USD".to_string(),
+                },
+                service_costs: vec![service_cost("Amazon DynamoDB", 4.0)],
+            },
+            CategoryCost {
+                category: "Storage".to_string(),
+                subtotal: Cost {
+                    amount: 3.0.into(),
+                    unit: "USD".to_string(),
+                },
+                service_costs: vec![service_cost("Amazon Simple Storage Service", 3.0)],
+            },
+            CategoryCost {
+                category: "Other".to_string(),
+                subtotal: Cost {
+                    amount: 50.0.into(),
+                    unit: "USD".to_string(),
+                },
+                service_costs: vec![service_cost("AWS CloudTrail", 50.0)],
+            },
+        ];
+
+        let actual_categories = group_by_category(&service_costs, &HashMap::new());
+
+        assert_eq!(expected_categories, actual_categories);
+    }
+}
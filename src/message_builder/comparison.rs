@@ -0,0 +1,569 @@
+use crate::config::Config;
+use crate::cost_explorer::amount::Amount;
+use crate::cost_explorer::cost_response_parser::{Cost, ServiceCost, UsageTypeCost};
+use std::collections::HashMap;
+
+/// Costs less than this amount are regarded as zero (displayed as `0.00`).
+pub const NEGLIGIBLE_AMOUNT: Amount = Amount::new(5, 3);
+
+/// Thresholds of a change to be regarded as notable.
+///
+/// A change is notable only if it exceeds both of them, so that
+/// a trivial change (e.g. 0.01 USD -> 0.03 USD, +200%) is ignored.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChangeTolerance {
+    /// Changes within this percentage are regarded as unchanged.
+    pub percent: f64,
+    /// Changes within this amount are regarded as unchanged.
+    pub amount: f64,
+}
+impl ChangeTolerance {
+    pub fn from_config(config: &Config) -> Self {
+        ChangeTolerance {
+            percent: config.unchanged_tolerance_percent,
+            amount: config.unchanged_tolerance_amount,
+        }
+    }
+
+    /// Whether the change from `previous` to `current` exceeds both thresholds.
+    ///
+    /// The percentage is not checked if `previous` is zero.
+    pub fn is_exceeded(&self, previous: Amount, current: Amount) -> bool {
+        if (current - previous).abs().to_f64() <= self.amount {
+            return false;
+        }
+        match percentage_change(previous, current) {
+            Some(percentage) => percentage.abs() > self.percent,
+            None => true,
+        }
+    }
+}
+
+/// Cost of a service in the previous and current periods.
+#[derive(Debug, PartialEq)]
+pub struct ServiceCostChange {
+    pub service_name: String,
+    /// Cost in the previous period (zero if the service did not appear).
+    pub previous: Cost,
+    /// Cost in the current period (zero if the service did not appear).
+    pub current: Cost,
+}
+
+impl ServiceCostChange {
+    /// Increase of the amount from the previous period.
+    pub fn difference(&self) -> Amount {
+        self.current.amount - self.previous.amount
+    }
+
+    /// Classify the change into `Trend`.
+    ///
+    /// Changes within `tolerance` (and services not charged
+    /// in both periods) are regarded as `Trend::Stable`.
+    pub fn trend(&self, tolerance: &ChangeTolerance) -> Trend {
+        if self.is_negligible() || !tolerance.is_exceeded(self.previous.amount, self.current.amount)
+        {
+            return Trend::Stable;
+        }
+        match percentage_change(self.previous.amount, self.current.amount) {
+            None => Trend::New,
+            Some(percentage) if percentage > 0.0 => Trend::Growing,
+            Some(_) => Trend::Shrinking,
+        }
+    }
+
+    /// Whether the service was charged in the previous period but not in the current one.
+    pub fn is_dropped_to_zero(&self) -> bool {
+        self.previous.amount.abs() >= NEGLIGIBLE_AMOUNT
+            && self.current.amount.abs() < NEGLIGIBLE_AMOUNT
+    }
+
+    /// Whether the service is not charged in both periods.
+    pub fn is_negligible(&self) -> bool {
+        self.previous.amount.abs() < NEGLIGIBLE_AMOUNT
+            && self.current.amount.abs() < NEGLIGIBLE_AMOUNT
+    }
+}
+
+/// Trend of the cost of a service since the previous period.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Trend {
+    /// Not charged in the previous period.
+    New,
+    Growing,
+    Shrinking,
+    /// Changed within the tolerance.
+    Stable,
+}
+impl Trend {
+    /// Trends in the displayed order.
+    pub const ALL: [Trend; 4] = [Trend::New, Trend::Growing, Trend::Shrinking, Trend::Stable];
+
+    /// Label displayed as the header of the group.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Trend::New => "新規",
+            Trend::Growing => "増加",
+            Trend::Shrinking => "減少",
+            Trend::Stable => "横ばい",
+        }
+    }
+}
+
+/// Format the change from `previous` to `current`.
+///
+/// # Example
+///
+/// ```
+/// let previous = Cost {
+///     amount: Amount::new(100, 0),
+///     unit: "USD".to_string(),
+/// };
+/// let current = Cost {
+///     amount: Amount::new(120, 0),
+///     unit: "USD".to_string(),
+/// };
+/// assert_eq!(
+///     "100.00 USD → 120.00 USD (+20.00 USD, +20.0%)",
+///     format_change(&previous, &current)
+/// );
+/// ```
+pub fn format_change(previous: &Cost, current: &Cost) -> String {
+    let difference = Cost {
+        amount: current.amount - previous.amount,
+        unit: current.unit.clone(),
+    };
+    let sign = if difference.amount.is_negative() {
+        ""
+    } else {
+        "+"
+    };
+    let percentage = match percentage_change(previous.amount, current.amount) {
+        Some(percentage) => format!("{}{:.1}%", sign, percentage),
+        None => "新規".to_string(),
+    };
+    format!(
+        "{} → {} ({}{}, {})",
+        previous, current, sign, difference, percentage
+    )
+}
+
+/// Percentage change from `previous` to `current`. (e.g. 100.0 -> 112.3: `Some(12.3)`)
+///
+/// Returns None if `previous` is zero.
+pub fn percentage_change(previous: Amount, current: Amount) -> Option<f64> {
+    if previous.abs() < NEGLIGIBLE_AMOUNT {
+        return None;
+    }
+    Some((current - previous).to_f64() / previous.to_f64() * 100.0)
+}
+
+/// Canonical name of the service, which is looked up in `aliases`.
+///
+/// AWS occasionally renames services, so `aliases` maps the old names
+/// to the current ones (e.g. `Amazon EC2` -> `Amazon Elastic Compute Cloud`).
+pub fn normalize_service_name<'a>(
+    service_name: &'a str,
+    aliases: &'a HashMap<String, String>,
+) -> &'a str {
+    aliases
+        .get(service_name)
+        .map(|x| x.as_str())
+        .unwrap_or(service_name)
+}
+
+/// Normalize the service names and sum up the costs of the same service.
+fn normalize_service_costs(
+    service_costs: &[ServiceCost],
+    aliases: &HashMap<String, String>,
+) -> Vec<ServiceCost> {
+    let mut normalized: Vec<ServiceCost> = vec![];
+    for service_cost in service_costs {
+        let service_name = normalize_service_name(&service_cost.service_name, aliases);
+        match normalized
+            .iter_mut()
+            .find(|x| x.service_name == service_name)
+        {
+            Some(x) => x.cost.amount += service_cost.cost.amount,
+            None => normalized.push(ServiceCost {
+                service_name: service_name.to_string(),
+                cost: service_cost.cost.clone(),
+            }),
+        }
+    }
+    normalized
+}
+
+/// Match the service costs of the previous and current periods
+/// by service names normalized with `aliases`.
+///
+/// The changes are ordered as `current`, followed by services
+/// which appear only in `previous`.
+pub fn diff_service_costs(
+    previous: &[ServiceCost],
+    current: &[ServiceCost],
+    aliases: &HashMap<String, String>,
+) -> Vec<ServiceCostChange> {
+    let previous = normalize_service_costs(previous, aliases);
+    let current = normalize_service_costs(current, aliases);
+    let zero_of = |cost: &Cost| Cost {
+        amount: Amount::ZERO,
+        unit: cost.unit.clone(),
+    };
+
+    let mut changes: Vec<ServiceCostChange> = current
+        .iter()
+        .map(|x| ServiceCostChange {
+            service_name: x.service_name.clone(),
+            previous: match previous.iter().find(|y| y.service_name == x.service_name) {
+                Some(y) => y.cost.clone(),
+                None => zero_of(&x.cost),
+            },
+            current: x.cost.clone(),
+        })
+        .collect();
+
+    changes.extend(
+        previous
+            .iter()
+            .filter(|x| !current.iter().any(|y| y.service_name == x.service_name))
+            .map(|x| ServiceCostChange {
+                service_name: x.service_name.clone(),
+                previous: x.cost.clone(),
+                current: zero_of(&x.cost),
+            }),
+    );
+    changes
+}
+
+/// Usage type whose cost has increased the most since the previous period
+/// and the amount of the increase.
+///
+/// Returns None if no usage type has increased.
+pub fn top_increasing_usage_type(
+    previous: &[UsageTypeCost],
+    current: &[UsageTypeCost],
+) -> Option<UsageTypeCost> {
+    current
+        .iter()
+        .map(|x| {
+            let previous_amount: Amount = previous
+                .iter()
+                .filter(|y| y.usage_type == x.usage_type)
+                .map(|y| y.cost.amount)
+                .sum();
+            UsageTypeCost {
+                usage_type: x.usage_type.clone(),
+                cost: Cost {
+                    amount: x.cost.amount - previous_amount,
+                    unit: x.cost.unit.clone(),
+                },
+                usage_quantity: None,
+            }
+        })
+        .filter(|x| x.cost.amount >= NEGLIGIBLE_AMOUNT)
+        .max_by(|a, b| a.cost.cmp(&b.cost))
+}
+
+/// Whether nothing notable happened since the previous period.
+///
+/// It is true if the changes of the total cost and every service cost
+/// are within `tolerance`.
+/// Services which are not charged in both periods are ignored,
+/// while a service newly charged beyond the amount of `tolerance` is regarded as notable.
+pub fn is_unchanged(
+    previous_total: &Cost,
+    current_total: &Cost,
+    changes: &[ServiceCostChange],
+    tolerance: &ChangeTolerance,
+) -> bool {
+    let within_tolerance = |previous: Amount, current: Amount| {
+        if previous.abs() < NEGLIGIBLE_AMOUNT && current.abs() < NEGLIGIBLE_AMOUNT {
+            return true;
+        }
+        !tolerance.is_exceeded(previous, current)
+    };
+
+    within_tolerance(previous_total.amount, current_total.amount)
+        && changes
+            .iter()
+            .all(|x| within_tolerance(x.previous.amount, x.current.amount))
+}
+
+#[cfg(test)]
+mod test_comparison {
+    use super::*;
+
+    fn service_cost(service_name: &str, amount: f64) -> ServiceCost {
+        ServiceCost {
+            service_name: service_name.to_string(),
+            cost: usd(amount),
+        }
+    }
+
+    fn usd(amount: f64) -> Cost {
+        Cost {
+            amount: amount.into(),
+            unit: "USD".to_string(),
+        }
+    }
+
+    fn percent_tolerance(percent: f64) -> ChangeTolerance {
+        ChangeTolerance {
+            percent,
+            amount: 0.0,
+        }
+    }
+
+    #[test]
+    fn calculate_percentage_change_correctly() {
+        assert_eq!(Some(50.0), percentage_change(100.0.into(), 150.0.into()));
+        assert_eq!(Some(-25.0), percentage_change(100.0.into(), 75.0.into()));
+        assert_eq!(None, percentage_change(0.0.into(), 75.0.into()));
+    }
+
+    #[test]
+    fn classify_changes_into_trends_correctly() {
+        let change = |previous: f64, current: f64| ServiceCostChange {
+            service_name: "AWS Lambda".to_string(),
+            previous: usd(previous),
+            current: usd(current),
+        };
+        let tolerance = percent_tolerance(5.0);
+
+        assert_eq!(Trend::New, change(0.0, 3.0).trend(&tolerance));
+        assert_eq!(Trend::Growing, change(100.0, 110.0).trend(&tolerance));
+        assert_eq!(Trend::Shrinking, change(100.0, 90.0).trend(&tolerance));
+        assert_eq!(Trend::Shrinking, change(100.0, 0.0).trend(&tolerance));
+        assert_eq!(Trend::Stable, change(100.0, 104.0).trend(&tolerance));
+        assert_eq!(Trend::Stable, change(0.0, 0.0).trend(&tolerance));
+    }
+
+    #[test]
+    fn require_both_percentage_and_amount_to_exceed_tolerance() {
+        let change = |previous: f64, current: f64| ServiceCostChange {
+            service_name: "AWS Lambda".to_string(),
+            previous: usd(previous),
+            current: usd(current),
+        };
+        let tolerance = ChangeTolerance {
+            percent: 5.0,
+            amount: 1.0,
+        };
+
+        // +200% but only +0.02 USD
+        assert_eq!(Trend::Stable, change(0.01, 0.03).trend(&tolerance));
+        // +2.00 USD but only +2%
+        assert_eq!(Trend::Stable, change(100.0, 102.0).trend(&tolerance));
+        // newly charged but less than the amount
+        assert_eq!(Trend::Stable, change(0.0, 0.5).trend(&tolerance));
+        assert_eq!(Trend::Growing, change(10.0, 12.0).trend(&tolerance));
+        assert_eq!(Trend::Shrinking, change(10.0, 8.0).trend(&tolerance));
+        assert_eq!(Trend::New, change(0.0, 3.0).trend(&tolerance));
+        assert!(tolerance.is_exceeded(10.0.into(), 12.0.into()));
+        assert!(!tolerance.is_exceeded(0.01.into(), 0.03.into()));
+        assert!(!tolerance.is_exceeded(100.0.into(), 102.0.into()));
+    }
+
+    #[test]
+    fn regard_trivial_changes_as_unchanged_by_amount() {
+        let previous = vec![
+            service_cost("AWS CloudTrail", 100.0),
+            service_cost("AWS Lambda", 0.01),
+        ];
+        let current = vec![
+            service_cost("AWS CloudTrail", 100.0),
+            service_cost("AWS Lambda", 0.03),
+            service_cost("AWS Glue", 0.5),
+        ];
+        let changes = diff_service_costs(&previous, &current, &HashMap::new());
+        let tolerance = ChangeTolerance {
+            percent: 5.0,
+            amount: 1.0,
+        };
+
+        assert!(is_unchanged(
+            &usd(100.01),
+            &usd(100.53),
+            &changes,
+            &tolerance
+        ));
+        assert!(!is_unchanged(
+            &usd(100.01),
+            &usd(100.53),
+            &changes,
+            &percent_tolerance(5.0)
+        ));
+    }
+
+    #[test]
+    fn detect_services_dropped_to_zero() {
+        let change = |previous: f64, current: f64| ServiceCostChange {
+            service_name: "AWS Glue".to_string(),
+            previous: usd(previous),
+            current: usd(current),
+        };
+
+        assert!(change(12.0, 0.0).is_dropped_to_zero());
+        assert!(change(12.0, 0.001).is_dropped_to_zero());
+        assert!(!change(12.0, 0.5).is_dropped_to_zero());
+        assert!(!change(0.0, 0.0).is_dropped_to_zero());
+        assert!(!change(0.0, 3.0).is_dropped_to_zero());
+    }
+
+    #[test]
+    fn format_change_correctly() {
+        assert_eq!(
+            "100.00 USD → 120.00 USD (+20.00 USD, +20.0%)",
+            format_change(&usd(100.0), &usd(120.0))
+        );
+        assert_eq!(
+            "100.00 USD → 75.00 USD (-25.00 USD, -25.0%)",
+            format_change(&usd(100.0), &usd(75.0))
+        );
+        assert_eq!(
+            "0.00 USD → 3.00 USD (+3.00 USD, 新規)",
+            format_change(&usd(0.0), &usd(3.0))
+        );
+    }
+
+    #[test]
+    fn diff_service_costs_correctly() {
+        let previous = vec![
+            service_cost("AWS CloudTrail", 1.0),
+            service_cost("AWS Lambda", 2.0),
+        ];
+        let current = vec![
+            service_cost("Amazon Simple Storage Service", 3.0),
+            service_cost("AWS CloudTrail", 1.5),
+        ];
+
+        let expected_changes = vec![
+            ServiceCostChange {
+                service_name: "Amazon Simple Storage Service".to_string(),
+                previous: usd(0.0),
+                current: usd(3.0),
+            },
+            ServiceCostChange {
+                service_name: "AWS CloudTrail".to_string(),
+                previous: usd(1.0),
+                current: usd(1.5),
+            },
+            ServiceCostChange {
+                service_name: "AWS Lambda".to_string(),
+                previous: usd(2.0),
+                current: usd(0.0),
+            },
+        ];
+
+        let actual_changes = diff_service_costs(&previous, &current, &HashMap::new());
+
+        assert_eq!(expected_changes, actual_changes);
+    }
+
+    #[test]
+    fn select_top_increasing_usage_type_correctly() {
+        let usage_type_cost = |usage_type: &str, amount: f64| UsageTypeCost {
+            usage_type: usage_type.to_string(),
+            cost: usd(amount),
+            usage_quantity: None,
+        };
+        let previous = vec![
+            usage_type_cost("APN1-DataTransfer-Out-Bytes", 10.0),
+            usage_type_cost("APN1-BoxUsage:t3.micro", 50.0),
+            usage_type_cost("APN1-TimedStorage-ByteHrs", 5.0),
+        ];
+        let current = vec![
+            usage_type_cost("APN1-DataTransfer-Out-Bytes", 40.0),
+            usage_type_cost("APN1-BoxUsage:t3.micro", 30.0),
+            usage_type_cost("APN1-Requests-Tier1", 20.0),
+        ];
+
+        assert_eq!(
+            Some(usage_type_cost("APN1-DataTransfer-Out-Bytes", 30.0)),
+            top_increasing_usage_type(&previous, &current)
+        );
+        assert_eq!(None, top_increasing_usage_type(&current, &previous[0..1]));
+    }
+
+    #[test]
+    fn regard_small_changes_as_unchanged() {
+        let previous = vec![
+            service_cost("AWS CloudTrail", 100.0),
+            service_cost("AWS Cost Explorer", 0.001),
+        ];
+        let current = vec![
+            service_cost("AWS CloudTrail", 103.0),
+            service_cost("AWS Cost Explorer", 0.0),
+        ];
+        let changes = diff_service_costs(&previous, &current, &HashMap::new());
+        let tolerance = percent_tolerance(5.0);
+
+        assert!(is_unchanged(&usd(100.0), &usd(103.0), &changes, &tolerance));
+    }
+
+    #[test]
+    fn regard_large_service_change_as_notable() {
+        let previous = vec![
+            service_cost("AWS CloudTrail", 100.0),
+            service_cost("AWS Lambda", 1.0),
+        ];
+        let current = vec![
+            service_cost("AWS CloudTrail", 100.0),
+            service_cost("AWS Lambda", 2.0),
+        ];
+        let changes = diff_service_costs(&previous, &current, &HashMap::new());
+        let tolerance = percent_tolerance(5.0);
+
+        assert!(!is_unchanged(
+            &usd(101.0),
+            &usd(102.0),
+            &changes,
+            &tolerance
+        ));
+    }
+
+    #[test]
+    fn regard_new_service_as_notable() {
+        let previous = vec![service_cost("AWS CloudTrail", 100.0)];
+        let current = vec![
+            service_cost("AWS CloudTrail", 100.0),
+            service_cost("AWS Lambda", 1.0),
+        ];
+        let changes = diff_service_costs(&previous, &current, &HashMap::new());
+        let tolerance = percent_tolerance(5.0);
+
+        assert!(!is_unchanged(
+            &usd(100.0),
+            &usd(101.0),
+            &changes,
+            &tolerance
+        ));
+    }
+
+    #[test]
+    fn match_renamed_service_by_alias() {
+        let aliases: HashMap<String, String> = vec![(
+            "Amazon EC2".to_string(),
+            "Amazon Elastic Compute Cloud".to_string(),
+        )]
+        .into_iter()
+        .collect();
+        let previous = vec![service_cost("Amazon EC2", 10.0)];
+        let current = vec![service_cost("Amazon Elastic Compute Cloud", 12.0)];
+
+        let expected_changes = vec![ServiceCostChange {
+            service_name: "Amazon Elastic Compute Cloud".to_string(),
+            previous: usd(10.0),
+            current: usd(12.0),
+        }];
+
+        assert_eq!(
+            expected_changes,
+            diff_service_costs(&previous, &current, &aliases)
+        );
+        assert_eq!(
+            2,
+            diff_service_costs(&previous, &current, &HashMap::new()).len()
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use crate::cost_explorer::cost_response_parser::ServiceCost;
+
+/// Render the service costs as CSV with the header row `service_name,amount,unit`.
+///
+/// The rows are sorted in descending order by amount like the message body.
+pub fn service_costs_to_csv(costs: &[ServiceCost]) -> String {
+    let mut sorted_costs: Vec<&ServiceCost> = costs.iter().collect();
+    sorted_costs.sort_by(|a, b| b.cost.cmp(&a.cost));
+
+    let mut lines = vec!["service_name,amount,unit".to_string()];
+    lines.extend(sorted_costs.iter().map(|x| {
+        format!(
+            "{},{},{}",
+            quote_field(&x.service_name),
+            x.cost.amount,
+            quote_field(&x.cost.unit)
+        )
+    }));
+    lines.join("\n")
+}
+
+/// Quote `field` with double quotes if it contains a comma, a double quote or a line break,
+/// doubling the double quotes in it. [See RFC 4180](https://www.rfc-editor.org/rfc/rfc4180)
+fn quote_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test_csv {
+    use super::*;
+    use crate::cost_explorer::cost_response_parser::Cost;
+
+    fn service_cost(service_name: &str, amount: f64) -> ServiceCost {
+        ServiceCost {
+            service_name: service_name.to_string(),
+            cost: Cost {
+                amount: amount.into(),
+                unit: "USD".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn sort_rows_by_amount_in_descending_order() {
+        let input_costs = vec![
+            service_cost("AWS CloudTrail", 0.01),
+            service_cost("AWS Lambda", 1.5),
+            service_cost("Amazon S3", 0.25),
+        ];
+
+        assert_eq!(
+            "service_name,amount,unit\nAWS Lambda,1.5,USD\nAmazon S3,0.25,USD\nAWS CloudTrail,0.01,USD",
+            service_costs_to_csv(&input_costs)
+        );
+    }
+
+    #[test]
+    fn quote_service_names_with_special_characters() {
+        let input_costs = vec![
+            service_cost("AWS Cost Explorer, Inc.", 0.18),
+            service_cost("The \"Best\" Service", 0.01),
+        ];
+
+        assert_eq!(
+            "service_name,amount,unit\n\"AWS Cost Explorer, Inc.\",0.18,USD\n\"The \"\"Best\"\" Service\",0.01,USD",
+            service_costs_to_csv(&input_costs)
+        );
+    }
+
+    #[test]
+    fn render_summed_amount_without_rounding_error() {
+        let mut input_cost = service_cost("AWS Lambda", 0.1);
+        input_cost.cost.amount += "0.2".parse().unwrap();
+
+        assert_eq!(
+            "service_name,amount,unit\nAWS Lambda,0.3,USD",
+            service_costs_to_csv(&[input_cost])
+        );
+    }
+
+    #[test]
+    fn render_only_header_without_services() {
+        assert_eq!("service_name,amount,unit", service_costs_to_csv(&[]));
+    }
+}
@@ -0,0 +1,224 @@
+use crate::config::Config;
+use crate::cost_explorer::amount::Amount;
+use crate::cost_explorer::cost_response_parser::Cost;
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Conversion of the costs into the display currency.
+#[derive(Debug, PartialEq)]
+pub struct CurrencyConversion {
+    /// Unit of the display currency (e.g. `JPY`)
+    pub unit: String,
+    /// Amount of the display currency per unit of the original currency
+    pub rate: f64,
+}
+impl CurrencyConversion {
+    /// Read the conversion from `display_currency` and `exchange_rate` of `config`.
+    ///
+    /// Returns None if the costs are displayed in the original currency.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        match (&config.display_currency, config.exchange_rate) {
+            (Some(unit), Some(rate)) => Some(CurrencyConversion {
+                unit: unit.clone(),
+                rate,
+            }),
+            _ => None,
+        }
+    }
+
+    /// # Example
+    ///
+    /// ```
+    /// let conversion = CurrencyConversion {
+    ///     unit: "JPY".to_string(),
+    ///     rate: 110.0,
+    /// };
+    /// let input_cost = Cost {
+    ///     amount: Amount::new(15, 1),
+    ///     unit: "USD".to_string(),
+    /// };
+    /// assert_eq!("¥165", format!("{}", conversion.convert(&input_cost)));
+    /// ```
+    pub fn convert(&self, cost: &Cost) -> Cost {
+        if cost.unit == self.unit {
+            return cost.clone();
+        }
+        Cost {
+            amount: Amount::from(cost.amount.to_f64() * self.rate),
+            unit: self.unit.clone(),
+        }
+    }
+}
+
+/// Currencies without minor units, whose amounts are displayed
+/// with their symbols and without decimals (e.g. `¥1,234,567`).
+const ZERO_DECIMAL_CURRENCIES: &[(&str, &str)] = &[("JPY", "¥")];
+
+/// Symbol of `unit` if it is displayed without decimals.
+pub fn zero_decimal_symbol(unit: &str) -> Option<&'static str> {
+    ZERO_DECIMAL_CURRENCIES
+        .iter()
+        .find(|(currency, _)| *currency == unit)
+        .map(|(_, symbol)| *symbol)
+}
+
+/// Format `amount` rounded to an integer with its digits grouped by three.
+///
+/// # Example
+///
+/// ```
+/// assert_eq!("-1,234,567", group_thousands(Amount::new(-12345674, 1)));
+/// ```
+pub fn group_thousands(amount: Amount) -> String {
+    let rounded = amount.round_dp(0);
+    let digits = rounded.abs().to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    match rounded.is_negative() {
+        true => format!("-{}", grouped),
+        false => grouped,
+    }
+}
+
+/// Convert `cost` into the display currency designated in `config`.
+pub fn convert_cost(cost: &Cost, config: &Config) -> Cost {
+    match CurrencyConversion::from_config(config) {
+        Some(conversion) => conversion.convert(cost),
+        None => cost.clone(),
+    }
+}
+
+/// Replace the units of the costs displayed in `text` with their labels in `labels`.
+///
+/// The labels of currencies displayed with symbols (e.g. `¥1,234`) follow the amounts,
+/// while the other units are replaced as they are.
+///
+/// # Example
+///
+/// ```
+/// let labels = vec![
+///     ("USD".to_string(), "ドル".to_string()),
+///     ("JPY".to_string(), "円".to_string()),
+/// ]
+/// .into_iter()
+/// .collect();
+/// assert_eq!(
+///     "12.00 ドル (1,320円)",
+///     localize_units("12.00 USD (¥1,320)", &labels)
+/// );
+/// ```
+pub fn localize_units(text: &str, labels: &HashMap<String, String>) -> String {
+    let mut localized = text.to_string();
+    for (unit, label) in labels {
+        let label = label.replace('$', "$$");
+        let (pattern, replacement) = match zero_decimal_symbol(unit) {
+            Some(symbol) => (
+                format!(r"(-?){}([0-9][0-9,]*)", regex::escape(symbol)),
+                format!("${{1}}${{2}}{}", label),
+            ),
+            None => (
+                format!(r"([0-9]) {}([^A-Za-z]|$)", regex::escape(unit)),
+                format!("${{1}} {}${{2}}", label),
+            ),
+        };
+        localized = Regex::new(&pattern)
+            .unwrap()
+            .replace_all(&localized, replacement.as_str())
+            .into_owned();
+    }
+    localized
+}
+
+#[cfg(test)]
+mod test_currency {
+    use super::*;
+
+    #[test]
+    fn convert_cost_into_display_currency_correctly() {
+        let config = Config {
+            display_currency: Some("JPY".to_string()),
+            exchange_rate: Some(110.0),
+            ..Config::default()
+        };
+        let input_cost = Cost {
+            amount: 1.5.into(),
+            unit: "USD".to_string(),
+        };
+
+        let expected_cost = Cost {
+            amount: 165.0.into(),
+            unit: "JPY".to_string(),
+        };
+
+        assert_eq!(expected_cost, convert_cost(&input_cost, &config));
+    }
+
+    #[test]
+    fn group_thousands_correctly() {
+        assert_eq!("0", group_thousands(0.0.into()));
+        assert_eq!("999", group_thousands(999.4.into()));
+        assert_eq!("1,000", group_thousands(999.5.into()));
+        assert_eq!("123,456", group_thousands(123456.0.into()));
+        assert_eq!("1,234,567", group_thousands(1234567.0.into()));
+        assert_eq!("-12,345", group_thousands(Amount::new(-12345, 0)));
+    }
+
+    #[test]
+    fn keep_cost_without_display_currency() {
+        let input_cost = Cost {
+            amount: 1.5.into(),
+            unit: "USD".to_string(),
+        };
+
+        assert_eq!(input_cost, convert_cost(&input_cost, &Config::default()));
+    }
+
+    fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(unit, label)| (unit.to_string(), label.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn localize_usd_label() {
+        let labels = labels(&[("USD", "ドル")]);
+
+        assert_eq!(
+            "07/01~07/23の請求額は、1234.50 ドルです。",
+            localize_units("07/01~07/23の請求額は、1234.50 USDです。", &labels)
+        );
+        assert_eq!(
+            "・AWS Glue: 11.00/10.00 ドル (110%)",
+            localize_units("・AWS Glue: 11.00/10.00 USD (110%)", &labels)
+        );
+        assert_eq!(
+            "・USD Billing Tool: 1.00 ドル",
+            localize_units("・USD Billing Tool: 1.00 USD", &labels)
+        );
+        assert_eq!("1.00 USDT", localize_units("1.00 USDT", &labels));
+    }
+
+    #[test]
+    fn localize_jpy_label_after_amount() {
+        let labels = labels(&[("JPY", "円")]);
+
+        assert_eq!(
+            "合計: 1,234,567円 → -5,000円",
+            localize_units("合計: ¥1,234,567 → -¥5,000", &labels)
+        );
+    }
+
+    #[test]
+    fn keep_units_without_labels() {
+        assert_eq!(
+            "1.00 USD, ¥100",
+            localize_units("1.00 USD, ¥100", &HashMap::new())
+        );
+    }
+}
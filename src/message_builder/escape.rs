@@ -0,0 +1,31 @@
+/// Escape the characters which Slack interprets as control sequences in mrkdwn.
+/// [See this](https://api.slack.com/reference/surfaces/formatting#escaping)
+///
+/// # Example
+///
+/// ```
+/// assert_eq!("R&amp;D &lt;test&gt;", escape_mrkdwn("R&D <test>"));
+/// ```
+pub fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod test_escape {
+    use super::*;
+
+    #[test]
+    fn escape_each_special_character_correctly() {
+        assert_eq!("R&amp;D", escape_mrkdwn("R&D"));
+        assert_eq!("&lt;internal", escape_mrkdwn("<internal"));
+        assert_eq!("a -&gt; b", escape_mrkdwn("a -> b"));
+        assert_eq!("AWS Lambda", escape_mrkdwn("AWS Lambda"));
+    }
+
+    #[test]
+    fn escape_ampersand_only_once() {
+        assert_eq!("&amp;lt;", escape_mrkdwn("&lt;"));
+    }
+}
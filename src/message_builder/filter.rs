@@ -0,0 +1,122 @@
+use regex::Regex;
+use std::str::FromStr;
+
+/// Regular expression matched against the service names (e.g. `^AWS (Lambda|Glue)$`).
+#[derive(Debug, Clone)]
+pub struct ServicePattern(Regex);
+impl ServicePattern {
+    /// Whether the pattern matches any part of `service_name`.
+    pub fn is_match(&self, service_name: &str) -> bool {
+        self.0.is_match(service_name)
+    }
+}
+impl PartialEq for ServicePattern {
+    fn eq(&self, other: &ServicePattern) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+impl FromStr for ServicePattern {
+    type Err = regex::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Regex::new(s).map(ServicePattern)
+    }
+}
+
+/// Whether the service is reported under `prefix` and `pattern`.
+///
+/// The service must match both of them if both are set,
+/// and every service is reported if neither is set.
+pub fn is_reported_service(
+    service_name: &str,
+    prefix: Option<&str>,
+    pattern: Option<&ServicePattern>,
+) -> bool {
+    prefix.is_none_or(|x| service_name.starts_with(x))
+        && pattern.is_none_or(|x| x.is_match(service_name))
+}
+
+/// Whether the service is reported under `allowlist` and `blocklist`,
+/// whose items are matched case-insensitively as substrings of `service_name`.
+///
+/// The service must match an item of `allowlist` unless it is empty,
+/// and must not match any item of `blocklist`.
+pub fn is_listed_service(service_name: &str, allowlist: &[String], blocklist: &[String]) -> bool {
+    let service_name = service_name.to_lowercase();
+    let contains = |x: &String| service_name.contains(&x.to_lowercase());
+    (allowlist.is_empty() || allowlist.iter().any(contains)) && !blocklist.iter().any(contains)
+}
+
+#[cfg(test)]
+mod test_filter {
+    use super::*;
+
+    #[test]
+    fn filter_services_by_prefix() {
+        assert!(is_reported_service(
+            "Amazon Simple Storage Service",
+            Some("Amazon "),
+            None
+        ));
+        assert!(!is_reported_service("AWS Lambda", Some("Amazon "), None));
+        assert!(is_reported_service("AWS Lambda", None, None));
+    }
+
+    #[test]
+    fn filter_services_by_pattern() {
+        let pattern: ServicePattern = "^AWS (Lambda|Glue)$".parse().unwrap();
+
+        assert!(is_reported_service("AWS Glue", None, Some(&pattern)));
+        assert!(!is_reported_service(
+            "AWS Lambda@Edge",
+            None,
+            Some(&pattern)
+        ));
+        assert!(!is_reported_service(
+            "AWS Lambda",
+            Some("Amazon "),
+            Some(&pattern)
+        ));
+    }
+
+    #[test]
+    fn filter_services_by_allowlist() {
+        let allowlist = vec!["ec2".to_string(), "Simple Storage".to_string()];
+
+        assert!(is_listed_service("Amazon EC2", &allowlist, &[]));
+        assert!(is_listed_service(
+            "Amazon Simple Storage Service",
+            &allowlist,
+            &[]
+        ));
+        assert!(!is_listed_service("AWS Lambda", &allowlist, &[]));
+    }
+
+    #[test]
+    fn filter_services_by_blocklist() {
+        let blocklist = vec!["cloudtrail".to_string()];
+
+        assert!(!is_listed_service("AWS CloudTrail", &[], &blocklist));
+        assert!(is_listed_service("AWS Lambda", &[], &blocklist));
+    }
+
+    #[test]
+    fn filter_services_by_allowlist_and_blocklist() {
+        let allowlist = vec!["Amazon".to_string()];
+        let blocklist = vec!["EC2 - Other".to_string()];
+
+        assert!(is_listed_service("Amazon EC2", &allowlist, &blocklist));
+        assert!(!is_listed_service("EC2 - Other", &allowlist, &blocklist));
+        assert!(!is_listed_service(
+            "Amazon EC2 - Other",
+            &allowlist,
+            &blocklist
+        ));
+        assert!(!is_listed_service("AWS Lambda", &allowlist, &blocklist));
+    }
+
+    #[test]
+    fn reject_invalid_pattern() {
+        assert!("AWS (Lambda".parse::<ServicePattern>().is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::str::FromStr;
+
+/// Language of the message templates.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Locale {
+    /// `07/01~07/11の請求額は、1.62 USDです。` (`ja`)
+    #[default]
+    Ja,
+    /// `Total cost for 07/01~07/11 is 1.62 USD.` (`en`)
+    En,
+}
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ja" => Ok(Locale::Ja),
+            "en" => Ok(Locale::En),
+            _ => Err("must be ja or en".to_string()),
+        }
+    }
+}
+impl Locale {
+    /// Header of the message reporting the total `cost` over the `period`.
+    pub fn header(&self, period: &str, cost: &str) -> String {
+        match self {
+            Locale::Ja => format!("{}の請求額は、{}です。", period, cost),
+            Locale::En => format!("Total cost for {} is {}.", period, cost),
+        }
+    }
+
+    /// Prefix of each line of the cost ranking.
+    pub fn bullet(&self) -> &'static str {
+        match self {
+            Locale::Ja => "・",
+            Locale::En => "- ",
+        }
+    }
+
+    /// Label of the services collapsed into a line, followed by their `count` if any.
+    pub fn others(&self, count: Option<usize>) -> String {
+        let label = match self {
+            Locale::Ja => "その他",
+            Locale::En => "Others",
+        };
+        match count {
+            Some(count) => format!("{} ({} services)", label, count),
+            None => label.to_string(),
+        }
+    }
+
+    /// Projected total `cost` at the end of the month.
+    pub fn forecast(&self, cost: &str) -> String {
+        match self {
+            Locale::Ja => format!("月末予測 {}", cost),
+            Locale::En => format!("forecast {}", cost),
+        }
+    }
+
+    /// Warning that the `projected_total` at the end of the month exceeds the `budget`.
+    pub fn budget_alert(&self, projected_total: &str, budget: &str) -> String {
+        match self {
+            Locale::Ja => format!(
+                "⚠️月末予測 {} が予算 {} を超過する見込みです",
+                projected_total, budget
+            ),
+            Locale::En => format!(
+                "⚠️The forecast {} is expected to exceed the budget {}",
+                projected_total, budget
+            ),
+        }
+    }
+
+    /// Warning of the `services` which are no longer charged.
+    pub fn dropped_services(&self, services: &str) -> String {
+        match self {
+            Locale::Ja => format!("⚠️0になったサービス: {}", services),
+            Locale::En => format!("⚠️Services dropped to zero: {}", services),
+        }
+    }
+
+    /// Title of the comparison with the previous period.
+    pub fn comparison_title(&self) -> &'static str {
+        match self {
+            Locale::Ja => "【前月同期比較】",
+            Locale::En => "[Compared with the same period of the previous month]",
+        }
+    }
+
+    /// Line of the `change` of the total cost in the comparison.
+    pub fn total_change(&self, change: &str) -> String {
+        match self {
+            Locale::Ja => format!("合計: {}", change),
+            Locale::En => format!("Total: {}", change),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_locale {
+    use super::*;
+
+    #[test]
+    fn format_header_in_each_locale() {
+        assert_eq!(
+            "07/01~07/11の請求額は、1.62 USDです。",
+            Locale::Ja.header("07/01~07/11", "1.62 USD")
+        );
+        assert_eq!(
+            "Total cost for 07/01~07/11 is 1.62 USD.",
+            Locale::En.header("07/01~07/11", "1.62 USD")
+        );
+    }
+
+    #[test]
+    fn format_sections_in_each_locale() {
+        assert_eq!("その他 (3 services)", Locale::Ja.others(Some(3)));
+        assert_eq!("Others", Locale::En.others(None));
+        assert_eq!("forecast 2.34 USD", Locale::En.forecast("2.34 USD"));
+        assert_eq!(
+            "⚠️The forecast 120.00 USD is expected to exceed the budget 100.00 USD",
+            Locale::En.budget_alert("120.00 USD", "100.00 USD")
+        );
+        assert_eq!(
+            "⚠️Services dropped to zero: AWS Glue",
+            Locale::En.dropped_services("AWS Glue")
+        );
+        assert_eq!(
+            "Total: 1.00 USD → 1.62 USD",
+            Locale::En.total_change("1.00 USD → 1.62 USD")
+        );
+    }
+
+    #[test]
+    fn parse_locale() {
+        assert_eq!(Ok(Locale::Ja), "ja".parse());
+        assert_eq!(Ok(Locale::En), "en".parse());
+        assert!("fr".parse::<Locale>().is_err());
+    }
+}
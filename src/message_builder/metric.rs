@@ -0,0 +1,110 @@
+use crate::cost_explorer::cost_response_parser::Cost;
+use std::collections::HashMap;
+
+/// Metric of the usage amount (e.g. `120.5 GB`, `720.0 Hrs`).
+pub const USAGE_METRIC: &str = "UsageQuantity";
+
+/// Built-in number of decimals displayed for each metric.
+/// The cost metrics (e.g. `AmortizedCost`) are displayed with the default 2 decimals.
+const DEFAULT_DECIMALS: &[(&str, usize)] = &[(USAGE_METRIC, 1)];
+
+/// The number of decimals displayed for `metric`.
+///
+/// `custom_decimals` takes precedence over the built-in ones
+/// and unknown metrics are displayed with 2 decimals.
+pub fn decimals_of(metric: &str, custom_decimals: &HashMap<String, usize>) -> usize {
+    if let Some(decimals) = custom_decimals.get(metric) {
+        return *decimals;
+    }
+    DEFAULT_DECIMALS
+        .iter()
+        .find(|(name, _)| *name == metric)
+        .map(|(_, decimals)| *decimals)
+        .unwrap_or(2)
+}
+
+/// Format the `value` of `metric` with its unit.
+///
+/// # Example
+///
+/// ```
+/// let usage = Cost {
+///     amount: 120.54.into(),
+///     unit: "GB".to_string(),
+/// };
+/// assert_eq!("120.5 GB", format_metric(USAGE_METRIC, &usage, &HashMap::new()));
+/// ```
+pub fn format_metric(
+    metric: &str,
+    value: &Cost,
+    custom_decimals: &HashMap<String, usize>,
+) -> String {
+    format!(
+        "{:.*} {}",
+        decimals_of(metric, custom_decimals),
+        value.amount,
+        value.unit
+    )
+}
+
+/// Format the values of multiple metrics side by side.
+///
+/// # Example
+/// `12.34 USD / 120.5 GB`
+pub fn format_metrics(
+    values: &[(&str, &Cost)],
+    custom_decimals: &HashMap<String, usize>,
+) -> String {
+    values
+        .iter()
+        .map(|(metric, value)| format_metric(metric, value, custom_decimals))
+        .collect::<Vec<String>>()
+        .join(" / ")
+}
+
+#[cfg(test)]
+mod test_metric {
+    use super::*;
+
+    fn value(amount: f64, unit: &str) -> Cost {
+        Cost {
+            amount: amount.into(),
+            unit: unit.to_string(),
+        }
+    }
+
+    #[test]
+    fn format_cost_and_usage_side_by_side() {
+        let cost = value(12.345, "USD");
+        let usage = value(720.04, "Hrs");
+
+        assert_eq!(
+            "12.35 USD / 720.0 Hrs",
+            format_metrics(
+                &[("AmortizedCost", &cost), (USAGE_METRIC, &usage)],
+                &HashMap::new()
+            )
+        );
+    }
+
+    #[test]
+    fn override_decimals_by_custom_format() {
+        let custom_decimals: HashMap<String, usize> = vec![
+            ("AmortizedCost".to_string(), 0),
+            (USAGE_METRIC.to_string(), 3),
+        ]
+        .into_iter()
+        .collect();
+        let cost = value(1234.5, "JPY");
+        let usage = value(1.23456, "GB");
+
+        assert_eq!(
+            "1235 JPY / 1.235 GB",
+            format_metrics(
+                &[("AmortizedCost", &cost), (USAGE_METRIC, &usage)],
+                &custom_decimals
+            )
+        );
+        assert_eq!(2, decimals_of("BlendedCost", &custom_decimals));
+    }
+}
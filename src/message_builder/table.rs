@@ -0,0 +1,183 @@
+use std::str::FromStr;
+
+/// Layout of the lines of the service costs.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ServiceLayout {
+    /// `・AWS Lambda: 1.50 USD` (`bullet`)
+    #[default]
+    Bullet,
+    /// Service names padded so that the colons line up, followed by the right-aligned costs
+    /// in a code block. (`aligned`)
+    Aligned,
+}
+impl FromStr for ServiceLayout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bullet" => Ok(ServiceLayout::Bullet),
+            "aligned" => Ok(ServiceLayout::Aligned),
+            _ => Err("must be bullet or aligned".to_string()),
+        }
+    }
+}
+
+/// Alignment of the cells in a column.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Render `rows` under `headers` as a plain-text table in a code block,
+/// in which each column is padded to its widest cell.
+///
+/// # Example
+///
+/// ```
+/// let table = render_table(
+///     &[("月", Align::Left), ("合計", Align::Right)],
+///     &[vec!["2021/06".to_string(), "12.00 USD".to_string()]],
+/// );
+/// assert_eq!("```\n月      |      合計\n--------+----------\n2021/06 | 12.00 USD\n```", table);
+/// ```
+pub fn render_table(headers: &[(&str, Align)], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, (header, _))| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| display_width(cell))
+                .chain(std::iter::once(display_width(header)))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let render_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(headers.iter().zip(widths.iter()))
+            .map(|(cell, ((_, align), width))| {
+                let padding = " ".repeat(width - display_width(cell));
+                match align {
+                    Align::Left => format!("{}{}", cell, padding),
+                    Align::Right => format!("{}{}", padding, cell),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" | ")
+            .trim_end()
+            .to_string()
+    };
+
+    let mut lines = vec![render_row(headers.iter().map(|(x, _)| *x).collect())];
+    lines.push(
+        widths
+            .iter()
+            .map(|width| "-".repeat(*width))
+            .collect::<Vec<String>>()
+            .join("-+-"),
+    );
+    for row in rows {
+        lines.push(render_row(row.iter().map(|x| x.as_str()).collect()));
+    }
+    format!("```\n{}\n```", lines.join("\n"))
+}
+
+/// Format `rows` of labels and values into lines in which the labels are padded
+/// to the widest one so that the colons line up, and the values are right-aligned.
+///
+/// # Example
+///
+/// ```
+/// let lines = align_rows(&[
+///     ("AWS Lambda".to_string(), "1.50 USD".to_string()),
+///     ("AWS Glue".to_string(), "12.00 USD".to_string()),
+/// ]);
+/// assert_eq!(vec!["AWS Lambda :  1.50 USD", "AWS Glue   : 12.00 USD"], lines);
+/// ```
+pub fn align_rows(rows: &[(String, String)]) -> Vec<String> {
+    let label_width = rows
+        .iter()
+        .map(|(x, _)| display_width(x))
+        .max()
+        .unwrap_or(0);
+    let value_width = rows
+        .iter()
+        .map(|(_, x)| display_width(x))
+        .max()
+        .unwrap_or(0);
+    rows.iter()
+        .map(|(label, value)| {
+            format!(
+                "{}{} : {}{}",
+                label,
+                " ".repeat(label_width - display_width(label)),
+                " ".repeat(value_width - display_width(value)),
+                value
+            )
+        })
+        .collect()
+}
+
+/// Width of `text` in a monospace font, where full-width characters
+/// (e.g. Japanese) take up two columns.
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+#[cfg(test)]
+mod test_table {
+    use super::*;
+
+    #[test]
+    fn pad_cells_to_widest_one_in_column() {
+        let rows = vec![
+            vec!["2021/06".to_string(), "12.00 USD".to_string()],
+            vec!["2021/07 (~07/23)".to_string(), "5.00 USD".to_string()],
+        ];
+
+        let actual_table = render_table(&[("月", Align::Left), ("合計", Align::Right)], &rows);
+
+        assert_eq!(
+            "```\n月               |      合計\n-----------------+----------\n2021/06          | 12.00 USD\n2021/07 (~07/23) |  5.00 USD\n```",
+            actual_table
+        );
+    }
+
+    #[test]
+    fn align_colons_and_values_of_rows() {
+        let rows = vec![
+            ("AWS Lambda".to_string(), "1.50 USD".to_string()),
+            (
+                "Amazon Simple Storage Service".to_string(),
+                "1234.00 USD".to_string(),
+            ),
+            ("その他 (2 services)".to_string(), "0.75 USD".to_string()),
+        ];
+
+        assert_eq!(
+            vec![
+                "AWS Lambda                    :    1.50 USD",
+                "Amazon Simple Storage Service : 1234.00 USD",
+                "その他 (2 services)           :    0.75 USD",
+            ],
+            align_rows(&rows)
+        );
+    }
+
+    #[test]
+    fn parse_service_layout_correctly() {
+        assert_eq!(Ok(ServiceLayout::Aligned), "aligned".parse());
+        assert_eq!(Ok(ServiceLayout::Bullet), "bullet".parse());
+        assert!("table".parse::<ServiceLayout>().is_err());
+    }
+
+    #[test]
+    fn count_full_width_characters_as_two_columns() {
+        assert_eq!(6, display_width("前月比"));
+        assert_eq!(9, display_width("12.00 USD"));
+    }
+}
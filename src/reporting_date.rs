@@ -1,24 +1,26 @@
-use chrono::{Date, DateTime, Datelike, TimeZone};
+use crate::cost_explorer::cost_response_parser::ReportedDateRange;
+use chrono::{Date, DateTime, Datelike, Duration, TimeZone};
 use chrono_tz::Tz;
 use rusoto_ce::DateInterval;
 use std::error;
 use std::fmt::Display;
+use std::str::FromStr;
 
 /// Convert the timezone of the input datetime into the designated one
-pub fn date_in_specified_timezone<T: TimeZone>(
+pub fn datetime_in_specified_timezone<T: TimeZone>(
     datetime: DateTime<T>,
     tz_string: String,
-) -> Result<Date<Tz>, Box<dyn error::Error>> {
+) -> Result<DateTime<Tz>, Box<dyn error::Error>> {
     let timezone: Result<Tz, _> = tz_string.parse();
     match timezone {
-        Ok(timezone) => Ok(datetime.with_timezone(&timezone).date()),
+        Ok(timezone) => Ok(datetime.with_timezone(&timezone)),
         Err(e) => Err(format!("Invalid Timezone!: {}", e).into()),
     }
 }
 
 #[cfg(test)]
 mod test_date_with_timezone {
-    use super::date_in_specified_timezone;
+    use super::datetime_in_specified_timezone;
     use chrono::{Local, TimeZone, Utc};
 
     #[test]
@@ -29,7 +31,9 @@ mod test_date_with_timezone {
 
         let tz_string = "Asia/Tokyo".to_string();
 
-        let actual_date = date_in_specified_timezone(input_datetime, tz_string).unwrap();
+        let actual_date = datetime_in_specified_timezone(input_datetime, tz_string)
+            .unwrap()
+            .date();
 
         assert_eq!("2021-07-31JST", format!("{}", actual_date));
     }
@@ -42,7 +46,9 @@ mod test_date_with_timezone {
 
         let tz_string = "Asia/Tokyo".to_string();
 
-        let actual_date = date_in_specified_timezone(input_datetime, tz_string).unwrap();
+        let actual_date = datetime_in_specified_timezone(input_datetime, tz_string)
+            .unwrap()
+            .date();
 
         assert_eq!("2021-08-01JST", format!("{}", actual_date));
     }
@@ -55,10 +61,23 @@ mod test_date_with_timezone {
 
         let tz_string = "Invalid/Timezone".to_string();
 
-        let actual_date = date_in_specified_timezone(input_datetime, tz_string);
+        let actual_date = datetime_in_specified_timezone(input_datetime, tz_string);
 
         assert!(actual_date.is_err());
     }
+
+    #[test]
+    fn convert_timezone_of_datetime_correctly() {
+        let input_datetime = Utc
+            .datetime_from_str("2021-07-31 15:05:00", "%Y-%m-%d %H:%M:%S")
+            .unwrap();
+
+        let tz_string = "Asia/Tokyo".to_string();
+
+        let actual_datetime = datetime_in_specified_timezone(input_datetime, tz_string).unwrap();
+
+        assert_eq!("2021-08-01 00:05:00 JST", format!("{}", actual_datetime));
+    }
 }
 
 /// The date period to retrive the AWS costs.
@@ -88,19 +107,201 @@ where
     pub fn new(reporting_date: Date<T>) -> Self {
         let first_day_of_month = reporting_date.with_day(1).unwrap();
 
-        let start_date: Date<T>;
-        if reporting_date == first_day_of_month {
+        let start_date: Date<T> = if reporting_date == first_day_of_month {
             // First day of the previous month
-            start_date = first_day_of_month.pred().with_day(1).unwrap();
+            first_day_of_month.pred().with_day(1).unwrap()
         } else {
-            start_date = first_day_of_month;
+            first_day_of_month
+        };
+
+        ReportDateRange {
+            start_date,
+            end_date: reporting_date,
+        }
+    }
+
+    /// Set the date period of the last `days` days up to the reporting date.
+    /// (e.g. 7 days: 7/3 -> 6/26 ~ 7/3, 30 days: 7/23 -> 6/23 ~ 7/23, 3/1 -> 1/30 ~ 3/1)
+    pub fn trailing_days(reporting_date: Date<T>, days: i64) -> Self {
+        ReportDateRange {
+            start_date: reporting_date.clone() - Duration::days(days),
+            end_date: reporting_date,
+        }
+    }
+
+    /// Set the date period from the beginning of the fiscal year to the reporting date.
+    ///
+    /// The fiscal year starts on the first date of `fiscal_year_start_month`.
+    /// (e.g. April: 2022/3/15 -> 2021/4/1 ~ 2022/3/15, 2021/5/10 -> 2021/4/1 ~ 5/10)
+    ///
+    /// If the reporting date is the first date of the fiscal year,
+    /// the start date is set as the first date of the previous fiscal year.
+    /// (e.g. April: 2021/4/1 -> 2020/4/1 ~ 2021/4/1)
+    pub fn fiscal_year_to_date(reporting_date: Date<T>, fiscal_year_start_month: u32) -> Self {
+        let timezone = reporting_date.timezone();
+        let fiscal_year = if reporting_date.month() >= fiscal_year_start_month {
+            reporting_date.year()
+        } else {
+            reporting_date.year() - 1
+        };
+
+        let mut start_date = timezone.ymd(fiscal_year, fiscal_year_start_month, 1);
+        if reporting_date == start_date {
+            start_date = timezone.ymd(fiscal_year - 1, fiscal_year_start_month, 1);
         }
 
         ReportDateRange {
-            start_date: start_date,
+            start_date,
             end_date: reporting_date,
         }
     }
+
+    /// The same period in the previous month, which is compared with this period.
+    /// (e.g. 7/1 ~ 7/23 -> 6/1 ~ 6/23, 8/1 -> 6/1 ~ 7/1)
+    ///
+    /// Dates which do not exist in the previous month are
+    /// set to the last date of the month. (e.g. 3/1 ~ 3/31 -> 2/1 ~ 2/28)
+    pub fn previous_period(&self) -> Self {
+        ReportDateRange {
+            start_date: one_month_before(&self.start_date),
+            end_date: one_month_before(&self.end_date),
+        }
+    }
+
+    /// The period of the last `months` months up to the reporting date,
+    /// whose monthly totals are compared.
+    /// (e.g. 3 months: 7/1 ~ 7/23 -> 5/1 ~ 7/23, 8/1 -> 5/1 ~ 8/1)
+    ///
+    /// The last month is the reported month of `ReportDateRange::new`,
+    /// regardless of the start date of this period.
+    pub fn recent_months(&self, months: u32) -> Self {
+        let mut start_date = ReportDateRange::new(self.end_date.clone()).start_date;
+        for _ in 1..months {
+            start_date = one_month_before(&start_date);
+        }
+        ReportDateRange {
+            start_date,
+            end_date: self.end_date.clone(),
+        }
+    }
+
+    /// The period from the reporting date to the end of its month,
+    /// whose cost is forecasted. (e.g. 7/23 -> 7/23 ~ 8/1)
+    ///
+    /// Returns None if the reporting date is the first date of the month,
+    /// because the reported month has already ended.
+    pub fn forecast_period(&self) -> Option<Self> {
+        if self.end_date.day() == 1 {
+            return None;
+        }
+        let first_day_of_next_month = self.end_date.with_day(28).unwrap() + Duration::days(4);
+        Some(ReportDateRange {
+            start_date: self.end_date.clone(),
+            end_date: first_day_of_next_month.with_day(1).unwrap(),
+        })
+    }
+
+    /// The last day of the period, whose cost is compared in the daily report.
+    /// (e.g. 7/1 ~ 7/23 -> 7/22 ~ 7/23)
+    pub fn latest_day(&self) -> Self {
+        ReportDateRange {
+            start_date: self.end_date.clone() - Duration::days(1),
+            end_date: self.end_date.clone(),
+        }
+    }
+
+    /// The period `days` days before this period. (e.g. 7 days: 8/2 ~ 8/3 -> 7/26 ~ 7/27)
+    pub fn days_before(&self, days: i64) -> Self {
+        ReportDateRange {
+            start_date: self.start_date.clone() - Duration::days(days),
+            end_date: self.end_date.clone() - Duration::days(days),
+        }
+    }
+
+    /// Timezone of the reporting date.
+    pub fn timezone(&self) -> T {
+        self.end_date.timezone()
+    }
+}
+
+/// Maximum number of the days of `WindowAlignment::Rolling`,
+/// which is within the 14 months of the history retrievable from Cost Explorer.
+pub const MAX_ROLLING_DAYS: i64 = 365;
+
+/// Window in which the costs are aggregated up to the reporting date.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum WindowAlignment {
+    /// From the first date of the calendar month. (`calendar`)
+    #[default]
+    Calendar,
+    /// The last N (1 to `MAX_ROLLING_DAYS`) days regardless of the month boundary.
+    /// (`rolling_{N}_days`, e.g. `rolling_7_days` or `rolling_30_days`)
+    Rolling(i64),
+}
+impl FromStr for WindowAlignment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rolling_days = s
+            .strip_prefix("rolling_")
+            .and_then(|x| x.strip_suffix("_days"))
+            .and_then(|x| x.parse::<i64>().ok());
+        match (s, rolling_days) {
+            ("calendar", _) => Ok(WindowAlignment::Calendar),
+            (_, Some(days)) if (1..=MAX_ROLLING_DAYS).contains(&days) => {
+                Ok(WindowAlignment::Rolling(days))
+            }
+            _ => Err(format!(
+                "must be calendar or rolling_{{N}}_days with N from 1 to {} (e.g. rolling_7_days)",
+                MAX_ROLLING_DAYS
+            )),
+        }
+    }
+}
+
+/// Day with which the cost of the latest day is compared in the daily report.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DailyBaseline {
+    /// The day before. (`previous_day`)
+    PreviousDay,
+    /// The same weekday one week ago, which is less affected by
+    /// the difference between weekdays and weekends. (`same_weekday_last_week`)
+    SameWeekdayLastWeek,
+}
+impl DailyBaseline {
+    /// The number of days between the baseline and the compared day.
+    pub fn days(&self) -> i64 {
+        match self {
+            DailyBaseline::PreviousDay => 1,
+            DailyBaseline::SameWeekdayLastWeek => 7,
+        }
+    }
+
+    /// Label of the comparison displayed in the message.
+    pub fn label(&self) -> &'static str {
+        match self {
+            DailyBaseline::PreviousDay => "前日比",
+            DailyBaseline::SameWeekdayLastWeek => "前週同曜日比",
+        }
+    }
+}
+impl FromStr for DailyBaseline {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "previous_day" => Ok(DailyBaseline::PreviousDay),
+            "same_weekday_last_week" => Ok(DailyBaseline::SameWeekdayLastWeek),
+            _ => Err("must be previous_day or same_weekday_last_week".to_string()),
+        }
+    }
+}
+
+/// The same date in the previous month, or its last date if it does not exist.
+fn one_month_before<T: TimeZone>(date: &Date<T>) -> Date<T> {
+    let last_day_of_previous_month = date.with_day(1).unwrap().pred();
+    let day = date.day().min(last_day_of_previous_month.day());
+    last_day_of_previous_month.with_day(day).unwrap()
 }
 impl<T> From<&ReportDateRange<T>> for DateInterval
 where
@@ -114,6 +315,18 @@ where
         }
     }
 }
+impl<T> From<&ReportDateRange<T>> for ReportedDateRange<T>
+where
+    T: TimeZone,
+    <T as TimeZone>::Offset: Display,
+{
+    fn from(from: &ReportDateRange<T>) -> ReportedDateRange<T> {
+        ReportedDateRange {
+            start_date: from.start_date.clone(),
+            end_date: from.end_date.clone(),
+        }
+    }
+}
 impl<T> PartialEq for ReportDateRange<T>
 where
     T: TimeZone,
@@ -128,6 +341,7 @@ where
 mod date_range_tests {
     use super::*;
     use chrono::{Local, TimeZone};
+    use chrono_tz::Asia::Tokyo;
     use rusoto_ce::DateInterval;
 
     #[test]
@@ -158,6 +372,255 @@ mod date_range_tests {
         assert_eq!(expected_date_range, actual_date_range);
     }
 
+    #[test]
+    fn rolling_30_days_in_middle_of_month() {
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 6, 23),
+            end_date: Local.ymd(2021, 7, 23),
+        };
+
+        assert_eq!(
+            expected_date_range,
+            ReportDateRange::trailing_days(Local.ymd(2021, 7, 23), 30)
+        );
+    }
+
+    #[test]
+    fn rolling_30_days_across_short_month() {
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 1, 30),
+            end_date: Local.ymd(2021, 3, 1),
+        };
+
+        assert_eq!(
+            expected_date_range,
+            ReportDateRange::trailing_days(Local.ymd(2021, 3, 1), 30)
+        );
+    }
+
+    #[test]
+    fn trailing_7_days_across_month_boundary() {
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 6, 26),
+            end_date: Local.ymd(2021, 7, 3),
+        };
+
+        assert_eq!(
+            expected_date_range,
+            ReportDateRange::trailing_days(Local.ymd(2021, 7, 3), 7)
+        );
+    }
+
+    #[test]
+    fn trailing_7_days_across_year_boundary() {
+        let expected_date_range = ReportDateRange {
+            start_date: Tokyo.ymd(2021, 12, 29),
+            end_date: Tokyo.ymd(2022, 1, 5),
+        };
+
+        let actual_date_range = ReportDateRange::trailing_days(Tokyo.ymd(2022, 1, 5), 7);
+
+        assert_eq!(expected_date_range, actual_date_range);
+        assert_eq!(
+            DateInterval {
+                start: "2021-12-29".to_string(),
+                end: "2022-01-05".to_string(),
+            },
+            DateInterval::from(&actual_date_range)
+        );
+    }
+
+    #[test]
+    fn parse_window_alignment_correctly() {
+        assert_eq!(Ok(WindowAlignment::Calendar), "calendar".parse());
+        assert_eq!(Ok(WindowAlignment::Rolling(30)), "rolling_30_days".parse());
+        assert_eq!(Ok(WindowAlignment::Rolling(7)), "rolling_7_days".parse());
+        assert!("rolling".parse::<WindowAlignment>().is_err());
+        assert!("rolling_0_days".parse::<WindowAlignment>().is_err());
+        assert_eq!(
+            Ok(WindowAlignment::Rolling(365)),
+            "rolling_365_days".parse()
+        );
+        assert!("rolling_366_days".parse::<WindowAlignment>().is_err());
+        assert!("rolling_99999999999_days"
+            .parse::<WindowAlignment>()
+            .is_err());
+        assert!("rolling_week_days".parse::<WindowAlignment>().is_err());
+    }
+
+    #[test]
+    fn reporting_before_fiscal_year_boundary() {
+        let input_date = Local.ymd(2022, 3, 15);
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 4, 1),
+            end_date: Local.ymd(2022, 3, 15),
+        };
+
+        let actual_date_range = ReportDateRange::fiscal_year_to_date(input_date, 4);
+
+        assert_eq!(expected_date_range, actual_date_range);
+    }
+
+    #[test]
+    fn reporting_after_fiscal_year_boundary() {
+        let input_date = Local.ymd(2021, 5, 10);
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 4, 1),
+            end_date: Local.ymd(2021, 5, 10),
+        };
+
+        let actual_date_range = ReportDateRange::fiscal_year_to_date(input_date, 4);
+
+        assert_eq!(expected_date_range, actual_date_range);
+    }
+
+    #[test]
+    fn reporting_at_beginning_of_fiscal_year() {
+        let input_date = Local.ymd(2021, 4, 1);
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2020, 4, 1),
+            end_date: Local.ymd(2021, 4, 1),
+        };
+
+        let actual_date_range = ReportDateRange::fiscal_year_to_date(input_date, 4);
+
+        assert_eq!(expected_date_range, actual_date_range);
+    }
+
+    #[test]
+    fn same_weekday_last_week_across_month_boundary() {
+        let latest_day = ReportDateRange::new(Local.ymd(2021, 8, 3)).latest_day();
+
+        let expected_latest_day = ReportDateRange {
+            start_date: Local.ymd(2021, 8, 2),
+            end_date: Local.ymd(2021, 8, 3),
+        };
+        let expected_baseline = ReportDateRange {
+            start_date: Local.ymd(2021, 7, 26),
+            end_date: Local.ymd(2021, 7, 27),
+        };
+
+        assert_eq!(expected_latest_day, latest_day);
+        assert_eq!(
+            expected_baseline,
+            latest_day.days_before(DailyBaseline::SameWeekdayLastWeek.days())
+        );
+        assert_eq!(
+            Local.ymd(2021, 8, 2).weekday(),
+            expected_baseline.start_date.weekday()
+        );
+    }
+
+    #[test]
+    fn previous_day_at_beginning_of_month() {
+        let latest_day = ReportDateRange::new(Local.ymd(2021, 8, 2)).latest_day();
+
+        let expected_baseline = ReportDateRange {
+            start_date: Local.ymd(2021, 7, 31),
+            end_date: Local.ymd(2021, 8, 1),
+        };
+
+        assert_eq!(
+            expected_baseline,
+            latest_day.days_before(DailyBaseline::PreviousDay.days())
+        );
+    }
+
+    #[test]
+    fn parse_daily_baseline_correctly() {
+        assert_eq!(
+            Ok(DailyBaseline::SameWeekdayLastWeek),
+            "same_weekday_last_week".parse()
+        );
+        assert_eq!(Ok(DailyBaseline::PreviousDay), "previous_day".parse());
+        assert!("yesterday".parse::<DailyBaseline>().is_err());
+    }
+
+    #[test]
+    fn previous_period_in_middle_of_month() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 7, 23));
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 6, 1),
+            end_date: Local.ymd(2021, 6, 23),
+        };
+
+        assert_eq!(expected_date_range, input_date_range.previous_period());
+    }
+
+    #[test]
+    fn previous_period_at_beginning_of_month() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 8, 1));
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 6, 1),
+            end_date: Local.ymd(2021, 7, 1),
+        };
+
+        assert_eq!(expected_date_range, input_date_range.previous_period());
+    }
+
+    #[test]
+    fn previous_period_of_date_not_in_previous_month() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 3, 31));
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 2, 1),
+            end_date: Local.ymd(2021, 2, 28),
+        };
+
+        assert_eq!(expected_date_range, input_date_range.previous_period());
+    }
+
+    #[test]
+    fn recent_months_across_year_boundary() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2022, 2, 10));
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 12, 1),
+            end_date: Local.ymd(2022, 2, 10),
+        };
+
+        assert_eq!(expected_date_range, input_date_range.recent_months(3));
+    }
+
+    #[test]
+    fn recent_months_at_beginning_of_month() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 8, 1));
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 5, 1),
+            end_date: Local.ymd(2021, 8, 1),
+        };
+
+        assert_eq!(expected_date_range, input_date_range.recent_months(3));
+    }
+
+    #[test]
+    fn forecast_until_end_of_month() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 12, 23));
+
+        let expected_date_range = ReportDateRange {
+            start_date: Local.ymd(2021, 12, 23),
+            end_date: Local.ymd(2022, 1, 1),
+        };
+
+        assert_eq!(
+            Some(expected_date_range),
+            input_date_range.forecast_period()
+        );
+    }
+
+    #[test]
+    fn no_forecast_at_beginning_of_month() {
+        let input_date_range = ReportDateRange::new(Local.ymd(2021, 8, 1));
+
+        assert_eq!(None, input_date_range.forecast_period());
+    }
+
     #[test]
     fn convert_into_date_interval_correctly() {
         let input_date_range = &ReportDateRange {
@@ -0,0 +1,177 @@
+use async_trait::async_trait;
+use rusoto_core::signature::SignedRequest;
+use rusoto_core::{Client, Region};
+use serde_json::{json, Value};
+use std::error;
+
+use crate::config::Config;
+
+/// Error in reading the secret.
+pub type SecretError = Box<dyn error::Error + Send + Sync>;
+
+/// Trait to call GetSecretValue method of Secrets Manager API.
+#[async_trait]
+pub trait GetSecretValue {
+    async fn get_secret_value(&self, secret_id: &str) -> Result<String, SecretError>;
+}
+
+/// Client to send requests to Secrets Manager API of the region of the Lambda function.
+///
+/// The requests are signed by the shared client of rusoto.
+pub struct SecretsManagerClient {
+    client: Client,
+    region: Region,
+}
+impl SecretsManagerClient {
+    pub fn new() -> Self {
+        SecretsManagerClient {
+            client: Client::shared(),
+            region: Region::default(),
+        }
+    }
+}
+#[async_trait]
+impl GetSecretValue for SecretsManagerClient {
+    async fn get_secret_value(&self, secret_id: &str) -> Result<String, SecretError> {
+        let mut request = SignedRequest::new("POST", "secretsmanager", &self.region, "/");
+        request.set_content_type("application/x-amz-json-1.1".to_string());
+        request.add_header("x-amz-target", "secretsmanager.GetSecretValue");
+        request.set_payload(Some(serde_json::to_vec(&json!({ "SecretId": secret_id }))?));
+
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|e| format!("Failed to send GetSecretValue request: {:?}", e))?;
+        let response = response.buffer().await?;
+        if !response.status.is_success() {
+            return Err(format!(
+                "GetSecretValue failed with HTTP status {}: {}",
+                response.status,
+                String::from_utf8_lossy(&response.body)
+            )
+            .into());
+        }
+        parse_get_secret_value_response(&serde_json::from_slice(&response.body)?)
+    }
+}
+
+/// Take `SecretString` from the response of GetSecretValue.
+///
+/// Binary secrets are not supported since the Webhook URL is a string.
+fn parse_get_secret_value_response(response: &Value) -> Result<String, SecretError> {
+    match response["SecretString"].as_str() {
+        Some(secret) => Ok(secret.trim().to_string()),
+        None => Err("GetSecretValue returned no SecretString".into()),
+    }
+}
+
+/// Resolve the Incoming Webhook URL of Slack.
+///
+/// It is read from the secret of `SLACK_WEBHOOK_SECRET_ARN` if set,
+/// otherwise `SLACK_WEBHOOK_URL` is returned as is.
+pub async fn resolve_webhook_url(
+    config: &Config,
+    provider: &impl GetSecretValue,
+) -> Result<Option<String>, SecretError> {
+    match &config.slack_webhook_secret_arn {
+        Some(arn) => Ok(Some(provider.get_secret_value(arn).await?)),
+        None => Ok(config.slack_webhook_url.clone()),
+    }
+}
+
+/// Stub of the client which returns the secret given in advance.
+#[cfg(test)]
+#[derive(Default)]
+pub struct SecretStub {
+    pub secret: Option<String>,
+    /// IDs of the secrets requested so far.
+    pub requested: std::sync::Mutex<Vec<String>>,
+}
+#[cfg(test)]
+#[async_trait]
+impl GetSecretValue for SecretStub {
+    async fn get_secret_value(&self, secret_id: &str) -> Result<String, SecretError> {
+        self.requested.lock().unwrap().push(secret_id.to_string());
+        self.secret
+            .clone()
+            .ok_or_else(|| "ResourceNotFoundException".into())
+    }
+}
+
+#[cfg(test)]
+mod test_secret_provider {
+    use super::*;
+
+    const SECRET_ARN: &str =
+        "arn:aws:secretsmanager:ap-northeast-1:123456789012:secret:slack-webhook";
+
+    #[tokio::test]
+    async fn resolve_webhook_url_from_secret() {
+        let config = Config {
+            slack_webhook_url: Some("https://hooks.slack.com/services/plain".to_string()),
+            slack_webhook_secret_arn: Some(SECRET_ARN.to_string()),
+            ..Default::default()
+        };
+        let provider = SecretStub {
+            secret: Some("https://hooks.slack.com/services/secret".to_string()),
+            ..Default::default()
+        };
+
+        let actual_url = resolve_webhook_url(&config, &provider).await.unwrap();
+
+        assert_eq!(
+            Some("https://hooks.slack.com/services/secret".to_string()),
+            actual_url
+        );
+        assert_eq!(
+            vec![SECRET_ARN.to_string()],
+            *provider.requested.lock().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn fall_back_to_webhook_url_without_secret_arn() {
+        let config = Config {
+            slack_webhook_url: Some("https://hooks.slack.com/services/plain".to_string()),
+            ..Default::default()
+        };
+        let provider = SecretStub::default();
+
+        let actual_url = resolve_webhook_url(&config, &provider).await.unwrap();
+
+        assert_eq!(
+            Some("https://hooks.slack.com/services/plain".to_string()),
+            actual_url
+        );
+        assert!(provider.requested.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn return_error_when_secret_is_unavailable() {
+        let config = Config {
+            slack_webhook_secret_arn: Some(SECRET_ARN.to_string()),
+            ..Default::default()
+        };
+
+        assert!(resolve_webhook_url(&config, &SecretStub::default())
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn take_secret_string_from_response() {
+        assert_eq!(
+            "https://hooks.slack.com/services/secret",
+            parse_get_secret_value_response(&json!({
+                "ARN": SECRET_ARN,
+                "SecretString": "https://hooks.slack.com/services/secret\n",
+            }))
+            .unwrap()
+        );
+        assert!(parse_get_secret_value_response(
+            &json!({"ARN": SECRET_ARN, "SecretBinary": "aGVsbG8="})
+        )
+        .is_err());
+    }
+}
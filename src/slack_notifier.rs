@@ -1,60 +1,530 @@
-use crate::message_builder::NotificationMessage;
+use crate::message_builder::{NotificationMessage, Severity};
 
-use dotenv::dotenv;
+use chrono::{DateTime, Utc};
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
 use std::result::Result;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
 
 extern crate slack_hook;
 
-use slack_hook::{Attachment, Error, HexColor, PayloadBuilder, Slack, SlackText, TryFrom};
+use slack_hook::{Attachment, Error, HexColor, Payload, PayloadBuilder, SlackText, TryFrom};
+
+/// Waiting time before retrying when `Retry-After` header is missing or invalid.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Color of the attachment of the comparison with the previous period.
+const COMPARISON_COLOR: &str = "#439fe0";
+
+/// The maximum number of characters of the text of a Block Kit section block.
+const SECTION_TEXT_LIMIT: usize = 3000;
+
+/// Colors of the attachment for each severity of the message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeverityColors {
+    pub normal: HexColor,
+    pub warning: HexColor,
+    pub critical: HexColor,
+}
+impl Default for SeverityColors {
+    fn default() -> Self {
+        let color_of = |severity: Severity| HexColor::try_from(severity.color()).unwrap();
+        SeverityColors {
+            normal: color_of(Severity::Normal),
+            warning: color_of(Severity::Warning),
+            critical: color_of(Severity::Critical),
+        }
+    }
+}
+impl SeverityColors {
+    /// Color for `severity`.
+    fn of(&self, severity: Severity) -> &HexColor {
+        match severity {
+            Severity::Normal => &self.normal,
+            Severity::Warning => &self.warning,
+            Severity::Critical => &self.critical,
+        }
+    }
+}
 
 impl NotificationMessage {
     /// Create `Attachment` object of Slack message from `NotificationMessage` object.
-    fn as_attachment(self, color: &str) -> Attachment {
+    fn as_attachment(&self, color: &HexColor) -> Attachment {
         Attachment {
-            pretext: Some(SlackText::new(self.header)),
-            text: Some(SlackText::new(self.body)),
-            color: Some(HexColor::try_from(color).unwrap()),
+            pretext: Some(SlackText::new(self.header.as_str())),
+            text: match self.body.is_empty() {
+                true => None,
+                false => Some(SlackText::new(self.body.as_str())),
+            },
+            color: Some(color.clone()),
             ..Attachment::default()
         }
     }
+
+    /// Create the attachments of the message, whose color is that of its severity in `colors`.
+    ///
+    /// The comparison with the previous period, if any, follows the costs
+    /// as the second attachment with its own color.
+    /// The footer is displayed in the footer field of the last attachment.
+    pub(crate) fn as_attachments(&self, colors: &SeverityColors) -> Vec<Attachment> {
+        let mut attachments = vec![self.as_attachment(colors.of(self.severity))];
+        if let Some(comparison) = &self.comparison {
+            attachments.push(Attachment {
+                text: Some(SlackText::new(comparison.as_str())),
+                color: Some(HexColor::try_from(COMPARISON_COLOR).unwrap()),
+                ..Attachment::default()
+            });
+        }
+        if let (Some(footer), Some(last)) = (&self.footer, attachments.last_mut()) {
+            last.footer = Some(SlackText::new(footer.as_str()));
+        }
+        attachments
+    }
+
+    /// Create the Block Kit blocks of the message.
+    ///
+    /// The header is displayed in a header block, followed by the body in section blocks,
+    /// each of which holds up to `SECTION_TEXT_LIMIT` characters.
+    /// The comparison with the previous period, if any, follows a divider,
+    /// and the footer is displayed in a context block.
+    pub(crate) fn as_blocks(&self) -> Vec<Value> {
+        let section =
+            |text: String| json!({"type": "section", "text": {"type": "mrkdwn", "text": text}});
+        let mut blocks = vec![json!({
+            "type": "header",
+            "text": {"type": "plain_text", "text": self.header},
+        })];
+        blocks.extend(
+            split_text(&self.body, SECTION_TEXT_LIMIT)
+                .into_iter()
+                .map(section),
+        );
+        if let Some(comparison) = &self.comparison {
+            blocks.push(json!({"type": "divider"}));
+            blocks.extend(
+                split_text(comparison, SECTION_TEXT_LIMIT)
+                    .into_iter()
+                    .map(section),
+            );
+        }
+        if let Some(footer) = &self.footer {
+            blocks.push(json!({
+                "type": "context",
+                "elements": [{"type": "mrkdwn", "text": footer}],
+            }));
+        }
+        blocks
+    }
+}
+
+/// Split `text` into chunks of up to `limit` characters at the line breaks.
+/// A line longer than `limit` is split in the middle.
+fn split_text(text: &str, limit: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = vec![];
+    let mut chunk = String::new();
+    for line in text.lines() {
+        let separator = if chunk.is_empty() { 0 } else { 1 };
+        if chunk.chars().count() + separator + line.chars().count() <= limit {
+            if separator == 1 {
+                chunk.push('\n');
+            }
+            chunk.push_str(line);
+            continue;
+        }
+        if !chunk.is_empty() {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        let chars: Vec<char> = line.chars().collect();
+        let mut pieces = chars.chunks(limit).map(|x| x.iter().collect::<String>());
+        let last = pieces.next_back().unwrap_or_default();
+        chunks.extend(pieces);
+        chunk = last;
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
 }
 
 /// Trait to send message to Slack.
 pub trait SendMessage {
-    fn send(self, message: NotificationMessage) -> Result<(), Error>;
+    fn send(&self, message: NotificationMessage) -> Result<(), Error>;
+}
+impl<S: SendMessage + ?Sized> SendMessage for Box<S> {
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        (**self).send(message)
+    }
+}
+
+/// Service to which the message is sent.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum NotifierKind {
+    /// Slack Incoming Webhook. (`slack`)
+    #[default]
+    Slack,
+    /// Slack Web API, which posts the cost breakdown as a reply
+    /// in the thread of the total cost. (`slack_thread`)
+    SlackThread,
+    /// Microsoft Teams Incoming Webhook. (`teams`)
+    Teams,
+    /// Discord Webhook. (`discord`)
+    Discord,
+    /// Amazon SNS topic. (`sns`)
+    Sns,
+}
+impl FromStr for NotifierKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slack" => Ok(NotifierKind::Slack),
+            "slack_thread" => Ok(NotifierKind::SlackThread),
+            "teams" => Ok(NotifierKind::Teams),
+            "discord" => Ok(NotifierKind::Discord),
+            "sns" => Ok(NotifierKind::Sns),
+            _ => Err("must be slack, slack_thread, teams, discord or sns".to_string()),
+        }
+    }
+}
+
+/// Format of the payload posted to Slack Incoming Webhook.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SlackFormat {
+    /// Legacy attachments colored by the severity. (`attachments`)
+    #[default]
+    Attachments,
+    /// Block Kit blocks. (`blocks`)
+    Blocks,
+}
+impl FromStr for SlackFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "attachments" => Ok(SlackFormat::Attachments),
+            "blocks" => Ok(SlackFormat::Blocks),
+            _ => Err("must be attachments or blocks".to_string()),
+        }
+    }
+}
+
+/// Response of Slack Incoming Webhook.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Value of `Retry-After` header, which is returned with the status 429.
+    pub retry_after: Option<String>,
+}
+
+/// Trait to post a payload to Slack Incoming Webhook.
+pub trait PostWebhook {
+    fn post(&self, payload: &Value) -> Result<WebhookResponse, Error>;
+}
+
+/// Client to post a payload to the Webhook URL.
+pub struct WebhookClient {
+    url: Url,
+    client: Client,
+}
+impl WebhookClient {
+    pub fn new(webhook_url: &str) -> Result<Self, Error> {
+        Ok(WebhookClient {
+            url: Url::parse(webhook_url)?,
+            client: Client::new(),
+        })
+    }
+}
+impl PostWebhook for WebhookClient {
+    /// Post the payload and return the status and `Retry-After` header of the response.
+    fn post(&self, payload: &Value) -> Result<WebhookResponse, Error> {
+        let response = self.client.post(self.url.clone()).json(payload).send()?;
+        let retry_after = response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        Ok(WebhookResponse {
+            status: response.status().as_u16(),
+            retry_after,
+        })
+    }
+}
+
+/// Policy to retry sending when Slack responds with the status 429 (rate limited).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitPolicy {
+    /// The maximum number of retries.
+    pub max_retries: u32,
+    /// Upper limit of the waiting time before each retry.
+    pub max_wait: Duration,
+}
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        RateLimitPolicy {
+            max_retries: 3,
+            max_wait: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Wait for `duration` without stalling the worker of the tokio runtime.
+///
+/// In the multi-threaded runtime of `main`, the worker hands its other tasks
+/// over to another thread while `tokio::time::sleep` is awaited in place.
+/// Outside of the runtime, it falls back to `thread::sleep`.
+///
+/// It must not be called on a current-thread runtime, in which `block_in_place` panics.
+pub fn sleep_off_worker(duration: Duration) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => tokio::task::block_in_place(|| handle.block_on(tokio::time::sleep(duration))),
+        Err(_) => thread::sleep(duration),
+    }
 }
 
 /// An object to send notification message to Slack.
-pub struct SlackNotifier {
-    /// `Slack` object which is initialized with Webhook URL.
-    slack: Slack,
-}
-impl SlackNotifier {
-    /// Construct a `SlackNotifier` object.
-    /// In this method, `Slack` object is initialized with Webhook URL
-    /// which is set as an environment variable.
-    pub fn new() -> Self {
-        dotenv().ok();
-        let webhook_url = dotenv::var("SLACK_WEBHOOK_URL").expect("Webhook URL not found.");
-        let slack = Slack::new(webhook_url.as_ref()).unwrap();
-        SlackNotifier { slack: slack }
-    }
-}
-impl SendMessage for SlackNotifier {
+pub struct SlackNotifier<W: PostWebhook = WebhookClient> {
+    /// Client which is initialized with Webhook URL.
+    webhook: W,
+    /// Policy to retry when the request is rate limited.
+    rate_limit_policy: RateLimitPolicy,
+    /// Colors of the attachment for each severity.
+    severity_colors: SeverityColors,
+    /// Format of the payload.
+    format: SlackFormat,
+    /// Function to wait before retrying.
+    sleep: Box<dyn Fn(Duration) + Send + Sync>,
+}
+impl<W: PostWebhook> SlackNotifier<W> {
+    /// Construct a `SlackNotifier` object which posts messages via `webhook`.
+    pub fn with_webhook(webhook: W) -> Self {
+        SlackNotifier {
+            webhook,
+            rate_limit_policy: RateLimitPolicy::default(),
+            severity_colors: SeverityColors::default(),
+            format: SlackFormat::default(),
+            sleep: Box::new(thread::sleep),
+        }
+    }
+
+    /// Set the policy to retry when the request is rate limited.
+    pub fn rate_limit_policy(mut self, rate_limit_policy: RateLimitPolicy) -> Self {
+        self.rate_limit_policy = rate_limit_policy;
+        self
+    }
+
+    /// Set the colors of the attachment for each severity.
+    pub fn severity_colors(mut self, severity_colors: SeverityColors) -> Self {
+        self.severity_colors = severity_colors;
+        self
+    }
+
+    /// Set the format of the payload.
+    pub fn format(mut self, format: SlackFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Set the function to wait before retrying, which is `thread::sleep` by default.
+    pub fn sleep(mut self, sleep: impl Fn(Duration) + Send + Sync + 'static) -> Self {
+        self.sleep = Box::new(sleep);
+        self
+    }
+
+    /// Build the payload of `message` in the format.
+    ///
+    /// The header is also set to `text` of the Block Kit payload,
+    /// which is displayed in the notifications.
+    fn build_payload(&self, message: &NotificationMessage) -> Result<Value, Error> {
+        match self.format {
+            SlackFormat::Attachments => {
+                let payload: Payload = PayloadBuilder::new()
+                    .attachments(message.as_attachments(&self.severity_colors))
+                    .build()?;
+                Ok(serde_json::to_value(payload)?)
+            }
+            SlackFormat::Blocks => Ok(json!({
+                "text": message.header,
+                "blocks": message.as_blocks(),
+            })),
+        }
+    }
+}
+impl<W: PostWebhook> SendMessage for SlackNotifier<W> {
     /// Send message to Slack
-    fn send(self, message: NotificationMessage) -> Result<(), Error> {
-        let payload = PayloadBuilder::new()
-            .attachments(vec![message.as_attachment("#36a64f")])
-            .build()
-            .unwrap();
+    ///
+    /// If Slack responds with the status 429, it waits for the duration
+    /// designated by `Retry-After` header (up to `max_wait`) and retries.
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let payload = self.build_payload(&message)?;
 
-        self.slack.send(&payload)
+        let mut retries = 0;
+        loop {
+            let response = self.webhook.post(&payload)?;
+            match response.status {
+                200..=299 => return Ok(()),
+                429 if retries < self.rate_limit_policy.max_retries => {
+                    let wait = parse_retry_after(response.retry_after.as_deref(), Utc::now())
+                        .min(self.rate_limit_policy.max_wait);
+                    println!("Rate limited by Slack. Retry after {:?}", wait);
+                    (self.sleep)(wait);
+                    retries += 1;
+                }
+                status => return Err(Error::from(format!("HTTP error {}", status))),
+            }
+        }
+    }
+}
+
+/// Notifier which sends the same message to several destinations.
+pub struct BroadcastNotifier<S: SendMessage> {
+    /// Notifiers labeled with their destinations (e.g. Webhook URLs).
+    destinations: Vec<(String, S)>,
+}
+impl<S: SendMessage> BroadcastNotifier<S> {
+    pub fn new(destinations: Vec<(String, S)>) -> Self {
+        BroadcastNotifier { destinations }
+    }
+}
+impl<S: SendMessage> SendMessage for BroadcastNotifier<S> {
+    /// Send the message to all the destinations.
+    ///
+    /// If any of them fails, it returns an error which reports the failed destinations
+    /// together with the succeeded ones, after trying all of them.
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let mut succeeded: Vec<&str> = vec![];
+        let mut failed: Vec<String> = vec![];
+        for (label, notifier) in &self.destinations {
+            match notifier.send(message.clone()) {
+                Ok(_) => succeeded.push(label),
+                Err(e) => failed.push(format!("{} ({})", label, e)),
+            }
+        }
+        if failed.is_empty() {
+            return Ok(());
+        }
+        Err(Error::from(format!(
+            "Failed to send to {} of {} destinations: {}. Succeeded: {}",
+            failed.len(),
+            self.destinations.len(),
+            failed.join(", "),
+            match succeeded.is_empty() {
+                true => "none".to_string(),
+                false => succeeded.join(", "),
+            }
+        )))
+    }
+}
+
+/// Sink to which the message is routed when it cannot be delivered to Slack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FallbackSink {
+    /// Print the message to the standard output, i.e. CloudWatch Logs of the Lambda function.
+    /// (`stdout`)
+    Stdout,
+}
+impl FromStr for FallbackSink {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdout" => Ok(FallbackSink::Stdout),
+            _ => Err("must be stdout".to_string()),
+        }
+    }
+}
+impl SendMessage for FallbackSink {
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        match self {
+            FallbackSink::Stdout => {
+                println!("Undelivered message:\n{}", to_plain_text(&message));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Notifier which prints the message to the standard output instead of sending it,
+/// so that the handler can be run without hitting any service. (`DRY_RUN`)
+pub struct DryRunNotifier;
+impl SendMessage for DryRunNotifier {
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        println!("{}", to_plain_text(&message));
+        Ok(())
+    }
+}
+
+/// Render the header, body, comparison and footer of the message line by line.
+pub(crate) fn to_plain_text(message: &NotificationMessage) -> String {
+    let mut lines = vec![message.header.as_str(), message.body.as_str()];
+    lines.extend(message.comparison.as_deref());
+    lines.extend(message.footer.as_deref());
+    lines.join("\n")
+}
+
+/// Notifier which routes the message to `fallback` if `primary` fails to send it.
+///
+/// The failure of `primary` is regarded as recovered when `fallback` succeeds,
+/// so that the report is not lost.
+/// Without `fallback`, it behaves in the same way as `primary`.
+pub struct FailoverNotifier<P: SendMessage, F: SendMessage> {
+    primary: P,
+    fallback: Option<F>,
+}
+impl<P: SendMessage, F: SendMessage> FailoverNotifier<P, F> {
+    pub fn new(primary: P, fallback: Option<F>) -> Self {
+        FailoverNotifier { primary, fallback }
+    }
+}
+impl<P: SendMessage, F: SendMessage> SendMessage for FailoverNotifier<P, F> {
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let fallback = match &self.fallback {
+            Some(fallback) => fallback,
+            None => return self.primary.send(message),
+        };
+        match self.primary.send(message.clone()) {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                println!(
+                    "Failed to send the message: {}. Route it to the fallback.",
+                    e
+                );
+                fallback.send(message)
+            }
+        }
+    }
+}
+
+/// Parse the value of `Retry-After` header into the waiting time.
+///
+/// The value is either the number of seconds (e.g. `120`)
+/// or HTTP-date (e.g. `Wed, 21 Oct 2015 07:28:00 GMT`).
+/// If the value is missing or invalid, it returns 1 second.
+fn parse_retry_after(value: Option<&str>, now: DateTime<Utc>) -> Duration {
+    let value = match value {
+        Some(value) => value.trim(),
+        None => return DEFAULT_RETRY_AFTER,
+    };
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Duration::from_secs(seconds);
+    }
+    match DateTime::parse_from_rfc2822(value) {
+        Ok(date) => (date.with_timezone(&Utc) - now)
+            .to_std()
+            .unwrap_or(Duration::ZERO),
+        Err(_) => DEFAULT_RETRY_AFTER,
     }
 }
 
 #[cfg(test)]
 mod test_build_attachment {
-    use crate::message_builder::NotificationMessage;
+    use super::SeverityColors;
+    use crate::message_builder::{NotificationMessage, Severity};
     use slack_hook::{Attachment, HexColor, SlackText, TryFrom};
 
     #[test]
@@ -62,6 +532,7 @@ mod test_build_attachment {
         let sample_message = NotificationMessage {
             header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
             body: "・AWS CloudTrail: 0.01 USD\n・AWS Cost Explorer: 0.18 USD".to_string(),
+            ..Default::default()
         };
 
         let expected_attchment = Attachment {
@@ -72,8 +543,565 @@ mod test_build_attachment {
             color: Some(HexColor::try_from("#36a64f").unwrap()),
             ..Attachment::default()
         };
-        let actual_attachment = sample_message.as_attachment("#36a64f");
+        let actual_attachment =
+            sample_message.as_attachment(&HexColor::try_from("#36a64f").unwrap());
 
         assert_eq!(expected_attchment, actual_attachment);
     }
+
+    #[test]
+    fn omit_text_when_body_is_empty() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "".to_string(),
+            ..Default::default()
+        };
+
+        let actual_attachment =
+            sample_message.as_attachment(&HexColor::try_from("#36a64f").unwrap());
+
+        assert_eq!(None, actual_attachment.text);
+    }
+
+    #[test]
+    fn append_comparison_as_second_attachment() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            severity: Severity::Critical,
+            comparison: Some(
+                "【前月同期比較】\n合計: 1.00 USD → 1.62 USD (+0.62 USD, +62.0%)".to_string(),
+            ),
+            footer: None,
+        };
+
+        let expected_attachments = vec![
+            Attachment {
+                pretext: Some(SlackText::new("07/01~07/11の請求額は、1.62 USDです。")),
+                text: Some(SlackText::new("・AWS CloudTrail: 1.62 USD")),
+                color: Some(HexColor::try_from("#d00000").unwrap()),
+                ..Attachment::default()
+            },
+            Attachment {
+                text: Some(SlackText::new(
+                    "【前月同期比較】\n合計: 1.00 USD → 1.62 USD (+0.62 USD, +62.0%)",
+                )),
+                color: Some(HexColor::try_from("#439fe0").unwrap()),
+                ..Attachment::default()
+            },
+        ];
+
+        assert_eq!(
+            expected_attachments,
+            sample_message.as_attachments(&SeverityColors::default())
+        );
+    }
+
+    #[test]
+    fn display_footer_in_last_attachment() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            comparison: Some("【前月同期比較】".to_string()),
+            footer: Some("Dashboard: https://example.com/cost".to_string()),
+            ..Default::default()
+        };
+
+        let actual_attachments = sample_message.as_attachments(&SeverityColors::default());
+
+        assert_eq!(None, actual_attachments[0].footer);
+        assert_eq!(
+            Some(SlackText::new("Dashboard: https://example.com/cost")),
+            actual_attachments[1].footer
+        );
+    }
+
+    #[test]
+    fn omit_footer_when_not_configured() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            None,
+            sample_message.as_attachments(&SeverityColors::default())[0].footer
+        );
+    }
+
+    #[test]
+    fn color_attachment_by_custom_severity_colors() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            severity: Severity::Warning,
+            ..Default::default()
+        };
+        let colors = SeverityColors {
+            warning: HexColor::try_from("#ffcc00").unwrap(),
+            ..SeverityColors::default()
+        };
+
+        assert_eq!(
+            Some(HexColor::try_from("#ffcc00").unwrap()),
+            sample_message.as_attachments(&colors)[0].color
+        );
+    }
+
+    #[test]
+    fn send_only_one_attachment_without_comparison() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            1,
+            sample_message
+                .as_attachments(&SeverityColors::default())
+                .len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_build_blocks {
+    use super::*;
+
+    #[test]
+    fn build_blocks_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            comparison: Some("【前月同期比較】".to_string()),
+            footer: Some("Dashboard: https://example.com/cost".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            vec![
+                json!({
+                    "type": "header",
+                    "text": {"type": "plain_text", "text": "07/01~07/11の請求額は、1.62 USDです。"},
+                }),
+                json!({
+                    "type": "section",
+                    "text": {"type": "mrkdwn", "text": "・AWS CloudTrail: 1.62 USD"},
+                }),
+                json!({"type": "divider"}),
+                json!({
+                    "type": "section",
+                    "text": {"type": "mrkdwn", "text": "【前月同期比較】"},
+                }),
+                json!({
+                    "type": "context",
+                    "elements": [{"type": "mrkdwn", "text": "Dashboard: https://example.com/cost"}],
+                }),
+            ],
+            sample_message.as_blocks()
+        );
+    }
+
+    #[test]
+    fn split_long_body_into_sections() {
+        let line = format!("・{}: 1.00 USD", "S".repeat(1990));
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、3.00 USDです。".to_string(),
+            body: [line.as_str(); 3].join("\n"),
+            ..Default::default()
+        };
+
+        let blocks = sample_message.as_blocks();
+
+        assert_eq!(4, blocks.len());
+        for block in &blocks[1..] {
+            assert_eq!("section", block["type"]);
+            assert_eq!(line, block["text"]["text"]);
+        }
+    }
+
+    #[test]
+    fn split_text_at_line_breaks_within_limit() {
+        assert_eq!(vec!["ab\ncd", "ef"], split_text("ab\ncd\nef", 5));
+        assert_eq!(vec!["abcde", "fg\nh"], split_text("abcdefg\nh", 5));
+        assert_eq!(vec!["あいう", "え"], split_text("あいうえ", 3));
+        assert!(split_text("", 5).is_empty());
+    }
+
+    #[test]
+    fn post_blocks_with_header_as_text() {
+        struct WebhookStub;
+        impl PostWebhook for WebhookStub {
+            fn post(&self, _payload: &Value) -> Result<WebhookResponse, Error> {
+                unreachable!()
+            }
+        }
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+        let notifier = SlackNotifier::with_webhook(WebhookStub).format(SlackFormat::Blocks);
+
+        let payload = notifier.build_payload(&sample_message).unwrap();
+
+        assert_eq!("07/01~07/11の請求額は、1.62 USDです。", payload["text"]);
+        assert_eq!(json!(sample_message.as_blocks()), payload["blocks"]);
+        assert!(payload.get("attachments").is_none());
+    }
+}
+
+#[cfg(test)]
+mod test_rate_limit {
+    use super::*;
+    use chrono::TimeZone;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+
+    /// Stub of the webhook which returns the designated responses in order.
+    struct WebhookStub {
+        responses: RefCell<Vec<WebhookResponse>>,
+        posted: Rc<RefCell<u32>>,
+    }
+    impl PostWebhook for WebhookStub {
+        fn post(&self, _payload: &Value) -> Result<WebhookResponse, Error> {
+            *self.posted.borrow_mut() += 1;
+            Ok(self.responses.borrow_mut().remove(0))
+        }
+    }
+
+    fn rate_limited(retry_after: Option<&str>) -> WebhookResponse {
+        WebhookResponse {
+            status: 429,
+            retry_after: retry_after.map(|x| x.to_string()),
+        }
+    }
+
+    fn ok() -> WebhookResponse {
+        WebhookResponse {
+            status: 200,
+            retry_after: None,
+        }
+    }
+
+    /// Durations which the notifier waited for before retrying.
+    type WaitedDurations = Arc<Mutex<Vec<Duration>>>;
+
+    fn prepare_notifier(
+        responses: Vec<WebhookResponse>,
+        rate_limit_policy: RateLimitPolicy,
+    ) -> (
+        SlackNotifier<WebhookStub>,
+        Rc<RefCell<u32>>,
+        WaitedDurations,
+    ) {
+        let posted = Rc::new(RefCell::new(0));
+        let waited = Arc::new(Mutex::new(vec![]));
+        let webhook = WebhookStub {
+            responses: RefCell::new(responses),
+            posted: posted.clone(),
+        };
+        let waited_in_sleep = waited.clone();
+        let notifier = SlackNotifier::with_webhook(webhook)
+            .rate_limit_policy(rate_limit_policy)
+            .sleep(move |duration| waited_in_sleep.lock().unwrap().push(duration));
+
+        (notifier, posted, waited)
+    }
+
+    fn sample_message() -> NotificationMessage {
+        NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn retry_after_waiting_designated_seconds() {
+        let (notifier, posted, waited) = prepare_notifier(
+            vec![rate_limited(Some("3")), ok()],
+            RateLimitPolicy::default(),
+        );
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_ok());
+        assert_eq!(2, *posted.borrow());
+        assert_eq!(vec![Duration::from_secs(3)], *waited.lock().unwrap());
+    }
+
+    #[test]
+    fn cap_waiting_time_by_max_wait() {
+        let (notifier, _posted, waited) = prepare_notifier(
+            vec![rate_limited(Some("120")), ok()],
+            RateLimitPolicy {
+                max_retries: 3,
+                max_wait: Duration::from_secs(10),
+            },
+        );
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_ok());
+        assert_eq!(vec![Duration::from_secs(10)], *waited.lock().unwrap());
+    }
+
+    #[test]
+    fn return_error_when_retries_are_exhausted() {
+        let (notifier, posted, _waited) = prepare_notifier(
+            vec![rate_limited(Some("1")), rate_limited(Some("1")), ok()],
+            RateLimitPolicy {
+                max_retries: 1,
+                max_wait: Duration::from_secs(10),
+            },
+        );
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_err());
+        assert_eq!(2, *posted.borrow());
+    }
+
+    #[test]
+    fn return_error_without_retry_for_other_statuses() {
+        let (notifier, posted, _waited) = prepare_notifier(
+            vec![
+                WebhookResponse {
+                    status: 500,
+                    retry_after: None,
+                },
+                ok(),
+            ],
+            RateLimitPolicy::default(),
+        );
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_err());
+        assert_eq!(1, *posted.borrow());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn sleep_without_stalling_other_tasks_on_worker() {
+        let sleeping = tokio::spawn(async { sleep_off_worker(Duration::from_millis(300)) });
+        // The test itself runs outside of the worker, so it can block the thread.
+        thread::sleep(Duration::from_millis(50));
+        let started = std::time::Instant::now();
+
+        tokio::spawn(async {}).await.unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(200));
+        sleeping.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn retry_on_current_thread_runtime_by_default() {
+        let webhook = WebhookStub {
+            responses: RefCell::new(vec![
+                WebhookResponse {
+                    status: 429,
+                    retry_after: Some("0".to_string()),
+                },
+                ok(),
+            ]),
+            posted: Rc::new(RefCell::new(0)),
+        };
+        let notifier = SlackNotifier::with_webhook(webhook);
+
+        assert!(notifier.send(sample_message()).is_ok());
+        assert_eq!(2, *notifier.webhook.posted.borrow());
+    }
+
+    #[test]
+    fn sleep_outside_of_runtime() {
+        let started = std::time::Instant::now();
+
+        sleep_off_worker(Duration::from_millis(10));
+
+        assert!(started.elapsed() >= Duration::from_millis(10));
+    }
+
+    #[test]
+    fn parse_retry_after_in_seconds() {
+        let now = Utc.ymd(2021, 7, 23).and_hms(0, 0, 0);
+
+        assert_eq!(Duration::from_secs(30), parse_retry_after(Some("30"), now));
+    }
+
+    #[test]
+    fn parse_retry_after_in_http_date() {
+        let now = Utc.ymd(2021, 7, 23).and_hms(0, 0, 0);
+
+        assert_eq!(
+            Duration::from_secs(90),
+            parse_retry_after(Some("Fri, 23 Jul 2021 00:01:30 GMT"), now)
+        );
+        assert_eq!(
+            Duration::ZERO,
+            parse_retry_after(Some("Thu, 22 Jul 2021 23:59:00 GMT"), now)
+        );
+    }
+
+    #[test]
+    fn use_default_for_missing_or_invalid_retry_after() {
+        let now = Utc.ymd(2021, 7, 23).and_hms(0, 0, 0);
+
+        assert_eq!(DEFAULT_RETRY_AFTER, parse_retry_after(None, now));
+        assert_eq!(DEFAULT_RETRY_AFTER, parse_retry_after(Some("soon"), now));
+    }
+}
+
+#[cfg(test)]
+mod test_failover {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Stub of the notifier which fails or records the sent messages.
+    #[derive(Default)]
+    struct NotifierStub {
+        fail: bool,
+        sent: RefCell<Vec<String>>,
+    }
+    impl SendMessage for NotifierStub {
+        fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+            if self.fail {
+                return Err(Error::from("HTTP error 500"));
+            }
+            self.sent.borrow_mut().push(message.header);
+            Ok(())
+        }
+    }
+
+    fn sample_message() -> NotificationMessage {
+        NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn route_message_to_fallback_when_primary_fails() {
+        let notifier = FailoverNotifier::new(
+            NotifierStub {
+                fail: true,
+                ..Default::default()
+            },
+            Some(NotifierStub::default()),
+        );
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_ok());
+        assert_eq!(
+            vec!["07/01~07/11の請求額は、1.62 USDです。".to_string()],
+            *notifier.fallback.unwrap().sent.borrow()
+        );
+    }
+
+    #[test]
+    fn skip_fallback_when_primary_succeeds() {
+        let notifier =
+            FailoverNotifier::new(NotifierStub::default(), Some(NotifierStub::default()));
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_ok());
+        assert_eq!(1, notifier.primary.sent.borrow().len());
+        assert!(notifier.fallback.unwrap().sent.borrow().is_empty());
+    }
+
+    #[test]
+    fn return_error_without_fallback() {
+        let notifier: FailoverNotifier<NotifierStub, NotifierStub> = FailoverNotifier::new(
+            NotifierStub {
+                fail: true,
+                ..Default::default()
+            },
+            None,
+        );
+
+        assert!(notifier.send(sample_message()).is_err());
+    }
+
+    #[test]
+    fn print_message_in_dry_run() {
+        let message = NotificationMessage {
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            footer: Some("Sent from dev".to_string()),
+            ..sample_message()
+        };
+
+        assert_eq!(
+            "07/01~07/11の請求額は、1.62 USDです。\n・AWS CloudTrail: 1.62 USD\nSent from dev",
+            to_plain_text(&message)
+        );
+        assert!(DryRunNotifier.send(message).is_ok());
+        assert!(DryRunNotifier.send(NotificationMessage::default()).is_ok());
+    }
+
+    #[test]
+    fn send_message_to_all_destinations() {
+        let notifier = BroadcastNotifier::new(vec![
+            (
+                "https://hooks.slack.com/a".to_string(),
+                NotifierStub::default(),
+            ),
+            (
+                "https://hooks.slack.com/b".to_string(),
+                NotifierStub::default(),
+            ),
+        ]);
+
+        assert!(notifier.send(sample_message()).is_ok());
+        for (_, destination) in &notifier.destinations {
+            assert_eq!(1, destination.sent.borrow().len());
+        }
+    }
+
+    #[test]
+    fn report_failed_and_succeeded_destinations() {
+        let failing = || NotifierStub {
+            fail: true,
+            ..Default::default()
+        };
+        let notifier = BroadcastNotifier::new(vec![
+            (
+                "https://hooks.slack.com/a".to_string(),
+                NotifierStub::default(),
+            ),
+            ("https://hooks.slack.com/b".to_string(), failing()),
+            (
+                "https://hooks.slack.com/c".to_string(),
+                NotifierStub::default(),
+            ),
+        ]);
+
+        let res = notifier.send(sample_message());
+
+        assert_eq!(
+            "Failed to send to 1 of 3 destinations: https://hooks.slack.com/b (HTTP error 500). Succeeded: https://hooks.slack.com/a, https://hooks.slack.com/c",
+            res.unwrap_err().to_string()
+        );
+        assert_eq!(1, notifier.destinations[2].1.sent.borrow().len());
+
+        let all_failing = BroadcastNotifier::new(vec![
+            ("https://hooks.slack.com/a".to_string(), failing()),
+            ("https://hooks.slack.com/b".to_string(), failing()),
+        ]);
+        assert!(all_failing
+            .send(sample_message())
+            .unwrap_err()
+            .to_string()
+            .ends_with("Succeeded: none"));
+    }
+
+    #[test]
+    fn parse_fallback_sink_correctly() {
+        assert_eq!(Ok(FallbackSink::Stdout), "stdout".parse());
+        assert!("sns".parse::<FallbackSink>().is_err());
+    }
 }
@@ -0,0 +1,176 @@
+use crate::message_builder::NotificationMessage;
+use crate::slack_notifier::{SendMessage, SeverityColors};
+
+use reqwest::Client;
+use serde_json::{json, Value};
+use slack_hook::Error;
+
+/// Endpoint of chat.postMessage method of Slack Web API.
+const CHAT_POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+/// Trait to call chat.postMessage method of Slack Web API.
+pub trait PostChatMessage {
+    /// Post the payload and return the timestamp (`ts`) of the posted message.
+    fn post_message(&self, payload: &Value) -> Result<String, Error>;
+}
+
+/// Client to call Slack Web API with a bot token.
+pub struct ChatApiClient {
+    token: String,
+    client: Client,
+}
+impl ChatApiClient {
+    pub fn new(token: &str) -> Self {
+        ChatApiClient {
+            token: token.to_string(),
+            client: Client::new(),
+        }
+    }
+}
+impl PostChatMessage for ChatApiClient {
+    fn post_message(&self, payload: &Value) -> Result<String, Error> {
+        let response: Value = self
+            .client
+            .post(CHAT_POST_MESSAGE_URL)
+            .bearer_auth(&self.token)
+            .json(payload)
+            .send()?
+            .json()?;
+        parse_post_message_response(&response)
+    }
+}
+
+/// Extract the timestamp of the posted message from the response of chat.postMessage.
+///
+/// Slack Web API responds with the status 200 even on failure,
+/// so `ok` field is checked.
+fn parse_post_message_response(response: &Value) -> Result<String, Error> {
+    if response["ok"] != json!(true) {
+        let error = response["error"].as_str().unwrap_or("unknown_error");
+        return Err(Error::from(format!("Slack API error {}", error)));
+    }
+    match response["ts"].as_str() {
+        Some(ts) => Ok(ts.to_string()),
+        None => Err(Error::from("Slack API response without ts")),
+    }
+}
+
+/// An object to send notification message to a Slack channel as a thread.
+///
+/// The total cost is posted as the parent message, and the cost breakdown
+/// as its reply, so that the channel is kept tidy.
+pub struct SlackThreadNotifier<C: PostChatMessage = ChatApiClient> {
+    client: C,
+    /// ID or name of the channel to which the messages are posted.
+    channel: String,
+    /// Colors of the attachment for each severity.
+    severity_colors: SeverityColors,
+}
+impl<C: PostChatMessage> SlackThreadNotifier<C> {
+    pub fn new(client: C, channel: &str) -> Self {
+        SlackThreadNotifier {
+            client,
+            channel: channel.to_string(),
+            severity_colors: SeverityColors::default(),
+        }
+    }
+
+    /// Set the colors of the attachment for each severity.
+    pub fn severity_colors(mut self, severity_colors: SeverityColors) -> Self {
+        self.severity_colors = severity_colors;
+        self
+    }
+}
+impl<C: PostChatMessage> SendMessage for SlackThreadNotifier<C> {
+    /// Post the total cost and then its breakdown as a reply in the thread.
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let (total_message, breakdown_message) = message.split();
+        let thread_ts = self.client.post_message(&json!({
+            "channel": self.channel,
+            "attachments": total_message.as_attachments(&self.severity_colors),
+        }))?;
+        self.client.post_message(&json!({
+            "channel": self.channel,
+            "thread_ts": thread_ts,
+            "attachments": breakdown_message.as_attachments(&self.severity_colors),
+        }))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_slack_thread {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Stub of the client which records the posted payloads.
+    #[derive(Default)]
+    struct ChatClientStub {
+        fail: bool,
+        posted: RefCell<Vec<Value>>,
+    }
+    impl PostChatMessage for ChatClientStub {
+        fn post_message(&self, payload: &Value) -> Result<String, Error> {
+            if self.fail {
+                return Err(Error::from("Slack API error channel_not_found"));
+            }
+            self.posted.borrow_mut().push(payload.clone());
+            Ok(format!("1627000000.00010{}", self.posted.borrow().len()))
+        }
+    }
+
+    fn sample_message() -> NotificationMessage {
+        NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn post_breakdown_as_reply_to_total() {
+        let notifier = SlackThreadNotifier::new(ChatClientStub::default(), "C0123456789");
+
+        let res = notifier.send(sample_message());
+
+        assert!(res.is_ok());
+        let posted = notifier.client.posted.borrow();
+        assert_eq!(2, posted.len());
+        assert_eq!(
+            json!("07/01~07/11の請求額は、1.62 USDです。"),
+            posted[0]["attachments"][0]["pretext"]
+        );
+        assert_eq!(Value::Null, posted[0]["attachments"][0]["text"]);
+        assert_eq!(Value::Null, posted[0]["thread_ts"]);
+        assert_eq!(
+            json!("・AWS CloudTrail: 1.62 USD"),
+            posted[1]["attachments"][0]["text"]
+        );
+        assert_eq!(json!("1627000000.000101"), posted[1]["thread_ts"]);
+        assert_eq!(json!("C0123456789"), posted[1]["channel"]);
+    }
+
+    #[test]
+    fn return_error_when_parent_fails() {
+        let notifier = SlackThreadNotifier::new(
+            ChatClientStub {
+                fail: true,
+                ..Default::default()
+            },
+            "C0123456789",
+        );
+
+        assert!(notifier.send(sample_message()).is_err());
+        assert!(notifier.client.posted.borrow().is_empty());
+    }
+
+    #[test]
+    fn parse_post_message_response_correctly() {
+        assert_eq!(
+            "1627000000.000100",
+            parse_post_message_response(&json!({"ok": true, "ts": "1627000000.000100"})).unwrap()
+        );
+        assert!(parse_post_message_response(&json!({"ok": false, "error": "not_authed"})).is_err());
+        assert!(parse_post_message_response(&json!({"ok": true})).is_err());
+    }
+}
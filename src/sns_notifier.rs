@@ -0,0 +1,235 @@
+use crate::discord_notifier::truncate_with_ellipsis;
+use crate::message_builder::NotificationMessage;
+use crate::slack_notifier::{to_plain_text, SendMessage};
+
+use async_trait::async_trait;
+use rusoto_core::signature::SignedRequest;
+use rusoto_core::{Client, Region};
+use slack_hook::Error;
+use std::error;
+use std::future::Future;
+
+/// Maximum number of characters of the subject of SNS message.
+const MAX_SUBJECT_CHARS: usize = 100;
+
+/// Error in publishing the message.
+pub type PublishError = Box<dyn error::Error + Send + Sync>;
+
+/// Trait to call Publish method of SNS API.
+#[async_trait]
+pub trait Publish {
+    async fn publish(
+        &self,
+        topic_arn: &str,
+        subject: &str,
+        message: &str,
+    ) -> Result<(), PublishError>;
+}
+
+/// Client to send requests to SNS API of the region of the Lambda function.
+///
+/// The requests are signed by the shared client of rusoto.
+pub struct SnsClient {
+    client: Client,
+    region: Region,
+}
+impl SnsClient {
+    pub fn new() -> Self {
+        SnsClient {
+            client: Client::shared(),
+            region: Region::default(),
+        }
+    }
+}
+#[async_trait]
+impl Publish for SnsClient {
+    async fn publish(
+        &self,
+        topic_arn: &str,
+        subject: &str,
+        message: &str,
+    ) -> Result<(), PublishError> {
+        let mut request = SignedRequest::new("POST", "sns", &self.region, "/");
+        request.set_content_type("application/x-www-form-urlencoded".to_string());
+        request.set_payload(Some(publish_payload(topic_arn, subject, message)?));
+        let mut response = self
+            .client
+            .sign_and_dispatch(request)
+            .await
+            .map_err(|e| format!("Failed to send Publish request: {:?}", e))?;
+        let response = response.buffer().await?;
+        if !response.status.is_success() {
+            return Err(format!(
+                "SNS Publish failed with HTTP status {}: {}",
+                response.status,
+                String::from_utf8_lossy(&response.body)
+            )
+            .into());
+        }
+        Ok(())
+    }
+}
+
+/// Encode the parameters of Publish into the form body of the request.
+///
+/// The message, which may be up to 256 KB, is too long for the query string.
+fn publish_payload(topic_arn: &str, subject: &str, message: &str) -> Result<Vec<u8>, PublishError> {
+    let params = [
+        ("Action", "Publish"),
+        ("Version", "2010-03-31"),
+        ("TopicArn", topic_arn),
+        ("Subject", subject),
+        ("Message", message),
+    ];
+    Ok(serde_urlencoded::to_string(params)?.into_bytes())
+}
+
+/// Run the request to completion from the synchronous `send`.
+///
+/// In the multi-threaded runtime of `main`, the worker hands its other tasks
+/// over to another thread while the request is driven in place.
+/// Outside of the runtime, a single-threaded runtime is built for it.
+fn block_on<F: Future<Output = Result<(), PublishError>>>(future: F) -> Result<(), PublishError> {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => tokio::task::block_in_place(|| handle.block_on(future)),
+        Err(_) => tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(future),
+    }
+}
+
+/// An object to publish notification message to an SNS topic.
+///
+/// The header is used as the subject, which is truncated to 100 characters,
+/// and the whole message is published as the body so that it is readable
+/// by the e-mail subscriptions.
+pub struct SnsNotifier<C: Publish = SnsClient> {
+    client: C,
+    topic_arn: String,
+}
+impl<C: Publish> SnsNotifier<C> {
+    pub fn new(client: C, topic_arn: &str) -> Self {
+        SnsNotifier {
+            client,
+            topic_arn: topic_arn.to_string(),
+        }
+    }
+}
+impl<C: Publish + Sync> SendMessage for SnsNotifier<C> {
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let subject = truncate_with_ellipsis(&message.header, MAX_SUBJECT_CHARS);
+        let body = to_plain_text(&message);
+        block_on(self.client.publish(&self.topic_arn, &subject, &body))
+            .map_err(|e| Error::from(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test_sns_notifier {
+    use super::*;
+    use std::sync::Mutex;
+
+    const TOPIC_ARN: &str = "arn:aws:sns:ap-northeast-1:123456789012:cost-notification";
+
+    /// Stub of the client which records the published subjects and messages.
+    #[derive(Default)]
+    struct PublishStub {
+        fail: bool,
+        published: Mutex<Vec<(String, String, String)>>,
+    }
+    #[async_trait]
+    impl Publish for PublishStub {
+        async fn publish(
+            &self,
+            topic_arn: &str,
+            subject: &str,
+            message: &str,
+        ) -> Result<(), PublishError> {
+            if self.fail {
+                return Err("SNS Publish failed with HTTP status 403".into());
+            }
+            self.published.lock().unwrap().push((
+                topic_arn.to_string(),
+                subject.to_string(),
+                message.to_string(),
+            ));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn publish_header_and_body_to_topic() {
+        let notifier = SnsNotifier::new(PublishStub::default(), TOPIC_ARN);
+        let message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 0.01 USD\n・AWS Cost Explorer: 0.18 USD".to_string(),
+            ..Default::default()
+        };
+
+        assert!(notifier.send(message).is_ok());
+        assert_eq!(
+            vec![(
+                TOPIC_ARN.to_string(),
+                "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+                "07/01~07/11の請求額は、1.62 USDです。\n・AWS CloudTrail: 0.01 USD\n・AWS Cost Explorer: 0.18 USD"
+                    .to_string(),
+            )],
+            *notifier.client.published.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn truncate_subject_to_limit() {
+        let notifier = SnsNotifier::new(PublishStub::default(), TOPIC_ARN);
+        let header = format!("Total cost for {} is 1.62 USD.", "07/01~07/11 ".repeat(10));
+        let message = NotificationMessage {
+            header: header.clone(),
+            body: "- AWS CloudTrail: 1.62 USD".to_string(),
+            ..Default::default()
+        };
+
+        assert!(notifier.send(message).is_ok());
+        let published = notifier.client.published.lock().unwrap();
+        let (_, subject, body) = &published[0];
+        assert_eq!(MAX_SUBJECT_CHARS, subject.chars().count());
+        assert!(subject.starts_with("Total cost for 07/01~07/11 07/01~07/11"));
+        assert!(subject.ends_with('…'));
+        assert_eq!(format!("{}\n- AWS CloudTrail: 1.62 USD", header), *body);
+    }
+
+    #[test]
+    fn return_error_when_publish_fails() {
+        let notifier = SnsNotifier::new(
+            PublishStub {
+                fail: true,
+                ..Default::default()
+            },
+            TOPIC_ARN,
+        );
+
+        assert!(notifier.send(NotificationMessage::default()).is_err());
+    }
+
+    #[test]
+    fn encode_parameters_into_form_body() {
+        let payload =
+            publish_payload(TOPIC_ARN, "請求額 & 予測", "1行目\n2行目 = 1.62 USD").unwrap();
+
+        assert_eq!(
+            "Action=Publish&Version=2010-03-31\
+             &TopicArn=arn%3Aaws%3Asns%3Aap-northeast-1%3A123456789012%3Acost-notification\
+             &Subject=%E8%AB%8B%E6%B1%82%E9%A1%8D+%26+%E4%BA%88%E6%B8%AC\
+             &Message=1%E8%A1%8C%E7%9B%AE%0A2%E8%A1%8C%E7%9B%AE+%3D+1.62+USD",
+            String::from_utf8(payload).unwrap()
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn publish_inside_multi_thread_runtime() {
+        let notifier = SnsNotifier::new(PublishStub::default(), TOPIC_ARN);
+
+        assert!(notifier.send(NotificationMessage::default()).is_ok());
+        assert_eq!(1, notifier.client.published.lock().unwrap().len());
+    }
+}
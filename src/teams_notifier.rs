@@ -0,0 +1,118 @@
+use crate::message_builder::NotificationMessage;
+use crate::slack_notifier::SendMessage;
+
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use slack_hook::Error;
+
+impl NotificationMessage {
+    /// Create the MessageCard payload of Microsoft Teams from `NotificationMessage` object.
+    ///
+    /// The header is displayed as the title and the body as the text of the first section,
+    /// followed by the sections of the comparison and the footer, if any.
+    /// The card is colored by the severity of the message.
+    /// The line breaks are doubled since Teams joins the single ones.
+    fn as_message_card(&self) -> Value {
+        let to_text = |text: &str| text.replace('\n', "\n\n");
+        let mut sections = vec![];
+        if !self.body.is_empty() {
+            sections.push(json!({ "text": to_text(&self.body) }));
+        }
+        if let Some(comparison) = &self.comparison {
+            sections.push(json!({ "text": to_text(comparison) }));
+        }
+        if let Some(footer) = &self.footer {
+            sections.push(json!({ "text": to_text(footer) }));
+        }
+
+        json!({
+            "@type": "MessageCard",
+            "@context": "https://schema.org/extensions",
+            "summary": self.header,
+            "themeColor": self.severity.color().trim_start_matches('#'),
+            "title": self.header,
+            "sections": sections,
+        })
+    }
+}
+
+/// An object to send notification message to Microsoft Teams via Incoming Webhook.
+pub struct TeamsNotifier {
+    url: Url,
+    client: Client,
+}
+impl TeamsNotifier {
+    pub fn new(webhook_url: &str) -> Result<Self, Error> {
+        Ok(TeamsNotifier {
+            url: Url::parse(webhook_url)?,
+            client: Client::new(),
+        })
+    }
+}
+impl SendMessage for TeamsNotifier {
+    /// Send message to Teams as a MessageCard.
+    fn send(&self, message: NotificationMessage) -> Result<(), Error> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(&message.as_message_card())
+            .send()?;
+        match response.status().as_u16() {
+            200..=299 => Ok(()),
+            status => Err(Error::from(format!("HTTP error {}", status))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_message_card {
+    use crate::message_builder::{NotificationMessage, Severity};
+    use serde_json::json;
+
+    #[test]
+    fn build_message_card_correctly() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "・AWS CloudTrail: 0.01 USD\n・AWS Cost Explorer: 0.18 USD".to_string(),
+            severity: Severity::Warning,
+            ..Default::default()
+        };
+
+        let actual_card = sample_message.as_message_card();
+
+        assert_eq!(
+            json!({
+                "@type": "MessageCard",
+                "@context": "https://schema.org/extensions",
+                "summary": "07/01~07/11の請求額は、1.62 USDです。",
+                "themeColor": "daa038",
+                "title": "07/01~07/11の請求額は、1.62 USDです。",
+                "sections": [
+                    {"text": "・AWS CloudTrail: 0.01 USD\n\n・AWS Cost Explorer: 0.18 USD"}
+                ],
+            }),
+            actual_card
+        );
+    }
+
+    #[test]
+    fn append_comparison_and_footer_as_sections() {
+        let sample_message = NotificationMessage {
+            header: "07/01~07/11の請求額は、1.62 USDです。".to_string(),
+            body: "".to_string(),
+            comparison: Some("【前月同期比較】\n合計: 1.00 USD → 1.62 USD".to_string()),
+            footer: Some("Dashboard: https://example.com/cost".to_string()),
+            ..Default::default()
+        };
+
+        let actual_card = sample_message.as_message_card();
+
+        assert_eq!(
+            json!([
+                {"text": "【前月同期比較】\n\n合計: 1.00 USD → 1.62 USD"},
+                {"text": "Dashboard: https://example.com/cost"},
+            ]),
+            actual_card["sections"]
+        );
+    }
+}
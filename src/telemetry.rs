@@ -0,0 +1,270 @@
+use reqwest::{Client, Url};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::error;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Name of the span recorded for each run.
+pub const RUN_SPAN_NAME: &str = "request_cost_and_notify";
+
+/// Value of an attribute of the span.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Int(i64),
+    String(String),
+}
+
+/// Span finished at the end of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSpan {
+    pub name: String,
+    pub start_time: SystemTime,
+    pub end_time: SystemTime,
+    /// Attributes in the recorded order
+    ///
+    /// # Example
+    /// `[("fetch.duration_ms", Int(812)), ..., ("outcome", String("success"))]`
+    pub attributes: Vec<(String, AttributeValue)>,
+}
+impl RunSpan {
+    /// Value of the attribute `key`.
+    pub fn attribute(&self, key: &str) -> Option<&AttributeValue> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Whether the run ended successfully.
+    fn is_success(&self) -> bool {
+        self.attribute("outcome") == Some(&AttributeValue::String("success".to_string()))
+    }
+}
+
+/// Recorder of the durations of the phases of a run.
+///
+/// Each phase lasts from the end of the previous phase (or the start of the run).
+pub struct SpanRecorder {
+    name: String,
+    start_time: SystemTime,
+    phase_start: Instant,
+    attributes: Vec<(String, AttributeValue)>,
+}
+impl SpanRecorder {
+    /// Start recording the span named `name`.
+    pub fn start(name: &str) -> Self {
+        SpanRecorder {
+            name: name.to_string(),
+            start_time: SystemTime::now(),
+            phase_start: Instant::now(),
+            attributes: vec![],
+        }
+    }
+
+    /// Record the duration of `phase` as `{phase}.duration_ms` and start the next phase.
+    pub fn end_phase(&mut self, phase: &str) {
+        let duration = self.phase_start.elapsed();
+        self.attributes.push((
+            format!("{}.duration_ms", phase),
+            AttributeValue::Int(duration.as_millis() as i64),
+        ));
+        self.phase_start = Instant::now();
+    }
+
+    /// Finish the span with the outcome of the run (`success` or `failure`).
+    pub fn finish(mut self, succeeded: bool) -> RunSpan {
+        let outcome = match succeeded {
+            true => "success",
+            false => "failure",
+        };
+        self.attributes.push((
+            "outcome".to_string(),
+            AttributeValue::String(outcome.to_string()),
+        ));
+        RunSpan {
+            name: self.name,
+            start_time: self.start_time,
+            end_time: SystemTime::now(),
+            attributes: self.attributes,
+        }
+    }
+}
+
+/// Trait to export the finished span.
+///
+/// It is shared across the await points of the run, so it must be `Sync`.
+pub trait ExportSpan: Sync {
+    fn export(&self, span: &RunSpan) -> Result<(), Box<dyn error::Error>>;
+}
+
+/// Exporter which sends the span to an OpenTelemetry collector
+/// via OTLP/HTTP in JSON encoding.
+pub struct OtlpExporter {
+    url: Url,
+    client: Client,
+}
+impl OtlpExporter {
+    /// Construct an exporter sending to `{endpoint}/v1/traces`.
+    /// (e.g. `http://localhost:4318`)
+    pub fn new(endpoint: &str) -> Result<Self, Box<dyn error::Error>> {
+        let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+        Ok(OtlpExporter {
+            url: Url::parse(&url)?,
+            client: Client::new(),
+        })
+    }
+}
+impl ExportSpan for OtlpExporter {
+    fn export(&self, span: &RunSpan) -> Result<(), Box<dyn error::Error>> {
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(&to_otlp_json(span, &random_hex(2), &random_hex(1)))
+            .send()?;
+        match response.status().is_success() {
+            true => Ok(()),
+            false => {
+                Err(format!("OTLP export failed with HTTP status {}", response.status()).into())
+            }
+        }
+    }
+}
+
+/// Encode the span into the body of the OTLP/HTTP request.
+fn to_otlp_json(span: &RunSpan, trace_id: &str, span_id: &str) -> Value {
+    let unix_nanos = |time: &SystemTime| {
+        time.duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_string()
+    };
+    let attributes: Vec<Value> = span
+        .attributes
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                AttributeValue::Int(x) => json!({ "intValue": x.to_string() }),
+                AttributeValue::String(x) => json!({ "stringValue": x }),
+            };
+            json!({ "key": key, "value": value })
+        })
+        .collect();
+
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [
+                    { "key": "service.name", "value": { "stringValue": "aws-cost-notification" } }
+                ]
+            },
+            "scopeSpans": [{
+                "scope": { "name": env!("CARGO_PKG_NAME") },
+                "spans": [{
+                    "traceId": trace_id,
+                    "spanId": span_id,
+                    "name": span.name,
+                    "kind": 1,
+                    "startTimeUnixNano": unix_nanos(&span.start_time),
+                    "endTimeUnixNano": unix_nanos(&span.end_time),
+                    "attributes": attributes,
+                    "status": { "code": if span.is_success() { 1 } else { 2 } },
+                }]
+            }]
+        }]
+    })
+}
+
+/// Random hex string of `words` * 8 bytes used as the trace and span IDs.
+fn random_hex(words: usize) -> String {
+    (0..words)
+        .map(|_| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_u128(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos(),
+            );
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// Exporter which keeps the exported spans in memory.
+#[cfg(test)]
+#[derive(Default)]
+pub struct InMemoryExporter {
+    pub spans: std::sync::Mutex<Vec<RunSpan>>,
+}
+#[cfg(test)]
+impl ExportSpan for InMemoryExporter {
+    fn export(&self, span: &RunSpan) -> Result<(), Box<dyn error::Error>> {
+        self.spans.lock().unwrap().push(span.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_telemetry {
+    use super::*;
+
+    #[test]
+    fn record_phases_and_outcome_correctly() {
+        let mut recorder = SpanRecorder::start(RUN_SPAN_NAME);
+        recorder.end_phase("fetch");
+        recorder.end_phase("send");
+
+        let span = recorder.finish(false);
+        let keys: Vec<&str> = span.attributes.iter().map(|(k, _)| k.as_str()).collect();
+
+        assert_eq!(RUN_SPAN_NAME, span.name);
+        assert_eq!(
+            vec!["fetch.duration_ms", "send.duration_ms", "outcome"],
+            keys
+        );
+        assert_eq!(
+            Some(&AttributeValue::String("failure".to_string())),
+            span.attribute("outcome")
+        );
+        assert!(span.start_time <= span.end_time);
+    }
+
+    #[test]
+    fn encode_span_into_otlp_json_correctly() {
+        let span = RunSpan {
+            name: RUN_SPAN_NAME.to_string(),
+            start_time: UNIX_EPOCH + std::time::Duration::from_secs(1),
+            end_time: UNIX_EPOCH + std::time::Duration::from_secs(2),
+            attributes: vec![
+                ("fetch.duration_ms".to_string(), AttributeValue::Int(120)),
+                (
+                    "outcome".to_string(),
+                    AttributeValue::String("success".to_string()),
+                ),
+            ],
+        };
+
+        let body = to_otlp_json(
+            &span,
+            "0af7651916cd43dd8448eb211c80319c",
+            "b7ad6b7169203331",
+        );
+        let encoded_span = &body["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+
+        assert_eq!("0af7651916cd43dd8448eb211c80319c", encoded_span["traceId"]);
+        assert_eq!("1000000000", encoded_span["startTimeUnixNano"]);
+        assert_eq!("2000000000", encoded_span["endTimeUnixNano"]);
+        assert_eq!(
+            json!({ "key": "fetch.duration_ms", "value": { "intValue": "120" } }),
+            encoded_span["attributes"][0]
+        );
+        assert_eq!(1, encoded_span["status"]["code"]);
+    }
+
+    #[test]
+    fn generate_ids_of_valid_length() {
+        assert_eq!(32, random_hex(2).len());
+        assert_eq!(16, random_hex(1).len());
+    }
+}